@@ -1,7 +1,7 @@
 use thiserror::Error;
 
 /// Erros comuns da biblioteca Ethernity
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum Error {
     /// Erro de comunicação com o node Ethereum
     #[error("Erro de RPC: {0}")]
@@ -23,6 +23,10 @@ pub enum Error {
     #[error("Timeout: {0}")]
     TimeoutError(String),
     
+    /// Resposta maior que o limite configurado
+    #[error("Resposta muito grande: {0}")]
+    ResponseTooLarge(String),
+    
     /// Recurso não encontrado
     #[error("Não encontrado: {0}")]
     NotFound(String),
@@ -5,9 +5,9 @@
  */
 
 use async_trait::async_trait;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::types::{EventType, TransactionHash};
-use ethereum_types::Address;
+use ethereum_types::{Address, H256};
 
 /// Trait para provedores RPC
 #[async_trait]
@@ -28,7 +28,39 @@ pub trait RpcProvider: Send + Sync {
     async fn get_block_number(&self) -> Result<u64>;
 
     /// Obtém o hash de um bloco
-    async fn get_block_hash(&self, block_number: u64) -> Result<ethereum_types::H256>;
+    async fn get_block_hash(&self, block_number: u64) -> Result<H256>;
+
+    /// Obtém os hashes das transações de um bloco, na ordem de inclusão
+    async fn get_block_transactions(&self, _block_number: u64) -> Result<Vec<TransactionHash>> {
+        Err(Error::RpcError("get_block_transactions não suportado pelo provedor".to_string()))
+    }
+
+    /// Obtém os traces de todas as transações de um bloco
+    ///
+    /// Retorna uma lista JSON no formato do `callTracer`, na ordem das transações.
+    async fn get_block_traces(&self, _block_number: u64) -> Result<Vec<u8>> {
+        Err(Error::RpcError("get_block_traces não suportado pelo provedor".to_string()))
+    }
+
+    /// Obtém os recibos de todas as transações de um bloco, na ordem das transações
+    async fn get_block_receipts(&self, _block_number: u64) -> Result<Vec<u8>> {
+        Err(Error::RpcError("get_block_receipts não suportado pelo provedor".to_string()))
+    }
+
+    /// Obtém as alterações de estado de uma transação
+    ///
+    /// Retorna um objeto JSON `{"pre": {...}, "post": {...}}` no formato do
+    /// `prestateTracer` em modo diff.
+    async fn get_state_diff(&self, _tx_hash: TransactionHash) -> Result<Vec<u8>> {
+        Err(Error::RpcError("get_state_diff não suportado pelo provedor".to_string()))
+    }
+
+    /// Lê um slot de storage de um contrato
+    ///
+    /// Com `block_number` igual a `None` é usado o bloco mais recente.
+    async fn get_storage_at(&self, _address: Address, _slot: H256, _block_number: Option<u64>) -> Result<H256> {
+        Err(Error::RpcError("get_storage_at não suportado pelo provedor".to_string()))
+    }
 }
 
 /// Trait para detectores de eventos
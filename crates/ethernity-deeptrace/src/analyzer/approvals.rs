@@ -0,0 +1,241 @@
+use crate::trace::{CallTrace, CallType};
+use crate::utils;
+use crate::{Approval, ApprovalSource};
+use ethereum_types::{Address, U256};
+
+/// `Approval(address,address,uint256)` (ERC20; no ERC721 o valor é um tópico)
+const APPROVAL_SIG: &str = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+/// `ApprovalForAll(address,address,bool)` (ERC721/ERC1155)
+const APPROVAL_FOR_ALL_SIG: &str = "0x17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31";
+
+/// `permit(address,address,uint256,uint256,uint8,bytes32,bytes32)` (EIP-2612)
+const ERC2612_PERMIT: [u8; 4] = [0xd5, 0x05, 0xac, 0xcf];
+/// `permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)` (DAI)
+const DAI_PERMIT: [u8; 4] = [0x8f, 0xcb, 0xaf, 0x0c];
+/// `permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)` (Permit2)
+const PERMIT2_SINGLE: [u8; 4] = [0x2b, 0x67, 0xb5, 0x70];
+/// `permit(address,((address,uint160,uint48,uint48)[],address,uint256),bytes)` (Permit2)
+const PERMIT2_BATCH: [u8; 4] = [0x2a, 0x2d, 0x80, 0xd1];
+/// Endereço canônico do Permit2, o mesmo em todas as redes
+const PERMIT2_ADDRESS: [u8; 20] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0xd4, 0x73, 0x03, 0x0f,
+    0x11, 0x6d, 0xde, 0xe9, 0xf6, 0xb4, 0x3a, 0xc7, 0x8b, 0xa3,
+];
+
+/// Extrai approvals dos logs do recibo e permits das chamadas do trace.
+///
+/// Um permit EIP-2612 bem-sucedido também emite `Approval`, então aparece
+/// duas vezes com origens diferentes. Chamadas revertidas são ignoradas e
+/// DELEGATECALLs não são contadas, pois repetem o calldata do proxy.
+pub fn extract_approvals(trace: &CallTrace, receipt: &serde_json::Value, log_calls: &[usize]) -> Vec<Approval> {
+    let mut approvals = Vec::new();
+    if let Some(logs) = receipt.get("logs").and_then(|l| l.as_array()) {
+        for (log_index, log) in logs.iter().enumerate() {
+            let call_index = log_calls.get(log_index).copied().unwrap_or(0);
+            approvals.extend(parse_approval_log(log, call_index));
+        }
+    }
+    collect_permits(trace, &mut 0, false, &mut approvals);
+    approvals.sort_by_key(|approval| approval.call_index);
+    approvals
+}
+
+fn parse_approval_log(log: &serde_json::Value, call_index: usize) -> Option<Approval> {
+    let topics: Vec<&str> = log.get("topics")?.as_array()?.iter().filter_map(|t| t.as_str()).collect();
+    let source = match topics.first()?.to_lowercase().as_str() {
+        APPROVAL_SIG if topics.len() == 3 => ApprovalSource::ApprovalEvent,
+        APPROVAL_FOR_ALL_SIG if topics.len() == 3 => ApprovalSource::ApprovalForAll,
+        _ => return None,
+    };
+    let value = utils::parse_u256_hex(log.get("data").and_then(|d| d.as_str()).unwrap_or("0x0"));
+    let amount = match source {
+        ApprovalSource::ApprovalForAll if !value.is_zero() => U256::MAX,
+        _ => value,
+    };
+    Some(Approval {
+        source,
+        token: utils::parse_address(log.get("address").and_then(|a| a.as_str()).unwrap_or("")),
+        owner: utils::parse_address(topics[1]),
+        spender: utils::parse_address(topics[2]),
+        amount,
+        deadline: None,
+        call_index,
+    })
+}
+
+fn collect_permits(trace: &CallTrace, index: &mut usize, reverted: bool, out: &mut Vec<Approval>) {
+    let call_index = *index;
+    *index += 1;
+    let reverted = reverted || trace.error.is_some();
+
+    let call_type = trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call);
+    if !reverted && call_type == CallType::Call && !trace.to.is_empty() {
+        out.extend(decode_permit(utils::parse_address(&trace.to), &utils::decode_hex(&trace.input), call_index));
+    }
+
+    for child in trace.calls.iter().flatten() {
+        collect_permits(child, index, reverted, out);
+    }
+}
+
+/// Decodifica o calldata de um permit enviado a `to`
+fn decode_permit(to: Address, input: &[u8], call_index: usize) -> Vec<Approval> {
+    if input.len() < 4 {
+        return Vec::new();
+    }
+    let words: Vec<U256> = input[4..].chunks_exact(32).map(U256::from_big_endian).collect();
+    let approval = |source, token, owner, spender, amount, deadline| Approval {
+        source,
+        token,
+        owner,
+        spender,
+        amount,
+        deadline,
+        call_index,
+    };
+
+    let selector = &input[..4];
+    if selector == ERC2612_PERMIT && words.len() >= 7 {
+        let (owner, spender) = (word_address(&words[0]), word_address(&words[1]));
+        vec![approval(ApprovalSource::Erc2612Permit, to, owner, spender, words[2], Some(words[3]))]
+    } else if selector == DAI_PERMIT && words.len() >= 8 {
+        let (owner, spender) = (word_address(&words[0]), word_address(&words[1]));
+        let amount = if words[4].is_zero() { U256::zero() } else { U256::MAX };
+        vec![approval(ApprovalSource::DaiPermit, to, owner, spender, amount, Some(words[3]))]
+    } else if to == Address::from(PERMIT2_ADDRESS) && selector == PERMIT2_SINGLE && words.len() >= 8 {
+        // owner, (token, amount, expiration, nonce), spender, sigDeadline, offset(signature)
+        let (owner, token, spender) = (word_address(&words[0]), word_address(&words[1]), word_address(&words[5]));
+        vec![approval(ApprovalSource::Permit2, token, owner, spender, words[2], Some(words[3]))]
+    } else if to == Address::from(PERMIT2_ADDRESS) && selector == PERMIT2_BATCH {
+        decode_permit2_batch(&words, call_index).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Uma autorização por item de um `PermitBatch` do Permit2
+fn decode_permit2_batch(words: &[U256], call_index: usize) -> Option<Vec<Approval>> {
+    let offset_to_index = |offset: &U256| -> Option<usize> {
+        if *offset > U256::from(words.len() * 32) || !offset.low_u64().is_multiple_of(32) {
+            return None;
+        }
+        Some(offset.as_usize() / 32)
+    };
+    let owner = word_address(words.first()?);
+    // O struct começa em `offset(batch)`; `details` é relativo a ele
+    let batch = offset_to_index(words.get(1)?)?;
+    let details = batch + offset_to_index(words.get(batch)?)?;
+    let spender = word_address(words.get(batch + 1)?);
+    let len = words.get(details)?;
+    if *len > U256::from(words.len()) {
+        return None;
+    }
+
+    (0..len.as_usize())
+        .map(|i| {
+            let item = details + 1 + i * 4;
+            Some(Approval {
+                source: ApprovalSource::Permit2,
+                token: word_address(words.get(item)?),
+                owner,
+                spender,
+                amount: *words.get(item + 1)?,
+                deadline: Some(*words.get(item + 2)?),
+                call_index,
+            })
+        })
+        .collect()
+}
+
+fn word_address(word: &U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn addr(n: u64) -> String {
+        format!("0x{:040x}", n)
+    }
+
+    fn word(n: u64) -> String {
+        format!("{:064x}", n)
+    }
+
+    fn node(from: u64, to: &str, input: String, call_type: &str, error: Option<&str>, calls: Option<Vec<CallTrace>>) -> CallTrace {
+        CallTrace {
+            from: addr(from), gas: "0".into(), gas_used: "0".into(),
+            to: to.into(), input, output: "0x".into(), value: "0".into(),
+            error: error.map(|e| e.into()), calls, call_type: Some(call_type.into()), logs: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_approval_events() {
+        let receipt = json!({"logs": [
+            {"address": addr(0x10), "topics": [APPROVAL_SIG, format!("0x{}", word(1)), format!("0x{}", word(2))], "data": format!("0x{}", "f".repeat(64))},
+            // ERC721: tokenId indexado
+            {"address": addr(0x11), "topics": [APPROVAL_SIG, format!("0x{}", word(1)), format!("0x{}", word(2)), format!("0x{}", word(7))], "data": "0x"},
+            {"address": addr(0x12), "topics": [APPROVAL_FOR_ALL_SIG, format!("0x{}", word(1)), format!("0x{}", word(3))], "data": format!("0x{}", word(1))}
+        ]});
+        let trace = node(1, &addr(2), "0x".into(), "CALL", None, None);
+        let approvals = extract_approvals(&trace, &receipt, &[0, 0, 0]);
+
+        assert_eq!(approvals.len(), 2);
+        assert_eq!(approvals[0].source, ApprovalSource::ApprovalEvent);
+        assert_eq!(approvals[0].token, Address::from_low_u64_be(0x10));
+        assert_eq!(approvals[0].owner, Address::from_low_u64_be(1));
+        assert_eq!(approvals[0].spender, Address::from_low_u64_be(2));
+        assert!(approvals[0].is_unlimited());
+        assert_eq!(approvals[1].source, ApprovalSource::ApprovalForAll);
+        assert_eq!(approvals[1].spender, Address::from_low_u64_be(3));
+        assert_eq!(approvals[1].amount, U256::MAX);
+    }
+
+    #[test]
+    fn test_extract_permit_calls() {
+        let permit2 = format!("0x{}", hex::encode(PERMIT2_ADDRESS));
+        let erc2612 = format!("0x{}{}{}{}{}{}{}{}", hex::encode(ERC2612_PERMIT), word(1), word(2), word(500), word(99), word(27), word(0), word(0));
+        let dai = format!("0x{}{}{}{}{}{}{}{}{}", hex::encode(DAI_PERMIT), word(1), word(3), word(0), word(0), word(1), word(27), word(0), word(0));
+        let single = format!(
+            "0x{}{}{}{}{}{}{}{}{}",
+            hex::encode(PERMIT2_SINGLE), word(1), word(0x20), word(40), word(77), word(0), word(4), word(88), word(0x100)
+        );
+        // owner, offset(batch) = 0x60, offset(sig); batch: offset(details) = 0x60, spender, sigDeadline; details[2]
+        let batch = format!(
+            "0x{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
+            hex::encode(PERMIT2_BATCH), word(1), word(0x60), word(0x200),
+            word(0x60), word(5), word(88),
+            word(2), word(0x21), word(10), word(66), word(0), word(0x22), word(20), word(67), word(0)
+        );
+        let trace = node(1, &addr(0x99), "0x".into(), "CALL", None, Some(vec![
+            node(0x99, &addr(0x10), erc2612.clone(), "CALL", None, Some(vec![
+                node(0x10, &addr(0x11), erc2612.clone(), "DELEGATECALL", None, None),
+            ])),
+            node(0x99, &addr(0x12), dai, "CALL", None, None),
+            node(0x99, &permit2, single.clone(), "CALL", None, None),
+            node(0x99, &permit2, batch, "CALL", None, None),
+            node(0x99, &addr(0x13), erc2612, "CALL", Some("execution reverted"), None),
+            // Seletor do Permit2 em outro contrato é ignorado
+            node(0x99, &addr(0x14), single, "CALL", None, None),
+        ]));
+        let approvals = extract_approvals(&trace, &json!({"logs": []}), &[]);
+
+        assert_eq!(approvals.len(), 5);
+        let a = |n: u64| Address::from_low_u64_be(n);
+        assert_eq!(approvals[0].source, ApprovalSource::Erc2612Permit);
+        assert_eq!((approvals[0].token, approvals[0].owner, approvals[0].spender), (a(0x10), a(1), a(2)));
+        assert_eq!((approvals[0].amount, approvals[0].deadline, approvals[0].call_index), (U256::from(500u64), Some(U256::from(99u64)), 1));
+        assert_eq!(approvals[1].source, ApprovalSource::DaiPermit);
+        assert!(approvals[1].is_unlimited());
+        assert_eq!(approvals[2].source, ApprovalSource::Permit2);
+        assert_eq!((approvals[2].token, approvals[2].spender, approvals[2].amount), (a(0x20), a(4), U256::from(40u64)));
+        assert_eq!(approvals[2].deadline, Some(U256::from(77u64)));
+        assert_eq!((approvals[3].token, approvals[3].spender, approvals[3].amount), (a(0x21), a(5), U256::from(10u64)));
+        assert_eq!((approvals[4].token, approvals[4].amount, approvals[4].call_index), (a(0x22), U256::from(20u64), 5));
+    }
+}
@@ -1,60 +1,23 @@
-use crate::trace::{CallTrace, CallTree, CallNode, CallType};
-use crate::utils;
-use crate::TraceAnalysisConfig;
-use ethereum_types::U256;
+use crate::trace::{CallTrace, CallTree};
+use crate::{DeepTraceError, TraceAnalysisConfig};
 
-pub(super) struct TempNode {
-    pub children: Vec<usize>,
-}
-
-pub fn build_call_tree(trace: &CallTrace, config: &TraceAnalysisConfig) -> Result<CallTree, ()> {
-    let mut nodes = Vec::new();
-    build_call_tree_recursive(trace, 0, &mut nodes, config)?;
-    Ok(CallTree {
-        root: CallNode {
-            index: 0,
-            depth: 0,
-            call_type: trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call),
-            from: utils::parse_address(&trace.from),
-            to: if trace.to.is_empty() { None } else { Some(utils::parse_address(&trace.to)) },
-            value: U256::from_dec_str(&trace.value).unwrap_or(U256::zero()),
-            gas: U256::from_dec_str(&trace.gas).unwrap_or(U256::zero()),
-            gas_used: U256::from_dec_str(&trace.gas_used).unwrap_or(U256::zero()),
-            input: utils::decode_hex(&trace.input),
-            output: utils::decode_hex(&trace.output),
-            error: trace.error.clone(),
-            children: Vec::new(),
-        },
-    })
-}
-
-fn build_call_tree_recursive(trace: &CallTrace, depth: usize, nodes: &mut Vec<TempNode>, config: &TraceAnalysisConfig) -> Result<(), ()> {
-    if depth > config.max_depth {
-        return Ok(());
+/// Constrói a árvore completa em arena, lendo o trace por referência
+///
+/// Chamadas abaixo de `max_depth` são descartadas, ou rejeitadas com
+/// `DeepTraceError::DepthLimit` quando `strict_depth` está habilitado.
+pub fn build_call_tree(trace: &CallTrace, config: &TraceAnalysisConfig) -> Result<CallTree, DeepTraceError> {
+    let tree = CallTree::from_trace_lossy(trace, config.max_depth);
+    if config.strict_depth && tree.is_truncated() {
+        return Err(DeepTraceError::DepthLimit(config.max_depth));
     }
-
-    let node = TempNode {
-        children: Vec::new(),
-    };
-    let node_index = nodes.len();
-    nodes.push(node);
-
-    if let Some(calls) = &trace.calls {
-        for child_call in calls {
-            let child_index = nodes.len();
-            build_call_tree_recursive(child_call, depth + 1, nodes, config)?;
-            if let Some(parent) = nodes.get_mut(node_index) {
-                parent.children.push(child_index);
-            }
-        }
-    }
-
-    Ok(())
+    Ok(tree)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trace::CallType;
+    use ethereum_types::{Address, U256};
 
     fn basic_trace() -> CallTrace {
         CallTrace {
@@ -77,8 +40,10 @@ mod tests {
                 error: None,
                 calls: None,
                 call_type: Some("CALL".into()),
+                logs: None,
             }]),
             call_type: Some("CALL".into()),
+            logs: None,
         }
     }
 
@@ -86,19 +51,43 @@ mod tests {
     fn test_build_call_tree_basic() {
         let trace = basic_trace();
         let tree = build_call_tree(&trace, &TraceAnalysisConfig::default()).unwrap();
-        assert_eq!(tree.root.index, 0);
-        assert_eq!(tree.root.depth, 0);
-        assert_eq!(tree.root.call_type, CallType::Call);
-        assert_eq!(tree.root.children.len(), 0);
+        assert_eq!(tree.root().index, 0);
+        assert_eq!(tree.root().depth, 0);
+        assert_eq!(tree.root().call_type, CallType::Call);
+        assert_eq!(tree.root().value, U256::from(10u64));
+        assert_eq!(tree.root().children, vec![1]);
+
+        let child = tree.find_by_index(1).unwrap();
+        assert_eq!((child.depth, child.parent), (1, Some(0)));
+        assert_eq!(child.to, Some(Address::from_low_u64_be(4)));
     }
 
     #[test]
-    fn test_build_call_tree_recursive_depth_limit() {
+    fn test_build_call_tree_depth_limit() {
         let trace = basic_trace();
-        let mut nodes = Vec::new();
-        let mut cfg = TraceAnalysisConfig::default();
-        cfg.max_depth = 0;
-        build_call_tree_recursive(&trace, 0, &mut nodes, &cfg).unwrap();
-        assert_eq!(nodes.len(), 1);
+        let cfg = TraceAnalysisConfig { max_depth: 0, ..Default::default() };
+        let tree = build_call_tree(&trace, &cfg).unwrap();
+        assert_eq!(tree.total_calls(), 1);
+        assert!(tree.root().children.is_empty());
+        assert!(tree.is_truncated());
+
+        let tree = build_call_tree(&trace, &TraceAnalysisConfig::default()).unwrap();
+        assert!(!tree.is_truncated());
+
+        let strict = TraceAnalysisConfig { strict_depth: true, ..cfg };
+        assert_eq!(build_call_tree(&trace, &strict).unwrap_err(), DeepTraceError::DepthLimit(0));
+        let strict = TraceAnalysisConfig { max_depth: 1, ..strict };
+        assert_eq!(build_call_tree(&trace, &strict).unwrap().total_calls(), 2);
+    }
+
+    #[test]
+    fn test_build_call_tree_wide_trace() {
+        let mut trace = basic_trace();
+        let child = trace.calls.take().unwrap().remove(0);
+        trace.calls = Some(vec![child; 10_000]);
+        let tree = build_call_tree(&trace, &TraceAnalysisConfig::default()).unwrap();
+        assert_eq!(tree.total_calls(), 10_001);
+        assert_eq!(tree.root().children.len(), 10_000);
+        assert_eq!(tree.children(tree.root()).last().unwrap().index, 10_000);
     }
 }
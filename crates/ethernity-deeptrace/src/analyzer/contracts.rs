@@ -1,11 +1,11 @@
-use crate::{ContractCreation, ContractType};
+use crate::{ContractCreation, ContractType, DeepTraceError};
 use crate::trace::{CallTrace, CallType};
 use crate::utils;
 use ethereum_types::Address;
 use std::collections::VecDeque;
 use std::sync::Arc;
 
-pub async fn extract_contract_creations(rpc: Arc<dyn ethernity_core::traits::RpcProvider>, trace: &CallTrace) -> Result<Vec<ContractCreation>, ()> {
+pub async fn extract_contract_creations(rpc: Arc<dyn ethernity_core::traits::RpcProvider>, trace: &CallTrace) -> Result<Vec<ContractCreation>, DeepTraceError> {
     let mut creations = Vec::new();
     let mut queue = VecDeque::new();
     queue.push_back((trace, 0usize));
@@ -14,7 +14,7 @@ pub async fn extract_contract_creations(rpc: Arc<dyn ethernity_core::traits::Rpc
         if call_type == CallType::Create || call_type == CallType::Create2 {
             let contract_address = utils::parse_address(&node.to);
             if contract_address != Address::zero() {
-                let bytecode = rpc.get_code(contract_address).await.map_err(|_| ())?;
+                let bytecode = rpc.get_code(contract_address).await?;
                 let contract_type = determine_contract_type(&bytecode)?;
                 let from = utils::parse_address(&node.from);
                 creations.push(ContractCreation {
@@ -35,7 +35,7 @@ pub async fn extract_contract_creations(rpc: Arc<dyn ethernity_core::traits::Rpc
     Ok(creations)
 }
 
-fn determine_contract_type(bytecode: &[u8]) -> Result<ContractType, ()> {
+fn determine_contract_type(bytecode: &[u8]) -> Result<ContractType, DeepTraceError> {
     let erc20_signatures: &[[u8; 4]] = &[
         [0x70, 0xa0, 0x82, 0x31],
         [0xa9, 0x05, 0x9c, 0xbb],
@@ -120,7 +120,8 @@ mod tests {
         let trace = CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x0000000000000000000000000000000000000100".into(), input: "0x".into(), output: "0x".into(), value: "0".into(), error: None,
-            calls: None, call_type: Some("CREATE".into())
+            calls: None, call_type: Some("CREATE".into()),
+            logs: None,
         };
         let rpc = Arc::new(MockRpc { code: vec![0x63,0x70,0xa0,0x82,0x31,0x00,0x00,0x63,0xa9,0x05,0x9c,0xbb,0x00,0x00,0x00] });
         let res = extract_contract_creations(rpc, &trace).await.unwrap();
@@ -134,12 +135,14 @@ mod tests {
         let child = CallTrace {
             from: "0x02".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x0000000000000000000000000000000000000200".into(), input: "0x".into(), output: "0x".into(), value: "0".into(),
-            error: None, calls: None, call_type: Some("CREATE2".into())
+            error: None, calls: None, call_type: Some("CREATE2".into()),
+            logs: None,
         };
         let root = CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x0000000000000000000000000000000000000100".into(), input: "0x".into(), output: "0x".into(), value: "0".into(),
-            error: None, calls: Some(vec![child]), call_type: Some("CREATE".into())
+            error: None, calls: Some(vec![child]), call_type: Some("CREATE".into()),
+            logs: None,
         };
         let rpc = Arc::new(CountingRpc { code: vec![0x36,0x3d,0x3d,0x37], calls: Mutex::new(Vec::new()) });
         let res = extract_contract_creations(rpc.clone(), &root).await.unwrap();
@@ -155,7 +158,8 @@ mod tests {
         let trace = CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x".into(), input: "0x".into(), output: "0x".into(), value: "0".into(),
-            error: None, calls: None, call_type: Some("CALL".into())
+            error: None, calls: None, call_type: Some("CALL".into()),
+            logs: None,
         };
         let rpc = Arc::new(CountingRpc { code: vec![], calls: Mutex::new(Vec::new()) });
         let res = extract_contract_creations(rpc.clone(), &trace).await.unwrap();
@@ -168,7 +172,8 @@ mod tests {
         let trace = CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x0000000000000000000000000000000000000100".into(), input: "0x".into(), output: "0x".into(), value: "0".into(),
-            error: None, calls: None, call_type: Some("CREATE".into())
+            error: None, calls: None, call_type: Some("CREATE".into()),
+            logs: None,
         };
         let rpc = Arc::new(ErrorRpc);
         assert!(extract_contract_creations(rpc, &trace).await.is_err());
@@ -179,7 +184,8 @@ mod tests {
         let trace = CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x0000000000000000000000000000000000000000".into(), input: "0x".into(), output: "0x".into(), value: "0".into(),
-            error: None, calls: None, call_type: Some("CREATE".into())
+            error: None, calls: None, call_type: Some("CREATE".into()),
+            logs: None,
         };
         let rpc = Arc::new(CountingRpc { code: vec![], calls: Mutex::new(Vec::new()) });
         let res = extract_contract_creations(rpc.clone(), &trace).await.unwrap();
@@ -0,0 +1,103 @@
+use crate::trace::{CallTrace, CallType};
+use crate::utils;
+use crate::{DeepTraceError, EthTransfer, TraceAnalysisConfig};
+use ethereum_types::Address;
+
+/// Extrai as movimentações de ETH nativo do call trace.
+///
+/// Considera CALL, CREATE/CREATE2 e SELFDESTRUCT com `value` não nulo.
+/// DELEGATECALL/CALLCODE não movem saldo entre contas e chamadas revertidas
+/// (junto com suas subchamadas) são ignoradas.
+pub fn extract_eth_transfers(trace: &CallTrace, config: &TraceAnalysisConfig) -> Result<Vec<EthTransfer>, DeepTraceError> {
+    let mut transfers = Vec::new();
+    let mut index = 0;
+    extract_eth_transfers_recursive(trace, 0, false, &mut index, &mut transfers, config);
+    Ok(transfers)
+}
+
+fn extract_eth_transfers_recursive(
+    trace: &CallTrace,
+    depth: usize,
+    reverted: bool,
+    index: &mut usize,
+    transfers: &mut Vec<EthTransfer>,
+    config: &TraceAnalysisConfig,
+) {
+    if depth > config.max_depth { return; }
+    let call_index = *index;
+    *index += 1;
+    // Os índices seguem a pré-ordem da árvore mesmo dentro de subárvores revertidas
+    let reverted = reverted || trace.error.is_some();
+
+    let call_type = trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call);
+    let moves_value = matches!(
+        call_type,
+        CallType::Call | CallType::Create | CallType::Create2 | CallType::SelfDestruct
+    );
+    let amount = utils::parse_quantity(&trace.value);
+    if !reverted && moves_value && !amount.is_zero() {
+        transfers.push(EthTransfer {
+            from: utils::parse_address(&trace.from),
+            to: if trace.to.is_empty() { Address::zero() } else { utils::parse_address(&trace.to) },
+            amount,
+            call_type,
+            call_index,
+        });
+    }
+
+    if let Some(calls) = &trace.calls {
+        for child_call in calls {
+            extract_eth_transfers_recursive(child_call, depth + 1, reverted, index, transfers, config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U256;
+
+    fn node(from: u64, to: u64, value: &str, call_type: &str, error: Option<&str>, calls: Option<Vec<CallTrace>>) -> CallTrace {
+        CallTrace {
+            from: format!("0x{:040x}", from), gas: "0".into(), gas_used: "0".into(),
+            to: format!("0x{:040x}", to), input: "0x".into(), output: "0x".into(), value: value.into(),
+            error: error.map(|e| e.into()), calls, call_type: Some(call_type.into()),
+            logs: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_eth_transfers_internal_calls_and_selfdestruct() {
+        let trace = node(1, 2, "0xde0b6b3a7640000", "CALL", None, Some(vec![
+            node(2, 3, "0x0", "STATICCALL", None, None),
+            node(2, 4, "0x5", "DELEGATECALL", None, None),
+            node(2, 5, "0x10", "CALL", None, Some(vec![
+                node(5, 6, "0x10", "SELFDESTRUCT", None, None),
+            ])),
+            node(2, 7, "0x20", "CALL", Some("execution reverted"), Some(vec![
+                node(7, 8, "0x20", "CALL", None, None),
+            ])),
+            node(2, 9, "100", "CREATE", None, None),
+        ]));
+        let transfers = extract_eth_transfers(&trace, &TraceAnalysisConfig::default()).unwrap();
+
+        assert_eq!(transfers.len(), 4);
+        assert_eq!(transfers[0].amount, U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(transfers[0].call_index, 0);
+        assert_eq!(transfers[1].to, Address::from_low_u64_be(5));
+        assert_eq!(transfers[1].call_index, 3);
+        assert_eq!(transfers[2].call_type, CallType::SelfDestruct);
+        assert_eq!(transfers[2].to, Address::from_low_u64_be(6));
+        assert_eq!(transfers[3].call_type, CallType::Create);
+        assert_eq!(transfers[3].amount, U256::from(100u64));
+        assert_eq!(transfers[3].call_index, 7);
+    }
+
+    #[test]
+    fn test_extract_eth_transfers_respects_depth() {
+        let trace = node(1, 2, "0x1", "CALL", None, Some(vec![node(2, 3, "0x1", "CALL", None, None)]));
+        let cfg = TraceAnalysisConfig { max_depth: 0, ..Default::default() };
+        let transfers = extract_eth_transfers(&trace, &cfg).unwrap();
+        assert_eq!(transfers.len(), 1);
+    }
+}
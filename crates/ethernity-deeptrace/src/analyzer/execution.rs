@@ -1,26 +1,26 @@
 use crate::trace::{CallTrace, CallType};
 use crate::utils;
 use crate::ExecutionStep;
-use crate::TraceAnalysisConfig;
-use ethereum_types::{Address, U256};
+use crate::{DeepTraceError, TraceAnalysisConfig};
+use ethereum_types::Address;
 
-pub fn build_execution_path(trace: &CallTrace, config: &TraceAnalysisConfig) -> Result<Vec<ExecutionStep>, ()> {
+pub fn build_execution_path(trace: &CallTrace, config: &TraceAnalysisConfig) -> Result<Vec<ExecutionStep>, DeepTraceError> {
     let mut path = Vec::new();
     build_execution_path_recursive(trace, 0, &mut path, config)?;
     Ok(path)
 }
 
-fn build_execution_path_recursive(trace: &CallTrace, depth: usize, path: &mut Vec<ExecutionStep>, config: &TraceAnalysisConfig) -> Result<(), ()> {
+fn build_execution_path_recursive(trace: &CallTrace, depth: usize, path: &mut Vec<ExecutionStep>, config: &TraceAnalysisConfig) -> Result<(), DeepTraceError> {
     if depth > config.max_depth { return Ok(()); }
     let step = ExecutionStep {
         depth,
         call_type: trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call),
         from: utils::parse_address(&trace.from),
         to: if trace.to.is_empty() { Address::zero() } else { utils::parse_address(&trace.to) },
-        value: U256::from_dec_str(&trace.value).unwrap_or(U256::zero()),
+        value: utils::parse_quantity(&trace.value),
         input: utils::decode_hex(&trace.input),
         output: utils::decode_hex(&trace.output),
-        gas_used: U256::from_dec_str(&trace.gas_used).unwrap_or(U256::zero()),
+        gas_used: utils::parse_quantity(&trace.gas_used),
         error: trace.error.clone(),
     };
     path.push(step);
@@ -43,9 +43,11 @@ mod tests {
             calls: Some(vec![CallTrace {
                 from: "0x0000000000000000000000000000000000000002".into(), gas: "0".into(), gas_used: "0".into(),
                 to: "".into(), input: "0x".into(), output: "0x".into(), value: "0".into(), error: None,
-                calls: None, call_type: Some("CALL".into())
+                calls: None, call_type: Some("CALL".into()),
+                logs: None,
             }]),
             call_type: Some("CALL".into()),
+            logs: None,
         }
     }
 
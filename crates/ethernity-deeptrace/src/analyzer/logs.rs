@@ -0,0 +1,223 @@
+use crate::trace::{CallLog, CallTrace, CallTree, CallType};
+use crate::utils;
+use ethereum_types::Address;
+
+/// Associa cada log do recibo ao índice (pré-ordem da árvore) da chamada que o emitiu.
+///
+/// Os índices são os nós de `tree`, construída a partir de `trace`: logs de
+/// subárvores descartadas pelo `max_depth` ficam com o ancestral mais profundo
+/// mantido na árvore.
+///
+/// Quando o trace traz os logs de cada frame (`callTracer` com `withLog`), a
+/// ordem de emissão é reconstruída a partir do campo `position` e conferida
+/// com o recibo. Caso contrário usa uma heurística posicional: o log é
+/// atribuído à primeira chamada executando no contexto do endereço emissor,
+/// sem retroceder em relação ao log anterior. Logs sem correspondência ficam
+/// com a chamada raiz (índice 0).
+pub fn map_logs_to_calls(trace: &CallTrace, tree: &CallTree, receipt: &serde_json::Value) -> Vec<usize> {
+    let receipt_logs: Vec<Address> = receipt
+        .get("logs")
+        .and_then(|l| l.as_array())
+        .map(|logs| {
+            logs.iter()
+                .map(|log| utils::parse_address(log.get("address").and_then(|a| a.as_str()).unwrap_or("")))
+                .collect()
+        })
+        .unwrap_or_default();
+    if receipt_logs.is_empty() {
+        return Vec::new();
+    }
+
+    let mut traced = Vec::new();
+    collect_trace_logs(tree, trace, 0, false, false, &mut traced);
+    let matches_receipt = traced.len() == receipt_logs.len()
+        && traced.iter().zip(&receipt_logs).all(|((_, address), expected)| address == expected);
+    if matches_receipt {
+        return traced.into_iter().map(|(call_index, _)| call_index).collect();
+    }
+
+    map_logs_by_position(trace, tree, &receipt_logs)
+}
+
+/// Filho `position` do nó `index` na árvore; `None` quando a subárvore foi truncada.
+/// Os filhos de um nó mantido correspondem, na ordem, às subchamadas do frame.
+fn arena_child(tree: &CallTree, index: usize, truncated: bool, position: usize) -> Option<usize> {
+    if truncated {
+        return None;
+    }
+    tree.find_by_index(index)?.children.get(position).copied()
+}
+
+/// Percorre o trace emitindo `(índice da chamada, endereço)` na ordem de execução dos logs.
+/// Em frames truncados (`truncated`), `call_index` é o do ancestral mantido na árvore.
+fn collect_trace_logs(
+    tree: &CallTree,
+    trace: &CallTrace,
+    call_index: usize,
+    truncated: bool,
+    reverted: bool,
+    out: &mut Vec<(usize, Address)>,
+) {
+    // Logs de chamadas revertidas não chegam ao recibo
+    let reverted = reverted || trace.error.is_some();
+
+    let calls = trace.calls.as_deref().unwrap_or(&[]);
+    let mut logs: Vec<&CallLog> = trace.logs.iter().flatten().collect();
+    // Sem `position` o log é tratado como emitido após todas as subchamadas
+    logs.sort_by_key(|log| log_position(log).unwrap_or(calls.len()));
+    let mut pending = logs.into_iter().peekable();
+
+    for (position, child) in calls.iter().enumerate() {
+        while let Some(log) = pending.next_if(|log| log_position(log).unwrap_or(calls.len()) <= position) {
+            if !reverted {
+                out.push((call_index, utils::parse_address(&log.address)));
+            }
+        }
+        match arena_child(tree, call_index, truncated, position) {
+            Some(child_index) => collect_trace_logs(tree, child, child_index, false, reverted, out),
+            None => collect_trace_logs(tree, child, call_index, true, reverted, out),
+        }
+    }
+    if !reverted {
+        out.extend(pending.map(|log| (call_index, utils::parse_address(&log.address))));
+    }
+}
+
+fn log_position(log: &CallLog) -> Option<usize> {
+    log.position.as_deref().map(|p| utils::parse_quantity(p).low_u64() as usize)
+}
+
+/// Heurística para traces sem logs: casa o emissor com o contexto de execução das chamadas
+fn map_logs_by_position(trace: &CallTrace, tree: &CallTree, receipt_logs: &[Address]) -> Vec<usize> {
+    let mut frames = Vec::new();
+    collect_frames(tree, trace, 0, false, false, &mut frames);
+
+    let mut last = 0;
+    receipt_logs
+        .iter()
+        .map(|emitter| {
+            let frame = frames
+                .iter()
+                .find(|(index, context)| context == emitter && *index >= last)
+                .or_else(|| frames.iter().rev().find(|(_, context)| context == emitter));
+            match frame {
+                Some((index, _)) => {
+                    last = *index;
+                    *index
+                }
+                None => 0,
+            }
+        })
+        .collect()
+}
+
+/// Lista `(índice, endereço de contexto)` das chamadas não revertidas em pré-ordem;
+/// frames truncados usam o índice do ancestral mantido na árvore
+fn collect_frames(
+    tree: &CallTree,
+    trace: &CallTrace,
+    call_index: usize,
+    truncated: bool,
+    reverted: bool,
+    frames: &mut Vec<(usize, Address)>,
+) {
+    let reverted = reverted || trace.error.is_some();
+
+    if !reverted {
+        let call_type = trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call);
+        // DELEGATECALL/CALLCODE executam no contexto (e emitem logs como) o chamador
+        let context = match call_type {
+            CallType::DelegateCall | CallType::CallCode => &trace.from,
+            _ => &trace.to,
+        };
+        if !context.is_empty() {
+            frames.push((call_index, utils::parse_address(context)));
+        }
+    }
+
+    for (position, child) in trace.calls.iter().flatten().enumerate() {
+        match arena_child(tree, call_index, truncated, position) {
+            Some(child_index) => collect_frames(tree, child, child_index, false, reverted, frames),
+            None => collect_frames(tree, child, call_index, true, reverted, frames),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TraceAnalysisConfig;
+    use serde_json::json;
+
+    fn addr(n: u64) -> String {
+        format!("0x{:040x}", n)
+    }
+
+    fn log(address: u64, position: Option<&str>) -> CallLog {
+        CallLog { address: addr(address), topics: vec![], data: "0x".into(), position: position.map(|p| p.into()) }
+    }
+
+    fn node(from: u64, to: u64, call_type: &str, error: Option<&str>, logs: Option<Vec<CallLog>>, calls: Option<Vec<CallTrace>>) -> CallTrace {
+        CallTrace {
+            from: addr(from), gas: "0".into(), gas_used: "0".into(),
+            to: addr(to), input: "0x".into(), output: "0x".into(), value: "0".into(),
+            error: error.map(|e| e.into()), calls, call_type: Some(call_type.into()), logs,
+        }
+    }
+
+    fn map_logs(trace: &CallTrace, receipt: &serde_json::Value) -> Vec<usize> {
+        let tree = CallTree::from_trace_lossy(trace, TraceAnalysisConfig::default().max_depth);
+        map_logs_to_calls(trace, &tree, receipt)
+    }
+
+    fn receipt(emitters: &[u64]) -> serde_json::Value {
+        json!({"logs": emitters.iter().map(|e| json!({"address": addr(*e), "topics": []})).collect::<Vec<_>>()})
+    }
+
+    #[test]
+    fn test_map_logs_with_trace_logs() {
+        // 0: router(2) -> 1: pool(3) -> 2: token(4) transfer; pool emite Swap após a subchamada
+        let trace = node(1, 2, "CALL", None, None, Some(vec![
+            node(2, 3, "CALL", None, Some(vec![log(3, Some("0x1"))]), Some(vec![
+                node(3, 4, "CALL", None, Some(vec![log(4, Some("0x0"))]), None),
+            ])),
+            node(2, 5, "CALL", Some("execution reverted"), Some(vec![log(5, None)]), None),
+            node(2, 4, "CALL", None, Some(vec![log(4, None)]), None),
+        ]));
+        assert_eq!(map_logs(&trace, &receipt(&[4, 3, 4])), vec![2, 1, 4]);
+    }
+
+    #[test]
+    fn test_map_logs_by_position_without_trace_logs() {
+        // O token 4 é chamado duas vezes; o proxy 6 delega para 7 e emite no próprio contexto
+        let trace = node(1, 2, "CALL", None, None, Some(vec![
+            node(2, 4, "CALL", None, None, None),
+            node(2, 6, "CALL", None, None, Some(vec![node(6, 7, "DELEGATECALL", None, None, None)])),
+            node(2, 4, "CALL", None, None, None),
+        ]));
+        assert_eq!(map_logs(&trace, &receipt(&[4, 6, 4, 9])), vec![1, 2, 4, 0]);
+        assert!(map_logs(&trace, &json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_map_logs_past_max_depth() {
+        // Cadeia até a profundidade 11, além do `max_depth` padrão (10), seguida de um irmão
+        let trace = |with_logs: bool| {
+            let logs = |address| with_logs.then(|| vec![log(address, Some("0x0"))]);
+            let mut deep = node(111, 112, "CALL", None, logs(112), None);
+            for depth in (1..11).rev() {
+                deep = node(100 + depth, 101 + depth, "CALL", None, None, Some(vec![deep]));
+            }
+            let sibling = node(1, 50, "CALL", None, logs(50), None);
+            node(1, 2, "CALL", None, None, Some(vec![deep, sibling]))
+        };
+        let tree = CallTree::from_trace_lossy(&trace(true), TraceAnalysisConfig::default().max_depth);
+        assert_eq!(tree.total_calls(), 12);
+        assert_eq!(tree.find_by_index(11).unwrap().to, Some(Address::from_low_u64_be(50)));
+
+        // O log truncado fica com o nó mais profundo mantido (índice 10) e o irmão mantém o próprio índice,
+        // tanto com os logs do trace quanto pela heurística posicional
+        assert_eq!(map_logs(&trace(true), &receipt(&[112, 50])), vec![10, 11]);
+        assert_eq!(map_logs(&trace(false), &receipt(&[112, 50])), vec![10, 11]);
+    }
+}
@@ -1,21 +1,34 @@
 // New modularized analyzer
+mod approvals;
 mod call_tree;
 mod token;
+mod eth;
+mod logs;
+mod proxy;
+mod state_diff;
 mod contracts;
 mod execution;
 mod stats;
 
+pub use proxy::{ProxyResolver, EIP1967_IMPLEMENTATION_SLOT};
+pub use state_diff::StateDiffAnalyzer;
 pub use stats::AnalysisStats;
 
+use approvals::extract_approvals;
 use call_tree::build_call_tree;
 use contracts::extract_contract_creations;
+use eth::extract_eth_transfers;
 use execution::build_execution_path;
+use logs::map_logs_to_calls;
 use token::extract_token_transfers;
 
 use crate::memory::MemoryManager;
-use crate::{trace::*, ContractCreation, ExecutionStep, TokenTransfer, TraceAnalysisConfig};
+use crate::{trace::*, Approval, ContractCreation, DexDecoder, LiquidityAction, SwapAction, DeepTraceError, EthTransfer, ExecutionStep, StateDiff, StorageWrite, TokenTransfer, TraceAnalysisConfig};
 use ethereum_types::{H256};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 
 pub struct AnalysisContext {
     pub tx_hash: H256,
@@ -39,26 +52,113 @@ impl TraceAnalyzer {
         &self,
         trace: &CallTrace,
         receipt: &serde_json::Value,
-    ) -> Result<TraceAnalysisResult, ()> {
-        let call_tree = build_call_tree(trace, &self.context.config)?;
-        let token_transfers = extract_token_transfers(receipt).await?;
-        let contract_creations = extract_contract_creations(self.context.rpc_client.clone(), trace).await?;
-        let execution_path = build_execution_path(trace, &self.context.config)?;
+    ) -> Result<TraceAnalysisResult, DeepTraceError> {
+        let deadline = Instant::now() + Duration::from_millis(self.context.config.timeout_ms);
+        self.analyze_until(trace, receipt, deadline).await
+    }
+
+    /// Analisa o trace respeitando um prazo absoluto e o `memory_limit` da configuração
+    pub async fn analyze_until(
+        &self,
+        trace: &CallTrace,
+        receipt: &serde_json::Value,
+        deadline: Instant,
+    ) -> Result<TraceAnalysisResult, DeepTraceError> {
+        let config = &self.context.config;
+        // A reserva cobre as estruturas derivadas do trace enquanto a análise estiver em andamento
+        let _reservation = self
+            .context
+            .memory_manager
+            .reserve(estimate_trace_memory(trace), config.memory_limit)?;
+
+        let mut call_tree = build_call_tree(trace, config)?;
+        if config.enable_proxy_resolution {
+            let resolver = ProxyResolver::new(self.context.rpc_client.clone());
+            let block = (self.context.block_number > 0).then_some(self.context.block_number);
+            let resolved = tokio::time::timeout_at(deadline, resolver.resolve(&call_tree, block))
+                .await
+                .map_err(|_| DeepTraceError::Timeout(config.timeout_ms))?;
+            call_tree.apply_proxy_info(&resolved);
+        }
+        check_deadline(deadline, config.timeout_ms)?;
+        let log_calls = map_logs_to_calls(trace, &call_tree, receipt);
+        let token_transfers =
+            with_deadline(deadline, config.timeout_ms, extract_token_transfers(receipt, &log_calls)).await?;
+        let decoder = DexDecoder::new(self.context.rpc_client.clone());
+        let dex = with_deadline(deadline, config.timeout_ms, async {
+            Ok(decoder.decode(receipt, &log_calls, &token_transfers).await)
+        })
+        .await?;
+        let approvals = extract_approvals(trace, receipt, &log_calls);
+        let eth_transfers = extract_eth_transfers(trace, config)?;
+        check_deadline(deadline, config.timeout_ms)?;
+        let contract_creations = with_deadline(
+            deadline,
+            config.timeout_ms,
+            extract_contract_creations(self.context.rpc_client.clone(), trace),
+        )
+        .await?;
+        let execution_path = build_execution_path(trace, config)?;
+        let state_diff = if config.enable_state_diff {
+            let analyzer = StateDiffAnalyzer::new(self.context.rpc_client.clone());
+            Some(with_deadline(deadline, config.timeout_ms, analyzer.analyze(self.context.tx_hash)).await?)
+        } else {
+            None
+        };
 
         Ok(TraceAnalysisResult {
             call_tree,
             token_transfers,
+            swaps: dex.swaps,
+            liquidity_changes: dex.liquidity_changes,
+            approvals,
+            eth_transfers,
             contract_creations,
             execution_path,
+            state_diff,
+            storage_writes: Vec::new(),
         })
     }
 }
 
+/// Executa uma fase da análise, falhando com `DeepTraceError::Timeout` após o prazo
+pub(crate) async fn with_deadline<T, F>(deadline: Instant, timeout_ms: u64, phase: F) -> Result<T, DeepTraceError>
+where
+    F: Future<Output = Result<T, DeepTraceError>>,
+{
+    tokio::time::timeout_at(deadline, phase)
+        .await
+        .map_err(|_| DeepTraceError::Timeout(timeout_ms))?
+}
+
+fn check_deadline(deadline: Instant, timeout_ms: u64) -> Result<(), DeepTraceError> {
+    if Instant::now() >= deadline {
+        return Err(DeepTraceError::Timeout(timeout_ms));
+    }
+    Ok(())
+}
+
+/// Estima a memória das estruturas derivadas do trace (árvore e caminho de execução)
+fn estimate_trace_memory(trace: &CallTrace) -> usize {
+    let node = std::mem::size_of::<CallNode>()
+        + std::mem::size_of::<ExecutionStep>()
+        + trace.input.len()
+        + trace.output.len();
+    node + trace.calls.iter().flatten().map(estimate_trace_memory).sum::<usize>()
+}
+
 pub struct TraceAnalysisResult {
     pub call_tree: CallTree,
     pub token_transfers: Vec<TokenTransfer>,
+    pub swaps: Vec<SwapAction>,
+    pub liquidity_changes: Vec<LiquidityAction>,
+    pub approvals: Vec<Approval>,
+    pub eth_transfers: Vec<EthTransfer>,
     pub contract_creations: Vec<ContractCreation>,
     pub execution_path: Vec<ExecutionStep>,
+    pub state_diff: Option<StateDiff>,
+    /// SSTOREs reconstruídos de traces de opcodes; vazio com o `callTracer`
+    pub storage_writes: Vec<StorageWrite>,
 }
 
 #[cfg(test)]
@@ -83,7 +183,8 @@ mod tests {
         CallTrace {
             from: "0x01".into(), gas: "0".into(), gas_used: "0".into(),
             to: "0x02".into(), input: "0x".into(), output: "0x".into(), value: "0".into(), error: None,
-            calls: None, call_type: Some("CALL".into())
+            calls: None, call_type: Some("CALL".into()),
+            logs: None,
         }
     }
 
@@ -102,8 +203,10 @@ mod tests {
         let receipt = json!({"logs": []});
         let result = analyzer.analyze(&trace, &receipt).await.unwrap();
         assert_eq!(result.token_transfers.len(), 0);
+        assert_eq!(result.eth_transfers.len(), 0);
         assert_eq!(result.contract_creations.len(), 0);
         assert_eq!(result.execution_path.len(), 1);
+        assert!(result.state_diff.is_none());
     }
 
     struct MockRpcSuccess;
@@ -155,8 +258,10 @@ mod tests {
                 error: None,
                 calls: None,
                 call_type: Some("CREATE".into()),
+                logs: None,
             }]),
             call_type: Some("CALL".into()),
+            logs: None,
         }
     }
 
@@ -180,9 +285,11 @@ mod tests {
         }]});
         let result = analyzer.analyze(&trace, &receipt).await.unwrap();
         assert_eq!(result.token_transfers.len(), 1);
+        // O emissor (0x01) não aparece como contexto de nenhuma chamada
+        assert_eq!(result.token_transfers[0].call_index, 0);
         assert_eq!(result.contract_creations.len(), 1);
         assert_eq!(result.execution_path.len(), 2);
-        assert_eq!(result.call_tree.root.call_type, CallType::Call);
+        assert_eq!(result.call_tree.root().call_type, CallType::Call);
     }
 
     #[tokio::test]
@@ -201,6 +308,65 @@ mod tests {
         assert!(analyzer.analyze(&trace, &receipt).await.is_err());
     }
 
+    struct MockRpcSlow;
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for MockRpcSlow {
+        async fn get_transaction_trace(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_transaction_receipt(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_code(&self, _address: ethereum_types::Address) -> ethernity_core::error::Result<Vec<u8>> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(vec![])
+        }
+        async fn call(&self, _to: ethereum_types::Address, _data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(0) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<ethereum_types::H256> { Ok(ethereum_types::H256::zero()) }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_enforces_timeout() {
+        let ctx = AnalysisContext {
+            tx_hash: H256::zero(),
+            block_number: 0,
+            timestamp: chrono::Utc::now(),
+            rpc_client: Arc::new(MockRpcSlow),
+            memory_manager: Arc::new(MemoryManager::new()),
+            config: TraceAnalysisConfig { timeout_ms: 20, ..Default::default() },
+        };
+        let analyzer = TraceAnalyzer::new(ctx);
+        let receipt = json!({"logs": []});
+        let res = analyzer.analyze(&creation_trace(), &receipt).await;
+        assert!(matches!(res, Err(DeepTraceError::Timeout(20))));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_enforces_memory_limit_and_releases_reservation() {
+        let memory_manager = Arc::new(MemoryManager::new());
+        let ctx = AnalysisContext {
+            tx_hash: H256::zero(),
+            block_number: 0,
+            timestamp: chrono::Utc::now(),
+            rpc_client: Arc::new(MockRpc),
+            memory_manager: memory_manager.clone(),
+            config: TraceAnalysisConfig { memory_limit: 16, ..Default::default() },
+        };
+        let analyzer = TraceAnalyzer::new(ctx);
+        let receipt = json!({"logs": []});
+        let res = analyzer.analyze(&simple_trace(), &receipt).await;
+        assert!(matches!(res, Err(DeepTraceError::MemoryLimit(16))));
+
+        let ctx = AnalysisContext {
+            tx_hash: H256::zero(),
+            block_number: 0,
+            timestamp: chrono::Utc::now(),
+            rpc_client: Arc::new(MockRpc),
+            memory_manager: memory_manager.clone(),
+            config: TraceAnalysisConfig::default(),
+        };
+        TraceAnalyzer::new(ctx).analyze(&simple_trace(), &receipt).await.unwrap();
+        assert_eq!(memory_manager.allocated_bytes(), 0);
+    }
+
     #[test]
     fn test_new_stores_context() {
         let ctx = AnalysisContext {
@@ -0,0 +1,206 @@
+use crate::trace::{CallTree, CallType, ProxyInfo};
+use crate::utils;
+use ethereum_types::{Address, H256};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Slot EIP-1967 da implementação: `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+pub const EIP1967_IMPLEMENTATION_SLOT: [u8; 32] = [
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// Implementações lidas por `(proxy, bloco)`; `None` quando o slot está vazio ou ilegível
+type ImplementationCache = HashMap<(Address, Option<u64>), Option<Address>>;
+
+/// Resolve chamadas a proxies EIP-1967 para o contrato de implementação.
+///
+/// Uma chamada é considerada proxy quando delega (DELEGATECALL) para o
+/// endereço gravado no slot de implementação do contrato chamado. A leitura
+/// do slot é feita uma vez por proxy e bloco.
+pub struct ProxyResolver {
+    rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>,
+    cache: Mutex<ImplementationCache>,
+}
+
+impl ProxyResolver {
+    pub fn new(rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>) -> Self {
+        Self { rpc_client, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Endereço de implementação gravado no slot EIP-1967 do contrato.
+    ///
+    /// Retorna `None` para slots vazios ou quando o provedor não consegue ler o storage.
+    pub async fn implementation_of(&self, proxy: Address, block_number: Option<u64>) -> Option<Address> {
+        if let Some(cached) = self.cache.lock().get(&(proxy, block_number)) {
+            return *cached;
+        }
+
+        let implementation = self
+            .rpc_client
+            .get_storage_at(proxy, H256::from(EIP1967_IMPLEMENTATION_SLOT), block_number)
+            .await
+            .ok()
+            .map(|word| Address::from_slice(&word.as_bytes()[12..]))
+            .filter(|address| !address.is_zero());
+
+        self.cache.lock().insert((proxy, block_number), implementation);
+        implementation
+    }
+
+    /// Resolve os proxies da árvore, indexados pelos nós de `tree`
+    pub async fn resolve(&self, tree: &CallTree, block_number: Option<u64>) -> HashMap<usize, ProxyInfo> {
+        let candidates = collect_candidates(tree);
+
+        let mut resolved = HashMap::new();
+        for candidate in candidates {
+            let Some(implementation) = self.implementation_of(candidate.proxy, block_number).await else {
+                continue;
+            };
+            if !candidate.delegates.contains(&implementation) {
+                continue;
+            }
+            let function = candidate
+                .selector
+                .and_then(|s| utils::known_selector_name(&s))
+                .map(str::to_string);
+            resolved.insert(candidate.index, ProxyInfo { implementation, selector: candidate.selector, function });
+        }
+        resolved
+    }
+}
+
+/// Chamada que delega para outro contrato no próprio contexto
+struct Candidate {
+    index: usize,
+    proxy: Address,
+    selector: Option<[u8; 4]>,
+    delegates: Vec<Address>,
+}
+
+/// Nós da árvore com alguma subchamada DELEGATECALL feita no próprio contexto
+fn collect_candidates(tree: &CallTree) -> Vec<Candidate> {
+    tree.nodes()
+        .iter()
+        .filter_map(|node| {
+            let proxy = node.to?;
+            let delegates: Vec<Address> = tree
+                .children(node)
+                .filter(|child| child.call_type == CallType::DelegateCall && child.from == proxy)
+                .filter_map(|child| child.to)
+                .collect();
+            if delegates.is_empty() {
+                return None;
+            }
+            let selector = node.input.get(..4).map(|s| s.try_into().expect("slice de 4 bytes"));
+            Some(Candidate { index: node.index, proxy, selector, delegates })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::CallTrace;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn addr(n: u64) -> String {
+        format!("0x{:040x}", n)
+    }
+
+    fn node(from: u64, to: u64, input: &str, call_type: &str, calls: Option<Vec<CallTrace>>) -> CallTrace {
+        CallTrace {
+            from: addr(from), gas: "0".into(), gas_used: "0".into(),
+            to: addr(to), input: input.into(), output: "0x".into(), value: "0".into(),
+            error: None, calls, call_type: Some(call_type.into()), logs: None,
+        }
+    }
+
+    fn tree(trace: &CallTrace) -> CallTree {
+        CallTree::from_trace_lossy(trace, usize::MAX)
+    }
+
+    /// Proxy 0x10 aponta para 0x20; o contrato 0x30 usa o slot para outra finalidade
+    struct StorageRpc {
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for StorageRpc {
+        async fn get_transaction_trace(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_transaction_receipt(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_code(&self, _address: Address) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(0) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<H256> { Ok(H256::zero()) }
+        async fn get_storage_at(&self, address: Address, slot: H256, _block_number: Option<u64>) -> ethernity_core::error::Result<H256> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(slot, H256::from(EIP1967_IMPLEMENTATION_SLOT));
+            match address.to_low_u64_be() {
+                0x10 => Ok(H256::from(Address::from_low_u64_be(0x20))),
+                0x30 => Ok(H256::from_low_u64_be(0x99)),
+                _ => Err(ethernity_core::Error::RpcError("sem storage".into())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_eip1967_proxies() {
+        // 0: EOA -> router(2); 1: router -> proxy(0x10) transfer; 2: proxy delega para 0x20;
+        // 3: router -> 0x30 que delega para uma biblioteca diferente do slot
+        let trace = node(1, 2, "0x", "CALL", Some(vec![
+            node(2, 0x10, "0xa9059cbb0000", "CALL", Some(vec![
+                node(0x10, 0x20, "0xa9059cbb0000", "DELEGATECALL", None),
+            ])),
+            node(2, 0x30, "0x12345678", "CALL", Some(vec![
+                node(0x30, 0x40, "0x12345678", "DELEGATECALL", None),
+            ])),
+            node(2, 0x10, "0x", "CALL", Some(vec![
+                node(0x10, 0x20, "0x", "DELEGATECALL", None),
+            ])),
+        ]));
+        let rpc = Arc::new(StorageRpc { reads: AtomicUsize::new(0) });
+        let resolver = ProxyResolver::new(rpc.clone());
+        let resolved = resolver.resolve(&tree(&trace), Some(100)).await;
+
+        assert_eq!(resolved.len(), 2);
+        let info = &resolved[&1];
+        assert_eq!(info.implementation, Address::from_low_u64_be(0x20));
+        assert_eq!(info.selector, Some([0xa9, 0x05, 0x9c, 0xbb]));
+        assert_eq!(info.function.as_deref(), Some("transfer(address,uint256)"));
+        assert_eq!(resolved[&5].selector, None);
+        // O proxy repetido é lido do cache
+        assert_eq!(rpc.reads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_index_after_truncated_branch() {
+        // Ramo até a profundidade 11 (truncado pelo `max_depth` 10) seguido de uma chamada ao proxy
+        let mut deep = node(0x61, 0x62, "0x", "CALL", None);
+        for depth in (1..11).rev() {
+            deep = node(0x50 + depth, 0x51 + depth, "0x", "CALL", Some(vec![deep]));
+        }
+        let trace = node(1, 2, "0x", "CALL", Some(vec![
+            deep,
+            node(2, 0x10, "0xa9059cbb", "CALL", Some(vec![node(0x10, 0x20, "0xa9059cbb", "DELEGATECALL", None)])),
+        ]));
+        let mut tree = CallTree::from_trace_lossy(&trace, 10);
+        let resolver = ProxyResolver::new(Arc::new(StorageRpc { reads: AtomicUsize::new(0) }));
+        let resolved = resolver.resolve(&tree, None).await;
+        tree.apply_proxy_info(&resolved);
+
+        let proxied: Vec<_> = tree.nodes().iter().filter(|n| n.proxy.is_some()).collect();
+        assert_eq!(proxied.len(), 1);
+        assert_eq!((proxied[0].index, proxied[0].to), (11, Some(Address::from_low_u64_be(0x10))));
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_storage_is_unresolved() {
+        let trace = node(1, 0x50, "0x", "CALL", Some(vec![node(0x50, 0x20, "0x", "DELEGATECALL", None)]));
+        let resolver = ProxyResolver::new(Arc::new(StorageRpc { reads: AtomicUsize::new(0) }));
+        assert!(resolver.resolve(&tree(&trace), None).await.is_empty());
+        assert_eq!(resolver.implementation_of(Address::from_low_u64_be(0x30), None).await, Some(Address::from_low_u64_be(0x99)));
+    }
+}
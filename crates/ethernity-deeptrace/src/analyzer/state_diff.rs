@@ -0,0 +1,217 @@
+use crate::utils;
+use crate::{AccountDiff, DeepTraceError, StateDiff, StorageChange, ValueChange};
+use ethereum_types::{H256, U256};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Obtém e interpreta as alterações de estado de uma transação.
+///
+/// Usa o `prestateTracer` em modo diff, permitindo que detectores leiam
+/// mudanças de reservas e saldos diretamente do storage em vez de inferi-las
+/// a partir de eventos.
+pub struct StateDiffAnalyzer {
+    rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>,
+}
+
+impl StateDiffAnalyzer {
+    pub fn new(rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Obtém o diff de estado da transação pelo provedor RPC
+    pub async fn analyze(&self, tx_hash: H256) -> Result<StateDiff, DeepTraceError> {
+        let bytes = self.rpc_client.get_state_diff(tx_hash).await?;
+        let value: Value = serde_json::from_slice(&bytes).map_err(|e| DeepTraceError::TraceDecode(e.to_string()))?;
+        Self::parse(&value)
+    }
+
+    /// Interpreta a saída `{"pre": ..., "post": ...}` do `prestateTracer` em modo diff.
+    ///
+    /// Em `pre` ficam os valores anteriores dos campos alterados e em `post`
+    /// apenas os novos valores. Slots ausentes de um dos lados valem zero e
+    /// contas ausentes de `post` foram removidas.
+    pub fn parse(value: &Value) -> Result<StateDiff, DeepTraceError> {
+        let side = |key: &str| -> Result<Map<String, Value>, DeepTraceError> {
+            match value.get(key) {
+                None | Some(Value::Null) => Ok(Map::new()),
+                Some(Value::Object(accounts)) => Ok(accounts.clone()),
+                Some(_) => Err(DeepTraceError::TraceDecode(format!("campo `{}` do diff de estado não é um objeto", key))),
+            }
+        };
+        let pre = side("pre")?;
+        let post = side("post")?;
+
+        let addresses: BTreeSet<&String> = pre.keys().chain(post.keys()).collect();
+        let accounts = addresses
+            .into_iter()
+            .map(|address| account_diff(address, pre.get(address), post.get(address)))
+            .filter(|account| {
+                account.balance.is_some()
+                    || account.nonce.is_some()
+                    || account.code_changed
+                    || !account.storage.is_empty()
+                    || account.created
+                    || account.destroyed
+            })
+            .collect();
+
+        Ok(StateDiff { accounts })
+    }
+}
+
+fn account_diff(address: &str, pre: Option<&Value>, post: Option<&Value>) -> AccountDiff {
+    let created = pre.is_none();
+    let destroyed = pre.is_some() && post.is_none();
+
+    // Campos ausentes em `post` não mudaram, exceto quando a conta foi removida
+    let change = |field: &str| -> (Option<&Value>, Option<&Value>) {
+        let before = pre.and_then(|p| p.get(field));
+        let after = match post {
+            Some(post) => post.get(field).or(before),
+            None => None,
+        };
+        (before, after)
+    };
+
+    let (before, after) = change("balance");
+    let balance = ValueChange { before: quantity(before), after: quantity(after) };
+    let (before, after) = change("nonce");
+    let nonce = ValueChange { before: nonce(before), after: nonce(after) };
+    let (before, after) = change("code");
+    let code_changed = code(before) != code(after);
+
+    AccountDiff {
+        address: utils::parse_address(address),
+        balance: (balance.before != balance.after).then_some(balance),
+        nonce: (nonce.before != nonce.after).then_some(nonce),
+        code_changed,
+        storage: storage_changes(pre, post),
+        created,
+        destroyed,
+    }
+}
+
+fn storage_changes(pre: Option<&Value>, post: Option<&Value>) -> Vec<StorageChange> {
+    let storage = |account: Option<&Value>| account.and_then(|a| a.get("storage")).and_then(|s| s.as_object()).cloned().unwrap_or_default();
+    let before = storage(pre);
+    let after = storage(post);
+
+    let slots: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    slots
+        .into_iter()
+        .map(|slot| StorageChange {
+            slot: word(Some(&Value::String(slot.clone()))),
+            before: word(before.get(slot)),
+            after: word(after.get(slot)),
+        })
+        .filter(|change| change.before != change.after)
+        .collect()
+}
+
+fn quantity(value: Option<&Value>) -> U256 {
+    value.and_then(|v| v.as_str()).map(utils::parse_quantity).unwrap_or_default()
+}
+
+/// O Geth retorna o nonce como número; outras implementações como quantidade hex
+fn nonce(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or(0),
+        other => quantity(other).low_u64(),
+    }
+}
+
+fn code(value: Option<&Value>) -> Vec<u8> {
+    value.and_then(|v| v.as_str()).map(utils::decode_hex).unwrap_or_default()
+}
+
+fn word(value: Option<&Value>) -> H256 {
+    let bytes = value.and_then(|v| v.as_str()).map(utils::decode_hex).unwrap_or_default();
+    let mut word = [0u8; 32];
+    let len = bytes.len().min(32);
+    word[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    H256::from(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ethereum_types::Address;
+    use serde_json::json;
+
+    const POOL: &str = "0x0000000000000000000000000000000000000002";
+    const RESERVES_SLOT: &str = "0x0000000000000000000000000000000000000000000000000000000000000008";
+
+    fn sample_diff() -> Value {
+        json!({
+            "pre": {
+                "0x0000000000000000000000000000000000000001": {"balance": "0x10", "nonce": 1},
+                POOL: {
+                    "balance": "0x0",
+                    "nonce": 1,
+                    "code": "0x6000",
+                    "storage": {
+                        RESERVES_SLOT: "0x0000000000000000000000000000000000000000000000000000000000000064",
+                        "0x0000000000000000000000000000000000000000000000000000000000000009": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    }
+                },
+                "0x0000000000000000000000000000000000000004": {"balance": "0x1", "nonce": 0, "code": "0x00"}
+            },
+            "post": {
+                "0x0000000000000000000000000000000000000001": {"balance": "0x8", "nonce": 2},
+                POOL: {"storage": {RESERVES_SLOT: "0x0000000000000000000000000000000000000000000000000000000000000032"}},
+                "0x0000000000000000000000000000000000000003": {"balance": "0x5", "nonce": 1, "code": "0x6001"}
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_state_diff() {
+        let diff = StateDiffAnalyzer::parse(&sample_diff()).unwrap();
+        assert_eq!(diff.accounts.len(), 4);
+
+        let sender = diff.account(&Address::from_low_u64_be(1)).unwrap();
+        assert_eq!(sender.balance, Some(ValueChange { before: U256::from(16u64), after: U256::from(8u64) }));
+        assert_eq!(sender.nonce, Some(ValueChange { before: 1, after: 2 }));
+        assert!(sender.storage.is_empty());
+
+        let pool = diff.account(&Address::from_low_u64_be(2)).unwrap();
+        assert!(pool.balance.is_none() && pool.nonce.is_none() && !pool.code_changed);
+        assert_eq!(pool.storage.len(), 2);
+        let reserves = pool.storage_change(&H256::from_low_u64_be(8)).unwrap();
+        assert_eq!(reserves.before, H256::from_low_u64_be(100));
+        assert_eq!(reserves.after, H256::from_low_u64_be(50));
+        // Slots ausentes de `post` foram zerados
+        assert_eq!(pool.storage_change(&H256::from_low_u64_be(9)).unwrap().after, H256::zero());
+
+        let created = diff.account(&Address::from_low_u64_be(3)).unwrap();
+        assert!(created.created && created.code_changed);
+        let destroyed = diff.account(&Address::from_low_u64_be(4)).unwrap();
+        assert!(destroyed.destroyed && destroyed.code_changed);
+        assert_eq!(destroyed.balance.unwrap().after, U256::zero());
+
+        assert!(StateDiffAnalyzer::parse(&json!({"pre": []})).is_err());
+    }
+
+    struct DiffRpc;
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for DiffRpc {
+        async fn get_transaction_trace(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_transaction_receipt(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_code(&self, _address: Address) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(0) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<H256> { Ok(H256::zero()) }
+        async fn get_state_diff(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> {
+            Ok(serde_json::to_vec(&sample_diff()).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_fetches_from_provider() {
+        let diff = StateDiffAnalyzer::new(Arc::new(DiffRpc)).analyze(H256::zero()).await.unwrap();
+        assert_eq!(diff.accounts.len(), 4);
+    }
+}
@@ -53,14 +53,15 @@ mod tests {
         let child = CallNode {
             index:1, depth:1, call_type:CallType::Call,
             from: addr(1), to: Some(addr(2)), value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(),
-            input: vec![], output: vec![], error: Some("err".into()), children: vec![]
+            input: vec![], output: vec![], error: Some("err".into()), children: vec![],
+            parent: Some(0), proxy: None,
         };
         let root = CallNode {
             index:0, depth:0, call_type:CallType::Call,
             from: addr(0), to: Some(addr(1)), value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(),
-            input: vec![], output: vec![], error: None, children: vec![child.clone()]};
-        let call_tree = CallTree{root};
-        let result = TraceAnalysisResult{ call_tree, token_transfers: vec![TokenTransfer{token_type:TokenType::Erc20, token_address:addr(3), from:addr(0), to:addr(1), amount:U256::one(), token_id:None, call_index:0}], contract_creations: vec![ContractCreation{creator:addr(0), contract_address:addr(4), init_code:vec![], contract_type:ContractType::Unknown, call_index:0}], execution_path: vec![ExecutionStep{depth:0,call_type:CallType::Call,from:addr(0),to:addr(1),value:U256::zero(),input:vec![],output:vec![],gas_used:U256::one(),error:None}, ExecutionStep{depth:1,call_type:CallType::Call,from:addr(1),to:addr(2),value:U256::zero(),input:vec![],output:vec![],gas_used:U256::from(2u64),error:None}] };
+            input: vec![], output: vec![], error: None, children: vec![1], parent: None, proxy: None};
+        let call_tree = CallTree::from_nodes(vec![root, child]);
+        let result = TraceAnalysisResult{ call_tree, token_transfers: vec![TokenTransfer{token_type:TokenType::Erc20, token_address:addr(3), from:addr(0), to:addr(1), amount:U256::one(), token_id:None, call_index:0}], swaps: vec![], liquidity_changes: vec![], approvals: vec![], eth_transfers: vec![], contract_creations: vec![ContractCreation{creator:addr(0), contract_address:addr(4), init_code:vec![], contract_type:ContractType::Unknown, call_index:0}], execution_path: vec![ExecutionStep{depth:0,call_type:CallType::Call,from:addr(0),to:addr(1),value:U256::zero(),input:vec![],output:vec![],gas_used:U256::one(),error:None}, ExecutionStep{depth:1,call_type:CallType::Call,from:addr(1),to:addr(2),value:U256::zero(),input:vec![],output:vec![],gas_used:U256::from(2u64),error:None}], state_diff: None, storage_writes: vec![] };
         let stats = result.calculate_stats(42);
         assert_eq!(stats.total_calls, 2);
         assert_eq!(stats.failed_calls, 1);
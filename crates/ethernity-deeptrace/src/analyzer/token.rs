@@ -1,26 +1,54 @@
-use crate::{TokenTransfer, TokenType};
+use crate::{DeepTraceError, TokenTransfer, TokenType};
 use crate::utils;
 use ethereum_types::U256;
 
-pub async fn extract_token_transfers(receipt: &serde_json::Value) -> Result<Vec<TokenTransfer>, ()> {
+/// `Transfer(address,address,uint256)` (ERC20 e ERC721)
+const TRANSFER_SIG: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// `TransferSingle(address,address,address,uint256,uint256)` (ERC1155)
+const TRANSFER_SINGLE_SIG: &str = "0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62";
+/// `TransferBatch(address,address,address,uint256[],uint256[])` (ERC1155)
+const TRANSFER_BATCH_SIG: &str = "0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb";
+
+/// Extrai as transferências de tokens dos logs do recibo.
+///
+/// `log_calls` associa cada log (pela posição no recibo) ao índice da chamada
+/// que o emitiu; logs sem associação ficam com a chamada raiz.
+pub async fn extract_token_transfers(receipt: &serde_json::Value, log_calls: &[usize]) -> Result<Vec<TokenTransfer>, DeepTraceError> {
     let mut transfers = Vec::new();
     if let Some(logs) = receipt.get("logs").and_then(|l| l.as_array()) {
         for (log_index, log) in logs.iter().enumerate() {
-            if let Some(tr) = parse_token_transfer_log(log, log_index).await? {
-                transfers.push(tr);
+            let call_index = log_calls.get(log_index).copied().unwrap_or(0);
+            let topic0 = log
+                .get("topics")
+                .and_then(|t| t.as_array())
+                .and_then(|t| t.first())
+                .and_then(|t| t.as_str())
+                .unwrap_or("")
+                .to_lowercase();
+            match topic0.as_str() {
+                TRANSFER_SINGLE_SIG => {
+                    if let Some(tr) = parse_erc1155_single_log(log, call_index)? {
+                        transfers.push(tr);
+                    }
+                }
+                TRANSFER_BATCH_SIG => transfers.extend(parse_erc1155_batch_log(log, call_index)?),
+                _ => {
+                    if let Some(tr) = parse_token_transfer_log(log, call_index).await? {
+                        transfers.push(tr);
+                    }
+                }
             }
         }
     }
     Ok(transfers)
 }
 
-async fn parse_token_transfer_log(log: &serde_json::Value, call_index: usize) -> Result<Option<TokenTransfer>, ()> {
+async fn parse_token_transfer_log(log: &serde_json::Value, call_index: usize) -> Result<Option<TokenTransfer>, DeepTraceError> {
     let topics = match log.get("topics").and_then(|t| t.as_array()) {
         Some(t) if t.len() >= 3 => t,
         _ => return Ok(None),
     };
-    let transfer_sig = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
-    if topics[0].as_str().unwrap_or("") != transfer_sig { return Ok(None); }
+    if !topics[0].as_str().unwrap_or("").eq_ignore_ascii_case(TRANSFER_SIG) { return Ok(None); }
     let from = utils::parse_address(topics[1].as_str().unwrap_or(""));
     let to = utils::parse_address(topics[2].as_str().unwrap_or(""));
     let (token_type, amount, token_id) = if topics.len() == 4 {
@@ -36,6 +64,74 @@ async fn parse_token_transfer_log(log: &serde_json::Value, call_index: usize) ->
     Ok(Some(TokenTransfer { token_type, token_address, from, to, amount, token_id, call_index }))
 }
 
+/// Endereços `from`/`to` dos eventos ERC1155 (tópicos 2 e 3; o tópico 1 é o operador)
+fn erc1155_parties(log: &serde_json::Value) -> Option<(ethereum_types::Address, ethereum_types::Address)> {
+    let topics = log.get("topics").and_then(|t| t.as_array()).filter(|t| t.len() >= 4)?;
+    let from = utils::parse_address(topics[2].as_str().unwrap_or(""));
+    let to = utils::parse_address(topics[3].as_str().unwrap_or(""));
+    Some((from, to))
+}
+
+/// Divide o campo `data` de um log em palavras ABI de 32 bytes
+fn data_words(log: &serde_json::Value) -> Vec<U256> {
+    let data = utils::decode_hex(log.get("data").and_then(|d| d.as_str()).unwrap_or(""));
+    data.chunks_exact(32).map(U256::from_big_endian).collect()
+}
+
+fn parse_erc1155_single_log(log: &serde_json::Value, call_index: usize) -> Result<Option<TokenTransfer>, DeepTraceError> {
+    let (from, to) = match erc1155_parties(log) {
+        Some(parties) => parties,
+        None => return Ok(None),
+    };
+    let words = data_words(log);
+    if words.len() < 2 { return Ok(None); }
+    let token_address = utils::parse_address(log.get("address").and_then(|a| a.as_str()).unwrap_or(""));
+    Ok(Some(TokenTransfer {
+        token_type: TokenType::Erc1155,
+        token_address,
+        from,
+        to,
+        amount: words[1],
+        token_id: Some(words[0]),
+        call_index,
+    }))
+}
+
+fn parse_erc1155_batch_log(log: &serde_json::Value, call_index: usize) -> Result<Vec<TokenTransfer>, DeepTraceError> {
+    let (from, to) = match erc1155_parties(log) {
+        Some(parties) => parties,
+        None => return Ok(Vec::new()),
+    };
+    let words = data_words(log);
+    // `data` = offset(ids), offset(values), seguidos dos arrays dinâmicos
+    let read_array = |offset_word: usize| -> Option<Vec<U256>> {
+        let offset = words.get(offset_word)?;
+        if *offset > U256::from(usize::MAX / 2) || offset.low_u64() % 32 != 0 { return None; }
+        let start = offset.as_usize() / 32;
+        let len = words.get(start)?;
+        if *len > U256::from(words.len()) { return None; }
+        words.get(start + 1..start + 1 + len.as_usize()).map(|s| s.to_vec())
+    };
+    let (ids, amounts) = match (read_array(0), read_array(1)) {
+        (Some(ids), Some(amounts)) if ids.len() == amounts.len() => (ids, amounts),
+        _ => return Ok(Vec::new()),
+    };
+    let token_address = utils::parse_address(log.get("address").and_then(|a| a.as_str()).unwrap_or(""));
+    Ok(ids
+        .into_iter()
+        .zip(amounts)
+        .map(|(id, amount)| TokenTransfer {
+            token_type: TokenType::Erc1155,
+            token_address,
+            from,
+            to,
+            amount,
+            token_id: Some(id),
+            call_index,
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,7 +176,64 @@ mod tests {
             {"topics": [transfer_sig, "0x0", "0x1"], "data": "0x1"},
             {"topics": ["0x0"]}
         ]});
-        let trs = extract_token_transfers(&receipt).await.unwrap();
+        let trs = extract_token_transfers(&receipt, &[]).await.unwrap();
         assert_eq!(trs.len(), 1);
     }
+
+    fn word(n: u64) -> String {
+        format!("{:064x}", n)
+    }
+
+    #[tokio::test]
+    async fn test_extract_erc1155_single_and_batch() {
+        // Recibo no formato retornado por eth_getTransactionReceipt
+        let operator = "0x000000000000000000000000000000000000000000000000000000000000000a";
+        let from = "0x0000000000000000000000000000000000000000000000000000000000000002";
+        let to = "0x0000000000000000000000000000000000000000000000000000000000000003";
+        let single_data = format!("0x{}{}", word(7), word(25));
+        let batch_data = format!(
+            "0x{}{}{}{}{}{}{}{}",
+            word(0x40), word(0xa0),
+            word(2), word(1), word(2),
+            word(2), word(10), word(20)
+        );
+        let receipt = json!({"status": "0x1", "logs": [
+            {
+                "address": "0x0000000000000000000000000000000000000010",
+                "topics": [TRANSFER_SINGLE_SIG, operator, from, to],
+                "data": single_data,
+                "logIndex": "0x0"
+            },
+            {
+                "address": "0x0000000000000000000000000000000000000010",
+                "topics": [TRANSFER_BATCH_SIG, operator, from, to],
+                "data": batch_data,
+                "logIndex": "0x1"
+            }
+        ]});
+        let trs = extract_token_transfers(&receipt, &[3, 5]).await.unwrap();
+        assert_eq!(trs.len(), 3);
+        assert!(trs.iter().all(|t| t.token_type == TokenType::Erc1155));
+        assert_eq!(trs[0].token_id, Some(U256::from(7u64)));
+        assert_eq!(trs[0].amount, U256::from(25u64));
+        assert_eq!(trs[0].from, ethereum_types::Address::from_low_u64_be(2));
+        assert_eq!(trs[0].to, ethereum_types::Address::from_low_u64_be(3));
+        assert_eq!(trs[1].token_id, Some(U256::from(1u64)));
+        assert_eq!(trs[1].amount, U256::from(10u64));
+        assert_eq!(trs[2].token_id, Some(U256::from(2u64)));
+        assert_eq!(trs[2].amount, U256::from(20u64));
+        assert_eq!(trs[0].call_index, 3);
+        assert_eq!(trs[2].call_index, 5);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_erc1155_logs_are_ignored() {
+        let topic = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let receipt = json!({"logs": [
+            {"topics": [TRANSFER_SINGLE_SIG, topic, topic, topic], "data": "0x01"},
+            {"topics": [TRANSFER_BATCH_SIG, topic, topic], "data": "0x"},
+            {"topics": [TRANSFER_BATCH_SIG, topic, topic, topic], "data": format!("0x{}{}", word(0x1000), word(0x40))}
+        ]});
+        assert!(extract_token_transfers(&receipt, &[]).await.unwrap().is_empty());
+    }
 }
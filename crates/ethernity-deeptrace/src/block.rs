@@ -0,0 +1,278 @@
+use crate::{BlockMevCorrelator, DeepTraceError, TransactionAnalysis};
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Quantidade de fluxos mantidos por ativo em `BlockAnalysis::top_value_flows`
+const TOP_VALUE_FLOWS_PER_ASSET: usize = 5;
+
+/// Fluxos agregados por (ativo, origem, destino): valor total e transações envolvidas
+type FlowMap = HashMap<(Option<Address>, Address, Address), (U256, HashSet<H256>)>;
+
+/// Resultado da análise de todas as transações de um bloco
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockAnalysis {
+    pub block_number: u64,
+    /// Transações analisadas, na ordem do bloco
+    pub transactions: Vec<TransactionAnalysis>,
+    /// Transações cuja análise falhou
+    pub failed_transactions: Vec<(H256, DeepTraceError)>,
+    /// Maiores fluxos de valor de cada ativo do bloco, agrupados por ativo (ETH
+    /// primeiro) e em ordem decrescente dentro do ativo. Quantidades de ativos
+    /// diferentes não são comparáveis entre si.
+    pub top_value_flows: Vec<ValueFlow>,
+    /// Remetentes com mais de uma transação no bloco
+    pub repeated_actors: Vec<ActorActivity>,
+    /// Candidatos a MEV identificados entre as transações
+    pub mev_candidates: Vec<MevCandidate>,
+}
+
+/// Fluxo de valor agregado entre dois endereços no bloco
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueFlow {
+    /// Token transferido; `None` para ETH nativo
+    pub asset: Option<Address>,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    pub transactions: usize,
+}
+
+/// Atividade de um remetente no bloco
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActorActivity {
+    pub address: Address,
+    pub transactions: Vec<H256>,
+}
+
+/// Candidato a MEV
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MevCandidate {
+    pub kind: MevCandidateKind,
+    pub actor: Address,
+    /// Token do sandwich (comprado no front-run) ou da arbitragem
+    pub asset: Address,
+    /// Transações envolvidas, na ordem do bloco
+    pub transactions: Vec<H256>,
+}
+
+/// Tipo de candidato a MEV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MevCandidateKind {
+    /// Sandwich do `BlockMevCorrelator`: o ator compra e vende o token no mesmo pool ao redor de outra transação
+    Sandwich,
+    /// Um ator termina a transação com mais do token que enviou
+    Arbitrage,
+}
+
+impl BlockAnalysis {
+    /// Constrói a análise do bloco calculando os agregados entre transações
+    pub fn new(
+        block_number: u64,
+        transactions: Vec<TransactionAnalysis>,
+        failed_transactions: Vec<(H256, DeepTraceError)>,
+    ) -> Self {
+        let top_value_flows = top_value_flows(&transactions);
+        let repeated_actors = repeated_actors(&transactions);
+        let mut mev_candidates = sandwich_candidates(&transactions);
+        mev_candidates.extend(arbitrage_candidates(&transactions));
+
+        Self {
+            block_number,
+            transactions,
+            failed_transactions,
+            top_value_flows,
+            repeated_actors,
+            mev_candidates,
+        }
+    }
+}
+
+fn top_value_flows(transactions: &[TransactionAnalysis]) -> Vec<ValueFlow> {
+    let mut flows = FlowMap::new();
+
+    for tx in transactions {
+        let tokens = tx.token_transfers.iter().map(|t| (Some(t.token_address), t.from, t.to, t.amount));
+        let eth = tx.eth_transfers.iter().map(|t| (None, t.from, t.to, t.amount));
+        for (asset, from, to, amount) in tokens.chain(eth) {
+            let entry = flows.entry((asset, from, to)).or_default();
+            entry.0 = entry.0.saturating_add(amount);
+            entry.1.insert(tx.tx_hash);
+        }
+    }
+
+    let mut by_asset: BTreeMap<Option<Address>, Vec<ValueFlow>> = BTreeMap::new();
+    for ((asset, from, to), (amount, txs)) in flows {
+        by_asset.entry(asset).or_default().push(ValueFlow { asset, from, to, amount, transactions: txs.len() });
+    }
+
+    by_asset
+        .into_values()
+        .flat_map(|mut flows| {
+            flows.sort_by_key(|f| (Reverse(f.amount), f.from, f.to));
+            flows.truncate(TOP_VALUE_FLOWS_PER_ASSET);
+            flows
+        })
+        .collect()
+}
+
+fn repeated_actors(transactions: &[TransactionAnalysis]) -> Vec<ActorActivity> {
+    let mut order = Vec::new();
+    let mut by_sender: HashMap<Address, Vec<H256>> = HashMap::new();
+    for tx in transactions {
+        by_sender
+            .entry(tx.from)
+            .or_insert_with(|| {
+                order.push(tx.from);
+                Vec::new()
+            })
+            .push(tx.tx_hash);
+    }
+
+    let mut actors: Vec<ActorActivity> = order
+        .into_iter()
+        .filter_map(|address| {
+            let transactions = by_sender.remove(&address)?;
+            (transactions.len() > 1).then_some(ActorActivity { address, transactions })
+        })
+        .collect();
+    actors.sort_by_key(|a| Reverse(a.transactions.len()));
+    actors
+}
+
+/// Endereços controlados pelo ator da transação (remetente e contrato chamado)
+fn actor_addresses(tx: &TransactionAnalysis) -> Vec<Address> {
+    let mut addresses = vec![tx.from];
+    addresses.extend(tx.to);
+    addresses
+}
+
+/// Sandwiches do `BlockMevCorrelator` como candidatos do bloco
+fn sandwich_candidates(transactions: &[TransactionAnalysis]) -> Vec<MevCandidate> {
+    BlockMevCorrelator::new()
+        .correlate_transactions(transactions)
+        .into_iter()
+        .map(|sandwich| {
+            let mut involved = vec![sandwich.front_run];
+            involved.extend(sandwich.victims);
+            involved.push(sandwich.back_run);
+            MevCandidate {
+                kind: MevCandidateKind::Sandwich,
+                actor: sandwich.attacker,
+                asset: sandwich.token_out,
+                transactions: involved,
+            }
+        })
+        .collect()
+}
+
+fn arbitrage_candidates(transactions: &[TransactionAnalysis]) -> Vec<MevCandidate> {
+    let mut candidates = Vec::new();
+
+    for tx in transactions {
+        let distinct_tokens: HashSet<Address> = tx.token_transfers.iter().map(|t| t.token_address).collect();
+        if distinct_tokens.len() < 2 {
+            continue;
+        }
+        for actor in actor_addresses(tx) {
+            let mut balances: HashMap<Address, (U256, U256)> = HashMap::new();
+            for t in &tx.token_transfers {
+                let entry = balances.entry(t.token_address).or_default();
+                if t.from == actor { entry.0 = entry.0.saturating_add(t.amount); }
+                if t.to == actor { entry.1 = entry.1.saturating_add(t.amount); }
+            }
+            let profitable = balances
+                .iter()
+                .find(|(_, (sent, received))| !sent.is_zero() && received > sent);
+            if let Some((token, _)) = profitable {
+                candidates.push(MevCandidate {
+                    kind: MevCandidateKind::Arbitrage,
+                    actor,
+                    asset: *token,
+                    transactions: vec![tx.tx_hash],
+                });
+                break;
+            }
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallType, DexProtocol, EthTransfer, SwapAction, TokenTransfer, TokenType};
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn transfer(token: u64, from: u64, to: u64, amount: u64) -> TokenTransfer {
+        TokenTransfer { token_type: TokenType::Erc20, token_address: addr(token), from: addr(from), to: addr(to), amount: U256::from(amount), token_id: None, call_index: 0 }
+    }
+
+    fn tx(hash: u64, from: u64, to: u64, transfers: Vec<TokenTransfer>) -> TransactionAnalysis {
+        TransactionAnalysis { token_transfers: transfers, ..TransactionAnalysis::empty(H256::from_low_u64_be(hash), addr(from), Some(addr(to))) }
+    }
+
+    fn swap(token_in: u64, token_out: u64, amount_in: u64, amount_out: u64) -> SwapAction {
+        SwapAction {
+            protocol: DexProtocol::UniswapV2, pool: addr(50), pool_id: None, token_in: addr(token_in), token_out: addr(token_out),
+            amount_in: U256::from(amount_in), amount_out: U256::from(amount_out), log_index: 0, call_index: 0,
+        }
+    }
+
+    fn swapping(mut tx: TransactionAnalysis, swap: SwapAction) -> TransactionAnalysis {
+        tx.swaps = vec![swap];
+        tx
+    }
+
+    #[test]
+    fn test_sandwich_and_repeated_actor() {
+        // 100 = token, 200 = WETH, 50 = pool, 1 = bot, 2 = vítima
+        let txs = vec![
+            swapping(tx(1, 1, 1, vec![transfer(100, 50, 1, 10)]), swap(200, 100, 20, 10)),
+            swapping(tx(2, 2, 60, vec![transfer(100, 50, 2, 5)]), swap(200, 100, 11, 5)),
+            swapping(tx(3, 1, 1, vec![transfer(100, 1, 50, 10)]), swap(100, 200, 10, 22)),
+            // Só transferências do token, sem swap no pool: não é vítima nem back-run
+            tx(4, 1, 1, vec![transfer(100, 1, 50, 3)]),
+        ];
+        let analysis = BlockAnalysis::new(1, txs, vec![]);
+
+        assert_eq!(analysis.repeated_actors.len(), 1);
+        assert_eq!(analysis.repeated_actors[0].address, addr(1));
+        assert_eq!(analysis.repeated_actors[0].transactions.len(), 3);
+
+        assert_eq!(analysis.mev_candidates.len(), 1);
+        let candidate = &analysis.mev_candidates[0];
+        assert_eq!(candidate.kind, MevCandidateKind::Sandwich);
+        assert_eq!(candidate.asset, addr(100));
+        assert_eq!(candidate.transactions, vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2), H256::from_low_u64_be(3)]);
+    }
+
+    #[test]
+    fn test_arbitrage_and_top_value_flows() {
+        let mut arb = tx(1, 1, 9, vec![
+            transfer(100, 9, 50, 100),
+            transfer(200, 50, 51, 40),
+            transfer(100, 51, 9, 120),
+        ]);
+        arb.eth_transfers = vec![EthTransfer { from: addr(1), to: addr(9), amount: U256::from(1000u64), call_type: CallType::Call, call_index: 0 }];
+        let analysis = BlockAnalysis::new(1, vec![arb, tx(2, 3, 4, vec![transfer(100, 9, 50, 1)])], vec![]);
+
+        assert_eq!(analysis.mev_candidates.len(), 1);
+        assert_eq!(analysis.mev_candidates[0].kind, MevCandidateKind::Arbitrage);
+        assert_eq!(analysis.mev_candidates[0].actor, addr(9));
+
+        assert_eq!(analysis.top_value_flows[0].asset, None);
+        assert_eq!(analysis.top_value_flows[0].amount, U256::from(1000u64));
+        let flow = analysis.top_value_flows.iter().find(|f| f.asset == Some(addr(100)) && f.from == addr(9)).unwrap();
+        assert_eq!(flow.amount, U256::from(101u64));
+        assert_eq!(flow.transactions, 2);
+        // Cada ativo é ordenado separadamente: os 40 do token 200 não competem com os valores do token 100
+        let assets: Vec<_> = analysis.top_value_flows.iter().map(|f| f.asset).collect();
+        assert_eq!(assets, vec![None, Some(addr(100)), Some(addr(100)), Some(addr(200))]);
+        assert_eq!(analysis.top_value_flows[1].amount, U256::from(120u64));
+        assert!(analysis.repeated_actors.is_empty());
+    }
+}
@@ -1,23 +1,71 @@
+use crate::{DetectedPattern, PatternType};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Valor padrão das opções habilitadas em `Default`, para configurações
+/// serializadas antes da opção existir
+fn enabled() -> bool {
+    true
+}
 
 /// Configuração para detecção de padrões
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternDetectionConfig {
     /// Habilita detecção de padrões de token ERC20
     pub detect_erc20: bool,
+    /// Habilita detecção de flash loans (Aave, Balancer, Uniswap V3 e dYdX)
+    #[serde(default = "enabled")]
+    pub detect_flash_loans: bool,
+    /// Habilita detecção de reentrância
+    #[serde(default = "enabled")]
+    pub detect_reentrancy: bool,
+    /// Confiança mínima para um padrão entrar em `detected_patterns`; substitui a
+    /// `PatternDetector::min_confidence` de cada detector quando definida
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Confiança mínima por tipo de padrão; substitui `min_confidence` para o tipo
+    #[serde(default)]
+    pub min_confidence_by_type: HashMap<PatternType, f64>,
+}
+
+impl PatternDetectionConfig {
+    /// O padrão atinge a confiança mínima do seu tipo: a de `min_confidence_by_type`,
+    /// a de `min_confidence` ou, sem nenhuma delas, a `detector_min` do detector que o emitiu
+    pub fn accepts(&self, pattern: &DetectedPattern, detector_min: f64) -> bool {
+        let threshold = self
+            .min_confidence_by_type
+            .get(&pattern.pattern_type)
+            .copied()
+            .or(self.min_confidence)
+            .unwrap_or(detector_min);
+        pattern.confidence >= threshold
+    }
 }
 
 impl Default for PatternDetectionConfig {
     fn default() -> Self {
-        Self { detect_erc20: true }
+        Self {
+            detect_erc20: true,
+            detect_flash_loans: true,
+            detect_reentrancy: true,
+            min_confidence: None,
+            min_confidence_by_type: HashMap::new(),
+        }
     }
 }
 
+fn default_block_concurrency() -> usize {
+    16
+}
+
 /// Configuração para análise de traces
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceAnalysisConfig {
     /// Profundidade máxima de análise recursiva
     pub max_depth: usize,
+    /// Falha com `DeepTraceError::DepthLimit` em vez de truncar a árvore em `max_depth`
+    #[serde(default)]
+    pub strict_depth: bool,
     /// Limite de memória em bytes
     pub memory_limit: usize,
     /// Timeout para análise em milissegundos
@@ -26,6 +74,15 @@ pub struct TraceAnalysisConfig {
     pub enable_cache: bool,
     /// Habilita análise paralela quando possível
     pub enable_parallel: bool,
+    /// Transações de um bloco analisadas ao mesmo tempo com `enable_parallel`
+    #[serde(default = "default_block_concurrency")]
+    pub block_concurrency: usize,
+    /// Obtém as alterações de estado (`prestateTracer`) em uma chamada RPC adicional
+    #[serde(default)]
+    pub enable_state_diff: bool,
+    /// Resolve chamadas a proxies EIP-1967 lendo o slot de implementação via RPC
+    #[serde(default = "enabled")]
+    pub enable_proxy_resolution: bool,
     /// Habilita detecção de padrões específicos
     pub pattern_detection: PatternDetectionConfig,
 }
@@ -34,11 +91,93 @@ impl Default for TraceAnalysisConfig {
     fn default() -> Self {
         Self {
             max_depth: 10,
+            strict_depth: false,
             memory_limit: 100 * 1024 * 1024, // 100 MB
             timeout_ms: 30000, // 30 segundos
             enable_cache: true,
             enable_parallel: true,
+            block_concurrency: default_block_concurrency(),
+            enable_state_diff: false,
+            enable_proxy_resolution: true,
             pattern_detection: PatternDetectionConfig::default(),
         }
     }
 }
+
+/// Configuração do detector de wash trading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WashTradingConfig {
+    /// Quantidade de blocos recentes considerados
+    pub window_blocks: u64,
+    /// Tamanho máximo do grupo de endereços que troca valor entre si
+    pub max_cluster_size: usize,
+    /// Mínimo de transferências internas ao grupo para sinalizar
+    pub min_transfers: usize,
+    /// Desequilíbrio máximo (saldo líquido / volume) em pontos-base
+    pub max_imbalance_bps: u64,
+}
+
+impl Default for WashTradingConfig {
+    fn default() -> Self {
+        Self {
+            window_blocks: 10,
+            max_cluster_size: 4,
+            min_transfers: 3,
+            max_imbalance_bps: 1000, // 10%
+        }
+    }
+}
+
+/// Configuração do `DeepTraceService`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    /// Blocos aguardando análise; com a fila cheia o consumo de heads pausa
+    pub block_queue: usize,
+    /// Análises de transações aguardando o sink
+    pub result_queue: usize,
+    /// Intervalo de consulta do bloco atual em `run_polling`, em milissegundos
+    pub poll_interval_ms: u64,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            block_queue: 8,
+            result_queue: 256,
+            poll_interval_ms: 2000, // 2 segundos
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_config_serialized_before_new_options() {
+        // Formato de `TraceAnalysisConfig` anterior às opções de profundidade estrita, concorrência, state diff, proxies e padrões
+        let baseline = r#"{
+            "max_depth": 12,
+            "memory_limit": 1048576,
+            "timeout_ms": 5000,
+            "enable_cache": false,
+            "enable_parallel": true,
+            "pattern_detection": { "detect_erc20": false }
+        }"#;
+        let config: TraceAnalysisConfig = serde_json::from_str(baseline).unwrap();
+        let defaults = TraceAnalysisConfig::default();
+        assert_eq!((config.max_depth, config.timeout_ms, config.enable_cache), (12, 5000, false));
+        assert_eq!(config.strict_depth, defaults.strict_depth);
+        assert_eq!(config.enable_state_diff, defaults.enable_state_diff);
+        assert_eq!(config.enable_proxy_resolution, defaults.enable_proxy_resolution);
+        assert_eq!(config.block_concurrency, defaults.block_concurrency);
+        let patterns = &config.pattern_detection;
+        assert!(!patterns.detect_erc20);
+        assert!(patterns.detect_flash_loans && patterns.detect_reentrancy);
+        assert_eq!(patterns.min_confidence, None);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let reloaded: TraceAnalysisConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&reloaded).unwrap(), json);
+    }
+}
@@ -1,21 +1,38 @@
 use ethereum_types::{Address, H256, U256};
+use futures::StreamExt;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 
 use crate::{
-    analyzer::{AnalysisContext, TraceAnalysisResult, TraceAnalyzer},
+    analyzer::{with_deadline, AnalysisContext, TraceAnalysisResult, TraceAnalyzer},
+    block::BlockAnalysis,
     config::TraceAnalysisConfig,
+    error::DeepTraceError,
+    failure::{ErrorRegistry, FailureReport},
+    labels::LabelRegistry,
     memory,
-    patterns::{Erc20PatternDetector, PatternDetector},
-    trace::CallTrace,
-    types::{DetectedPattern, TransactionAnalysis},
+    patterns::{Erc20PatternDetector, FlashLoanPatternDetector, PatternDetector, ReentrancyPatternDetector},
+    trace::{CallTrace, StructLogTrace},
+    types::{DetectedPattern, PatternType, TransactionAnalysis},
 };
 
+/// Trace obtido do provedor
+enum FetchedTrace {
+    /// Formato do `callTracer` (ou Parity normalizado)
+    Calls(Box<CallTrace>),
+    /// Trace de opcodes, convertido após a leitura do recibo
+    StructLogs(serde_json::Value),
+}
+
 /// Analisador de traces de transações
 pub struct DeepTraceAnalyzer {
     pub(crate) config: TraceAnalysisConfig,
     pub(crate) rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>,
     pub(crate) memory_manager: Arc<memory::MemoryManager>,
     pub(crate) pattern_detectors: Vec<Box<dyn PatternDetector>>,
+    pub(crate) labels: Option<Arc<LabelRegistry>>,
+    pub(crate) error_registry: Option<Arc<ErrorRegistry>>,
 }
 
 impl DeepTraceAnalyzer {
@@ -30,6 +47,8 @@ impl DeepTraceAnalyzer {
         // Inicializa os detectores de padrões
         let detectors_all: Vec<(bool, Box<dyn PatternDetector>)> = vec![
             (config.pattern_detection.detect_erc20, Box::new(Erc20PatternDetector::new())),
+            (config.pattern_detection.detect_flash_loans, Box::new(FlashLoanPatternDetector::new())),
+            (config.pattern_detection.detect_reentrancy, Box::new(ReentrancyPatternDetector::new())),
         ];
 
         let pattern_detectors: Vec<Box<dyn PatternDetector>> = detectors_all
@@ -42,13 +61,85 @@ impl DeepTraceAnalyzer {
             rpc_client,
             memory_manager,
             pattern_detectors,
+            labels: None,
+            error_registry: None,
         }
     }
 
+    /// Registra um detector de padrões adicional
+    ///
+    /// Os detectores são executados na ordem de registro, após os habilitados
+    /// em `PatternDetectionConfig`.
+    pub fn add_pattern_detector(&mut self, detector: Box<dyn PatternDetector>) {
+        self.pattern_detectors.push(detector);
+    }
+
+    /// Variante de `add_pattern_detector` para encadeamento na construção
+    pub fn with_pattern_detector(mut self, detector: Box<dyn PatternDetector>) -> Self {
+        self.add_pattern_detector(detector);
+        self
+    }
+
+    /// Anexa rótulos e eventos de watchlist a cada análise
+    pub fn set_labels(&mut self, labels: Arc<LabelRegistry>) {
+        self.labels = Some(labels);
+    }
+
+    /// Variante de `set_labels` para encadeamento na construção
+    pub fn with_labels(mut self, labels: Arc<LabelRegistry>) -> Self {
+        self.set_labels(labels);
+        self
+    }
+
+    /// Erros customizados usados para decodificar os reverts no `FailureReport`
+    pub fn set_error_registry(&mut self, registry: Arc<ErrorRegistry>) {
+        self.error_registry = Some(registry);
+    }
+
+    /// Variante de `set_error_registry` para encadeamento na construção
+    pub fn with_error_registry(mut self, registry: Arc<ErrorRegistry>) -> Self {
+        self.set_error_registry(registry);
+        self
+    }
+
+    /// Tipos de padrão dos detectores registrados, na ordem de execução
+    pub fn pattern_types(&self) -> Vec<PatternType> {
+        self.pattern_detectors.iter().map(|d| d.pattern_type()).collect()
+    }
+
     /// Analisa uma transação pelo hash
-    pub async fn analyze_transaction(&self, tx_hash: H256) -> Result<TransactionAnalysis, ()> {
-        let trace = self.fetch_trace(tx_hash).await?;
-        let receipt = self.fetch_receipt(tx_hash).await?;
+    ///
+    /// Todas as fases compartilham o prazo de `timeout_ms`. Se o prazo expirar
+    /// durante a detecção de padrões, a análise do trace é retornada com os
+    /// padrões já detectados e `partial` marcado.
+    pub async fn analyze_transaction(&self, tx_hash: H256) -> Result<TransactionAnalysis, DeepTraceError> {
+        self.analyze_prefetched(tx_hash, None, None).await
+    }
+
+    /// Analisa uma transação reaproveitando trace e recibo já obtidos
+    async fn analyze_prefetched(
+        &self,
+        tx_hash: H256,
+        trace: Option<CallTrace>,
+        receipt: Option<serde_json::Value>,
+    ) -> Result<TransactionAnalysis, DeepTraceError> {
+        let timeout_ms = self.config.timeout_ms;
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let fetched = match trace {
+            Some(trace) => FetchedTrace::Calls(Box::new(trace)),
+            None => with_deadline(deadline, timeout_ms, self.fetch_trace(tx_hash)).await?,
+        };
+        let receipt = match receipt {
+            Some(receipt) => receipt,
+            None => with_deadline(deadline, timeout_ms, self.fetch_receipt(tx_hash)).await?,
+        };
+        let (trace, storage_writes) = match fetched {
+            FetchedTrace::Calls(trace) => (*trace, Vec::new()),
+            FetchedTrace::StructLogs(value) => {
+                let parsed = StructLogTrace::from_json(&value, StructLogTrace::root_from_receipt(&receipt))?;
+                (parsed.call_trace, parsed.storage_writes)
+            }
+        };
         let (block_number, from, to, gas_used, status) = Self::parse_receipt_info(&receipt);
         let timestamp = chrono::Utc::now(); // Simplificado
 
@@ -62,10 +153,11 @@ impl DeepTraceAnalyzer {
         };
 
         let trace_analyzer = TraceAnalyzer::new(context);
-        let analysis = trace_analyzer.analyze(&trace, &receipt).await.map_err(|_| ())?;
-        let patterns = self.detect_patterns(&analysis).await?;
+        let mut analysis = trace_analyzer.analyze_until(&trace, &receipt, deadline).await?;
+        analysis.storage_writes = storage_writes;
+        let (patterns, partial) = self.detect_patterns(&analysis, deadline).await?;
 
-        Ok(Self::build_transaction_analysis(
+        let mut result = Self::build_transaction_analysis(
             tx_hash,
             block_number,
             timestamp,
@@ -75,25 +167,36 @@ impl DeepTraceAnalyzer {
             status,
             analysis,
             patterns,
-        ))
+        );
+        result.partial = partial || result.call_tree.is_truncated();
+        result.failure = FailureReport::from_tree(&result.call_tree, self.error_registry.as_deref());
+        if let Some(labels) = &self.labels {
+            labels.annotate(&mut result);
+        }
+        Ok(result)
     }
 
-    async fn fetch_trace(&self, tx_hash: H256) -> Result<CallTrace, ()> {
+    async fn fetch_trace(&self, tx_hash: H256) -> Result<FetchedTrace, DeepTraceError> {
         let bytes = self
             .rpc_client
             .get_transaction_trace(tx_hash)
-            .await
-            .map_err(|_| ())?;
-        serde_json::from_slice(&bytes).map_err(|_| ())
+            .await?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&bytes).map_err(|e| DeepTraceError::TraceDecode(e.to_string()))?;
+        if StructLogTrace::is_struct_log_trace(&value) {
+            return Ok(FetchedTrace::StructLogs(value));
+        }
+        serde_json::from_value(value)
+            .map(|trace| FetchedTrace::Calls(Box::new(trace)))
+            .map_err(|e| DeepTraceError::TraceDecode(e.to_string()))
     }
 
-    async fn fetch_receipt(&self, tx_hash: H256) -> Result<serde_json::Value, ()> {
+    async fn fetch_receipt(&self, tx_hash: H256) -> Result<serde_json::Value, DeepTraceError> {
         let bytes = self
             .rpc_client
             .get_transaction_receipt(tx_hash)
-            .await
-            .map_err(|_| ())?;
-        serde_json::from_slice(&bytes).map_err(|_| ())
+            .await?;
+        serde_json::from_slice(&bytes).map_err(|e| DeepTraceError::ReceiptDecode(e.to_string()))
     }
 
     fn parse_receipt_info(
@@ -167,25 +270,50 @@ impl DeepTraceAnalyzer {
             status,
             call_tree: analysis.call_tree,
             token_transfers: analysis.token_transfers,
+            swaps: analysis.swaps,
+            liquidity_changes: analysis.liquidity_changes,
+            approvals: analysis.approvals,
+            eth_transfers: analysis.eth_transfers,
             contract_creations: analysis.contract_creations,
             detected_patterns: patterns,
             execution_path: analysis.execution_path,
+            state_diff: analysis.state_diff,
+            storage_writes: analysis.storage_writes,
+            labels: Vec::new(),
+            watchlist_events: Vec::new(),
+            failure: None,
+            partial: false,
         }
     }
 
-    async fn detect_patterns(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, ()> {
+    /// Executa os detectores até o prazo; o booleano indica resultado parcial
+    ///
+    /// Padrões abaixo da confiança mínima configurada (ou, sem configuração, da
+    /// `min_confidence` do detector) são descartados.
+    async fn detect_patterns(
+        &self,
+        analysis: &TraceAnalysisResult,
+        deadline: Instant,
+    ) -> Result<(Vec<DetectedPattern>, bool), DeepTraceError> {
         let mut patterns = Vec::new();
 
         for detector in &self.pattern_detectors {
-            let detected = detector.detect(analysis).await.map_err(|_| ())?;
-            patterns.extend(detected);
+            match tokio::time::timeout_at(deadline, detector.detect(analysis)).await {
+                Ok(detected) => {
+                    let detector_min = detector.min_confidence();
+                    patterns.extend(
+                        detected?.into_iter().filter(|p| self.config.pattern_detection.accepts(p, detector_min)),
+                    )
+                }
+                Err(_) => return Ok((patterns, true)),
+            }
         }
 
-        Ok(patterns)
+        Ok((patterns, false))
     }
 
     /// Analisa um lote de transações
-    pub async fn analyze_batch(&self, tx_hashes: &[H256]) -> Result<Vec<TransactionAnalysis>, ()> {
+    pub async fn analyze_batch(&self, tx_hashes: &[H256]) -> Result<Vec<TransactionAnalysis>, DeepTraceError> {
         let mut results = Vec::with_capacity(tx_hashes.len());
 
         if self.config.enable_parallel {
@@ -217,6 +345,67 @@ impl DeepTraceAnalyzer {
         Ok(results)
     }
 
+    /// Analisa todas as transações de um bloco
+    ///
+    /// Traces e recibos são obtidos em lote (`debug_traceBlockByNumber`/`trace_block`
+    /// e `eth_getBlockReceipts`) quando o provedor suporta; caso contrário cada
+    /// transação é buscada individualmente. Falhas em transações isoladas não
+    /// interrompem a análise do bloco.
+    pub async fn analyze_block(&self, block_number: u64) -> Result<BlockAnalysis, DeepTraceError> {
+        let tx_hashes = self.rpc_client.get_block_transactions(block_number).await?;
+        let mut traces = self.fetch_block_traces(block_number, tx_hashes.len()).await.into_iter();
+        let mut receipts = self.fetch_block_receipts(block_number, tx_hashes.len()).await.into_iter();
+
+        let jobs: Vec<_> = tx_hashes
+            .iter()
+            .map(|&tx_hash| (tx_hash, traces.next().flatten(), receipts.next().flatten()))
+            .collect();
+
+        // Sem traces em lote cada transação faz as próprias chamadas RPC; o limite evita disparar o bloco inteiro
+        let concurrency = if self.config.enable_parallel { self.config.block_concurrency.max(1) } else { 1 };
+        let outcomes: Vec<_> = futures::stream::iter(jobs)
+            .map(|(tx_hash, trace, receipt)| self.analyze_prefetched(tx_hash, trace, receipt))
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        let mut transactions = Vec::with_capacity(outcomes.len());
+        let mut failed = Vec::new();
+        for (tx_hash, outcome) in tx_hashes.into_iter().zip(outcomes) {
+            match outcome {
+                Ok(analysis) => transactions.push(analysis),
+                Err(e) => failed.push((tx_hash, e)),
+            }
+        }
+
+        Ok(BlockAnalysis::new(block_number, transactions, failed))
+    }
+
+    /// Traces do bloco na ordem das transações; vazio se indisponível ou inconsistente
+    async fn fetch_block_traces(&self, block_number: u64, expected: usize) -> Vec<Option<CallTrace>> {
+        let Ok(bytes) = self.rpc_client.get_block_traces(block_number).await else {
+            return Vec::new();
+        };
+        match serde_json::from_slice::<Vec<serde_json::Value>>(&bytes) {
+            Ok(entries) if entries.len() == expected => entries
+                .into_iter()
+                .map(|entry| serde_json::from_value(entry).ok())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Recibos do bloco na ordem das transações; vazio se indisponível ou inconsistente
+    async fn fetch_block_receipts(&self, block_number: u64, expected: usize) -> Vec<Option<serde_json::Value>> {
+        let Ok(bytes) = self.rpc_client.get_block_receipts(block_number).await else {
+            return Vec::new();
+        };
+        match serde_json::from_slice::<Vec<serde_json::Value>>(&bytes) {
+            Ok(entries) if entries.len() == expected => entries.into_iter().map(Some).collect(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Obtém estatísticas de uso de memória
 pub fn memory_stats(&self) -> memory::MemoryUsageStats {
         self.memory_manager.memory_usage()
@@ -228,7 +417,7 @@ mod tests {
     use super::*;
     use async_trait::async_trait;
     use serde_json::json;
-    use crate::{CallNode, CallTree, CallType, PatternType};
+    use crate::{CallNode, CallTree, CallType, PatternDetectionConfig, PatternType};
 
     struct MockRpc {
         trace: Vec<u8>,
@@ -284,7 +473,7 @@ mod tests {
     impl PatternDetector for DummyDetector {
         fn pattern_type(&self) -> PatternType { PatternType::Unknown }
 
-        async fn detect(&self, _analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, ()> {
+        async fn detect(&self, _analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError> {
             Ok(vec![DetectedPattern {
                 pattern_type: PatternType::Unknown,
                 confidence: 1.0,
@@ -327,25 +516,31 @@ mod tests {
 
     fn empty_analysis() -> TraceAnalysisResult {
         TraceAnalysisResult {
-            call_tree: CallTree {
-                root: CallNode {
-                    index: 0,
-                    depth: 0,
-                    call_type: CallType::Call,
-                    from: Address::zero(),
-                    to: None,
-                    value: U256::zero(),
-                    gas: U256::zero(),
-                    gas_used: U256::zero(),
-                    input: Vec::new(),
-                    output: Vec::new(),
-                    error: None,
-                    children: Vec::new(),
-                },
-            },
+            call_tree: CallTree::from_nodes(vec![CallNode {
+                index: 0,
+                depth: 0,
+                call_type: CallType::Call,
+                from: Address::zero(),
+                to: None,
+                value: U256::zero(),
+                gas: U256::zero(),
+                gas_used: U256::zero(),
+                input: Vec::new(),
+                output: Vec::new(),
+                error: None,
+                children: Vec::new(),
+                parent: None,
+                proxy: None,
+            }]),
             token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            liquidity_changes: Vec::new(),
+            approvals: Vec::new(),
+            eth_transfers: Vec::new(),
             contract_creations: Vec::new(),
             execution_path: Vec::new(),
+            state_diff: None,
+            storage_writes: Vec::new(),
         }
     }
 
@@ -359,8 +554,8 @@ mod tests {
         });
         let mut config = TraceAnalysisConfig::default();
         config.enable_parallel = false;
-        let mut analyzer = DeepTraceAnalyzer::new(rpc, Some(config));
-        analyzer.pattern_detectors = vec![Box::new(DummyDetector)];
+        let analyzer = DeepTraceAnalyzer::new(rpc, Some(config)).with_pattern_detector(Box::new(DummyDetector));
+        assert_eq!(analyzer.pattern_types(), vec![PatternType::Erc20Creation, PatternType::FlashLoan, PatternType::Reentrancy, PatternType::Unknown]);
         let res = analyzer.analyze_transaction(H256::zero()).await.unwrap();
         assert_eq!(res.block_number, 16);
         assert_eq!(res.from, Address::from_low_u64_be(1));
@@ -370,12 +565,44 @@ mod tests {
         assert_eq!(res.detected_patterns.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_analyze_struct_log_trace() {
+        let trace = json!({"gas": 21000, "failed": false, "returnValue": "", "structLogs": [
+            {"pc": 0, "op": "SSTORE", "gas": 100, "gasCost": 3, "depth": 1, "stack": ["0x7", "0x1"]},
+            {"pc": 1, "op": "STOP", "gas": 90, "gasCost": 0, "depth": 1, "stack": []}
+        ]});
+        let rpc = Arc::new(MockRpc {
+            trace: serde_json::to_vec(&trace).unwrap(),
+            receipt: sample_receipt_bytes(),
+            fail_trace: false,
+            fail_receipt: false,
+        });
+        let analyzer = DeepTraceAnalyzer::new(rpc, None);
+        assert!(matches!(analyzer.fetch_trace(H256::zero()).await, Ok(FetchedTrace::StructLogs(_))));
+
+        let res = analyzer.analyze_transaction(H256::zero()).await.unwrap();
+        assert_eq!(res.call_tree.root().to, Some(Address::from_low_u64_be(2)));
+        assert!(res.failure.is_none());
+        assert_eq!(res.storage_writes.len(), 1);
+        assert_eq!(res.storage_writes[0].address, Address::from_low_u64_be(2));
+        assert_eq!(res.storage_writes[0].value, H256::from_low_u64_be(7));
+    }
+
     #[tokio::test]
     async fn test_fetch_error_paths() {
         let rpc = Arc::new(MockRpc { trace: vec![], receipt: vec![], fail_trace: true, fail_receipt: true });
         let analyzer = DeepTraceAnalyzer::new(rpc, None);
-        assert!(analyzer.fetch_trace(H256::zero()).await.is_err());
-        assert!(analyzer.fetch_receipt(H256::zero()).await.is_err());
+        assert!(matches!(analyzer.fetch_trace(H256::zero()).await, Err(DeepTraceError::RpcFailure(_))));
+        assert!(matches!(analyzer.fetch_receipt(H256::zero()).await, Err(DeepTraceError::RpcFailure(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decode_errors() {
+        let rpc = Arc::new(MockRpc { trace: b"{}".to_vec(), receipt: b"not json".to_vec(), fail_trace: false, fail_receipt: false });
+        let analyzer = DeepTraceAnalyzer::new(rpc, None);
+        assert!(matches!(analyzer.fetch_trace(H256::zero()).await, Err(DeepTraceError::TraceDecode(_))));
+        assert!(matches!(analyzer.fetch_receipt(H256::zero()).await, Err(DeepTraceError::ReceiptDecode(_))));
+        assert!(matches!(analyzer.analyze_transaction(H256::zero()).await, Err(DeepTraceError::TraceDecode(_))));
     }
 
     #[test]
@@ -417,9 +644,123 @@ mod tests {
             rpc_client: rpc,
             memory_manager: Arc::new(memory::MemoryManager::new()),
             pattern_detectors: vec![Box::new(DummyDetector)],
+            labels: None,
+            error_registry: None,
         };
-        let patterns = analyzer.detect_patterns(&empty_analysis()).await.unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let (patterns, partial) = analyzer.detect_patterns(&empty_analysis(), deadline).await.unwrap();
         assert_eq!(patterns.len(), 1);
+        assert!(!partial);
+    }
+
+    #[tokio::test]
+    async fn test_detect_patterns_applies_confidence_thresholds() {
+        let rpc = Arc::new(MockRpc { trace: vec![], receipt: vec![], fail_trace: false, fail_receipt: false });
+        let mut config = TraceAnalysisConfig::default();
+        config.pattern_detection.min_confidence = Some(0.5);
+        config.pattern_detection.min_confidence_by_type.insert(PatternType::Unknown, 1.5);
+        let analyzer = DeepTraceAnalyzer::new(rpc, Some(config)).with_pattern_detector(Box::new(DummyDetector));
+        let deadline = Instant::now() + Duration::from_secs(1);
+
+        let (patterns, _) = analyzer.detect_patterns(&empty_analysis(), deadline).await.unwrap();
+        assert!(patterns.is_empty());
+
+        let mut low = DummyDetector.detect(&empty_analysis()).await.unwrap().remove(0);
+        low.pattern_type = PatternType::FlashLoan;
+        assert!(analyzer.config.pattern_detection.accepts(&low, 1.0));
+        low.confidence = 0.4;
+        assert!(!analyzer.config.pattern_detection.accepts(&low, 0.0));
+
+        // Sem limiar configurado vale o do detector
+        let defaults = PatternDetectionConfig::default();
+        assert!(defaults.accepts(&low, 0.4) && !defaults.accepts(&low, 0.7));
+
+        // Limiares ausentes na configuração serializada assumem o padrão
+        let decoded: PatternDetectionConfig =
+            serde_json::from_str(r#"{"detect_erc20":true,"detect_flash_loans":false,"detect_reentrancy":true}"#).unwrap();
+        assert_eq!(decoded.min_confidence, None);
+    }
+
+    struct SlowDetector;
+
+    #[async_trait]
+    impl PatternDetector for SlowDetector {
+        fn pattern_type(&self) -> PatternType { PatternType::Unknown }
+
+        async fn detect(&self, _analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pattern_timeout_returns_partial_analysis() {
+        let rpc = Arc::new(MockRpc {
+            trace: sample_trace_bytes(),
+            receipt: sample_receipt_bytes(),
+            fail_trace: false,
+            fail_receipt: false,
+        });
+        let config = TraceAnalysisConfig { timeout_ms: 50, ..Default::default() };
+        let mut analyzer = DeepTraceAnalyzer::new(rpc, Some(config));
+        analyzer.pattern_detectors = vec![Box::new(DummyDetector), Box::new(SlowDetector)];
+
+        let res = analyzer.analyze_transaction(H256::zero()).await.unwrap();
+        assert!(res.partial);
+        assert_eq!(res.detected_patterns.len(), 1);
+        assert_eq!(res.call_tree.total_calls(), 1);
+    }
+
+    struct BlockRpc {
+        inner: MockRpc,
+        block_traces: Option<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for BlockRpc {
+        async fn get_transaction_trace(&self, tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> {
+            self.inner.get_transaction_trace(tx).await
+        }
+        async fn get_transaction_receipt(&self, tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> {
+            self.inner.get_transaction_receipt(tx).await
+        }
+        async fn get_code(&self, _address: Address) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(16) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<H256> { Ok(H256::zero()) }
+        async fn get_block_transactions(&self, _block_number: u64) -> ethernity_core::error::Result<Vec<H256>> {
+            Ok(vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)])
+        }
+        async fn get_block_traces(&self, _block_number: u64) -> ethernity_core::error::Result<Vec<u8>> {
+            self.block_traces.clone().ok_or_else(|| ethernity_core::Error::RpcError("Method not found".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_block_uses_block_traces_and_falls_back() {
+        let trace: serde_json::Value = serde_json::from_slice(&sample_trace_bytes()).unwrap();
+        let block_traces = serde_json::to_vec(&json!([trace.clone(), trace])).unwrap();
+        let rpc = Arc::new(BlockRpc {
+            inner: MockRpc { trace: vec![], receipt: sample_receipt_bytes(), fail_trace: true, fail_receipt: false },
+            block_traces: Some(block_traces),
+        });
+        let analyzer = DeepTraceAnalyzer::new(rpc, None);
+        let block = analyzer.analyze_block(16).await.unwrap();
+        assert_eq!(block.block_number, 16);
+        assert_eq!(block.transactions.len(), 2);
+        assert!(block.failed_transactions.is_empty());
+        assert_eq!(block.repeated_actors.len(), 1);
+
+        // Sem traces em lote cada transação é buscada individualmente
+        let rpc = Arc::new(BlockRpc {
+            inner: MockRpc { trace: vec![], receipt: sample_receipt_bytes(), fail_trace: true, fail_receipt: false },
+            block_traces: None,
+        });
+        let analyzer = DeepTraceAnalyzer::new(rpc, None);
+        let block = analyzer.analyze_block(16).await.unwrap();
+        assert!(block.transactions.is_empty());
+        assert_eq!(block.failed_transactions.len(), 2);
+        assert!(matches!(block.failed_transactions[0].1, DeepTraceError::RpcFailure(_)));
     }
 
     #[tokio::test]
@@ -448,6 +789,8 @@ mod tests {
     fn test_new_and_memory_stats() {
         let mut cfg = TraceAnalysisConfig::default();
         cfg.pattern_detection.detect_erc20 = false;
+        cfg.pattern_detection.detect_flash_loans = false;
+        cfg.pattern_detection.detect_reentrancy = false;
         let analyzer = DeepTraceAnalyzer::new(Arc::new(MockRpc { trace: vec![], receipt: vec![], fail_trace: false, fail_receipt: false }), Some(cfg));
         assert!(analyzer.pattern_detectors.is_empty());
         let stats = analyzer.memory_stats();
@@ -0,0 +1,585 @@
+use crate::utils;
+use crate::TokenTransfer;
+use ethereum_types::{Address, H256, U256};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `Swap(address,uint256,uint256,uint256,uint256,address)` (Uniswap V2 e forks)
+const V2_SWAP_SIG: &str = "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+/// `Mint(address,uint256,uint256)` (Uniswap V2)
+const V2_MINT_SIG: &str = "0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f";
+/// `Burn(address,uint256,uint256,address)` (Uniswap V2)
+const V2_BURN_SIG: &str = "0xdccd412f0b1252819cb1fd330b93224ca42612892bb3f4f789976e6d81936496";
+/// `Swap(address,address,int256,int256,uint160,uint128,int24)` (Uniswap V3)
+const V3_SWAP_SIG: &str = "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+/// `Mint(address,address,int24,int24,uint128,uint256,uint256)` (Uniswap V3)
+const V3_MINT_SIG: &str = "0x7a53080ba414158be7ec69b987b5fb7d07dee101fe85488f0853ae16239d0bde";
+/// `Burn(address,int24,int24,uint128,uint256,uint256)` (Uniswap V3)
+const V3_BURN_SIG: &str = "0x0c396cd989a39f4459b5fa1aed6a9a8dcdbc45908acfd67e028cd568da98982c";
+/// `Initialize(bytes32,address,address,uint24,int24,address,uint160,int24)` (Uniswap V4)
+const V4_INITIALIZE_SIG: &str = "0xdd466e674ea557f56295e2d0218a125ea4b4f0f6f3307b95f85e6110838d6438";
+/// `Swap(bytes32,address,int128,int128,uint160,uint128,int24,uint24)` (Uniswap V4)
+const V4_SWAP_SIG: &str = "0x40e9cecb9f5f1f1c5b9c97dec2917b7ee92e57ba5563708daca94dd84ad7112f";
+/// `ModifyLiquidity(bytes32,address,int24,int24,int256,bytes32)` (Uniswap V4)
+const V4_MODIFY_LIQUIDITY_SIG: &str = "0xf208f4912782fd25c7f114ca3723a2d5dd6f3bcc3ac8db5af63baa85f711d5ec";
+/// `TokenExchange(address,int128,uint256,int128,uint256)` (Curve StableSwap)
+const CURVE_EXCHANGE_SIG: &str = "0x8b3e96f2b889fa771c53c981b40daf005f63f637f1869f707052d15a3dd97140";
+/// `TokenExchangeUnderlying(address,int128,uint256,int128,uint256)` (Curve StableSwap)
+const CURVE_EXCHANGE_UNDERLYING_SIG: &str = "0xd013ca23e77a65003c2c659c5442c00c805371b7fc1ebd4c206c41d1536bd90b";
+/// `TokenExchange(address,uint256,uint256,uint256,uint256)` (Curve CryptoSwap)
+const CURVE_CRYPTO_EXCHANGE_SIG: &str = "0xb2e76ae99761dc136e598d4a629bb347eccb9532a5f8bbd72e18467c3c34cc98";
+/// `AddLiquidity(address,uint256[N],uint256[N],uint256,uint256)` e
+/// `RemoveLiquidity(address,uint256[N],uint256[N],uint256)` para pools de 2 e 3 moedas
+const CURVE_LIQUIDITY_SIGS: &[(&str, LiquidityKind, usize)] = &[
+    ("0x26f55a85081d24974e85c6c00045d0f0453991e95873f52bff0d21af4079a768", LiquidityKind::Mint, 2),
+    ("0x423f6495a08fc652425cf4ed0d1f9e37e571d9b9529b1c1c23cce780b2e7df0d", LiquidityKind::Mint, 3),
+    ("0x7c363854ccf79623411f8995b362bce5eddff18c927edc6f5dbbb5e05819a82c", LiquidityKind::Burn, 2),
+    ("0xa49d4cf02656aebf8c771f5a8585638a2a15ee6c97cf7205d4208ed7c1df252d", LiquidityKind::Burn, 3),
+];
+/// `Swap(bytes32,address,address,uint256,uint256)` (Balancer V2 Vault)
+const BALANCER_SWAP_SIG: &str = "0x2170c741c41531aec20e7c107c24eecfdd15e69c9bb0a8dd37b1840b9e0b207b";
+/// `PoolBalanceChanged(bytes32,address,address[],int256[],uint256[])` (Balancer V2 Vault)
+const BALANCER_BALANCE_CHANGED_SIG: &str = "0xe5ce249087ce04f05a957192435400fd97868dba0e6a4b4c049abf8af80dae78";
+
+const TOKEN0_SELECTOR: [u8; 4] = [0x0d, 0xfe, 0x16, 0x81];
+const TOKEN1_SELECTOR: [u8; 4] = [0xd2, 0x12, 0x20, 0xa7];
+/// `coins(uint256)` e a variante `coins(int128)` de pools antigos
+const CURVE_COINS_SELECTORS: [[u8; 4]; 2] = [[0xc6, 0x61, 0x06, 0x57], [0x23, 0x74, 0x6e, 0xb8]];
+/// `underlying_coins(uint256)` e `underlying_coins(int128)`
+const CURVE_UNDERLYING_SELECTORS: [[u8; 4]; 2] = [[0xb9, 0x94, 0x7e, 0xb0], [0xb7, 0x39, 0x95, 0x3e]];
+
+/// Protocolo DEX que emitiu o evento
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DexProtocol {
+    UniswapV2,
+    UniswapV3,
+    UniswapV4,
+    Curve,
+    Balancer,
+}
+
+/// Swap normalizado a partir do evento do pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapAction {
+    pub protocol: DexProtocol,
+    /// Pool do swap; no Uniswap V4 é o `PoolManager`, com o pool em `pool_id`
+    pub pool: Address,
+    /// Identificador do pool no Uniswap V4 e no Balancer
+    pub pool_id: Option<H256>,
+    /// Token pago ao pool (`Address::zero()` quando não resolvido ou ETH nativo no V4)
+    pub token_in: Address,
+    /// Token recebido do pool
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    /// Posição do log no recibo
+    pub log_index: usize,
+    /// Índice (pré-ordem da árvore) da chamada que emitiu o log
+    pub call_index: usize,
+}
+
+/// Tipo de movimentação de liquidez
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LiquidityKind {
+    Mint,
+    Burn,
+}
+
+/// Adição ou remoção de liquidez em um pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityAction {
+    pub protocol: DexProtocol,
+    pub kind: LiquidityKind,
+    pub pool: Address,
+    pub pool_id: Option<H256>,
+    /// Endereço informado pelo evento como responsável pela operação
+    pub provider: Address,
+    /// Tokens do pool, na ordem de `amounts`
+    pub tokens: Vec<Address>,
+    /// Quantidades depositadas ou retiradas (vazio no V4, que só informa a liquidez)
+    pub amounts: Vec<U256>,
+    /// Liquidez adicionada ou removida (Uniswap V3/V4)
+    pub liquidity: Option<U256>,
+    pub log_index: usize,
+    pub call_index: usize,
+}
+
+/// Ações de DEX decodificadas de um recibo
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DexActivity {
+    pub swaps: Vec<SwapAction>,
+    pub liquidity_changes: Vec<LiquidityAction>,
+}
+
+/// Endereços retornados por getters, indexados por `(contrato, calldata)`
+type GetterCache = HashMap<(Address, Vec<u8>), Option<Address>>;
+
+/// Decodifica eventos de swap e liquidez de Uniswap V2/V3/V4, Curve e Balancer.
+///
+/// Os tokens de pools V2/V3/Curve não constam nos eventos e são obtidos via
+/// `token0()`/`token1()`/`coins(i)`, com cache por pool. Se a consulta falhar,
+/// os tokens são inferidos das transferências da própria transação.
+pub struct DexDecoder {
+    rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>,
+    tokens: Mutex<GetterCache>,
+}
+
+impl DexDecoder {
+    pub fn new(rpc_client: Arc<dyn ethernity_core::traits::RpcProvider>) -> Self {
+        Self { rpc_client, tokens: Mutex::new(HashMap::new()) }
+    }
+
+    /// Decodifica os logs do recibo.
+    ///
+    /// `log_calls` associa cada log ao índice da chamada emissora e `transfers`
+    /// são as transferências da transação, usadas na inferência de tokens.
+    pub async fn decode(&self, receipt: &Value, log_calls: &[usize], transfers: &[TokenTransfer]) -> DexActivity {
+        let logs: Vec<RawLog> = receipt
+            .get("logs")
+            .and_then(|l| l.as_array())
+            .map(|logs| logs.iter().map(RawLog::parse).collect())
+            .unwrap_or_default();
+
+        // Moedas dos pools V4 inicializados na própria transação
+        let v4_pools: HashMap<H256, (Address, Address)> = logs
+            .iter()
+            .filter(|log| log.signature == V4_INITIALIZE_SIG)
+            .filter_map(|log| Some((*log.topics.get(1)?, (log.topic_address(2)?, log.topic_address(3)?))))
+            .collect();
+
+        let mut activity = DexActivity::default();
+        for (log_index, log) in logs.iter().enumerate() {
+            let call_index = log_calls.get(log_index).copied().unwrap_or(0);
+            let signature = log.signature.as_str();
+            if let Some(swap) = self.decode_swap(log, signature, &v4_pools, transfers).await {
+                activity.swaps.push(SwapAction { log_index, call_index, ..swap });
+            } else if let Some(change) = self.decode_liquidity(log, signature, &v4_pools).await {
+                activity.liquidity_changes.push(LiquidityAction { log_index, call_index, ..change });
+            }
+        }
+        activity
+    }
+
+    async fn decode_swap(
+        &self,
+        log: &RawLog,
+        signature: &str,
+        v4_pools: &HashMap<H256, (Address, Address)>,
+        transfers: &[TokenTransfer],
+    ) -> Option<SwapAction> {
+        let pool = log.address;
+        let (protocol, pool_id, tokens, amount_in, amount_out) = match signature {
+            V2_SWAP_SIG => {
+                let (in0, in1, out0, out1) = (log.word(0)?, log.word(1)?, log.word(2)?, log.word(3)?);
+                let zero_for_one = !in0.is_zero() && !out1.is_zero();
+                let (amount_in, amount_out) = if zero_for_one { (in0, out1) } else { (in1, out0) };
+                let tokens = self.pair_tokens(pool).await.map(|(t0, t1)| if zero_for_one { (t0, t1) } else { (t1, t0) });
+                (DexProtocol::UniswapV2, None, tokens, amount_in, amount_out)
+            }
+            V3_SWAP_SIG => {
+                // Valores do ponto de vista do pool: positivo = recebido
+                let (negative0, amount0) = signed(log.word(0)?);
+                let (_, amount1) = signed(log.word(1)?);
+                let zero_for_one = !negative0;
+                let (amount_in, amount_out) = if zero_for_one { (amount0, amount1) } else { (amount1, amount0) };
+                let tokens = self.pair_tokens(pool).await.map(|(t0, t1)| if zero_for_one { (t0, t1) } else { (t1, t0) });
+                (DexProtocol::UniswapV3, None, tokens, amount_in, amount_out)
+            }
+            V4_SWAP_SIG => {
+                // Valores do ponto de vista de quem chama: negativo = pago ao pool
+                let id = *log.topics.get(1)?;
+                let (negative0, amount0) = signed(log.word(0)?);
+                let (_, amount1) = signed(log.word(1)?);
+                let zero_for_one = negative0;
+                let (amount_in, amount_out) = if zero_for_one { (amount0, amount1) } else { (amount1, amount0) };
+                let tokens = v4_pools.get(&id).map(|&(c0, c1)| if zero_for_one { (c0, c1) } else { (c1, c0) });
+                (DexProtocol::UniswapV4, Some(id), tokens, amount_in, amount_out)
+            }
+            CURVE_EXCHANGE_SIG | CURVE_EXCHANGE_UNDERLYING_SIG | CURVE_CRYPTO_EXCHANGE_SIG => {
+                let (sold_id, amount_in, bought_id, amount_out) = (log.word(0)?, log.word(1)?, log.word(2)?, log.word(3)?);
+                let selectors = if signature == CURVE_EXCHANGE_UNDERLYING_SIG {
+                    CURVE_UNDERLYING_SELECTORS
+                } else {
+                    CURVE_COINS_SELECTORS
+                };
+                let token_in = self.curve_coin(pool, &selectors, sold_id.low_u64()).await;
+                let token_out = self.curve_coin(pool, &selectors, bought_id.low_u64()).await;
+                let tokens = token_in.zip(token_out);
+                (DexProtocol::Curve, None, tokens, amount_in, amount_out)
+            }
+            BALANCER_SWAP_SIG => {
+                // O Vault informa os tokens; as transferências também passam por ele
+                let id = *log.topics.get(1)?;
+                return Some(SwapAction {
+                    protocol: DexProtocol::Balancer,
+                    pool: balancer_pool(&id),
+                    pool_id: Some(id),
+                    token_in: log.topic_address(2)?,
+                    token_out: log.topic_address(3)?,
+                    amount_in: log.word(0)?,
+                    amount_out: log.word(1)?,
+                    log_index: 0,
+                    call_index: 0,
+                });
+            }
+            _ => return None,
+        };
+
+        let (token_in, token_out) = tokens.unwrap_or_else(|| {
+            (
+                infer_token(transfers, |t| t.to == pool && t.amount == amount_in),
+                infer_token(transfers, |t| t.from == pool && t.amount == amount_out),
+            )
+        });
+        Some(SwapAction {
+            protocol,
+            pool,
+            pool_id,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+            log_index: 0,
+            call_index: 0,
+        })
+    }
+
+    async fn decode_liquidity(
+        &self,
+        log: &RawLog,
+        signature: &str,
+        v4_pools: &HashMap<H256, (Address, Address)>,
+    ) -> Option<LiquidityAction> {
+        let pool = log.address;
+        let pair_tokens = || async { self.pair_tokens(pool).await.map(|(t0, t1)| vec![t0, t1]).unwrap_or_default() };
+        let action = |protocol, kind, provider, tokens, amounts, liquidity| LiquidityAction {
+            protocol,
+            kind,
+            pool,
+            pool_id: None,
+            provider,
+            tokens,
+            amounts,
+            liquidity,
+            log_index: 0,
+            call_index: 0,
+        };
+
+        match signature {
+            V2_MINT_SIG | V2_BURN_SIG => {
+                let kind = if signature == V2_MINT_SIG { LiquidityKind::Mint } else { LiquidityKind::Burn };
+                let amounts = vec![log.word(0)?, log.word(1)?];
+                Some(action(DexProtocol::UniswapV2, kind, log.topic_address(1)?, pair_tokens().await, amounts, None))
+            }
+            V3_MINT_SIG => {
+                // `sender` não é indexado: data = sender, amount, amount0, amount1
+                let amounts = vec![log.word(2)?, log.word(3)?];
+                let liquidity = log.word(1)?;
+                Some(action(DexProtocol::UniswapV3, LiquidityKind::Mint, log.topic_address(1)?, pair_tokens().await, amounts, Some(liquidity)))
+            }
+            V3_BURN_SIG => {
+                let (liquidity, amount0, amount1) = (log.word(0)?, log.word(1)?, log.word(2)?);
+                // Burn com liquidez zero apenas contabiliza taxas da posição
+                if liquidity.is_zero() {
+                    return None;
+                }
+                Some(action(DexProtocol::UniswapV3, LiquidityKind::Burn, log.topic_address(1)?, pair_tokens().await, vec![amount0, amount1], Some(liquidity)))
+            }
+            V4_MODIFY_LIQUIDITY_SIG => {
+                let id = *log.topics.get(1)?;
+                let (negative, liquidity) = signed(log.word(2)?);
+                if liquidity.is_zero() {
+                    return None;
+                }
+                let kind = if negative { LiquidityKind::Burn } else { LiquidityKind::Mint };
+                let tokens = v4_pools.get(&id).map(|&(c0, c1)| vec![c0, c1]).unwrap_or_default();
+                Some(LiquidityAction {
+                    pool_id: Some(id),
+                    ..action(DexProtocol::UniswapV4, kind, log.topic_address(2)?, tokens, vec![], Some(liquidity))
+                })
+            }
+            BALANCER_BALANCE_CHANGED_SIG => {
+                let id = *log.topics.get(1)?;
+                let tokens: Vec<Address> = log.array(0)?.iter().map(word_address).collect();
+                let deltas = log.array(1)?;
+                if tokens.len() != deltas.len() {
+                    return None;
+                }
+                let negative = deltas.iter().any(|d| signed(*d).0);
+                let kind = if negative { LiquidityKind::Burn } else { LiquidityKind::Mint };
+                let amounts = deltas.into_iter().map(|d| signed(d).1).collect();
+                Some(LiquidityAction {
+                    pool: balancer_pool(&id),
+                    pool_id: Some(id),
+                    ..action(DexProtocol::Balancer, kind, log.topic_address(2)?, tokens, amounts, None)
+                })
+            }
+            _ => {
+                let &(_, kind, coins) = CURVE_LIQUIDITY_SIGS.iter().find(|(sig, _, _)| *sig == signature)?;
+                let amounts: Vec<U256> = (0..coins).map(|i| log.word(i)).collect::<Option<_>>()?;
+                let mut tokens = Vec::with_capacity(coins);
+                for i in 0..coins {
+                    tokens.push(self.curve_coin(pool, &CURVE_COINS_SELECTORS, i as u64).await.unwrap_or_default());
+                }
+                Some(action(DexProtocol::Curve, kind, log.topic_address(1)?, tokens, amounts, None))
+            }
+        }
+    }
+
+    /// `token0()`/`token1()` de um par Uniswap V2/V3
+    async fn pair_tokens(&self, pool: Address) -> Option<(Address, Address)> {
+        let token0 = self.query_address(pool, TOKEN0_SELECTOR.to_vec()).await?;
+        let token1 = self.query_address(pool, TOKEN1_SELECTOR.to_vec()).await?;
+        Some((token0, token1))
+    }
+
+    /// Moeda `index` de um pool Curve, tentando as variantes do getter
+    async fn curve_coin(&self, pool: Address, selectors: &[[u8; 4]], index: u64) -> Option<Address> {
+        for selector in selectors {
+            let mut data = selector.to_vec();
+            let mut arg = [0u8; 32];
+            U256::from(index).to_big_endian(&mut arg);
+            data.extend_from_slice(&arg);
+            if let Some(coin) = self.query_address(pool, data).await {
+                return Some(coin);
+            }
+        }
+        None
+    }
+
+    /// Executa um getter que retorna `address`, com cache por contrato e calldata
+    async fn query_address(&self, contract: Address, data: Vec<u8>) -> Option<Address> {
+        let key = (contract, data);
+        if let Some(cached) = self.tokens.lock().get(&key) {
+            return *cached;
+        }
+
+        let result = self
+            .rpc_client
+            .call(contract, key.1.clone())
+            .await
+            .ok()
+            .filter(|output| output.len() >= 32)
+            .map(|output| Address::from_slice(&output[12..32]))
+            .filter(|address| !address.is_zero());
+
+        self.tokens.lock().insert(key, result);
+        result
+    }
+}
+
+/// Token da primeira transferência que satisfaz o critério
+fn infer_token(transfers: &[TokenTransfer], predicate: impl Fn(&TokenTransfer) -> bool) -> Address {
+    transfers
+        .iter()
+        .find(|t| predicate(t))
+        .map(|t| t.token_address)
+        .unwrap_or_default()
+}
+
+/// O poolId do Balancer V2 começa com o endereço do pool
+fn balancer_pool(pool_id: &H256) -> Address {
+    Address::from_slice(&pool_id.as_bytes()[..20])
+}
+
+/// Interpreta uma palavra ABI como inteiro com sinal, retornando `(negativo, módulo)`
+fn signed(word: U256) -> (bool, U256) {
+    if word.bit(255) {
+        (true, (!word).overflowing_add(U256::one()).0)
+    } else {
+        (false, word)
+    }
+}
+
+fn word_address(word: &U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+/// Log do recibo com tópicos e `data` já decodificados
+struct RawLog {
+    address: Address,
+    /// Tópico 0 em hexadecimal minúsculo
+    signature: String,
+    topics: Vec<H256>,
+    words: Vec<U256>,
+}
+
+impl RawLog {
+    fn parse(log: &Value) -> Self {
+        let topics: Vec<&str> = log
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| t.iter().map(|topic| topic.as_str().unwrap_or("")).collect())
+            .unwrap_or_default();
+        let data = utils::decode_hex(log.get("data").and_then(|d| d.as_str()).unwrap_or(""));
+        Self {
+            address: utils::parse_address(log.get("address").and_then(|a| a.as_str()).unwrap_or("")),
+            signature: topics.first().map(|t| t.to_lowercase()).unwrap_or_default(),
+            topics: topics.iter().map(|t| h256(t)).collect(),
+            words: data.chunks_exact(32).map(U256::from_big_endian).collect(),
+        }
+    }
+
+    fn topic_address(&self, index: usize) -> Option<Address> {
+        self.topics.get(index).map(|t| Address::from_slice(&t.as_bytes()[12..]))
+    }
+
+    fn word(&self, index: usize) -> Option<U256> {
+        self.words.get(index).copied()
+    }
+
+    /// Array dinâmico cujo offset está na palavra `offset_word`
+    fn array(&self, offset_word: usize) -> Option<Vec<U256>> {
+        let offset = self.words.get(offset_word)?;
+        if *offset > U256::from(usize::MAX / 2) || offset.low_u64() % 32 != 0 {
+            return None;
+        }
+        let start = offset.as_usize() / 32;
+        let len = self.words.get(start)?;
+        if *len > U256::from(self.words.len()) {
+            return None;
+        }
+        self.words.get(start + 1..start + 1 + len.as_usize()).map(|s| s.to_vec())
+    }
+}
+
+fn h256(topic: &str) -> H256 {
+    let bytes = utils::decode_hex(topic);
+    let mut word = [0u8; 32];
+    let len = bytes.len().min(32);
+    word[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    H256::from(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenType;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    fn word(n: u64) -> String {
+        format!("{:064x}", n)
+    }
+
+    fn neg(n: u64) -> String {
+        let mut bytes = [0u8; 32];
+        (!U256::from(n)).overflowing_add(U256::one()).0.to_big_endian(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    fn topic(n: u64) -> String {
+        format!("0x{}", word(n))
+    }
+
+    fn log(address: u64, topics: &[&str], data: String) -> Value {
+        json!({"address": format!("0x{:040x}", address), "topics": topics, "data": format!("0x{}", data)})
+    }
+
+    /// Par 0x10 com token0 = 0xa0 e token1 = 0xa1; pool Curve 0x30 com moedas 0xc0.. via `coins(int128)`
+    struct PoolRpc;
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for PoolRpc {
+        async fn get_transaction_trace(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_transaction_receipt(&self, _tx: ethernity_core::types::TransactionHash) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_code(&self, _address: Address) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn call(&self, to: Address, data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> {
+            let token = match (to.to_low_u64_be(), &data[..4]) {
+                (0x10, s) if s == TOKEN0_SELECTOR => 0xa0,
+                (0x10, s) if s == TOKEN1_SELECTOR => 0xa1,
+                (0x30, s) if s == CURVE_COINS_SELECTORS[1] => 0xc0 + data[35] as u64,
+                _ => return Err(ethernity_core::Error::RpcError("execution reverted".into())),
+            };
+            Ok(H256::from(Address::from_low_u64_be(token)).as_bytes().to_vec())
+        }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(0) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<H256> { Ok(H256::zero()) }
+    }
+
+    #[tokio::test]
+    async fn test_decode_swaps() {
+        let v4_id = topic(0x44);
+        let receipt = json!({"logs": [
+            // V2: 5 de token1 entram, 3 de token0 saem
+            log(0x10, &[V2_SWAP_SIG, &topic(1), &topic(2)], format!("{}{}{}{}", word(0), word(5), word(3), word(0))),
+            // V3 no pool 0x20 (tokens inferidos das transferências): pool recebe 7 de token0 e paga 9 de token1
+            log(0x20, &[V3_SWAP_SIG, &topic(1), &topic(2)], format!("{}{}{}{}{}", word(7), neg(9), word(0), word(0), word(0))),
+            log(0xff, &[V4_INITIALIZE_SIG, &v4_id, &topic(0), &topic(0xb1)], String::new()),
+            // V4: quem chama paga 4 de currency1 (negativo) e recebe 2 de ETH
+            log(0xff, &[V4_SWAP_SIG, &v4_id, &topic(1)], format!("{}{}{}{}{}{}", word(2), neg(4), word(0), word(0), word(0), word(0))),
+            log(0x30, &[CURVE_EXCHANGE_SIG, &topic(1)], format!("{}{}{}{}", word(2), word(100), word(0), word(99))),
+            log(0xbb, &[BALANCER_SWAP_SIG, "0x00000000000000000000000000000000000000e0000000000000000000000001", &topic(0xd0), &topic(0xd1)], format!("{}{}", word(6), word(8))),
+        ]});
+        let transfer = |token: u64, from: u64, to: u64, amount: u64| TokenTransfer {
+            token_type: TokenType::Erc20,
+            token_address: Address::from_low_u64_be(token),
+            from: Address::from_low_u64_be(from),
+            to: Address::from_low_u64_be(to),
+            amount: U256::from(amount),
+            token_id: None,
+            call_index: 0,
+        };
+        let transfers = vec![transfer(0xe1, 1, 0x20, 7), transfer(0xe2, 0x20, 1, 9)];
+
+        let decoder = DexDecoder::new(Arc::new(PoolRpc));
+        let activity = decoder.decode(&receipt, &[1, 2, 3, 3, 4, 5], &transfers).await;
+        let swaps = &activity.swaps;
+        assert_eq!(swaps.len(), 5);
+        assert!(activity.liquidity_changes.is_empty());
+
+        let a = |n: u64| Address::from_low_u64_be(n);
+        assert_eq!((swaps[0].protocol, swaps[0].token_in, swaps[0].token_out), (DexProtocol::UniswapV2, a(0xa1), a(0xa0)));
+        assert_eq!((swaps[0].amount_in, swaps[0].amount_out), (U256::from(5u64), U256::from(3u64)));
+        assert_eq!((swaps[0].log_index, swaps[0].call_index), (0, 1));
+
+        assert_eq!((swaps[1].token_in, swaps[1].token_out), (a(0xe1), a(0xe2)));
+        assert_eq!((swaps[1].amount_in, swaps[1].amount_out), (U256::from(7u64), U256::from(9u64)));
+
+        assert_eq!(swaps[2].pool_id, Some(h256(&v4_id)));
+        assert_eq!((swaps[2].token_in, swaps[2].token_out), (a(0xb1), Address::zero()));
+        assert_eq!((swaps[2].amount_in, swaps[2].amount_out), (U256::from(4u64), U256::from(2u64)));
+
+        assert_eq!((swaps[3].token_in, swaps[3].token_out), (a(0xc2), a(0xc0)));
+        assert_eq!(swaps[4].pool, a(0xe0));
+        assert_eq!((swaps[4].token_in, swaps[4].token_out), (a(0xd0), a(0xd1)));
+        assert_eq!(swaps[4].call_index, 5);
+    }
+
+    #[tokio::test]
+    async fn test_decode_liquidity_changes() {
+        let balancer_data = format!(
+            "{}{}{}{}{}{}{}{}{}{}{}{}",
+            word(0x60), word(0xc0), word(0x120),
+            word(2), word(0xd0), word(0xd1),
+            word(2), neg(10), neg(20),
+            word(2), word(0), word(0)
+        );
+        let receipt = json!({"logs": [
+            log(0x10, &[V2_MINT_SIG, &topic(1)], format!("{}{}", word(10), word(20))),
+            log(0x10, &[V3_BURN_SIG, &topic(3), &topic(0), &topic(0)], format!("{}{}{}", word(0), word(0), word(0))),
+            log(0x10, &[V3_BURN_SIG, &topic(3), &topic(0), &topic(0)], format!("{}{}{}", word(50), word(1), word(2))),
+            log(0x30, &[CURVE_LIQUIDITY_SIGS[1].0, &topic(4)], (0..8).map(word).collect::<String>()),
+            log(0xbb, &[BALANCER_BALANCE_CHANGED_SIG, "0x00000000000000000000000000000000000000e0000000000000000000000001", &topic(5)], balancer_data),
+        ]});
+        let activity = DexDecoder::new(Arc::new(PoolRpc)).decode(&receipt, &[], &[]).await;
+        let changes = &activity.liquidity_changes;
+        assert_eq!(changes.len(), 4);
+
+        let a = |n: u64| Address::from_low_u64_be(n);
+        assert_eq!((changes[0].kind, changes[0].provider), (LiquidityKind::Mint, a(1)));
+        assert_eq!(changes[0].tokens, vec![a(0xa0), a(0xa1)]);
+        assert_eq!(changes[1].kind, LiquidityKind::Burn);
+        assert_eq!(changes[1].liquidity, Some(U256::from(50u64)));
+        assert_eq!(changes[1].log_index, 2);
+        assert_eq!(changes[2].tokens, vec![a(0xc0), a(0xc1), a(0xc2)]);
+        assert_eq!(changes[2].amounts, vec![U256::zero(), U256::one(), U256::from(2u64)]);
+        assert_eq!((changes[3].kind, changes[3].pool), (LiquidityKind::Burn, a(0xe0)));
+        assert_eq!(changes[3].amounts, vec![U256::from(10u64), U256::from(20u64)]);
+    }
+}
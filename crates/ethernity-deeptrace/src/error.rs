@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Erros da análise de traces
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeepTraceError {
+    /// Falha na comunicação com o provedor RPC
+    #[error("Falha de RPC: {0}")]
+    RpcFailure(String),
+
+    /// Trace retornado pelo node não pôde ser decodificado
+    #[error("Trace inválido: {0}")]
+    TraceDecode(String),
+
+    /// Recibo retornado pelo node não pôde ser decodificado
+    #[error("Recibo inválido: {0}")]
+    ReceiptDecode(String),
+
+    /// Árvore de chamadas excedeu `max_depth` com `strict_depth` habilitado
+    #[error("Profundidade máxima excedida: {0}")]
+    DepthLimit(usize),
+
+    /// Análise excedeu o tempo limite (em milissegundos)
+    #[error("Timeout após {0} ms")]
+    Timeout(u64),
+
+    /// Análise excedeu o limite de memória (em bytes)
+    #[error("Limite de memória excedido: {0} bytes")]
+    MemoryLimit(usize),
+
+    /// Falha ao gravar ou ler análises persistidas
+    #[error("Falha no armazenamento: {0}")]
+    Storage(String),
+
+    /// Arquivo de rótulos de endereços inválido ou ilegível
+    #[error("Rótulos inválidos: {0}")]
+    LabelDecode(String),
+
+    /// ABI ou assinatura de erro customizado inválida
+    #[error("ABI de erros inválida: {0}")]
+    AbiDecode(String),
+}
+
+impl From<ethernity_core::Error> for DeepTraceError {
+    fn from(err: ethernity_core::Error) -> Self {
+        DeepTraceError::RpcFailure(err.to_string())
+    }
+}
@@ -0,0 +1,338 @@
+use crate::trace::{CallNode, CallTree, CallType};
+use crate::DeepTraceError;
+use ethabi::{AbiError, ParamType};
+use ethereum_types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Seletor de `Error(string)`
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Seletor de `Panic(uint256)`
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Motivo decodificado dos dados de revert de uma chamada
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RevertReason {
+    /// Revert sem dados, ou falha sem retorno (ex.: out of gas, opcode inválido)
+    Empty,
+    /// `require(cond, "mensagem")` ou `revert("mensagem")`
+    Error { message: String },
+    /// `Panic(uint256)` emitido pelo compilador (assert, overflow, divisão por zero...)
+    Panic { code: U256, description: String },
+    /// Erro customizado; `name` e `args` só são preenchidos quando o seletor está no `ErrorRegistry`
+    Custom {
+        selector: [u8; 4],
+        name: Option<String>,
+        args: Vec<String>,
+        #[serde(with = "crate::utils::hex_bytes")]
+        data: Vec<u8>,
+    },
+    /// Dados que não seguem a codificação ABI de erros
+    Raw {
+        #[serde(with = "crate::utils::hex_bytes")]
+        data: Vec<u8>,
+    },
+}
+
+impl RevertReason {
+    /// Decodifica os dados de retorno de uma chamada revertida
+    pub fn decode(data: &[u8], registry: Option<&ErrorRegistry>) -> Self {
+        if data.is_empty() {
+            return RevertReason::Empty;
+        }
+        let Some((selector, payload)) = data.split_first_chunk::<4>() else {
+            return RevertReason::Raw { data: data.to_vec() };
+        };
+        match *selector {
+            ERROR_SELECTOR => match ethabi::decode(&[ParamType::String], payload).ok().and_then(|t| t.into_iter().next()) {
+                Some(ethabi::Token::String(message)) => RevertReason::Error { message },
+                _ => RevertReason::Raw { data: data.to_vec() },
+            },
+            PANIC_SELECTOR => match ethabi::decode(&[ParamType::Uint(256)], payload).ok().and_then(|t| t.into_iter().next()) {
+                Some(ethabi::Token::Uint(code)) => RevertReason::Panic { code, description: panic_description(code).to_string() },
+                _ => RevertReason::Raw { data: data.to_vec() },
+            },
+            selector => {
+                let decoded = registry
+                    .and_then(|r| r.get(&selector))
+                    .and_then(|error| error.decode(payload).ok().map(|tokens| (error, tokens)));
+                RevertReason::Custom {
+                    selector,
+                    name: decoded.as_ref().map(|(error, _)| error.name.clone()),
+                    args: decoded.map(|(_, tokens)| tokens.iter().map(|t| t.to_string()).collect()).unwrap_or_default(),
+                    data: data.to_vec(),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RevertReason::Empty => write!(f, "sem dados de revert"),
+            RevertReason::Error { message } => write!(f, "Error(\"{}\")", message),
+            RevertReason::Panic { code, description } => write!(f, "Panic(0x{:02x}): {}", code, description),
+            RevertReason::Custom { name: Some(name), args, .. } => write!(f, "{}({})", name, args.join(", ")),
+            RevertReason::Custom { selector, .. } => write!(f, "erro customizado 0x{}", hex::encode(selector)),
+            RevertReason::Raw { data } => write!(f, "0x{}", hex::encode(data)),
+        }
+    }
+}
+
+/// Significado dos códigos de `Panic(uint256)` definidos pelo Solidity
+fn panic_description(code: U256) -> &'static str {
+    if code > U256::from(u8::MAX) {
+        return "código desconhecido";
+    }
+    match code.low_u32() {
+        0x00 => "panic genérico do compilador",
+        0x01 => "assert falhou",
+        0x11 => "overflow ou underflow aritmético",
+        0x12 => "divisão ou módulo por zero",
+        0x21 => "conversão para enum inválida",
+        0x22 => "array de bytes em storage mal codificado",
+        0x31 => "pop em array vazio",
+        0x32 => "acesso fora dos limites do array",
+        0x41 => "alocação excessiva de memória",
+        0x51 => "chamada a função interna não inicializada",
+        _ => "código desconhecido",
+    }
+}
+
+/// Erros customizados conhecidos, indexados pelo seletor
+#[derive(Debug, Clone, Default)]
+pub struct ErrorRegistry {
+    errors: HashMap<[u8; 4], AbiError>,
+}
+
+impl ErrorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adiciona ou substitui o erro com o mesmo seletor
+    pub fn insert(&mut self, error: AbiError) -> Option<AbiError> {
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&error.signature()[..4]);
+        self.errors.insert(selector, error)
+    }
+
+    /// Adiciona um erro pela assinatura, como `InsufficientBalance(uint256,uint256)`
+    pub fn insert_signature(&mut self, signature: &str) -> Result<(), DeepTraceError> {
+        let invalid = || DeepTraceError::AbiDecode(format!("assinatura inválida: {}", signature));
+        let signature = signature.trim();
+        let open = signature.find('(').ok_or_else(invalid)?;
+        // A lista de parâmetros é lida como uma tupla para aceitar tipos aninhados
+        let inputs = match ethabi::param_type::Reader::read(&signature[open..]) {
+            Ok(ParamType::Tuple(types)) => types,
+            _ => return Err(invalid()),
+        };
+        self.insert(AbiError {
+            name: signature[..open].to_string(),
+            inputs: inputs
+                .into_iter()
+                .map(|kind| ethabi::Param { name: String::new(), kind, internal_type: None })
+                .collect(),
+        });
+        Ok(())
+    }
+
+    /// Carrega os erros (`"type": "error"`) de uma ABI JSON; retorna quantos foram lidos
+    pub fn load_abi(&mut self, json: &str) -> Result<usize, DeepTraceError> {
+        let contract =
+            ethabi::Contract::load(json.as_bytes()).map_err(|e| DeepTraceError::AbiDecode(e.to_string()))?;
+        let mut count = 0;
+        for error in contract.errors() {
+            self.insert(error.clone());
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub fn get(&self, selector: &[u8; 4]) -> Option<&AbiError> {
+        self.errors.get(selector)
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Chamada que falhou, com o motivo decodificado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedCall {
+    pub call_index: usize,
+    pub depth: usize,
+    pub call_type: CallType,
+    pub from: Address,
+    pub to: Option<Address>,
+    /// Erro informado pelo node (ex.: `execution reverted`, `out of gas`)
+    pub error: String,
+    pub reason: RevertReason,
+}
+
+impl FailedCall {
+    fn from_node(node: &CallNode, registry: Option<&ErrorRegistry>) -> Option<Self> {
+        Some(Self {
+            call_index: node.index,
+            depth: node.depth,
+            call_type: node.call_type,
+            from: node.from,
+            to: node.to,
+            error: node.error.clone()?,
+            reason: RevertReason::decode(&node.output, registry),
+        })
+    }
+}
+
+/// Relatório forense das falhas de uma transação.
+///
+/// A origem é o revert mais profundo; quando a transação inteira reverteu,
+/// só são consideradas as falhas cuja cadeia de frames revertidos chega à raiz.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureReport {
+    /// Revert mais profundo, origem da falha
+    pub root_cause: FailedCall,
+    /// Frames revertidos da origem em direção à raiz, começando pela origem
+    pub propagation: Vec<FailedCall>,
+    /// Frame que absorveu o revert sem reverter (try/catch ou chamada de
+    /// baixo nível); `None` quando a falha chegou à raiz da transação
+    pub caught_at: Option<usize>,
+    /// Todas as chamadas que falharam, em pré-ordem
+    pub failed_calls: Vec<FailedCall>,
+}
+
+impl FailureReport {
+    /// Gera o relatório da árvore de chamadas; `None` se nenhuma chamada falhou
+    pub fn from_tree(tree: &CallTree, registry: Option<&ErrorRegistry>) -> Option<Self> {
+        let failed_calls: Vec<FailedCall> =
+            tree.nodes().iter().filter_map(|node| FailedCall::from_node(node, registry)).collect();
+
+        let chains: Vec<(usize, Vec<usize>, Option<usize>)> = failed_calls
+            .iter()
+            .enumerate()
+            .map(|(position, call)| {
+                let (chain, caught_at) = propagation(tree, call.call_index);
+                (position, chain, caught_at)
+            })
+            .collect();
+        let transaction_reverted = tree.root().error.is_some();
+        let (position, chain, caught_at) = chains
+            .into_iter()
+            .filter(|(_, _, caught_at)| !transaction_reverted || caught_at.is_none())
+            // Mais profundo primeiro; empates ficam com o primeiro em pré-ordem
+            .min_by_key(|(position, _, _)| (std::cmp::Reverse(failed_calls[*position].depth), *position))?;
+
+        let by_index: HashMap<usize, &FailedCall> = failed_calls.iter().map(|c| (c.call_index, c)).collect();
+        Some(Self {
+            root_cause: failed_calls[position].clone(),
+            propagation: chain.iter().map(|index| by_index[index].clone()).collect(),
+            caught_at,
+            failed_calls,
+        })
+    }
+
+    /// A falha reverteu a transação inteira
+    pub fn reverted_transaction(&self) -> bool {
+        self.caught_at.is_none()
+    }
+}
+
+/// Frames revertidos a partir de `index` subindo pelos pais, e o primeiro pai que não reverteu
+fn propagation(tree: &CallTree, index: usize) -> (Vec<usize>, Option<usize>) {
+    let mut chain = vec![index];
+    let mut node = &tree.nodes()[index];
+    while let Some(parent) = tree.parent(node) {
+        if parent.error.is_none() {
+            return (chain, Some(parent.index));
+        }
+        chain.push(parent.index);
+        node = parent;
+    }
+    (chain, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(index: usize, depth: usize, parent: Option<usize>, children: Vec<usize>, error: Option<&str>, output: Vec<u8>) -> CallNode {
+        CallNode {
+            index, depth, call_type: CallType::Call, from: Address::from_low_u64_be(1), to: Some(Address::from_low_u64_be(index as u64 + 2)),
+            value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(), input: vec![], output,
+            error: error.map(str::to_string), children, parent, proxy: None,
+        }
+    }
+
+    fn encode(selector: [u8; 4], tokens: &[ethabi::Token]) -> Vec<u8> {
+        [selector.to_vec(), ethabi::encode(tokens)].concat()
+    }
+
+    #[test]
+    fn test_decode_revert_reasons() {
+        let error = encode(ERROR_SELECTOR, &[ethabi::Token::String("saldo insuficiente".into())]);
+        assert_eq!(RevertReason::decode(&error, None), RevertReason::Error { message: "saldo insuficiente".into() });
+
+        let panic = RevertReason::decode(&encode(PANIC_SELECTOR, &[ethabi::Token::Uint(U256::from(0x11))]), None);
+        assert_eq!(panic.to_string(), "Panic(0x11): overflow ou underflow aritmético");
+
+        assert_eq!(RevertReason::decode(&[], None), RevertReason::Empty);
+        assert_eq!(RevertReason::decode(&[0x01, 0x02], None), RevertReason::Raw { data: vec![0x01, 0x02] });
+
+        // keccak256("InsufficientBalance(uint256,uint256)")[..4]
+        let custom = encode([0xcf, 0x47, 0x91, 0x81], &[ethabi::Token::Uint(U256::from(5)), ethabi::Token::Uint(U256::from(7))]);
+        let unknown = RevertReason::decode(&custom, None);
+        assert!(matches!(&unknown, RevertReason::Custom { name: None, args, .. } if args.is_empty()));
+        assert_eq!(unknown.to_string(), "erro customizado 0xcf479181");
+
+        let mut registry = ErrorRegistry::new();
+        registry.insert_signature("InsufficientBalance(uint256,uint256)").unwrap();
+        assert!(registry.insert_signature("Invalid").is_err());
+        let abi = r#"[{"type":"error","name":"Unauthorized","inputs":[{"name":"caller","type":"address"}]}]"#;
+        assert_eq!(registry.load_abi(abi).unwrap(), 1);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(RevertReason::decode(&custom, Some(&registry)).to_string(), "InsufficientBalance(5, 7)");
+    }
+
+    #[test]
+    fn test_failure_report_follows_propagation() {
+        let message = encode(ERROR_SELECTOR, &[ethabi::Token::String("STF".into())]);
+        // 0 -> 1 -> 2 -> 3 reverte até a raiz; 4 falha mais fundo, mas é capturado por 1
+        let tree = CallTree::from_nodes(vec![
+            node(0, 0, None, vec![1], Some("execution reverted"), message.clone()),
+            node(1, 1, Some(0), vec![2, 4], Some("execution reverted"), message.clone()),
+            node(2, 2, Some(1), vec![3], Some("execution reverted"), message.clone()),
+            node(3, 3, Some(2), vec![], Some("execution reverted"), message),
+            node(4, 2, Some(1), vec![5], None, vec![]),
+            node(5, 3, Some(4), vec![6], Some("out of gas"), vec![]),
+            node(6, 4, Some(5), vec![], Some("execution reverted"), vec![]),
+        ]);
+        let report = FailureReport::from_tree(&tree, None).unwrap();
+
+        assert_eq!(report.root_cause.call_index, 3);
+        assert_eq!(report.root_cause.reason, RevertReason::Error { message: "STF".into() });
+        assert_eq!(report.propagation.iter().map(|c| c.call_index).collect::<Vec<_>>(), vec![3, 2, 1, 0]);
+        assert!(report.reverted_transaction());
+        assert_eq!(report.failed_calls.len(), 6);
+
+        // Sem revert na raiz a origem é a falha mais profunda, capturada por 4
+        let mut nodes = tree.nodes().to_vec();
+        for node in &mut nodes[..4] {
+            node.error = None;
+        }
+        let report = FailureReport::from_tree(&CallTree::from_nodes(nodes.clone()), None).unwrap();
+        assert_eq!(report.root_cause.call_index, 6);
+        assert_eq!(report.propagation.iter().map(|c| c.call_index).collect::<Vec<_>>(), vec![6, 5]);
+        assert_eq!(report.caught_at, Some(4));
+
+        nodes[5].error = None;
+        nodes[6].error = None;
+        assert!(FailureReport::from_tree(&CallTree::from_nodes(nodes), None).is_none());
+    }
+}
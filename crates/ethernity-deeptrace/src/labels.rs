@@ -0,0 +1,336 @@
+use crate::{DeepTraceError, TransactionAnalysis};
+use ethereum_types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Categoria de um endereço conhecido
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelCategory {
+    Router,
+    Bridge,
+    Mixer,
+    /// Hot wallet de exchange centralizada
+    Exchange,
+    /// Qualquer categoria não reconhecida
+    #[serde(other)]
+    Other,
+}
+
+/// Rótulo de um endereço conhecido
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub address: Address,
+    pub name: String,
+    pub category: LabelCategory,
+}
+
+/// Papel de um endereço na transação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AddressRole {
+    /// Remetente da transação
+    Sender,
+    /// Destinatário da transação
+    Target,
+    Caller,
+    Callee,
+    /// Contrato de um token transferido ou autorizado
+    Token,
+    /// Origem de uma transferência de token ou de ETH
+    TransferFrom,
+    /// Destino de uma transferência de token ou de ETH
+    TransferTo,
+    /// Dono ou gastador de uma autorização
+    Approval,
+    /// Pool de DEX de um swap ou mudança de liquidez
+    Pool,
+    /// Contrato criado na transação
+    Created,
+}
+
+/// Lista de endereços monitorados
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchlist {
+    pub name: String,
+    pub addresses: HashSet<Address>,
+}
+
+impl Watchlist {
+    pub fn new(name: impl Into<String>, addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { name: name.into(), addresses: addresses.into_iter().collect() }
+    }
+}
+
+/// Ocorrência de um endereço monitorado em uma transação analisada
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedEvent {
+    /// Nome da watchlist que contém o endereço
+    pub watchlist: String,
+    pub address: Address,
+    pub label: Option<AddressLabel>,
+    pub tx_hash: H256,
+    pub block_number: u64,
+    /// Papéis do endereço na transação, sem repetição
+    pub roles: Vec<AddressRole>,
+    /// Chamadas (pré-ordem) em que o endereço aparece
+    pub call_indices: Vec<usize>,
+}
+
+/// Linha de um arquivo CSV de rótulos (`address,name,category`)
+#[derive(Deserialize)]
+struct LabelRecord {
+    address: Address,
+    name: String,
+    #[serde(default)]
+    category: Option<LabelCategory>,
+}
+
+/// Rótulos de endereços conhecidos e watchlists.
+///
+/// Quando registrado no `DeepTraceAnalyzer`, cada análise recebe os rótulos
+/// dos endereços que aparecem no trace e um `DetectedEvent` por endereço
+/// monitorado encontrado.
+#[derive(Debug, Clone, Default)]
+pub struct LabelRegistry {
+    labels: HashMap<Address, AddressLabel>,
+    watchlists: Vec<Watchlist>,
+}
+
+impl LabelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adiciona ou substitui o rótulo do endereço
+    pub fn insert(&mut self, label: AddressLabel) -> Option<AddressLabel> {
+        self.labels.insert(label.address, label)
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&AddressLabel> {
+        self.labels.get(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Carrega rótulos de um array JSON de `AddressLabel`; retorna quantos foram lidos
+    pub fn load_json(&mut self, json: &str) -> Result<usize, DeepTraceError> {
+        let labels: Vec<AddressLabel> =
+            serde_json::from_str(json).map_err(|e| DeepTraceError::LabelDecode(e.to_string()))?;
+        let count = labels.len();
+        labels.into_iter().for_each(|label| {
+            self.insert(label);
+        });
+        Ok(count)
+    }
+
+    /// Carrega rótulos de um CSV com cabeçalho `address,name,category`.
+    ///
+    /// A coluna `category` é opcional; valores vazios ou desconhecidos viram `Other`.
+    pub fn load_csv(&mut self, csv: &str) -> Result<usize, DeepTraceError> {
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(csv.as_bytes());
+        let mut count = 0;
+        for record in reader.deserialize::<LabelRecord>() {
+            let record = record.map_err(|e| DeepTraceError::LabelDecode(e.to_string()))?;
+            self.insert(AddressLabel {
+                address: record.address,
+                name: record.name,
+                category: record.category.unwrap_or(LabelCategory::Other),
+            });
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Carrega um arquivo de rótulos; a extensão `.csv` seleciona o formato CSV, as demais JSON
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<usize, DeepTraceError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| DeepTraceError::LabelDecode(e.to_string()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => self.load_csv(&content),
+            _ => self.load_json(&content),
+        }
+    }
+
+    /// Registra uma watchlist; nomes repetidos substituem a anterior
+    pub fn add_watchlist(&mut self, watchlist: Watchlist) {
+        self.watchlists.retain(|w| w.name != watchlist.name);
+        self.watchlists.push(watchlist);
+    }
+
+    /// Variante de `add_watchlist` para encadeamento na construção
+    pub fn with_watchlist(mut self, watchlist: Watchlist) -> Self {
+        self.add_watchlist(watchlist);
+        self
+    }
+
+    pub fn watchlists(&self) -> &[Watchlist] {
+        &self.watchlists
+    }
+
+    /// Rótulos dos endereços que aparecem na transação, ordenados por endereço
+    pub fn labels_for(&self, analysis: &TransactionAnalysis) -> Vec<AddressLabel> {
+        appearances(analysis)
+            .keys()
+            .filter_map(|address| self.labels.get(address).cloned())
+            .collect()
+    }
+
+    /// Eventos das watchlists para a transação, na ordem de registro das watchlists
+    pub fn events_for(&self, analysis: &TransactionAnalysis) -> Vec<DetectedEvent> {
+        if self.watchlists.is_empty() {
+            return Vec::new();
+        }
+        let appearances = appearances(analysis);
+        let mut events = Vec::new();
+        for watchlist in &self.watchlists {
+            for (address, (roles, call_indices)) in &appearances {
+                if !watchlist.addresses.contains(address) {
+                    continue;
+                }
+                events.push(DetectedEvent {
+                    watchlist: watchlist.name.clone(),
+                    address: *address,
+                    label: self.labels.get(address).cloned(),
+                    tx_hash: analysis.tx_hash,
+                    block_number: analysis.block_number,
+                    roles: roles.clone(),
+                    call_indices: call_indices.clone(),
+                });
+            }
+        }
+        events
+    }
+
+    /// Preenche `labels` e `watchlist_events` da análise
+    pub fn annotate(&self, analysis: &mut TransactionAnalysis) {
+        analysis.labels = self.labels_for(analysis);
+        analysis.watchlist_events = self.events_for(analysis);
+    }
+}
+
+/// Endereços presentes na transação, com papéis e chamadas ordenados e sem repetição
+fn appearances(analysis: &TransactionAnalysis) -> BTreeMap<Address, (Vec<AddressRole>, Vec<usize>)> {
+    let mut found: BTreeMap<Address, (Vec<AddressRole>, Vec<usize>)> = BTreeMap::new();
+    let mut add = |address: Address, role: AddressRole, call_index: Option<usize>| {
+        if address.is_zero() {
+            return;
+        }
+        let (roles, calls) = found.entry(address).or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+        if let Some(index) = call_index.filter(|i| !calls.contains(i)) {
+            calls.push(index);
+        }
+    };
+
+    add(analysis.from, AddressRole::Sender, None);
+    if let Some(to) = analysis.to {
+        add(to, AddressRole::Target, None);
+    }
+    for (index, step) in analysis.execution_path.iter().enumerate() {
+        add(step.from, AddressRole::Caller, Some(index));
+        add(step.to, AddressRole::Callee, Some(index));
+    }
+    for t in &analysis.token_transfers {
+        add(t.token_address, AddressRole::Token, Some(t.call_index));
+        add(t.from, AddressRole::TransferFrom, Some(t.call_index));
+        add(t.to, AddressRole::TransferTo, Some(t.call_index));
+    }
+    for t in &analysis.eth_transfers {
+        add(t.from, AddressRole::TransferFrom, Some(t.call_index));
+        add(t.to, AddressRole::TransferTo, Some(t.call_index));
+    }
+    for a in &analysis.approvals {
+        add(a.token, AddressRole::Token, Some(a.call_index));
+        add(a.owner, AddressRole::Approval, Some(a.call_index));
+        add(a.spender, AddressRole::Approval, Some(a.call_index));
+    }
+    for s in &analysis.swaps {
+        add(s.pool, AddressRole::Pool, Some(s.call_index));
+    }
+    for l in &analysis.liquidity_changes {
+        add(l.pool, AddressRole::Pool, Some(l.call_index));
+    }
+    for c in &analysis.contract_creations {
+        add(c.contract_address, AddressRole::Created, Some(c.call_index));
+    }
+
+    for (roles, calls) in found.values_mut() {
+        roles.sort();
+        calls.sort();
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallType, ExecutionStep, TokenTransfer, TokenType};
+    use ethereum_types::U256;
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn step(from: u64, to: u64) -> ExecutionStep {
+        ExecutionStep { depth: 0, call_type: CallType::Call, from: addr(from), to: addr(to), value: U256::zero(), input: vec![], output: vec![], gas_used: U256::zero(), error: None }
+    }
+
+    fn tx() -> TransactionAnalysis {
+        let transfer = TokenTransfer { token_type: TokenType::Erc20, token_address: addr(100), from: addr(2), to: addr(7), amount: U256::one(), token_id: None, call_index: 1 };
+        TransactionAnalysis {
+            block_number: 10,
+            token_transfers: vec![transfer],
+            execution_path: vec![step(1, 2), step(2, 100)],
+            ..TransactionAnalysis::empty(H256::from_low_u64_be(1), addr(1), Some(addr(2)))
+        }
+    }
+
+    #[test]
+    fn test_load_json_and_csv_labels() {
+        let mut registry = LabelRegistry::new();
+        let json = format!(r#"[{{"address": "{:?}", "name": "Router", "category": "router"}}]"#, addr(2));
+        assert_eq!(registry.load_json(&json).unwrap(), 1);
+
+        let csv = format!("address,name,category\n{:?}, Tornado Cash ,mixer\n{:?},\"Hot Wallet, 1\",\n{:?},Token,token\n", addr(7), addr(8), addr(100));
+        assert_eq!(registry.load_csv(&csv).unwrap(), 3);
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.get(&addr(2)).unwrap().category, LabelCategory::Router);
+        assert_eq!(registry.get(&addr(7)).unwrap().name, "Tornado Cash");
+        assert_eq!(registry.get(&addr(8)).unwrap().name, "Hot Wallet, 1");
+        assert_eq!(registry.get(&addr(8)).unwrap().category, LabelCategory::Other);
+        assert_eq!(registry.get(&addr(100)).unwrap().category, LabelCategory::Other);
+
+        assert!(matches!(registry.load_csv("address,name\nnot-an-address,x\n"), Err(DeepTraceError::LabelDecode(_))));
+        assert!(registry.load_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_annotate_labels_and_watchlist_events() {
+        let mut registry = LabelRegistry::new()
+            .with_watchlist(Watchlist::new("mixers", [addr(7), addr(9)]))
+            .with_watchlist(Watchlist::new("router", [addr(2)]));
+        registry.insert(AddressLabel { address: addr(7), name: "Mixer".into(), category: LabelCategory::Mixer });
+        registry.insert(AddressLabel { address: addr(2), name: "Router".into(), category: LabelCategory::Router });
+
+        let mut analysis = tx();
+        registry.annotate(&mut analysis);
+
+        assert_eq!(analysis.labels.iter().map(|l| l.address).collect::<Vec<_>>(), vec![addr(2), addr(7)]);
+        assert_eq!(analysis.watchlist_events.len(), 2);
+        let mixer = &analysis.watchlist_events[0];
+        assert_eq!((mixer.watchlist.as_str(), mixer.address), ("mixers", addr(7)));
+        assert_eq!(mixer.roles, vec![AddressRole::TransferTo]);
+        assert_eq!(mixer.call_indices, vec![1]);
+        assert_eq!(mixer.label.as_ref().unwrap().category, LabelCategory::Mixer);
+        let router = &analysis.watchlist_events[1];
+        assert_eq!(router.roles, vec![AddressRole::Target, AddressRole::Caller, AddressRole::Callee, AddressRole::TransferFrom]);
+        assert_eq!((router.tx_hash, router.block_number), (H256::from_low_u64_be(1), 10));
+    }
+}
@@ -14,6 +14,16 @@ mod utils;
 mod config;
 mod types;
 mod deeptrace;
+mod block;
+mod dex;
+mod mev;
+mod wash_trading;
+mod labels;
+mod failure;
+mod service;
+#[cfg(feature = "storage")]
+mod storage;
+mod error;
 
 pub use analyzer::*;
 // Re-exportações públicas
@@ -24,3 +34,13 @@ pub use utils::*;
 pub use config::*;
 pub use types::*;
 pub use deeptrace::*;
+pub use block::*;
+pub use dex::*;
+pub use mev::*;
+pub use wash_trading::*;
+pub use labels::*;
+pub use failure::*;
+pub use service::*;
+#[cfg(feature = "storage")]
+pub use storage::*;
+pub use error::*;
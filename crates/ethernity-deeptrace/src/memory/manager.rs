@@ -1,12 +1,15 @@
 use super::{BufferPool, SmartCache};
+use crate::DeepTraceError;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Gerenciador de memória para a workspace
 pub struct MemoryManager {
     caches: RwLock<HashMap<String, Arc<dyn std::any::Any + Send + Sync>>>,
     buffer_pools: RwLock<HashMap<String, Arc<BufferPool>>>,
+    allocated: Arc<AtomicUsize>,
 }
 
 impl MemoryManager {
@@ -15,9 +18,38 @@ impl MemoryManager {
         Self {
             caches: RwLock::new(HashMap::new()),
             buffer_pools: RwLock::new(HashMap::new()),
+            allocated: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Reserva memória para uma análise em andamento
+    ///
+    /// Falha com `DeepTraceError::MemoryLimit` se a soma das reservas ativas
+    /// ultrapassar `limit`. A reserva é liberada quando o guard é descartado.
+    pub fn reserve(&self, bytes: usize, limit: usize) -> Result<MemoryReservation, DeepTraceError> {
+        let mut current = self.allocated.load(Ordering::Relaxed);
+        loop {
+            let next = current.saturating_add(bytes);
+            if next > limit {
+                return Err(DeepTraceError::MemoryLimit(limit));
+            }
+            match self.allocated.compare_exchange_weak(current, next, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => {
+                    return Ok(MemoryReservation {
+                        allocated: self.allocated.clone(),
+                        bytes,
+                    })
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Bytes atualmente reservados por análises em andamento
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
     /// Registra um cache
     pub fn register_cache<K, V>(&self, name: &str, cache: Arc<SmartCache<K, V>>)
     where
@@ -36,7 +68,10 @@ impl MemoryManager {
 
     /// Obtém estatísticas de uso de memória
     pub fn memory_usage(&self) -> MemoryUsageStats {
-        let mut stats = MemoryUsageStats::default();
+        let mut stats = MemoryUsageStats {
+            allocated_bytes: self.allocated_bytes(),
+            ..Default::default()
+        };
 
         // Coleta estatísticas de caches
         for (name, _cache) in self.caches.read().iter() {
@@ -73,9 +108,30 @@ impl MemoryManager {
     }
 }
 
+/// Reserva de memória obtida com `MemoryManager::reserve`
+#[derive(Debug)]
+pub struct MemoryReservation {
+    allocated: Arc<AtomicUsize>,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    /// Quantidade de bytes reservada
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.allocated.fetch_sub(self.bytes, Ordering::AcqRel);
+    }
+}
+
 /// Estatísticas de uso de memória
 #[derive(Debug, Default, Clone)]
 pub struct MemoryUsageStats {
+    pub allocated_bytes: usize,
     pub cache_stats: HashMap<String, CacheStatsInfo>,
     pub buffer_pool_stats: HashMap<String, BufferPoolStatsInfo>,
 }
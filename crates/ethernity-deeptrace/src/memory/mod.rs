@@ -7,6 +7,6 @@ mod monitor;
 
 pub use cache::{CacheStats, SmartCache};
 pub use buffer_pool::{BufferPool, BufferPoolStats};
-pub use manager::{BufferPoolStatsInfo, CacheStatsInfo, MemoryManager, MemoryUsageStats};
+pub use manager::{BufferPoolStatsInfo, CacheStatsInfo, MemoryManager, MemoryReservation, MemoryUsageStats};
 pub use monitor::{MemoryMonitor, MemoryUsageSnapshot, SystemMemoryInfo};
 
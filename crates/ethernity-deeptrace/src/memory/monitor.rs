@@ -1,4 +1,5 @@
 use super::{MemoryManager, MemoryUsageStats};
+use crate::DeepTraceError;
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::time::Duration;
@@ -39,7 +40,7 @@ impl MemoryMonitor {
     }
 
     /// Inicia o monitoramento
-    pub async fn start_monitoring(&self) -> Result<(), ()> {
+    pub async fn start_monitoring(&self) -> Result<(), DeepTraceError> {
         let memory_manager = self.memory_manager.clone();
         let sampling_interval = self.sampling_interval;
         let history = self.history.clone();
@@ -0,0 +1,268 @@
+use crate::{BlockAnalysis, DexProtocol, LabelCategory, SwapAction, TransactionAnalysis};
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Distância máxima padrão (em transações) entre front-run e back-run
+const DEFAULT_MAX_SPAN: usize = 5;
+
+/// Sandwich reconstruído a partir de transações distintas do bloco
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandwichTriplet {
+    pub protocol: DexProtocol,
+    pub pool: Address,
+    /// Identificador do pool no Uniswap V4 e no Balancer
+    pub pool_id: Option<H256>,
+    /// Remetente do front-run
+    pub attacker: Address,
+    /// Token pago pelo atacante no front-run e recuperado no back-run
+    pub token_in: Address,
+    /// Token comprado no front-run e vendido no back-run
+    pub token_out: Address,
+    pub front_run: H256,
+    /// Transações de outros remetentes que trocaram na mesma direção, na ordem do bloco
+    pub victims: Vec<H256>,
+    pub back_run: H256,
+    /// `amount_in` do swap de front-run
+    pub front_amount_in: U256,
+    /// `amount_out` do swap de back-run
+    pub back_amount_out: U256,
+}
+
+impl SandwichTriplet {
+    /// Lucro bruto em `token_in`, sem descontar gas; `None` quando o back-run devolve menos
+    pub fn gross_profit(&self) -> Option<U256> {
+        self.back_amount_out.checked_sub(self.front_amount_in).filter(|p| !p.is_zero())
+    }
+}
+
+/// Correlaciona swaps de transações diferentes do bloco em sandwiches.
+///
+/// Um sandwich é formado por um front-run que troca `A -> B` em um pool, uma
+/// ou mais vítimas de outros remetentes trocando `A -> B` no mesmo pool e um
+/// back-run do mesmo ator trocando `B -> A` no mesmo pool. O ator é
+/// reconhecido pelo remetente ou pelo contrato chamado, desde que esse
+/// contrato não esteja rotulado como router nem seja chamado por outros
+/// remetentes entre o front-run e o back-run (um router compartilhado).
+#[derive(Debug, Clone)]
+pub struct BlockMevCorrelator {
+    max_span: usize,
+}
+
+impl Default for BlockMevCorrelator {
+    fn default() -> Self {
+        Self { max_span: DEFAULT_MAX_SPAN }
+    }
+}
+
+impl BlockMevCorrelator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limita a distância, em posições no bloco, entre front-run e back-run
+    pub fn with_max_span(mut self, max_span: usize) -> Self {
+        self.max_span = max_span.max(2);
+        self
+    }
+
+    /// Sandwiches encontrados no bloco, na ordem dos front-runs
+    pub fn correlate(&self, block: &BlockAnalysis) -> Vec<SandwichTriplet> {
+        self.correlate_transactions(&block.transactions)
+    }
+
+    /// Variante de `correlate` para transações já ordenadas como no bloco
+    pub fn correlate_transactions(&self, transactions: &[TransactionAnalysis]) -> Vec<SandwichTriplet> {
+        let mut triplets = Vec::new();
+        // Swaps (transação, swap) já usados como back-run
+        let mut consumed: HashSet<(usize, usize)> = HashSet::new();
+
+        for (i, front) in transactions.iter().enumerate() {
+            if !front.status {
+                continue;
+            }
+            for front_swap in &front.swaps {
+                let window_end = (i + self.max_span + 1).min(transactions.len());
+                let back = (i + 2..window_end).find_map(|j| {
+                    let candidate = &transactions[j];
+                    if !candidate.status || !same_actor(front, candidate, &transactions[i + 1..j]) {
+                        return None;
+                    }
+                    candidate
+                        .swaps
+                        .iter()
+                        .enumerate()
+                        .find(|(k, swap)| !consumed.contains(&(j, *k)) && reverses(front_swap, swap))
+                        .map(|(k, swap)| (j, k, swap))
+                });
+                let Some((j, k, back_swap)) = back else { continue };
+
+                let victims: Vec<H256> = transactions[i + 1..j]
+                    .iter()
+                    .filter(|tx| tx.status && tx.from != front.from && !shares_target(front, tx))
+                    .filter(|tx| tx.swaps.iter().any(|swap| same_direction(front_swap, swap)))
+                    .map(|tx| tx.tx_hash)
+                    .collect();
+                if victims.is_empty() {
+                    continue;
+                }
+
+                consumed.insert((j, k));
+                triplets.push(SandwichTriplet {
+                    protocol: front_swap.protocol,
+                    pool: front_swap.pool,
+                    pool_id: front_swap.pool_id,
+                    attacker: front.from,
+                    token_in: front_swap.token_in,
+                    token_out: front_swap.token_out,
+                    front_run: front.tx_hash,
+                    victims,
+                    back_run: transactions[j].tx_hash,
+                    front_amount_in: front_swap.amount_in,
+                    back_amount_out: back_swap.amount_out,
+                });
+            }
+        }
+
+        triplets
+    }
+}
+
+fn same_pool(a: &SwapAction, b: &SwapAction) -> bool {
+    a.pool == b.pool && a.pool_id == b.pool_id
+}
+
+fn same_direction(a: &SwapAction, b: &SwapAction) -> bool {
+    same_pool(a, b) && a.token_in == b.token_in && a.token_out == b.token_out
+}
+
+fn reverses(front: &SwapAction, back: &SwapAction) -> bool {
+    same_pool(front, back) && front.token_in == back.token_out && front.token_out == back.token_in
+}
+
+/// Mesmo remetente ou mesmo contrato de execução (bots com várias EOAs)
+///
+/// O contrato só identifica o ator quando não é um router rotulado e nenhum
+/// outro remetente o chama em `between`.
+fn same_actor(a: &TransactionAnalysis, b: &TransactionAnalysis, between: &[TransactionAnalysis]) -> bool {
+    if a.from == b.from {
+        return true;
+    }
+    a.to.is_some()
+        && a.to == b.to
+        && !calls_router(a)
+        && !calls_router(b)
+        && !between.iter().any(|tx| tx.to == a.to && tx.from != a.from && tx.from != b.from)
+}
+
+fn calls_router(tx: &TransactionAnalysis) -> bool {
+    tx.labels
+        .iter()
+        .any(|label| Some(label.address) == tx.to && label.category == LabelCategory::Router)
+}
+
+fn shares_target(a: &TransactionAnalysis, b: &TransactionAnalysis) -> bool {
+    a.to.is_some() && a.to == b.to
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AddressLabel;
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn swap(pool: u64, token_in: u64, token_out: u64, amount_in: u64, amount_out: u64) -> SwapAction {
+        SwapAction {
+            protocol: DexProtocol::UniswapV2, pool: addr(pool), pool_id: None,
+            token_in: addr(token_in), token_out: addr(token_out),
+            amount_in: U256::from(amount_in), amount_out: U256::from(amount_out), log_index: 0, call_index: 0,
+        }
+    }
+
+    fn tx(hash: u64, from: u64, to: u64, swaps: Vec<SwapAction>) -> TransactionAnalysis {
+        TransactionAnalysis { swaps, ..TransactionAnalysis::empty(H256::from_low_u64_be(hash), addr(from), Some(addr(to))) }
+    }
+
+    fn via_router(mut tx: TransactionAnalysis) -> TransactionAnalysis {
+        let router = tx.to.unwrap();
+        tx.labels = vec![AddressLabel { address: router, name: "Router".into(), category: LabelCategory::Router }];
+        tx
+    }
+
+    #[test]
+    fn test_correlate_sandwich_across_transactions() {
+        // 50 = pool WETH(100)/TOKEN(200); 1 = bot via contrato 9; 2 e 3 = vítimas via router 60
+        let block = BlockAnalysis::new(1, vec![
+            tx(1, 1, 9, vec![swap(50, 100, 200, 10, 1000)]),
+            tx(2, 2, 60, vec![swap(50, 100, 200, 5, 400)]),
+            tx(3, 4, 70, vec![swap(51, 100, 200, 5, 400)]),
+            tx(4, 3, 60, vec![swap(50, 100, 200, 1, 70)]),
+            // Outra EOA do mesmo bot fecha a posição
+            tx(5, 5, 9, vec![swap(50, 200, 100, 1000, 12)]),
+        ], vec![]);
+
+        let triplets = BlockMevCorrelator::new().correlate(&block);
+        assert_eq!(triplets.len(), 1);
+        let sandwich = &triplets[0];
+        assert_eq!(sandwich.attacker, addr(1));
+        assert_eq!((sandwich.token_in, sandwich.token_out), (addr(100), addr(200)));
+        assert_eq!(sandwich.front_run, H256::from_low_u64_be(1));
+        assert_eq!(sandwich.victims, vec![H256::from_low_u64_be(2), H256::from_low_u64_be(4)]);
+        assert_eq!(sandwich.back_run, H256::from_low_u64_be(5));
+        assert_eq!(sandwich.gross_profit(), Some(U256::from(2u64)));
+    }
+
+    #[test]
+    fn test_requires_victim_direction_and_span() {
+        let txs = vec![
+            tx(1, 1, 9, vec![swap(50, 100, 200, 10, 1000)]),
+            // Vítima na direção oposta não caracteriza sandwich
+            tx(2, 2, 60, vec![swap(50, 200, 100, 400, 5)]),
+            tx(3, 1, 9, vec![swap(50, 200, 100, 1000, 9)]),
+        ];
+        assert!(BlockMevCorrelator::new().correlate_transactions(&txs).is_empty());
+
+        let mut txs = vec![tx(1, 1, 9, vec![swap(50, 100, 200, 10, 1000)])];
+        txs.extend((2..5).map(|n| tx(n, n + 10, 60, vec![swap(50, 100, 200, 1, 90)])));
+        txs.push(tx(5, 1, 9, vec![swap(50, 200, 100, 1000, 9)]));
+        assert_eq!(BlockMevCorrelator::new().correlate_transactions(&txs)[0].gross_profit(), None);
+        assert!(BlockMevCorrelator::new().with_max_span(3).correlate_transactions(&txs).is_empty());
+
+        // Router compartilhado não identifica o mesmo ator
+        let txs = vec![
+            tx(1, 1, 60, vec![swap(50, 100, 200, 10, 1000)]),
+            tx(2, 2, 60, vec![swap(50, 100, 200, 5, 400)]),
+            tx(3, 3, 60, vec![swap(50, 200, 100, 1000, 12)]),
+        ];
+        assert!(BlockMevCorrelator::new().correlate_transactions(&txs).is_empty());
+    }
+
+    #[test]
+    fn test_shared_router_senders_are_distinct_actors() {
+        // 1 e 3 usam o router público 60; a vítima 2 passa pelo router 70
+        let txs = vec![
+            via_router(tx(1, 1, 60, vec![swap(50, 100, 200, 10, 1000)])),
+            tx(2, 2, 70, vec![swap(50, 100, 200, 5, 400)]),
+            via_router(tx(3, 3, 60, vec![swap(50, 200, 100, 1000, 12)])),
+        ];
+        assert!(BlockMevCorrelator::new().correlate_transactions(&txs).is_empty());
+
+        // Outro remetente no intervalo também chama o contrato
+        let txs = vec![
+            tx(1, 1, 60, vec![swap(50, 100, 200, 10, 1000)]),
+            tx(2, 2, 70, vec![swap(50, 100, 200, 5, 400)]),
+            tx(3, 4, 60, vec![]),
+            tx(4, 3, 60, vec![swap(50, 200, 100, 1000, 12)]),
+        ];
+        assert!(BlockMevCorrelator::new().correlate_transactions(&txs).is_empty());
+
+        // O mesmo remetente continua sendo o mesmo ator através do router
+        let txs = vec![
+            via_router(tx(1, 1, 60, vec![swap(50, 100, 200, 10, 1000)])),
+            tx(2, 2, 70, vec![swap(50, 100, 200, 5, 400)]),
+            via_router(tx(3, 1, 60, vec![swap(50, 200, 100, 1000, 12)])),
+        ];
+        assert_eq!(BlockMevCorrelator::new().correlate_transactions(&txs).len(), 1);
+    }
+}
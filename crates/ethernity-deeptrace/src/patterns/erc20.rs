@@ -1,5 +1,5 @@
 use super::PatternDetector;
-use crate::{analyzer::TraceAnalysisResult, DetectedPattern, PatternType, ContractType};
+use crate::{analyzer::TraceAnalysisResult, DeepTraceError, DetectedPattern, PatternType, ContractType};
 use async_trait::async_trait;
 
 pub struct Erc20PatternDetector;
@@ -16,7 +16,7 @@ impl PatternDetector for Erc20PatternDetector {
         PatternType::Erc20Creation
     }
 
-    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, ()> {
+    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError> {
         let mut patterns = Vec::new();
 
         for creation in &analysis.contract_creations {
@@ -0,0 +1,381 @@
+use super::PatternDetector;
+use crate::{analyzer::TraceAnalysisResult, CallType, DeepTraceError, DetectedPattern, ExecutionStep, PatternType, TokenTransfer};
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+/// `flashLoan(address,address[],uint256[],uint256[],address,bytes,uint16)` (Aave V2 e V3)
+const AAVE_FLASH_LOAN: [u8; 4] = [0xab, 0x9c, 0x4b, 0x5d];
+/// `flashLoanSimple(address,address,uint256,bytes,uint16)` (Aave V3)
+const AAVE_FLASH_LOAN_SIMPLE: [u8; 4] = [0x42, 0xb0, 0xb7, 0x7c];
+/// `flashLoan(address,address[],uint256[],bytes)` (Balancer V2 Vault)
+const BALANCER_FLASH_LOAN: [u8; 4] = [0x5c, 0x38, 0x44, 0x9e];
+/// `flash(address,uint256,uint256,bytes)` (Uniswap V3)
+const UNISWAP_V3_FLASH: [u8; 4] = [0x49, 0x0e, 0x6c, 0xbc];
+/// `operate((address,uint256)[],(uint8,uint256,(bool,uint8,uint8,uint256),uint256,uint256,address,uint256,bytes)[])` (dYdX Solo)
+const DYDX_OPERATE: [u8; 4] = [0xa6, 0x7a, 0x6a, 0x45];
+const FLASH_LOAN_SELECTORS: [[u8; 4]; 5] =
+    [AAVE_FLASH_LOAN, AAVE_FLASH_LOAN_SIMPLE, BALANCER_FLASH_LOAN, UNISWAP_V3_FLASH, DYDX_OPERATE];
+
+/// LendingPool do Aave V2 na mainnet
+const AAVE_V2_POOL: [u8; 20] = [
+    0x7d, 0x27, 0x68, 0xde, 0x32, 0xb0, 0xb8, 0x0b, 0x7a, 0x34, 0x54, 0xc0, 0x6b, 0xda, 0xc9, 0x4a, 0x69, 0xdd, 0xc7, 0xa9,
+];
+/// Taxas padrão de flash loan do Aave, em pontos-base
+const AAVE_V2_FEE_BPS: u64 = 9;
+const AAVE_V3_FEE_BPS: u64 = 5;
+/// O SoloMargin exige a devolução de 2 wei acima do valor retirado
+const DYDX_FEE_WEI: u64 = 2;
+
+/// Tipos de ação do dYdX Solo usados no flash loan: retirada, callback e depósito
+const DYDX_DEPOSIT: u64 = 0;
+const DYDX_WITHDRAW: u64 = 1;
+const DYDX_CALL: u64 = 8;
+
+/// Protocolo que concedeu o flash loan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlashLoanProvider {
+    AaveV2,
+    AaveV3,
+    Balancer,
+    UniswapV3,
+    DyDx,
+}
+
+/// Ativo emprestado em um flash loan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlashLoanAsset {
+    pub asset: Address,
+    pub amount: U256,
+    /// Taxa paga ao provedor; `None` quando não foi possível determiná-la
+    pub fee: Option<U256>,
+}
+
+/// Flash loan identificado no trace, serializado em `DetectedPattern::data`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlashLoan {
+    pub provider: FlashLoanProvider,
+    /// Contrato chamado (pool, vault ou SoloMargin)
+    pub pool: Address,
+    /// Contrato que recebe os fundos e o callback
+    pub receiver: Address,
+    pub loans: Vec<FlashLoanAsset>,
+    /// Índice da chamada no caminho de execução
+    pub call_index: usize,
+}
+
+/// Detector de flash loans pelo seletor das chamadas aos provedores conhecidos.
+///
+/// As taxas do Aave seguem as taxas padrão de cada versão (o trace não traz o
+/// `premium` do evento); nos demais provedores a taxa é a diferença entre o
+/// valor devolvido ao provedor e o emprestado dentro da chamada.
+pub struct FlashLoanPatternDetector;
+
+impl FlashLoanPatternDetector {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Flash loans identificados no resultado da análise
+    pub fn flash_loans(analysis: &TraceAnalysisResult) -> Vec<FlashLoan> {
+        let path = &analysis.execution_path;
+        path.iter()
+            .enumerate()
+            .filter(|(_, step)| step.call_type == CallType::Call && step.error.is_none())
+            .filter(|(_, step)| step.input.get(..4).is_some_and(|s| FLASH_LOAN_SELECTORS.iter().any(|f| f == s)))
+            .filter_map(|(index, step)| {
+                let end = subtree_end(path, index);
+                let transfers: Vec<&TokenTransfer> = analysis
+                    .token_transfers
+                    .iter()
+                    .filter(|t| t.call_index >= index && t.call_index < end)
+                    .collect();
+                decode_flash_loan(step, index, &transfers)
+            })
+            .collect()
+    }
+}
+
+impl Default for FlashLoanPatternDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PatternDetector for FlashLoanPatternDetector {
+    fn pattern_type(&self) -> PatternType {
+        PatternType::FlashLoan
+    }
+
+    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError> {
+        let patterns = Self::flash_loans(analysis)
+            .into_iter()
+            .map(|loan| DetectedPattern {
+                pattern_type: PatternType::FlashLoan,
+                confidence: 0.95,
+                addresses: vec![loan.pool, loan.receiver],
+                description: format!("Flash loan {:?} com {} ativo(s)", loan.provider, loan.loans.len()),
+                data: serde_json::to_value(&loan).unwrap_or(serde_json::Value::Null),
+            })
+            .collect();
+        Ok(patterns)
+    }
+}
+
+/// Fim (exclusivo) da subárvore do passo `index` no caminho em pré-ordem
+fn subtree_end(path: &[ExecutionStep], index: usize) -> usize {
+    let depth = path[index].depth;
+    path.iter()
+        .enumerate()
+        .skip(index + 1)
+        .find(|(_, step)| step.depth <= depth)
+        .map(|(i, _)| i)
+        .unwrap_or(path.len())
+}
+
+fn decode_flash_loan(step: &ExecutionStep, call_index: usize, transfers: &[&TokenTransfer]) -> Option<FlashLoan> {
+    let words: Vec<U256> = step.input[4..].chunks_exact(32).map(U256::from_big_endian).collect();
+    let pool = step.to;
+    let flash_loan = |provider, receiver, loans| Some(FlashLoan { provider, pool, receiver, loans, call_index });
+
+    match &step.input[..4] {
+        s if s == AAVE_FLASH_LOAN => {
+            let (provider, bps) = if pool == Address::from(AAVE_V2_POOL) {
+                (FlashLoanProvider::AaveV2, AAVE_V2_FEE_BPS)
+            } else {
+                (FlashLoanProvider::AaveV3, AAVE_V3_FEE_BPS)
+            };
+            let assets = array(&words, 1)?;
+            let amounts = array(&words, 2)?;
+            let modes = array(&words, 3)?;
+            let loans = assets
+                .iter()
+                .zip(&amounts)
+                .enumerate()
+                .map(|(i, (asset, amount))| {
+                    // Modos diferentes de zero abrem dívida em vez de cobrar a taxa
+                    let borrowed = modes.get(i).is_some_and(|mode| !mode.is_zero());
+                    let fee = if borrowed { U256::zero() } else { bps_fee(*amount, bps) };
+                    FlashLoanAsset { asset: word_address(asset), amount: *amount, fee: Some(fee) }
+                })
+                .collect();
+            flash_loan(provider, word_address(words.first()?), loans)
+        }
+        s if s == AAVE_FLASH_LOAN_SIMPLE && words.len() >= 3 => {
+            let asset = FlashLoanAsset {
+                asset: word_address(&words[1]),
+                amount: words[2],
+                fee: Some(bps_fee(words[2], AAVE_V3_FEE_BPS)),
+            };
+            flash_loan(FlashLoanProvider::AaveV3, word_address(&words[0]), vec![asset])
+        }
+        s if s == BALANCER_FLASH_LOAN => {
+            let tokens = array(&words, 1)?;
+            let amounts = array(&words, 2)?;
+            let loans = tokens
+                .iter()
+                .zip(&amounts)
+                .map(|(token, amount)| {
+                    let asset = word_address(token);
+                    FlashLoanAsset { asset, amount: *amount, fee: repaid_fee(transfers, pool, asset, *amount) }
+                })
+                .collect();
+            flash_loan(FlashLoanProvider::Balancer, word_address(words.first()?), loans)
+        }
+        s if s == UNISWAP_V3_FLASH && words.len() >= 3 => {
+            // token0/token1 não estão na chamada: os ativos vêm das transferências do pool
+            let loans = lent_assets(transfers, pool)
+                .into_iter()
+                .map(|(asset, amount)| FlashLoanAsset { asset, amount, fee: repaid_fee(transfers, pool, asset, amount) })
+                .collect();
+            flash_loan(FlashLoanProvider::UniswapV3, word_address(&words[0]), loans)
+        }
+        s if s == DYDX_OPERATE => {
+            let actions = dydx_actions(&words)?;
+            let withdraw = actions.iter().position(|(kind, _)| *kind == DYDX_WITHDRAW)?;
+            let call = withdraw + actions[withdraw..].iter().position(|(kind, _)| *kind == DYDX_CALL)?;
+            actions[call..].iter().position(|(kind, _)| *kind == DYDX_DEPOSIT)?;
+            let loans = lent_assets(transfers, pool)
+                .into_iter()
+                .map(|(asset, amount)| FlashLoanAsset { asset, amount, fee: Some(U256::from(DYDX_FEE_WEI)) })
+                .collect();
+            flash_loan(FlashLoanProvider::DyDx, actions[call].1, loans)
+        }
+        _ => None,
+    }
+}
+
+fn bps_fee(amount: U256, bps: u64) -> U256 {
+    amount.saturating_mul(U256::from(bps)) / U256::from(10_000u64)
+}
+
+/// Ativos transferidos pelo provedor, somados por token na ordem em que aparecem
+fn lent_assets(transfers: &[&TokenTransfer], provider: Address) -> Vec<(Address, U256)> {
+    let mut lent: Vec<(Address, U256)> = Vec::new();
+    for t in transfers.iter().filter(|t| t.from == provider && t.token_id.is_none()) {
+        match lent.iter_mut().find(|(asset, _)| *asset == t.token_address) {
+            Some((_, amount)) => *amount = amount.saturating_add(t.amount),
+            None => lent.push((t.token_address, t.amount)),
+        }
+    }
+    lent
+}
+
+/// Valor devolvido ao provedor acima do emprestado
+fn repaid_fee(transfers: &[&TokenTransfer], provider: Address, asset: Address, amount: U256) -> Option<U256> {
+    let repaid = transfers
+        .iter()
+        .filter(|t| t.to == provider && t.token_address == asset)
+        .fold(U256::zero(), |acc, t| acc.saturating_add(t.amount));
+    repaid.checked_sub(amount)
+}
+
+/// Tipo e `otherAddress` de cada ação do `operate`
+fn dydx_actions(words: &[U256]) -> Option<Vec<(u64, Address)>> {
+    let start = offset_index(words, words.get(1)?)?;
+    let len = words.get(start)?;
+    if *len > U256::from(words.len()) {
+        return None;
+    }
+    // Offsets dos elementos são relativos ao início dos dados do array
+    (0..len.as_usize())
+        .map(|i| {
+            let action = start + 1 + offset_index(words, words.get(start + 1 + i)?)?;
+            let kind = words.get(action)?;
+            Some((kind.low_u64(), word_address(words.get(action + 8)?)))
+        })
+        .collect()
+}
+
+/// Array dinâmico cujo offset está na palavra `offset_word`
+fn array(words: &[U256], offset_word: usize) -> Option<Vec<U256>> {
+    let start = offset_index(words, words.get(offset_word)?)?;
+    let len = words.get(start)?;
+    if *len > U256::from(words.len()) {
+        return None;
+    }
+    words.get(start + 1..start + 1 + len.as_usize()).map(|s| s.to_vec())
+}
+
+fn offset_index(words: &[U256], offset: &U256) -> Option<usize> {
+    if *offset > U256::from(words.len() * 32) || !offset.low_u64().is_multiple_of(32) {
+        return None;
+    }
+    Some(offset.as_usize() / 32)
+}
+
+fn word_address(word: &U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallNode, CallTree, TokenType};
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn encode(selector: [u8; 4], words: &[U256]) -> Vec<u8> {
+        let mut input = selector.to_vec();
+        for word in words {
+            let mut bytes = [0u8; 32];
+            word.to_big_endian(&mut bytes);
+            input.extend_from_slice(&bytes);
+        }
+        input
+    }
+
+    fn a(n: u64) -> U256 { U256::from(n) }
+
+    fn step(depth: usize, to: u64, input: Vec<u8>) -> ExecutionStep {
+        ExecutionStep { depth, call_type: CallType::Call, from: addr(1), to: addr(to), value: U256::zero(), input, output: vec![], gas_used: U256::zero(), error: None }
+    }
+
+    fn transfer(token: u64, from: u64, to: u64, amount: u64, call_index: usize) -> TokenTransfer {
+        TokenTransfer { token_type: TokenType::Erc20, token_address: addr(token), from: addr(from), to: addr(to), amount: U256::from(amount), token_id: None, call_index }
+    }
+
+    fn analysis(execution_path: Vec<ExecutionStep>, token_transfers: Vec<TokenTransfer>) -> TraceAnalysisResult {
+        let root = CallNode { index: 0, depth: 0, call_type: CallType::Call, from: addr(1), to: Some(addr(2)), value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(), input: vec![], output: vec![], error: None, children: vec![], parent: None, proxy: None };
+        TraceAnalysisResult {
+            call_tree: CallTree::from_nodes(vec![root]), token_transfers, swaps: vec![], liquidity_changes: vec![], approvals: vec![], eth_transfers: vec![],
+            contract_creations: vec![], execution_path, state_diff: None, storage_writes: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detect_aave_and_balancer_flash_loans() {
+        // flashLoan(receiver=9, assets=[100], amounts=[10000], modes=[0], onBehalfOf, params, referral)
+        let aave = encode(AAVE_FLASH_LOAN, &[a(9), a(0xe0), a(0x120), a(0x160), a(9), a(0x1a0), a(0), a(1), a(100), a(1), a(10_000), a(1), a(0), a(0)]);
+        // flashLoan(recipient=9, tokens=[100, 200], amounts=[500, 700], userData)
+        let balancer = encode(BALANCER_FLASH_LOAN, &[a(9), a(0x80), a(0xe0), a(0x140), a(2), a(100), a(200), a(2), a(500), a(700), a(0)]);
+        let mut reverted = step(1, 0x33, balancer.clone());
+        reverted.error = Some("execution reverted".into());
+        let path = vec![
+            step(0, 9, vec![]),
+            step(1, 0x7777, aave),
+            step(1, 0xba, balancer),
+            step(2, 100, vec![]),
+            step(2, 100, vec![]),
+            reverted,
+        ];
+        let transfers = vec![
+            transfer(100, 0xba, 9, 500, 3),
+            transfer(100, 9, 0xba, 501, 4),
+            // Fora da subárvore do flash loan
+            transfer(100, 9, 0xba, 1000, 5),
+        ];
+        let analysis = analysis(path, transfers);
+        let loans = FlashLoanPatternDetector::flash_loans(&analysis);
+
+        assert_eq!(loans.len(), 2);
+        assert_eq!(loans[0].provider, FlashLoanProvider::AaveV3);
+        assert_eq!(loans[0].receiver, addr(9));
+        assert_eq!(loans[0].loans, vec![FlashLoanAsset { asset: addr(100), amount: a(10_000), fee: Some(a(5)) }]);
+        assert_eq!(loans[1].provider, FlashLoanProvider::Balancer);
+        assert_eq!(loans[1].call_index, 2);
+        assert_eq!(loans[1].loans[0].fee, Some(a(1)));
+        assert_eq!(loans[1].loans[1], FlashLoanAsset { asset: addr(200), amount: a(700), fee: None });
+
+        let patterns = FlashLoanPatternDetector::new().detect(&analysis).await.unwrap();
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].pattern_type, PatternType::FlashLoan);
+        assert_eq!(patterns[0].data["provider"], "AaveV3");
+        assert_eq!(patterns[1].addresses, vec![addr(0xba), addr(9)]);
+    }
+
+    #[test]
+    fn test_detect_uniswap_v3_and_dydx_flash_loans() {
+        let flash = encode(UNISWAP_V3_FLASH, &[a(9), a(1000), a(0), a(0x80), a(0)]);
+        // operate(accounts, actions=[Withdraw, Call(otherAddress=9), Deposit]); cada ação ocupa 12 palavras com `data` vazio
+        let mut operate = vec![a(0x40), a(0xa0), a(1), a(9), a(0), a(3), a(0x60), a(0x1e0), a(0x360)];
+        for kind in [DYDX_WITHDRAW, DYDX_CALL, DYDX_DEPOSIT] {
+            operate.extend([a(kind), a(0), a(0), a(0), a(0), a(300), a(0), a(0), a(9), a(0), a(0x160), a(0)]);
+        }
+        let path = vec![
+            step(0, 9, vec![]),
+            step(1, 0x55, flash),
+            step(2, 100, vec![]),
+            step(1, 0x50, encode(DYDX_OPERATE, &operate)),
+            // operate sem callback é uma operação comum
+            step(1, 0x50, encode(DYDX_OPERATE, &[a(0x40), a(0x60), a(0), a(0)])),
+        ];
+        let transfers = vec![
+            transfer(100, 0x55, 9, 1000, 2),
+            transfer(100, 9, 0x55, 1003, 2),
+            transfer(300, 0x50, 9, 40, 3),
+            transfer(300, 9, 0x50, 42, 3),
+        ];
+        let loans = FlashLoanPatternDetector::flash_loans(&analysis(path, transfers));
+
+        assert_eq!(loans.len(), 2);
+        assert_eq!(loans[0].provider, FlashLoanProvider::UniswapV3);
+        assert_eq!(loans[0].loans, vec![FlashLoanAsset { asset: addr(100), amount: a(1000), fee: Some(a(3)) }]);
+        assert_eq!(loans[1].provider, FlashLoanProvider::DyDx);
+        assert_eq!(loans[1].receiver, addr(9));
+        assert_eq!(loans[1].loans, vec![FlashLoanAsset { asset: addr(300), amount: a(40), fee: Some(a(2)) }]);
+    }
+}
@@ -1,15 +1,20 @@
-use crate::{analyzer::TraceAnalysisResult, DetectedPattern, PatternType};
+use crate::{analyzer::TraceAnalysisResult, DeepTraceError, DetectedPattern, PatternType};
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait PatternDetector: Send + Sync {
     fn pattern_type(&self) -> PatternType;
-    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, ()>;
+    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError>;
+    /// Confiança mínima dos padrões do detector quando `PatternDetectionConfig` não define outra
     fn min_confidence(&self) -> f64 {
         0.7
     }
 }
 
 pub mod erc20;
+pub mod flash_loan;
+pub mod reentrancy;
 
 pub use erc20::Erc20PatternDetector;
+pub use flash_loan::{FlashLoan, FlashLoanAsset, FlashLoanPatternDetector, FlashLoanProvider};
+pub use reentrancy::{Reentrancy, ReentrancyPatternDetector};
@@ -0,0 +1,229 @@
+use super::PatternDetector;
+use crate::{analyzer::TraceAnalysisResult, CallType, DeepTraceError, DetectedPattern, ExecutionStep, PatternType};
+use async_trait::async_trait;
+use ethereum_types::{Address, H256};
+use serde::{Deserialize, Serialize};
+
+/// Confiança quando só a estrutura de chamadas indica a reentrada
+const NESTED_REENTRY_CONFIDENCE: f64 = 0.6;
+/// Confiança quando a vítima altera o storage depois da chamada que a reentrou
+const LATE_WRITE_CONFIDENCE: f64 = 0.9;
+
+/// Reentrada identificada no trace, serializada em `DetectedPattern::data`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reentrancy {
+    /// Contrato reentrado
+    pub victim: Address,
+    /// Contrato que fez a chamada reentrante
+    pub attacker: Address,
+    /// Frame externo da vítima, ainda em execução no momento da reentrada
+    pub outer_call_index: usize,
+    /// Chamada que reentrou a vítima
+    pub reentrant_call_index: usize,
+    /// Slots escritos pela vítima depois de a chamada externa retornar
+    pub late_writes: Vec<H256>,
+}
+
+/// Detector de reentrância pela intercalação da pilha de chamadas com as escritas de storage.
+///
+/// Uma chamada é reentrante quando entra em um contrato que já tem um frame
+/// aberto mais acima na pilha, com outro contrato no meio. Chamadas do próprio
+/// contrato para si mesmo, DELEGATECALL, STATICCALL e frames revertidos não
+/// contam. Quando o trace veio do struct logger, a reentrada só é reportada
+/// se a vítima escreveu no storage depois de a chamada externa
+/// que a reentrou retornar (o padrão checks-effects-interactions violado);
+/// nesse caso reentradas sem escrita tardia, como callbacks de flash loan, são
+/// descartadas. Sem escritas de storage (`callTracer`) basta a reentrada
+/// aninhada, com confiança menor.
+pub struct ReentrancyPatternDetector;
+
+impl ReentrancyPatternDetector {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Reentradas encontradas no resultado da análise, na ordem de execução
+    pub fn reentrancies(analysis: &TraceAnalysisResult) -> Vec<Reentrancy> {
+        let path = &analysis.execution_path;
+        let mut found = Vec::new();
+        // Frames abertos: (índice, contrato cujo storage está em uso)
+        let mut stack: Vec<(usize, Address)> = Vec::new();
+
+        for (index, step) in path.iter().enumerate() {
+            while stack.last().is_some_and(|(open, _)| path[*open].depth >= step.depth) {
+                stack.pop();
+            }
+            let context = match step.call_type {
+                CallType::DelegateCall | CallType::CallCode => stack.last().map(|(_, c)| *c).unwrap_or(step.to),
+                _ => step.to,
+            };
+
+            if step.call_type == CallType::Call && step.error.is_none() && !reverted(path, &stack) {
+                if let Some(reentrancy) = reentry(analysis, &stack, index, step) {
+                    found.push(reentrancy);
+                }
+            }
+            stack.push((index, context));
+        }
+        found
+    }
+}
+
+impl Default for ReentrancyPatternDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PatternDetector for ReentrancyPatternDetector {
+    fn pattern_type(&self) -> PatternType {
+        PatternType::Reentrancy
+    }
+
+    /// Reentradas só estruturais também são reportadas, com a confiança menor
+    fn min_confidence(&self) -> f64 {
+        NESTED_REENTRY_CONFIDENCE
+    }
+
+    async fn detect(&self, analysis: &TraceAnalysisResult) -> Result<Vec<DetectedPattern>, DeepTraceError> {
+        let patterns = Self::reentrancies(analysis)
+            .into_iter()
+            .map(|reentrancy| DetectedPattern {
+                pattern_type: PatternType::Reentrancy,
+                confidence: if reentrancy.late_writes.is_empty() { NESTED_REENTRY_CONFIDENCE } else { LATE_WRITE_CONFIDENCE },
+                addresses: vec![reentrancy.victim, reentrancy.attacker],
+                description: format!(
+                    "Reentrada em {:?} com {} escrita(s) após a chamada externa",
+                    reentrancy.victim,
+                    reentrancy.late_writes.len()
+                ),
+                data: serde_json::to_value(&reentrancy).unwrap_or(serde_json::Value::Null),
+            })
+            .collect();
+        Ok(patterns)
+    }
+}
+
+/// Algum frame aberto reverteu, descartando os efeitos da subárvore
+fn reverted(path: &[ExecutionStep], stack: &[(usize, Address)]) -> bool {
+    stack.iter().any(|(open, _)| path[*open].error.is_some())
+}
+
+fn reentry(analysis: &TraceAnalysisResult, stack: &[(usize, Address)], index: usize, step: &ExecutionStep) -> Option<Reentrancy> {
+    let (_, caller) = *stack.last()?;
+    if caller == step.to {
+        return None;
+    }
+    // Frame aberto mais externo da vítima
+    let position = stack.iter().position(|(_, context)| *context == step.to)?;
+    let outer = stack[position].0;
+    // Cada frame aberto da vítima em diante, com a subchamada que leva à reentrada
+    let mut path: Vec<usize> = stack[position..].iter().map(|(i, _)| *i).collect();
+    path.push(index);
+    let frames: Vec<(usize, usize)> = path.windows(2).map(|w| (w[0], w[1])).collect();
+
+    // Escritas da vítima feitas por um desses frames depois que a subchamada retornou
+    let late_writes: Vec<H256> = analysis
+        .storage_writes
+        .iter()
+        .filter(|w| !w.reverted && w.address == step.to)
+        .filter(|w| {
+            frames.iter().any(|(frame, child)| w.call_index == *frame && w.after_call.is_some_and(|after| after >= *child))
+        })
+        .map(|w| w.slot)
+        .collect();
+
+    if late_writes.is_empty() && !analysis.storage_writes.is_empty() {
+        return None;
+    }
+    Some(Reentrancy { victim: step.to, attacker: caller, outer_call_index: outer, reentrant_call_index: index, late_writes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallNode, CallTree, StorageWrite};
+    use ethereum_types::U256;
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn step(depth: usize, call_type: CallType, from: u64, to: u64) -> ExecutionStep {
+        ExecutionStep { depth, call_type, from: addr(from), to: addr(to), value: U256::zero(), input: vec![], output: vec![], gas_used: U256::zero(), error: None }
+    }
+
+    fn write(call_index: usize, address: u64, slot: u64, after_call: Option<usize>) -> StorageWrite {
+        StorageWrite { call_index, address: addr(address), slot: H256::from_low_u64_be(slot), value: H256::zero(), reverted: false, after_call }
+    }
+
+    fn analysis(execution_path: Vec<ExecutionStep>, storage_writes: Vec<StorageWrite>) -> TraceAnalysisResult {
+        let root = CallNode { index: 0, depth: 0, call_type: CallType::Call, from: addr(1), to: Some(addr(9)), value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(), input: vec![], output: vec![], error: None, children: vec![], parent: None, proxy: None };
+        TraceAnalysisResult {
+            call_tree: CallTree::from_nodes(vec![root]), token_transfers: vec![], swaps: vec![], liquidity_changes: vec![], approvals: vec![], eth_transfers: vec![],
+            contract_creations: vec![], execution_path, state_diff: None, storage_writes,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reentry_with_write_after_external_call() {
+        // 9 = atacante, 5 = cofre que envia ETH antes de zerar o saldo
+        let path = vec![
+            step(0, CallType::Call, 1, 9),
+            step(1, CallType::Call, 9, 5),
+            step(2, CallType::Call, 5, 9),
+            step(3, CallType::Call, 9, 5),
+            step(4, CallType::Call, 5, 9),
+            step(1, CallType::Call, 9, 7),
+        ];
+        let writes = vec![
+            // Saldo zerado pelo frame externo depois de a chamada 2 retornar
+            write(1, 5, 3, Some(2)),
+            // Escrita anterior à chamada externa
+            write(1, 5, 4, None),
+            write(3, 5, 3, Some(4)),
+        ];
+        let analysis = analysis(path, writes);
+        let found = ReentrancyPatternDetector::reentrancies(&analysis);
+
+        // Os callbacks para o atacante não têm escrita tardia e são descartados
+        assert_eq!(found, vec![Reentrancy {
+            victim: addr(5),
+            attacker: addr(9),
+            outer_call_index: 1,
+            reentrant_call_index: 3,
+            late_writes: vec![H256::from_low_u64_be(3)],
+        }]);
+
+        let patterns = ReentrancyPatternDetector::new().detect(&analysis).await.unwrap();
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].pattern_type, PatternType::Reentrancy);
+        assert_eq!(patterns[0].confidence, LATE_WRITE_CONFIDENCE);
+        assert_eq!(patterns[0].addresses, vec![addr(5), addr(9)]);
+    }
+
+    #[test]
+    fn test_nested_reentry_without_storage_data() {
+        let mut reverted = step(1, CallType::Call, 9, 6);
+        reverted.error = Some("execution reverted".into());
+        let path = vec![
+            step(0, CallType::Call, 1, 9),
+            step(1, CallType::Call, 9, 5),
+            // Callback do pool para o contrato de origem
+            step(2, CallType::Call, 5, 9),
+            // Chamada do contrato para si mesmo e leitura estática não contam
+            step(3, CallType::Call, 9, 9),
+            step(3, CallType::StaticCall, 9, 5),
+            // Biblioteca executando no contexto de 9
+            step(1, CallType::DelegateCall, 9, 8),
+            step(2, CallType::Call, 9, 9),
+            reverted,
+            step(2, CallType::Call, 6, 9),
+        ];
+        let found = ReentrancyPatternDetector::reentrancies(&analysis(path, vec![]));
+
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].victim, found[0].attacker), (addr(9), addr(5)));
+        assert_eq!((found[0].outer_call_index, found[0].reentrant_call_index), (0, 2));
+        assert!(found[0].late_writes.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{BlockAnalysis, DeepTraceAnalyzer, DeepTraceError, ServiceConfig, TransactionAnalysis};
+use async_trait::async_trait;
+use ethereum_types::H256;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+
+/// Destino das análises produzidas pelo `DeepTraceService` (ex.: um produtor Kafka)
+#[async_trait]
+pub trait AnalysisSink: Send + Sync {
+    /// Recebe a análise de uma transação, na ordem do bloco
+    async fn send(&self, analysis: TransactionAnalysis) -> Result<(), DeepTraceError>;
+
+    /// Chamado depois de todas as transações do bloco; `failed` lista as que não puderam ser analisadas
+    async fn block_completed(&self, _block_number: u64, _failed: &[(H256, DeepTraceError)]) -> Result<(), DeepTraceError> {
+        Ok(())
+    }
+
+    /// Chamado quando o bloco inteiro não pôde ser analisado; o serviço segue para o próximo
+    async fn block_failed(&self, _block_number: u64, _error: &DeepTraceError) -> Result<(), DeepTraceError> {
+        Ok(())
+    }
+}
+
+/// Contadores de uma execução do serviço
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServiceStats {
+    pub blocks: u64,
+    pub failed_blocks: u64,
+    /// Análises entregues ao sink
+    pub transactions: u64,
+    pub failed_transactions: u64,
+}
+
+/// Item da fila entre a análise e o sink
+enum Output {
+    Transaction(Box<TransactionAnalysis>),
+    BlockCompleted(u64, Vec<(H256, DeepTraceError)>),
+    BlockFailed(u64, DeepTraceError),
+}
+
+/// Serviço de análise contínua dos blocos novos.
+///
+/// Os heads recebidos alimentam uma fila limitada de blocos; cada bloco é
+/// analisado com `DeepTraceAnalyzer::analyze_block` e as análises seguem, por
+/// uma segunda fila limitada, para o sink. Quando o sink ou a análise ficam
+/// para trás as filas enchem e o consumo de heads pausa, em vez de acumular
+/// blocos em memória. Heads que pulam números enfileiram os blocos
+/// intermediários; heads repetidos ou anteriores (reorgs) são ignorados.
+pub struct DeepTraceService {
+    analyzer: Arc<DeepTraceAnalyzer>,
+    sink: Arc<dyn AnalysisSink>,
+    config: ServiceConfig,
+}
+
+impl DeepTraceService {
+    pub fn new(analyzer: Arc<DeepTraceAnalyzer>, sink: Arc<dyn AnalysisSink>, config: Option<ServiceConfig>) -> Self {
+        Self { analyzer, sink, config: config.unwrap_or_default() }
+    }
+
+    /// Consome os heads até o fim do stream ou até um erro do sink
+    pub async fn run<S>(&self, heads: S) -> Result<ServiceStats, DeepTraceError>
+    where
+        S: Stream<Item = u64> + Send,
+    {
+        let (block_tx, block_rx) = mpsc::channel(self.config.block_queue.max(1));
+        let (result_tx, result_rx) = mpsc::channel(self.config.result_queue.max(1));
+        let (_, _, delivered) = tokio::join!(
+            enqueue_heads(heads, block_tx),
+            self.analyze_blocks(block_rx, result_tx),
+            self.deliver(result_rx),
+        );
+        delivered
+    }
+
+    /// Variante de `run` que consulta o bloco atual a cada `poll_interval_ms`.
+    ///
+    /// Para receber heads por assinatura use `run` com o stream de
+    /// `EthernityRpcClient::subscribe_new_heads`.
+    pub async fn run_polling(&self) -> Result<ServiceStats, DeepTraceError> {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        self.run(poll_heads(self.analyzer.rpc_client.clone(), interval)).await
+    }
+
+    async fn analyze_blocks(&self, mut blocks: mpsc::Receiver<u64>, results: mpsc::Sender<Output>) {
+        loop {
+            // O sink pode parar enquanto a fila de blocos está vazia
+            let block_number = tokio::select! {
+                block_number = blocks.recv() => block_number,
+                _ = results.closed() => None,
+            };
+            let Some(block_number) = block_number else { return };
+            let outputs = match self.analyzer.analyze_block(block_number).await {
+                Ok(BlockAnalysis { transactions, failed_transactions, .. }) => {
+                    let mut outputs: Vec<Output> = transactions.into_iter().map(|tx| Output::Transaction(Box::new(tx))).collect();
+                    outputs.push(Output::BlockCompleted(block_number, failed_transactions));
+                    outputs
+                }
+                Err(e) => vec![Output::BlockFailed(block_number, e)],
+            };
+            for output in outputs {
+                if results.send(output).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, mut results: mpsc::Receiver<Output>) -> Result<ServiceStats, DeepTraceError> {
+        let mut stats = ServiceStats::default();
+        while let Some(output) = results.recv().await {
+            match output {
+                Output::Transaction(analysis) => {
+                    self.sink.send(*analysis).await?;
+                    stats.transactions += 1;
+                }
+                Output::BlockCompleted(block_number, failed) => {
+                    self.sink.block_completed(block_number, &failed).await?;
+                    stats.blocks += 1;
+                    stats.failed_transactions += failed.len() as u64;
+                }
+                Output::BlockFailed(block_number, error) => {
+                    self.sink.block_failed(block_number, &error).await?;
+                    stats.failed_blocks += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Stream com o número do bloco atual consultado a cada `interval`; falhas de RPC são ignoradas
+pub fn poll_heads(
+    rpc: Arc<dyn ethernity_core::traits::RpcProvider>,
+    interval: Duration,
+) -> impl Stream<Item = u64> + Send {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    futures::stream::unfold((rpc, ticker), |(rpc, mut ticker)| async move {
+        loop {
+            ticker.tick().await;
+            if let Ok(block_number) = rpc.get_block_number().await {
+                return Some((block_number, (rpc, ticker)));
+            }
+        }
+    })
+}
+
+/// Converte heads em números de bloco consecutivos na fila, até o fim do stream ou o fechamento da fila
+async fn enqueue_heads<S: Stream<Item = u64>>(heads: S, blocks: mpsc::Sender<u64>) {
+    futures::pin_mut!(heads);
+    let mut last: Option<u64> = None;
+    loop {
+        let head = tokio::select! {
+            head = heads.next() => head,
+            _ = blocks.closed() => return,
+        };
+        let Some(head) = head else { return };
+        let start = match last {
+            Some(last) if head <= last => continue,
+            Some(last) => last + 1,
+            None => head,
+        };
+        for block_number in start..=head {
+            if blocks.send(block_number).await.is_err() {
+                return;
+            }
+        }
+        last = Some(head);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::Address;
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    /// Bloco `n` com `n` transações; o bloco 3 falha por inteiro
+    struct HeadsRpc;
+
+    #[async_trait]
+    impl ethernity_core::traits::RpcProvider for HeadsRpc {
+        async fn get_transaction_trace(&self, _tx: H256) -> ethernity_core::error::Result<Vec<u8>> {
+            let trace = json!({"from": format!("{:?}", Address::from_low_u64_be(1)), "gas": "0", "gasUsed": "0", "to": format!("{:?}", Address::from_low_u64_be(2)), "input": "0x", "output": "0x", "value": "0", "type": "CALL"});
+            Ok(serde_json::to_vec(&trace).unwrap())
+        }
+        async fn get_transaction_receipt(&self, tx: H256) -> ethernity_core::error::Result<Vec<u8>> {
+            let receipt = json!({"blockNumber": format!("0x{:x}", tx.to_low_u64_be() / 100), "status": "0x1", "gasUsed": "0x0", "logs": []});
+            Ok(serde_json::to_vec(&receipt).unwrap())
+        }
+        async fn get_code(&self, _address: Address) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> ethernity_core::error::Result<Vec<u8>> { Ok(vec![]) }
+        async fn get_block_number(&self) -> ethernity_core::error::Result<u64> { Ok(7) }
+        async fn get_block_hash(&self, _block_number: u64) -> ethernity_core::error::Result<H256> { Ok(H256::zero()) }
+        async fn get_block_transactions(&self, block_number: u64) -> ethernity_core::error::Result<Vec<H256>> {
+            if block_number == 3 {
+                return Err(ethernity_core::Error::RpcError("bloco indisponível".into()));
+            }
+            Ok((0..block_number).map(|i| H256::from_low_u64_be(block_number * 100 + i)).collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct CollectSink {
+        transactions: Mutex<Vec<(u64, H256)>>,
+        blocks: Mutex<Vec<(u64, bool)>>,
+        fail_on: Option<u64>,
+    }
+
+    #[async_trait]
+    impl AnalysisSink for CollectSink {
+        async fn send(&self, analysis: TransactionAnalysis) -> Result<(), DeepTraceError> {
+            if Some(analysis.block_number) == self.fail_on {
+                return Err(DeepTraceError::RpcFailure("sink indisponível".into()));
+            }
+            self.transactions.lock().push((analysis.block_number, analysis.tx_hash));
+            Ok(())
+        }
+        async fn block_completed(&self, block_number: u64, _failed: &[(H256, DeepTraceError)]) -> Result<(), DeepTraceError> {
+            self.blocks.lock().push((block_number, true));
+            Ok(())
+        }
+        async fn block_failed(&self, block_number: u64, _error: &DeepTraceError) -> Result<(), DeepTraceError> {
+            self.blocks.lock().push((block_number, false));
+            Ok(())
+        }
+    }
+
+    fn analyzer() -> Arc<DeepTraceAnalyzer> {
+        Arc::new(DeepTraceAnalyzer::new(Arc::new(HeadsRpc), None))
+    }
+
+    #[tokio::test]
+    async fn test_run_fills_gaps_and_delivers_in_order() {
+        let sink = Arc::new(CollectSink::default());
+        let config = ServiceConfig { block_queue: 1, result_queue: 1, ..Default::default() };
+        let service = DeepTraceService::new(analyzer(), sink.clone(), Some(config));
+
+        // 1 -> 4 enfileira 2 e 3; o 4 repetido e o 2 (reorg) são ignorados
+        let stats = service.run(futures::stream::iter(vec![1, 4, 4, 2])).await.unwrap();
+
+        assert_eq!(stats, ServiceStats { blocks: 3, failed_blocks: 1, transactions: 7, failed_transactions: 0 });
+        assert_eq!(*sink.blocks.lock(), vec![(1, true), (2, true), (3, false), (4, true)]);
+        let transactions = sink.transactions.lock();
+        assert_eq!(transactions.iter().map(|(block, _)| *block).collect::<Vec<_>>(), vec![1, 2, 2, 4, 4, 4, 4]);
+        assert_eq!(transactions[1].1, H256::from_low_u64_be(200));
+        assert_eq!(transactions[6].1, H256::from_low_u64_be(403));
+    }
+
+    #[tokio::test]
+    async fn test_sink_error_stops_polling_service() {
+        let sink = Arc::new(CollectSink { fail_on: Some(7), ..Default::default() });
+        let config = ServiceConfig { poll_interval_ms: 1, ..Default::default() };
+        let service = DeepTraceService::new(analyzer(), sink.clone(), Some(config));
+
+        // O stream de polling nunca termina: o serviço só para pelo erro do sink
+        let result = tokio::time::timeout(Duration::from_secs(5), service.run_polling()).await.unwrap();
+        assert!(matches!(result, Err(DeepTraceError::RpcFailure(_))));
+        assert!(sink.transactions.lock().is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{BlockAnalysis, DeepTraceError, DetectedPattern, PatternType, TransactionAnalysis};
+use chrono::{DateTime, Utc};
+use ethereum_types::{Address, H256};
+use redb::{Database, MultimapTableDefinition, ReadTransaction, ReadableTable, ReadableTableMetadata, TableDefinition};
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Análises serializadas em JSON, por hash da transação
+const TRANSACTIONS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("transactions");
+/// `endereço ++ bloco (big-endian)` -> hashes das transações que tocam o endereço
+const ADDRESS_INDEX: MultimapTableDefinition<&[u8], &[u8]> = MultimapTableDefinition::new("address_index");
+/// `tipo do padrão ++ timestamp em ms (big-endian)` -> hashes das transações com o padrão
+const PATTERN_INDEX: MultimapTableDefinition<&[u8], &[u8]> = MultimapTableDefinition::new("pattern_index");
+
+/// Padrão detectado com a transação de origem
+#[derive(Debug, Clone)]
+pub struct StoredPattern {
+    pub tx_hash: H256,
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub pattern: DetectedPattern,
+}
+
+/// Armazenamento persistente de análises em um arquivo redb.
+///
+/// Cada transação é gravada uma vez por hash; gravar novamente substitui a
+/// análise e os índices anteriores. As operações são síncronas e devem ser
+/// executadas em `spawn_blocking` quando chamadas de código assíncrono.
+pub struct AnalysisStore {
+    db: Database,
+}
+
+impl AnalysisStore {
+    /// Abre (ou cria) o banco no caminho informado
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DeepTraceError> {
+        let db = Database::create(path).map_err(storage_error)?;
+        Self::with_database(db)
+    }
+
+    /// Banco em memória, útil para testes e análises temporárias
+    pub fn in_memory() -> Result<Self, DeepTraceError> {
+        let db = Database::builder()
+            .create_with_backend(redb::backends::InMemoryBackend::new())
+            .map_err(storage_error)?;
+        Self::with_database(db)
+    }
+
+    fn with_database(db: Database) -> Result<Self, DeepTraceError> {
+        // Cria as tabelas para que leituras em um banco vazio não falhem
+        let tx = db.begin_write().map_err(storage_error)?;
+        tx.open_table(TRANSACTIONS).map_err(storage_error)?;
+        tx.open_multimap_table(ADDRESS_INDEX).map_err(storage_error)?;
+        tx.open_multimap_table(PATTERN_INDEX).map_err(storage_error)?;
+        tx.commit().map_err(storage_error)?;
+        Ok(Self { db })
+    }
+
+    /// Grava a análise de uma transação
+    pub fn insert(&self, analysis: &TransactionAnalysis) -> Result<(), DeepTraceError> {
+        self.insert_all(std::slice::from_ref(analysis))
+    }
+
+    /// Grava todas as transações analisadas do bloco
+    pub fn insert_block(&self, block: &BlockAnalysis) -> Result<(), DeepTraceError> {
+        self.insert_all(&block.transactions)
+    }
+
+    fn insert_all(&self, analyses: &[TransactionAnalysis]) -> Result<(), DeepTraceError> {
+        let tx = self.db.begin_write().map_err(storage_error)?;
+        {
+            let mut transactions = tx.open_table(TRANSACTIONS).map_err(storage_error)?;
+            let mut addresses = tx.open_multimap_table(ADDRESS_INDEX).map_err(storage_error)?;
+            let mut patterns = tx.open_multimap_table(PATTERN_INDEX).map_err(storage_error)?;
+
+            for analysis in analyses {
+                let hash = analysis.tx_hash.as_bytes();
+                let previous = transactions
+                    .get(hash)
+                    .map_err(storage_error)?
+                    .map(|bytes| decode(bytes.value()))
+                    .transpose()?;
+                if let Some(previous) = previous {
+                    for key in address_keys(&previous) {
+                        addresses.remove(key.as_slice(), hash).map_err(storage_error)?;
+                    }
+                    for key in pattern_keys(&previous) {
+                        patterns.remove(key.as_slice(), hash).map_err(storage_error)?;
+                    }
+                }
+
+                let json = serde_json::to_vec(analysis).map_err(|e| DeepTraceError::Storage(e.to_string()))?;
+                transactions.insert(hash, json.as_slice()).map_err(storage_error)?;
+                for key in address_keys(analysis) {
+                    addresses.insert(key.as_slice(), hash).map_err(storage_error)?;
+                }
+                for key in pattern_keys(analysis) {
+                    patterns.insert(key.as_slice(), hash).map_err(storage_error)?;
+                }
+            }
+        }
+        tx.commit().map_err(storage_error)
+    }
+
+    /// Análise gravada para a transação
+    pub fn get(&self, tx_hash: H256) -> Result<Option<TransactionAnalysis>, DeepTraceError> {
+        let tx = self.db.begin_read().map_err(storage_error)?;
+        let transactions = tx.open_table(TRANSACTIONS).map_err(storage_error)?;
+        let bytes = transactions.get(tx_hash.as_bytes()).map_err(storage_error)?;
+        bytes.map(|bytes| decode(bytes.value())).transpose()
+    }
+
+    /// Transações que tocam o endereço nos blocos do intervalo, ordenadas por bloco
+    pub fn transactions_touching(
+        &self,
+        address: Address,
+        blocks: RangeInclusive<u64>,
+    ) -> Result<Vec<TransactionAnalysis>, DeepTraceError> {
+        let start = address_key(&address, *blocks.start());
+        let end = address_key(&address, *blocks.end());
+
+        let tx = self.db.begin_read().map_err(storage_error)?;
+        let hashes = indexed_hashes(&tx, ADDRESS_INDEX, &start, &end)?;
+        self.load(&tx, hashes)
+    }
+
+    /// Padrões do tipo detectados em transações com timestamp a partir de `since`
+    pub fn patterns_since(
+        &self,
+        pattern_type: PatternType,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<StoredPattern>, DeepTraceError> {
+        let start = pattern_key(pattern_type, since.timestamp_millis());
+        let end = pattern_key(pattern_type, i64::MAX);
+
+        let tx = self.db.begin_read().map_err(storage_error)?;
+        let hashes = indexed_hashes(&tx, PATTERN_INDEX, &start, &end)?;
+
+        let mut results = Vec::new();
+        for analysis in self.load(&tx, hashes)? {
+            let matching = analysis.detected_patterns.into_iter().filter(|p| p.pattern_type == pattern_type);
+            results.extend(matching.map(|pattern| StoredPattern {
+                tx_hash: analysis.tx_hash,
+                block_number: analysis.block_number,
+                timestamp: analysis.timestamp,
+                pattern,
+            }));
+        }
+        Ok(results)
+    }
+
+    /// Quantidade de transações gravadas
+    pub fn len(&self) -> Result<u64, DeepTraceError> {
+        let tx = self.db.begin_read().map_err(storage_error)?;
+        let transactions = tx.open_table(TRANSACTIONS).map_err(storage_error)?;
+        transactions.len().map_err(storage_error)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, DeepTraceError> {
+        Ok(self.len()? == 0)
+    }
+
+    fn load(&self, tx: &ReadTransaction, hashes: Vec<H256>) -> Result<Vec<TransactionAnalysis>, DeepTraceError> {
+        let transactions = tx.open_table(TRANSACTIONS).map_err(storage_error)?;
+        let mut seen = BTreeSet::new();
+        let mut analyses = Vec::new();
+        for hash in hashes {
+            if !seen.insert(hash) {
+                continue;
+            }
+            if let Some(bytes) = transactions.get(hash.as_bytes()).map_err(storage_error)? {
+                analyses.push(decode(bytes.value())?);
+            }
+        }
+        Ok(analyses)
+    }
+}
+
+/// Hashes gravados no índice entre as chaves `start` e `end` (inclusive), na ordem das chaves
+fn indexed_hashes(
+    tx: &ReadTransaction,
+    table: MultimapTableDefinition<&[u8], &[u8]>,
+    start: &[u8],
+    end: &[u8],
+) -> Result<Vec<H256>, DeepTraceError> {
+    let index = tx.open_multimap_table(table).map_err(storage_error)?;
+    let mut hashes = Vec::new();
+    for entry in index.range::<&[u8]>(start..=end).map_err(storage_error)? {
+        let (_, values) = entry.map_err(storage_error)?;
+        for value in values {
+            hashes.push(H256::from_slice(value.map_err(storage_error)?.value()));
+        }
+    }
+    Ok(hashes)
+}
+
+/// Endereços tocados pela transação: remetente, destino, chamadas, transferências e swaps
+fn touched_addresses(analysis: &TransactionAnalysis) -> BTreeSet<Address> {
+    let mut addresses = BTreeSet::new();
+    addresses.insert(analysis.from);
+    addresses.extend(analysis.to);
+    for step in &analysis.execution_path {
+        addresses.insert(step.from);
+        addresses.insert(step.to);
+    }
+    for t in &analysis.token_transfers {
+        addresses.extend([t.token_address, t.from, t.to]);
+    }
+    for t in &analysis.eth_transfers {
+        addresses.extend([t.from, t.to]);
+    }
+    for swap in &analysis.swaps {
+        addresses.extend([swap.pool, swap.token_in, swap.token_out]);
+    }
+    for creation in &analysis.contract_creations {
+        addresses.extend([creation.creator, creation.contract_address]);
+    }
+    addresses.remove(&Address::zero());
+    addresses
+}
+
+fn address_keys(analysis: &TransactionAnalysis) -> Vec<Vec<u8>> {
+    touched_addresses(analysis)
+        .iter()
+        .map(|address| address_key(address, analysis.block_number))
+        .collect()
+}
+
+fn address_key(address: &Address, block_number: u64) -> Vec<u8> {
+    let mut key = address.as_bytes().to_vec();
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key
+}
+
+fn pattern_keys(analysis: &TransactionAnalysis) -> Vec<Vec<u8>> {
+    let types: BTreeSet<String> = analysis.detected_patterns.iter().map(|p| format!("{:?}", p.pattern_type)).collect();
+    types
+        .into_iter()
+        .map(|name| pattern_key_from_name(&name, analysis.timestamp.timestamp_millis()))
+        .collect()
+}
+
+fn pattern_key(pattern_type: PatternType, timestamp_ms: i64) -> Vec<u8> {
+    pattern_key_from_name(&format!("{:?}", pattern_type), timestamp_ms)
+}
+
+/// O nome termina em `0` para que um tipo não seja prefixo de outro
+fn pattern_key_from_name(name: &str, timestamp_ms: i64) -> Vec<u8> {
+    let mut key = name.as_bytes().to_vec();
+    key.push(0);
+    key.extend_from_slice(&timestamp_ms.max(0).to_be_bytes());
+    key
+}
+
+fn decode(bytes: &[u8]) -> Result<TransactionAnalysis, DeepTraceError> {
+    serde_json::from_slice(bytes).map_err(|e| DeepTraceError::Storage(e.to_string()))
+}
+
+fn storage_error(err: impl Into<redb::Error>) -> DeepTraceError {
+    DeepTraceError::Storage(err.into().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenTransfer, TokenType};
+    use ethereum_types::U256;
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn tx(hash: u64, block: u64, from: u64, to: u64, timestamp: DateTime<Utc>) -> TransactionAnalysis {
+        TransactionAnalysis {
+            block_number: block,
+            timestamp,
+            ..TransactionAnalysis::empty(H256::from_low_u64_be(hash), addr(from), Some(addr(to)))
+        }
+    }
+
+    fn pattern(pattern_type: PatternType) -> DetectedPattern {
+        DetectedPattern { pattern_type, confidence: 1.0, addresses: vec![], data: serde_json::Value::Null, description: String::new() }
+    }
+
+    #[test]
+    fn test_query_by_address_and_block_range() {
+        let store = AnalysisStore::in_memory().unwrap();
+        let now = Utc::now();
+        let mut transfer = tx(2, 20, 3, 4, now);
+        transfer.token_transfers.push(TokenTransfer { token_type: TokenType::Erc20, token_address: addr(100), from: addr(4), to: addr(1), amount: U256::one(), token_id: None, call_index: 0 });
+        store.insert(&tx(1, 10, 1, 2, now)).unwrap();
+        store.insert(&transfer).unwrap();
+        store.insert(&tx(3, 30, 1, 5, now)).unwrap();
+        assert_eq!(store.len().unwrap(), 3);
+
+        let hashes = |txs: Vec<TransactionAnalysis>| txs.iter().map(|t| t.tx_hash.to_low_u64_be()).collect::<Vec<_>>();
+        assert_eq!(hashes(store.transactions_touching(addr(1), 0..=u64::MAX).unwrap()), vec![1, 2, 3]);
+        assert_eq!(hashes(store.transactions_touching(addr(1), 11..=30).unwrap()), vec![2, 3]);
+        assert_eq!(hashes(store.transactions_touching(addr(100), 0..=100).unwrap()), vec![2]);
+        assert!(store.transactions_touching(addr(2), 11..=100).unwrap().is_empty());
+
+        // Regravar substitui a análise e os índices
+        store.insert(&tx(1, 10, 7, 8, now)).unwrap();
+        assert_eq!(store.len().unwrap(), 3);
+        assert!(store.transactions_touching(addr(2), 0..=100).unwrap().is_empty());
+        assert_eq!(store.get(H256::from_low_u64_be(1)).unwrap().unwrap().from, addr(7));
+        assert!(store.get(H256::from_low_u64_be(9)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_patterns_since_and_reopen() {
+        let path = std::env::temp_dir().join(format!("ethernity-store-{}.redb", std::process::id()));
+        let now = Utc::now();
+        {
+            let store = AnalysisStore::open(&path).unwrap();
+            let mut old = tx(1, 1, 1, 2, now - chrono::Duration::hours(48));
+            old.detected_patterns.push(pattern(PatternType::FlashLoan));
+            let mut recent = tx(2, 2, 1, 2, now - chrono::Duration::hours(1));
+            recent.detected_patterns.extend([pattern(PatternType::FlashLoan), pattern(PatternType::Unknown)]);
+            let block = BlockAnalysis::new(2, vec![old, recent], vec![]);
+            store.insert_block(&block).unwrap();
+        }
+
+        let store = AnalysisStore::open(&path).unwrap();
+        let found = store.patterns_since(PatternType::FlashLoan, now - chrono::Duration::hours(24)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tx_hash, H256::from_low_u64_be(2));
+        assert_eq!(found[0].pattern.pattern_type, PatternType::FlashLoan);
+        assert_eq!(store.patterns_since(PatternType::FlashLoan, now - chrono::Duration::hours(72)).unwrap().len(), 2);
+        assert!(store.patterns_since(PatternType::Erc20Creation, now - chrono::Duration::hours(72)).unwrap().is_empty());
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
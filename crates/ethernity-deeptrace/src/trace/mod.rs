@@ -1,7 +1,11 @@
 mod detector;
+mod render;
+mod struct_logs;
 mod tree;
 mod types;
 
 pub use detector::TraceDetector;
-pub use tree::{CallNode, CallTree};
-pub use types::{CallTrace, CallType};
+pub use render::RenderOptions;
+pub use struct_logs::StructLogTrace;
+pub use tree::{CallNode, CallTree, ProxyInfo};
+pub use types::{CallLog, CallTrace, CallType};
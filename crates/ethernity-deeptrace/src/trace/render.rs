@@ -0,0 +1,276 @@
+use super::{CallNode, CallTree, CallType};
+use crate::utils::{self, DisplayUtils};
+use ethereum_types::Address;
+use std::collections::HashMap;
+
+/// Opções de renderização da árvore de chamadas em DOT/Mermaid
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Exibe o seletor da chamada, pelo nome quando conhecido
+    pub show_selector: bool,
+    /// Exibe o valor em ETH de chamadas com `value` não nulo
+    pub show_value: bool,
+    /// Exibe o gas usado
+    pub show_gas: bool,
+    /// Abrevia endereços (`0x1234…abcd`)
+    pub short_addresses: bool,
+    /// Nomes de seletores adicionais, com precedência sobre os conhecidos
+    pub selector_names: HashMap<[u8; 4], String>,
+    /// Profundidade máxima renderizada; subárvores abaixo dela são resumidas
+    pub max_depth: Option<usize>,
+    /// Máximo de filhos exibidos por nó; os demais são resumidos
+    pub max_children: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            show_selector: true,
+            show_value: true,
+            show_gas: true,
+            short_addresses: true,
+            selector_names: HashMap::new(),
+            max_depth: None,
+            max_children: None,
+        }
+    }
+}
+
+/// Grafo intermediário compartilhado pelos formatos de saída
+struct Graph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<(String, String, bool)>,
+}
+
+struct GraphNode {
+    id: String,
+    lines: Vec<String>,
+    failed: bool,
+    /// Nó que resume chamadas omitidas pela truncagem
+    summary: bool,
+}
+
+impl CallTree {
+    /// Renderiza a árvore no formato DOT do Graphviz
+    pub fn to_dot(&self, options: &RenderOptions) -> String {
+        let graph = Graph::build(self, options);
+        let mut out = String::from("digraph CallTree {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for node in &graph.nodes {
+            let label = node.lines.iter().map(|l| escape_dot(l)).collect::<Vec<_>>().join("\\n");
+            let style = if node.summary {
+                ", style=dashed"
+            } else if node.failed {
+                ", color=red"
+            } else {
+                ""
+            };
+            out.push_str(&format!("    {} [label=\"{}\"{}];\n", node.id, label, style));
+        }
+        for (from, to, dashed) in &graph.edges {
+            let style = if *dashed { " [style=dashed]" } else { "" };
+            out.push_str(&format!("    {} -> {}{};\n", from, to, style));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renderiza a árvore como diagrama Mermaid (`graph TD`)
+    pub fn to_mermaid(&self, options: &RenderOptions) -> String {
+        let graph = Graph::build(self, options);
+        let mut out = String::from("graph TD\n");
+        for node in &graph.nodes {
+            let label = node.lines.iter().map(|l| escape_mermaid(l)).collect::<Vec<_>>().join("<br/>");
+            out.push_str(&format!("    {}[\"{}\"]\n", node.id, label));
+        }
+        for (from, to, dashed) in &graph.edges {
+            let arrow = if *dashed { "-.->" } else { "-->" };
+            out.push_str(&format!("    {} {} {}\n", from, arrow, to));
+        }
+        for node in graph.nodes.iter().filter(|n| n.failed) {
+            out.push_str(&format!("    style {} stroke:#d00,stroke-width:2px\n", node.id));
+        }
+        out
+    }
+}
+
+impl Graph {
+    fn build(tree: &CallTree, options: &RenderOptions) -> Self {
+        let mut graph = Graph { nodes: Vec::new(), edges: Vec::new() };
+        graph.add_node(tree, tree.root(), options);
+        graph
+    }
+
+    fn add_node(&mut self, tree: &CallTree, node: &CallNode, options: &RenderOptions) -> String {
+        let id = format!("n{}", node.index);
+        self.nodes.push(GraphNode {
+            id: id.clone(),
+            lines: node_label(node, options),
+            failed: node.error.is_some(),
+            summary: false,
+        });
+
+        if node.children.is_empty() {
+            return id;
+        }
+        if options.max_depth.is_some_and(|max| node.depth >= max) {
+            let hidden = tree.subtree(node.index).len() - 1;
+            self.add_summary(&id, hidden);
+            return id;
+        }
+
+        let shown = options.max_children.unwrap_or(usize::MAX).min(node.children.len());
+        for child in tree.children(node).take(shown) {
+            let child_id = self.add_node(tree, child, options);
+            self.edges.push((id.clone(), child_id, false));
+        }
+        let hidden: usize = node.children[shown..].iter().map(|&child| tree.subtree(child).len()).sum();
+        if hidden > 0 {
+            self.add_summary(&id, hidden);
+        }
+        id
+    }
+
+    fn add_summary(&mut self, parent: &str, hidden: usize) {
+        let id = format!("{}_more", parent);
+        self.nodes.push(GraphNode {
+            id: id.clone(),
+            lines: vec![format!("+{} chamadas", hidden)],
+            failed: false,
+            summary: true,
+        });
+        self.edges.push((parent.to_string(), id, true));
+    }
+}
+
+fn node_label(node: &CallNode, options: &RenderOptions) -> Vec<String> {
+    let target = node
+        .to
+        .map(|to| format_address(&to, options.short_addresses))
+        .unwrap_or_else(|| "(criação)".to_string());
+    let mut lines = vec![format!("{} {}", call_type_name(node.call_type), target)];
+
+    if options.show_selector && node.input.len() >= 4 {
+        let selector: [u8; 4] = node.input[..4].try_into().expect("slice de 4 bytes");
+        let name = options
+            .selector_names
+            .get(&selector)
+            .cloned()
+            .or_else(|| utils::known_selector_name(&selector).map(str::to_string));
+        lines.push(name.unwrap_or_else(|| format!("0x{}", hex::encode(selector))));
+    }
+    if options.show_value && !node.value.is_zero() {
+        lines.push(format!("valor: {} ETH", DisplayUtils::format_amount(&node.value, 18)));
+    }
+    if options.show_gas {
+        lines.push(format!("gas: {}", DisplayUtils::format_gas(&node.gas_used)));
+    }
+    if let Some(error) = &node.error {
+        lines.push(format!("erro: {}", error));
+    }
+    lines
+}
+
+fn call_type_name(call_type: CallType) -> &'static str {
+    match call_type {
+        CallType::Call => "CALL",
+        CallType::StaticCall => "STATICCALL",
+        CallType::DelegateCall => "DELEGATECALL",
+        CallType::CallCode => "CALLCODE",
+        CallType::Create => "CREATE",
+        CallType::Create2 => "CREATE2",
+        CallType::SelfDestruct => "SELFDESTRUCT",
+        CallType::Unknown => "UNKNOWN",
+    }
+}
+
+fn format_address(address: &Address, short: bool) -> String {
+    let full = DisplayUtils::format_address(address);
+    if short {
+        format!("{}…{}", &full[..6], &full[full.len() - 4..])
+    } else {
+        full
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U256;
+
+    fn node(index: usize, depth: usize, call_type: CallType, input: Vec<u8>, parent: Option<usize>, children: Vec<usize>) -> CallNode {
+        CallNode {
+            index,
+            depth,
+            call_type,
+            from: Address::from_low_u64_be(1),
+            to: Some(Address::from_low_u64_be(index as u64 + 2)),
+            value: U256::zero(),
+            gas: U256::zero(),
+            gas_used: U256::from(21_000u64),
+            input,
+            output: vec![],
+            error: None,
+            children,
+            parent,
+            proxy: None,
+        }
+    }
+
+    fn sample_tree() -> CallTree {
+        let mut root = node(0, 0, CallType::Call, vec![], None, vec![1, 3, 4]);
+        root.value = U256::exp10(18);
+        let mut failed = node(3, 1, CallType::StaticCall, vec![0xde, 0xad, 0xbe, 0xef], Some(0), vec![]);
+        failed.error = Some("execution \"reverted\"".into());
+        CallTree::from_nodes(vec![
+            root,
+            node(1, 1, CallType::DelegateCall, vec![0xa9, 0x05, 0x9c, 0xbb, 0x00], Some(0), vec![2]),
+            node(2, 2, CallType::Call, vec![], Some(1), vec![]),
+            failed,
+            node(4, 1, CallType::Call, vec![], Some(0), vec![5]),
+            node(5, 2, CallType::Call, vec![], Some(4), vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = sample_tree().to_dot(&RenderOptions::default());
+        assert!(dot.starts_with("digraph CallTree {"));
+        assert!(dot.contains("n0 [label=\"CALL 0x0000…0002\\nvalor: 1"));
+        assert!(dot.contains("DELEGATECALL 0x0000…0003\\ntransfer(address,uint256)"));
+        assert!(dot.contains("0xdeadbeef"));
+        assert!(dot.contains("erro: execution \\\"reverted\\\"\", color=red]"));
+        assert!(dot.contains("n0 -> n1;"));
+    }
+
+    #[test]
+    fn test_to_mermaid_with_truncation_and_custom_labels() {
+        let mut options = RenderOptions {
+            show_gas: false,
+            short_addresses: false,
+            max_depth: Some(1),
+            max_children: Some(2),
+            ..Default::default()
+        };
+        options.selector_names.insert([0xde, 0xad, 0xbe, 0xef], "probe()".into());
+        let mermaid = sample_tree().to_mermaid(&options);
+
+        assert!(mermaid.starts_with("graph TD\n"));
+        assert!(mermaid.contains("probe()"));
+        assert!(!mermaid.contains("gas:"));
+        assert!(mermaid.contains("0x0000000000000000000000000000000000000003"));
+        assert!(mermaid.contains("#quot;reverted#quot;"));
+        assert!(mermaid.contains("style n3 stroke:#d00"));
+        // Filho de profundidade 2 resumido e terceiro filho da raiz omitido (com seu descendente)
+        assert!(mermaid.contains("n1 -.-> n1_more"));
+        assert!(mermaid.contains("n0_more[\"+2 chamadas\"]"));
+        assert!(!mermaid.contains("n4["));
+    }
+}
@@ -0,0 +1,390 @@
+use super::CallTrace;
+use crate::utils;
+use crate::{DeepTraceError, StorageWrite};
+use ethereum_types::{Address, H256, U256};
+use serde::Deserialize;
+
+/// Saída do struct logger padrão do Geth (`debug_traceTransaction` sem tracer)
+#[derive(Debug, Deserialize)]
+struct StructLogResult {
+    gas: u64,
+    failed: bool,
+    #[serde(rename = "returnValue", default)]
+    return_value: String,
+    #[serde(rename = "structLogs")]
+    struct_logs: Vec<StructLog>,
+}
+
+/// Passo de execução de um opcode
+#[derive(Debug, Deserialize)]
+struct StructLog {
+    op: String,
+    gas: u64,
+    #[serde(rename = "gasCost")]
+    gas_cost: u64,
+    depth: usize,
+    #[serde(default)]
+    stack: Option<Vec<String>>,
+    /// Memória em palavras de 32 bytes (presente com `enableMemory`)
+    #[serde(default)]
+    memory: Option<Vec<String>>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl StructLog {
+    /// Item `n` da pilha a partir do topo
+    fn stack(&self, n: usize) -> U256 {
+        self.stack
+            .as_ref()
+            .and_then(|stack| stack.len().checked_sub(n + 1).and_then(|i| stack.get(i)))
+            .map(|item| U256::from_str_radix(item.trim_start_matches("0x"), 16).unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Lê `len` bytes da memória a partir de `offset`, limitado à memória registrada
+    fn memory(&self, offset: U256, len: U256) -> Vec<u8> {
+        let Some(memory) = &self.memory else { return Vec::new() };
+        let size = memory.len() * 32;
+        if len.is_zero() || offset >= U256::from(size) {
+            return Vec::new();
+        }
+        let offset = offset.as_usize();
+        let len = len.min(U256::from(size - offset)).as_usize();
+        let bytes: Vec<u8> = memory[offset / 32..(offset + len).div_ceil(32)]
+            .iter()
+            .flat_map(|word| utils::decode_hex(word))
+            .collect();
+        let start = offset % 32;
+        bytes.get(start..start + len).map(|b| b.to_vec()).unwrap_or_default()
+    }
+}
+
+/// Frame de chamada em reconstrução
+struct Frame {
+    trace: CallTrace,
+    index: usize,
+    /// Endereço cujo storage é alterado; desconhecido em CREATE até o retorno
+    context: Option<Address>,
+    is_create: bool,
+    start_gas: Option<u64>,
+    gas_left: u64,
+    /// Índice da última subchamada encerrada
+    last_child: Option<usize>,
+    writes: Vec<StorageWrite>,
+}
+
+impl Frame {
+    fn new(trace: CallTrace, index: usize, context: Option<Address>, is_create: bool) -> Self {
+        Self { trace, index, context, is_create, start_gas: None, gas_left: 0, last_child: None, writes: Vec::new() }
+    }
+}
+
+/// Call trace e escritas de storage reconstruídos de um trace de opcodes
+#[derive(Debug, Clone)]
+pub struct StructLogTrace {
+    /// Árvore no mesmo formato do `callTracer`
+    pub call_trace: CallTrace,
+    /// SSTOREs agrupados por frame, na ordem em que os frames retornam
+    pub storage_writes: Vec<StorageWrite>,
+}
+
+impl StructLogTrace {
+    /// Verifica se o JSON é um trace do struct logger (`structLogs`)
+    pub fn is_struct_log_trace(value: &serde_json::Value) -> bool {
+        value.get("structLogs").is_some()
+    }
+
+    /// Frame raiz a partir do recibo.
+    ///
+    /// Os structLogs não trazem os campos da transação; `input` e `value` ficam vazios.
+    pub fn root_from_receipt(receipt: &serde_json::Value) -> CallTrace {
+        let field = |name: &str| receipt.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let (to, call_type) = match receipt.get("to").and_then(|v| v.as_str()) {
+            Some(to) => (to.to_string(), "CALL"),
+            None => (field("contractAddress"), "CREATE"),
+        };
+        CallTrace {
+            from: field("from"),
+            gas: "0x0".into(),
+            gas_used: "0x0".into(),
+            to,
+            input: "0x".into(),
+            output: "0x".into(),
+            value: "0x0".into(),
+            error: None,
+            calls: None,
+            call_type: Some(call_type.into()),
+            logs: None,
+        }
+    }
+
+    /// Reconstrói a árvore de chamadas a partir dos opcodes.
+    ///
+    /// `root` fornece os campos da transação (`from`, `to`, `input`, `value`).
+    /// O calldata das subchamadas e os retornos só são recuperados quando o
+    /// trace inclui a memória. Escritas em frames revertidos (ou sob eles) são
+    /// mantidas com `reverted = true`.
+    pub fn from_json(value: &serde_json::Value, root: CallTrace) -> Result<Self, DeepTraceError> {
+        // Desserializa por referência: o JSON de opcodes não é copiado
+        let result = StructLogResult::deserialize(value).map_err(|e| DeepTraceError::TraceDecode(e.to_string()))?;
+
+        let root_context = match root.call_type.as_deref() {
+            Some("CREATE") | Some("CREATE2") if root.to.is_empty() => None,
+            _ => Some(utils::parse_address(&root.to)),
+        };
+        let mut frames = vec![Frame::new(root, 0, root_context, false)];
+        let mut next_index = 1;
+
+        for (i, log) in result.struct_logs.iter().enumerate() {
+            while frames.len() > log.depth.max(1) {
+                let child = frames.pop().expect("frame filho");
+                finish_frame(child, Some(log), frames.last_mut().expect("frame pai"));
+            }
+
+            let frame = frames.last_mut().expect("frame atual");
+            frame.start_gas.get_or_insert(log.gas);
+            frame.gas_left = log.gas.saturating_sub(log.gas_cost);
+            if let Some(error) = &log.error {
+                frame.trace.error = Some(error.clone());
+            }
+
+            match log.op.as_str() {
+                "SSTORE" => frame.writes.push(StorageWrite {
+                    call_index: frame.index,
+                    address: frame.context.unwrap_or_default(),
+                    slot: word(log.stack(0)),
+                    value: word(log.stack(1)),
+                    reverted: false,
+                    after_call: frame.last_child,
+                }),
+                "RETURN" | "REVERT" => {
+                    frame.trace.output = format!("0x{}", hex::encode(log.memory(log.stack(0), log.stack(1))));
+                    if log.op == "REVERT" {
+                        frame.trace.error.get_or_insert_with(|| "execution reverted".to_string());
+                    }
+                }
+                "SELFDESTRUCT" => {
+                    let beneficiary = stack_address(log, 0);
+                    let child = child_trace(frame, "SELFDESTRUCT", beneficiary, U256::zero(), U256::zero(), Vec::new());
+                    frame.trace.calls.get_or_insert_with(Vec::new).push(child);
+                    frame.last_child = Some(next_index);
+                    next_index += 1;
+                }
+                "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL" | "CREATE" | "CREATE2" => {
+                    let op = log.op.as_str();
+                    let (child, context, is_create) = match op {
+                        "CALL" | "CALLCODE" => {
+                            let to = stack_address(log, 1);
+                            let input = log.memory(log.stack(3), log.stack(4));
+                            let context = if op == "CALL" { Some(to) } else { frame.context };
+                            (child_trace(frame, op, to, log.stack(2), log.stack(0), input), context, false)
+                        }
+                        "DELEGATECALL" | "STATICCALL" => {
+                            let to = stack_address(log, 1);
+                            let input = log.memory(log.stack(2), log.stack(3));
+                            let context = if op == "STATICCALL" { Some(to) } else { frame.context };
+                            (child_trace(frame, op, to, U256::zero(), log.stack(0), input), context, false)
+                        }
+                        _ => {
+                            let init_code = log.memory(log.stack(1), log.stack(2));
+                            let gas = U256::from(log.gas.saturating_sub(log.gas_cost));
+                            let mut child = child_trace(frame, op, Address::zero(), log.stack(0), gas, init_code);
+                            child.to = String::new();
+                            (child, None, true)
+                        }
+                    };
+                    // Chamadas a EOAs e precompilados não abrem frame e são fechadas no passo seguinte
+                    let entered = result.struct_logs.get(i + 1).is_some_and(|next| next.depth > log.depth);
+                    let mut child_frame = Frame::new(child, next_index, context, is_create);
+                    if !entered {
+                        child_frame.start_gas = Some(0);
+                    }
+                    frames.push(child_frame);
+                    next_index += 1;
+                }
+                _ => {}
+            }
+        }
+
+        while frames.len() > 1 {
+            let child = frames.pop().expect("frame filho");
+            finish_frame(child, None, frames.last_mut().expect("frame pai"));
+        }
+
+        let mut root = frames.pop().expect("frame raiz");
+        root.trace.gas_used = format!("0x{:x}", result.gas);
+        root.trace.output = format!("0x{}", result.return_value.trim_start_matches("0x"));
+        if result.failed {
+            root.trace.error.get_or_insert_with(|| "execution reverted".to_string());
+            root.writes.iter_mut().for_each(|write| write.reverted = true);
+        } else {
+            root.trace.error = None;
+        }
+
+        Ok(Self { call_trace: root.trace, storage_writes: root.writes })
+    }
+}
+
+/// Encerra um frame filho e o anexa ao pai.
+///
+/// `next` é o primeiro passo do pai após o retorno: o topo da pilha indica o
+/// sucesso da chamada (ou o endereço criado, em CREATE/CREATE2).
+fn finish_frame(mut child: Frame, next: Option<&StructLog>, parent: &mut Frame) {
+    let result = next.map(|log| log.stack(0));
+    let failed = result.is_some_and(|r| r.is_zero());
+
+    if child.is_create {
+        if let Some(created) = result.filter(|r| !r.is_zero()) {
+            let address = word_address(created);
+            child.trace.to = format!("{:?}", address);
+            // Escritas do construtor foram registradas antes de o endereço ser conhecido
+            for write in child.writes.iter_mut().filter(|w| w.address.is_zero()) {
+                write.address = address;
+            }
+        }
+    }
+
+    let start_gas = child.start_gas.unwrap_or(0);
+    child.trace.gas_used = format!("0x{:x}", start_gas.saturating_sub(child.gas_left));
+    if failed {
+        child.trace.error.get_or_insert_with(|| "execution reverted".to_string());
+    } else {
+        // Erros em passos internos não revertem o frame se a chamada retornou sucesso
+        child.trace.error = None;
+    }
+    if child.trace.error.is_some() {
+        child.writes.iter_mut().for_each(|write| write.reverted = true);
+    }
+
+    parent.last_child = Some(child.index);
+    parent.writes.append(&mut child.writes);
+    parent.trace.calls.get_or_insert_with(Vec::new).push(child.trace);
+}
+
+fn child_trace(parent: &Frame, op: &str, to: Address, value: U256, gas: U256, input: Vec<u8>) -> CallTrace {
+    CallTrace {
+        from: format!("{:?}", parent.context.unwrap_or_default()),
+        gas: format!("0x{:x}", gas),
+        gas_used: "0x0".into(),
+        to: format!("{:?}", to),
+        input: format!("0x{}", hex::encode(input)),
+        output: "0x".into(),
+        value: format!("0x{:x}", value),
+        error: None,
+        calls: None,
+        call_type: Some(op.into()),
+        logs: None,
+    }
+}
+
+fn stack_address(log: &StructLog, n: usize) -> Address {
+    word_address(log.stack(n))
+}
+
+fn word_address(value: U256) -> Address {
+    Address::from_slice(&word(value).as_bytes()[12..])
+}
+
+fn word(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn addr(n: u64) -> String {
+        format!("0x{:040x}", n)
+    }
+
+    fn step(op: &str, depth: usize, gas: u64, stack: &[u64]) -> serde_json::Value {
+        let stack: Vec<String> = stack.iter().map(|v| format!("0x{:x}", v)).collect();
+        json!({"pc": 0, "op": op, "gas": gas, "gasCost": 3, "depth": depth, "stack": stack})
+    }
+
+    fn root() -> CallTrace {
+        StructLogTrace::root_from_receipt(&json!({"from": addr(1), "to": addr(2)}))
+    }
+
+    #[test]
+    fn test_reconstruct_calls_and_storage_writes() {
+        // Pilha com o topo no final: CALL(gas, to, value, argsOffset, argsLength, ...)
+        let mut call = step("CALL", 1, 1000, &[0, 0, 5, 0, 4, 0x10, 500]);
+        call["memory"] = json!(["a9059cbb00000000000000000000000000000000000000000000000000000000"]);
+        let logs = json!({"gas": 900, "failed": false, "returnValue": "", "structLogs": [
+            step("SSTORE", 1, 1000, &[7, 1]),
+            call,
+            step("SSTORE", 2, 500, &[9, 2]),
+            step("DELEGATECALL", 2, 480, &[0, 0, 0, 0, 0x20, 100]),
+            step("SSTORE", 3, 100, &[3, 3]),
+            step("STOP", 3, 90, &[]),
+            step("POP", 2, 400, &[1]),
+            step("REVERT", 2, 390, &[0, 0]),
+            step("ISZERO", 1, 600, &[0]),
+            // CALL para uma EOA: não abre frame
+            step("CALL", 1, 590, &[0, 0, 0, 0, 0, 0x30, 50]),
+            step("POP", 1, 580, &[1]),
+            step("CREATE", 1, 570, &[0, 0, 0]),
+            step("SSTORE", 2, 300, &[1, 5]),
+            step("RETURN", 2, 290, &[0, 0]),
+            step("POP", 1, 200, &[0x40]),
+            step("SSTORE", 1, 190, &[6, 6]),
+            step("STOP", 1, 180, &[]),
+        ]});
+        let parsed = StructLogTrace::from_json(&logs, root()).unwrap();
+        let trace = &parsed.call_trace;
+
+        assert_eq!(trace.gas_used, "0x384");
+        assert!(trace.error.is_none());
+        let calls = trace.calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].to, addr(0x10));
+        assert_eq!(calls[0].from, addr(2));
+        assert_eq!(calls[0].value, "0x4");
+        assert_eq!(calls[0].input, "0xa9059cbb00");
+        assert_eq!(calls[0].error.as_deref(), Some("execution reverted"));
+        let delegate = &calls[0].calls.as_ref().unwrap()[0];
+        assert_eq!(delegate.call_type.as_deref(), Some("DELEGATECALL"));
+        assert_eq!((delegate.from.as_str(), delegate.to.as_str()), (addr(0x10).as_str(), addr(0x20).as_str()));
+        assert_eq!(calls[1].to, addr(0x30));
+        assert!(calls[1].calls.is_none() && calls[1].error.is_none());
+        assert_eq!(calls[2].call_type.as_deref(), Some("CREATE"));
+        assert_eq!(calls[2].to, addr(0x40));
+
+        let writes: Vec<(usize, Address, u64, bool, Option<usize>)> = parsed
+            .storage_writes
+            .iter()
+            .map(|w| (w.call_index, w.address, w.slot.to_low_u64_be(), w.reverted, w.after_call))
+            .collect();
+        let a = Address::from_low_u64_be;
+        // Ordem de retorno dos frames: raiz, depois as subárvores na ordem em que terminam
+        assert_eq!(writes, vec![
+            (0, a(2), 1, false, None),
+            (1, a(0x10), 2, true, None),
+            (2, a(0x10), 3, true, None),
+            (4, a(0x40), 5, false, None),
+            // Escrita da raiz depois do CREATE aponta para a última subchamada
+            (0, a(2), 6, false, Some(4)),
+        ]);
+    }
+
+    #[test]
+    fn test_failed_transaction_and_invalid_input() {
+        let logs = json!({"gas": 21000, "failed": true, "returnValue": "08c379a0", "structLogs": [
+            step("SSTORE", 1, 100, &[1, 1]),
+            step("REVERT", 1, 90, &[0, 0]),
+        ]});
+        let parsed = StructLogTrace::from_json(&logs, root()).unwrap();
+        assert_eq!(parsed.call_trace.error.as_deref(), Some("execution reverted"));
+        assert_eq!(parsed.call_trace.output, "0x08c379a0");
+        assert!(parsed.storage_writes[0].reverted);
+
+        assert!(StructLogTrace::is_struct_log_trace(&logs));
+        assert!(!StructLogTrace::is_struct_log_trace(&json!({"calls": []})));
+        assert!(StructLogTrace::from_json(&json!({"structLogs": 1}), root()).is_err());
+    }
+}
@@ -1,15 +1,27 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 use ethereum_types::{Address, U256};
-use ethernity_core::Error;
+use serde::{Deserialize, Serialize};
 use super::{CallTrace, CallType};
-
-#[derive(Debug, Clone)]
+use crate::utils;
+use crate::DeepTraceError;
+
+/// Árvore de chamadas armazenada como arena em pré-ordem.
+///
+/// A posição de cada nó em `nodes` é o seu `index`, o mesmo índice usado em
+/// `execution_path` e nos `call_index` das demais estruturas. Filhos e pai são
+/// referenciados por índice, então a subárvore de um nó ocupa uma faixa
+/// contígua da arena.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallTree {
-    pub root: CallNode,
+    nodes: Vec<CallNode>,
+    /// Subárvores abaixo de `max_depth` foram descartadas na construção
+    #[serde(skip)]
+    truncated: bool,
 }
 
 /// Nó da árvore de chamadas
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallNode {
     pub index: usize,
     pub depth: usize,
@@ -19,97 +31,190 @@ pub struct CallNode {
     pub value: U256,
     pub gas: U256,
     pub gas_used: U256,
+    #[serde(with = "crate::utils::hex_bytes")]
     pub input: Vec<u8>,
+    #[serde(with = "crate::utils::hex_bytes")]
     pub output: Vec<u8>,
     pub error: Option<String>,
-    pub children: Vec<CallNode>,
+    /// Índices dos filhos, na ordem de execução
+    pub children: Vec<usize>,
+    /// Índice do nó pai; `None` na raiz
+    pub parent: Option<usize>,
+    /// Implementação lógica quando `to` é um proxy EIP-1967
+    #[serde(default)]
+    pub proxy: Option<ProxyInfo>,
+}
+
+impl CallNode {
+    /// Contrato que executa a lógica da chamada: a implementação quando `to`
+    /// é um proxy resolvido, ou o próprio `to`
+    pub fn logical_target(&self) -> Option<Address> {
+        self.proxy.as_ref().map(|p| p.implementation).or(self.to)
+    }
+}
+
+/// Implementação resolvida para uma chamada a um proxy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyInfo {
+    /// Contrato de implementação que executa a lógica via DELEGATECALL
+    pub implementation: Address,
+    /// Seletor da função chamada no proxy
+    pub selector: Option<[u8; 4]>,
+    /// Assinatura do seletor, quando conhecida
+    pub function: Option<String>,
 }
 
 impl CallTree {
     /// Cria uma nova árvore de chamadas a partir de um trace
-    pub fn from_trace(trace: &CallTrace) -> Result<Self, ()> {
-        let mut index = 0;
-        let root = Self::build_node(trace, 0, &mut index)?;
+    ///
+    /// Campos inválidos no trace resultam em `DeepTraceError::TraceDecode`.
+    pub fn from_trace(trace: &CallTrace) -> Result<Self, DeepTraceError> {
+        Self::build(trace, None, Self::parse_node)
+    }
 
-        Ok(Self { root })
+    /// Constrói a árvore a partir do trace sem falhar em campos inválidos
+    ///
+    /// Quantidades aceitam hexadecimal ou decimal e valores inválidos viram
+    /// zero. Subárvores abaixo de `max_depth` são descartadas, como no
+    /// caminho de execução.
+    pub(crate) fn from_trace_lossy(trace: &CallTrace, max_depth: usize) -> Self {
+        let convert = |trace: &CallTrace, index, depth, parent| -> Result<CallNode, DeepTraceError> {
+            Ok(CallNode {
+                index,
+                depth,
+                call_type: trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call),
+                from: utils::parse_address(&trace.from),
+                to: if trace.to.is_empty() { None } else { Some(utils::parse_address(&trace.to)) },
+                value: utils::parse_quantity(&trace.value),
+                gas: utils::parse_quantity(&trace.gas),
+                gas_used: utils::parse_quantity(&trace.gas_used),
+                input: utils::decode_hex(&trace.input),
+                output: utils::decode_hex(&trace.output),
+                error: trace.error.clone(),
+                children: Vec::new(),
+                parent,
+                proxy: None,
+            })
+        };
+        Self::build(trace, Some(max_depth), convert).expect("conversão sem falhas")
     }
 
-    /// Constrói um nó da árvore recursivamente
-    fn build_node(trace: &CallTrace, depth: usize, index: &mut usize) -> Result<CallNode, ()> {
-        let current_index = *index;
-        *index += 1;
+    /// Cria a árvore a partir de nós já em pré-ordem, com `index` igual à posição
+    ///
+    /// # Panics
+    ///
+    /// Se `nodes` estiver vazio.
+    pub fn from_nodes(nodes: Vec<CallNode>) -> Self {
+        assert!(!nodes.is_empty(), "a árvore precisa de uma raiz");
+        Self { nodes, truncated: false }
+    }
 
-        // Converte os campos do trace
-        let from = Address::from_str(&trace.from.trim_start_matches("0x"))
-            .map_err(|_| Error::DecodeError(format!("Endereço inválido: {}", trace.from))).expect("ERR ");
+    /// Percorre o trace em pré-ordem sem recursão, convertendo cada frame uma única vez
+    fn build<F>(trace: &CallTrace, max_depth: Option<usize>, mut convert: F) -> Result<Self, DeepTraceError>
+    where
+        F: FnMut(&CallTrace, usize, usize, Option<usize>) -> Result<CallNode, DeepTraceError>,
+    {
+        let mut nodes: Vec<CallNode> = Vec::new();
+        let mut truncated = false;
+        let mut pending = vec![(trace, 0, None)];
+        while let Some((call, depth, parent)) = pending.pop() {
+            let index = nodes.len();
+            nodes.push(convert(call, index, depth, parent)?);
+            if let Some(parent) = parent {
+                nodes[parent].children.push(index);
+            }
+            if max_depth.is_some_and(|max| depth >= max) {
+                truncated |= call.calls.as_ref().is_some_and(|calls| !calls.is_empty());
+                continue;
+            }
+            // Filhos empilhados em ordem reversa para saírem na ordem de execução
+            for child in call.calls.iter().flatten().rev() {
+                pending.push((child, depth + 1, Some(index)));
+            }
+        }
+        Ok(Self { nodes, truncated })
+    }
 
-        let to = if trace.to.is_empty() {
-            None
-        } else {
-            Some(Address::from_str(&trace.to.trim_start_matches("0x"))
-                .map_err(|_| Error::DecodeError(format!("Endereço inválido: {}", trace.to))).expect("ERR "))
+    /// Converte um frame validando todos os campos
+    fn parse_node(trace: &CallTrace, index: usize, depth: usize, parent: Option<usize>) -> Result<CallNode, DeepTraceError> {
+        let invalid = |field: &str, value: &str| DeepTraceError::TraceDecode(format!("{} inválido: {}", field, value));
+        let parse_address = |value: &str| {
+            Address::from_str(value.trim_start_matches("0x")).map_err(|_| invalid("Endereço", value))
+        };
+        let parse_u256 = |field: &str, value: &str| U256::from_dec_str(value).map_err(|_| invalid(field, value));
+        let parse_bytes = |field: &str, value: &str| {
+            hex::decode(value.trim_start_matches("0x")).map_err(|_| invalid(field, value))
         };
 
-        let value = U256::from_dec_str(&trace.value)
-            .map_err(|_| Error::DecodeError(format!("Valor inválido: {}", trace.value))).expect("ERR ");
+        Ok(CallNode {
+            index,
+            depth,
+            call_type: trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call),
+            from: parse_address(&trace.from)?,
+            to: if trace.to.is_empty() { None } else { Some(parse_address(&trace.to)?) },
+            value: parse_u256("Valor", &trace.value)?,
+            gas: parse_u256("Gas", &trace.gas)?,
+            gas_used: parse_u256("Gas usado", &trace.gas_used)?,
+            input: parse_bytes("Input", &trace.input)?,
+            output: parse_bytes("Output", &trace.output)?,
+            error: trace.error.clone(),
+            children: Vec::new(),
+            parent,
+            proxy: None,
+        })
+    }
 
-        let gas = U256::from_dec_str(&trace.gas)
-            .map_err(|_| Error::DecodeError(format!("Gas inválido: {}", trace.gas))).expect("ERR");
+    /// Alguma chamada abaixo de `max_depth` ficou de fora da árvore
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
 
-        let gas_used = U256::from_dec_str(&trace.gas_used)
-            .map_err(|_| Error::DecodeError(format!("Gas usado inválido: {}", trace.gas_used))).expect("ERR");
+    /// Nó raiz (a transação)
+    pub fn root(&self) -> &CallNode {
+        &self.nodes[0]
+    }
 
-        let input = hex::decode(trace.input.trim_start_matches("0x"))
-            .map_err(|_| Error::DecodeError(format!("Input inválido: {}", trace.input))).expect("ERR");
+    /// Todos os nós, em pré-ordem
+    pub fn nodes(&self) -> &[CallNode] {
+        &self.nodes
+    }
 
-        let output = hex::decode(trace.output.trim_start_matches("0x"))
-            .map_err(|_| Error::DecodeError(format!("Output inválido: {}", trace.output))).expect("ERR");
+    /// Filhos de um nó, na ordem de execução
+    pub fn children<'a>(&'a self, node: &'a CallNode) -> impl Iterator<Item = &'a CallNode> + 'a {
+        node.children.iter().map(move |&child| &self.nodes[child])
+    }
 
-        let call_type = trace.call_type.as_deref().map(CallType::from).unwrap_or(CallType::Call);
+    /// Pai de um nó
+    pub fn parent(&self, node: &CallNode) -> Option<&CallNode> {
+        node.parent.map(|parent| &self.nodes[parent])
+    }
 
-        // Processa os filhos recursivamente
-        let mut children = Vec::new();
+    /// O nó e todos os seus descendentes, em pré-ordem
+    pub fn subtree(&self, index: usize) -> &[CallNode] {
+        let Some(node) = self.nodes.get(index) else { return &[] };
+        let end = self.nodes[index + 1..]
+            .iter()
+            .position(|n| n.depth <= node.depth)
+            .map(|offset| index + 1 + offset)
+            .unwrap_or(self.nodes.len());
+        &self.nodes[index..end]
+    }
 
-        if let Some(calls) = &trace.calls {
-            for call in calls {
-                children.push(Self::build_node(call, depth + 1, index)?);
+    /// Anota os nós (por índice em pré-ordem) com as implementações de proxy resolvidas
+    pub fn apply_proxy_info(&mut self, resolved: &HashMap<usize, ProxyInfo>) {
+        for (index, info) in resolved {
+            if let Some(node) = self.nodes.get_mut(*index) {
+                node.proxy = Some(info.clone());
             }
         }
-
-        Ok(CallNode {
-            index: current_index,
-            depth,
-            call_type,
-            from,
-            to,
-            value,
-            gas,
-            gas_used,
-            input,
-            output,
-            error: trace.error.clone(),
-            children,
-        })
     }
 
     /// Percorre a árvore em pré-ordem
-    pub fn traverse_preorder<F>(&self, mut f: F)
-    where
-        F: FnMut(&CallNode),
-    {
-        self.traverse_preorder_node(&self.root, &mut f);
-    }
-
-    /// Percorre um nó em pré-ordem
-    fn traverse_preorder_node<F>(&self, node: &CallNode, f: &mut F)
+    pub fn traverse_preorder<F>(&self, f: F)
     where
         F: FnMut(&CallNode),
     {
-        f(node);
-
-        for child in &node.children {
-            self.traverse_preorder_node(child, f);
-        }
+        self.nodes.iter().for_each(f);
     }
 
     /// Percorre a árvore em pós-ordem
@@ -117,91 +222,45 @@ impl CallTree {
     where
         F: FnMut(&CallNode),
     {
-        self.traverse_postorder_node(&self.root, &mut f);
-    }
-
-    /// Percorre um nó em pós-ordem
-    fn traverse_postorder_node<F>(&self, node: &CallNode, f: &mut F)
-    where
-        F: FnMut(&CallNode),
-    {
-        for child in &node.children {
-            self.traverse_postorder_node(child, f);
+        // Pilha de (nó, próximo filho a visitar)
+        let mut stack = vec![(0, 0)];
+        while let Some((index, next)) = stack.pop() {
+            let node = &self.nodes[index];
+            match node.children.get(next) {
+                Some(&child) => {
+                    stack.push((index, next + 1));
+                    stack.push((child, 0));
+                }
+                None => f(node),
+            }
         }
-
-        f(node);
     }
 
     /// Encontra um nó pelo índice
     pub fn find_by_index(&self, index: usize) -> Option<&CallNode> {
-        self.find_by_index_node(&self.root, index)
-    }
-
-    /// Encontra um nó pelo índice a partir de um nó
-    fn find_by_index_node<'a>(&self, node: &'a CallNode, index: usize) -> Option<&'a CallNode> {
-        if node.index == index {
-            return Some(node);
-        }
-
-        for child in &node.children {
-            if let Some(found) = self.find_by_index_node(child, index) {
-                return Some(found);
-            }
-        }
-
-        None
+        self.nodes.get(index)
     }
 
     /// Obtém o caminho até um nó
     pub fn path_to_node(&self, index: usize) -> Option<Vec<usize>> {
-        let mut path = Vec::new();
-        if self.path_to_node_rec(&self.root, index, &mut path) {
-            Some(path)
-        } else {
-            None
-        }
-    }
-
-    /// Obtém o caminho até um nó recursivamente
-    fn path_to_node_rec(&self, node: &CallNode, target_index: usize, path: &mut Vec<usize>) -> bool {
-        path.push(node.index);
-
-        if node.index == target_index {
-            return true;
-        }
-
-        for child in &node.children {
-            if self.path_to_node_rec(child, target_index, path) {
-                return true;
-            }
+        let mut node = self.nodes.get(index)?;
+        let mut path = vec![node.index];
+        while let Some(parent) = self.parent(node) {
+            path.push(parent.index);
+            node = parent;
         }
-
-        path.pop();
-        false
+        path.reverse();
+        Some(path)
     }
 
     /// Obtém a profundidade máxima da árvore
     pub fn max_depth(&self) -> usize {
-        self.max_depth_node(&self.root)
-    }
-
-    /// Obtém a profundidade máxima a partir de um nó
-    fn max_depth_node(&self, node: &CallNode) -> usize {
-        if node.children.is_empty() {
-            return node.depth;
-        }
-
-        node.children.iter()
-            .map(|child| self.max_depth_node(child))
-            .max()
-            .unwrap_or(node.depth)
+        self.nodes.iter().map(|node| node.depth).max().unwrap_or(0)
     }
 
     /// Conta o número total de nós na árvore
     pub fn total_calls(&self) -> usize {
-        let mut count = 0;
-        self.traverse_preorder(|_| count += 1);
-        count
+        self.nodes.len()
     }
 
     /// Filtra nós com base em um predicado
@@ -209,79 +268,35 @@ impl CallTree {
     where
         F: FnMut(&CallNode) -> bool,
     {
-        let mut result = Vec::new();
-        self.traverse_preorder(|node| {
-            if predicate(node) {
-                result.push(node.clone());
-            }
-        });
-        result
+        self.nodes.iter().filter(|node| predicate(node)).cloned().collect()
     }
 }
 
 impl CallTree {
     /// Obtém todos os nós em uma profundidade específica
     pub fn nodes_at_depth(&self, depth: usize) -> Vec<&CallNode> {
-        let mut nodes = Vec::new();
-        Self::collect_nodes_at_depth(&self.root, depth, &mut nodes);
-        nodes
-    }
-
-    fn collect_nodes_at_depth<'a>(node: &'a CallNode, target_depth: usize, nodes: &mut Vec<&'a CallNode>) {
-        if node.depth == target_depth {
-            nodes.push(node);
-        }
-        for child in &node.children {
-            Self::collect_nodes_at_depth(child, target_depth, nodes);
-        }
+        self.nodes.iter().filter(|node| node.depth == depth).collect()
     }
 
     /// Obtém todas as chamadas que falharam
     pub fn failed_calls(&self) -> Vec<&CallNode> {
-        let mut failed = Vec::new();
-        Self::collect_failed_calls(&self.root, &mut failed);
-        failed
-    }
-
-    fn collect_failed_calls<'a>(node: &'a CallNode, failed: &mut Vec<&'a CallNode>) {
-        if node.error.is_some() {
-            failed.push(node);
-        }
-        for child in &node.children {
-            Self::collect_failed_calls(child, failed);
-        }
+        self.nodes.iter().filter(|node| node.error.is_some()).collect()
     }
 
     /// Obtém todas as chamadas para um endereço específico
     pub fn calls_to_address(&self, address: &ethereum_types::Address) -> Vec<&CallNode> {
-        let mut calls = Vec::new();
-        Self::collect_calls_to_address(&self.root, address, &mut calls);
-        calls
+        self.nodes.iter().filter(|node| node.to == Some(*address)).collect()
     }
 
-    fn collect_calls_to_address<'a>(node: &'a CallNode, address: &ethereum_types::Address, calls: &mut Vec<&'a CallNode>) {
-        if node.to.map_or(false, |to| to == *address) {
-            calls.push(node);
-        }
-        for child in &node.children {
-            Self::collect_calls_to_address(child, address, calls);
-        }
+    /// Obtém as chamadas cuja implementação lógica é o endereço informado,
+    /// incluindo chamadas a proxies que delegam para ele
+    pub fn calls_to_implementation(&self, implementation: &Address) -> Vec<&CallNode> {
+        self.nodes.iter().filter(|node| node.logical_target() == Some(*implementation)).collect()
     }
 
     /// Obtém todas as chamadas de um endereço específico
     pub fn calls_from_address(&self, address: &ethereum_types::Address) -> Vec<&CallNode> {
-        let mut calls = Vec::new();
-        Self::collect_calls_from_address(&self.root, address, &mut calls);
-        calls
-    }
-
-    fn collect_calls_from_address<'a>(node: &'a CallNode, address: &ethereum_types::Address, calls: &mut Vec<&'a CallNode>) {
-        if node.from == *address {
-            calls.push(node);
-        }
-        for child in &node.children {
-            Self::collect_calls_from_address(child, address, calls);
-        }
+        self.nodes.iter().filter(|node| node.from == *address).collect()
     }
 }
 
@@ -314,50 +329,66 @@ mod tests {
                 error: Some("err".into()),
                 calls: None,
                 call_type: Some("CALL".into()),
+                logs: None,
             }]),
             call_type: Some("CALL".into()),
+            logs: None,
         }
     }
 
     #[test]
-    fn test_private_helpers() {
-        let trace = make_trace();
-        let mut idx = 0;
-        let node = CallTree::build_node(&trace, 0, &mut idx).unwrap();
-        let tree = CallTree{root: node};
-
-        let mut pre = Vec::new();
-        tree.traverse_preorder_node(&tree.root, &mut |n| pre.push(n.index));
-        assert_eq!(pre, vec![0,1]);
-
-        let mut post = Vec::new();
-        tree.traverse_postorder_node(&tree.root, &mut |n| post.push(n.index));
-        assert_eq!(post, vec![1,0]);
-
-        assert!(tree.find_by_index_node(&tree.root, 1).is_some());
-        assert!(tree.find_by_index_node(&tree.root, 99).is_none());
-
-        let mut path = Vec::new();
-        assert!(tree.path_to_node_rec(&tree.root, 1, &mut path));
-        assert_eq!(path, vec![0,1]);
-
-        assert_eq!(tree.max_depth_node(&tree.root), 1);
-
-        let mut depth_nodes = Vec::new();
-        CallTree::collect_nodes_at_depth(&tree.root, 1, &mut depth_nodes);
-        assert_eq!(depth_nodes.len(), 1);
-
-        let mut failed = Vec::new();
-        CallTree::collect_failed_calls(&tree.root, &mut failed);
-        assert_eq!(failed.len(), 1);
+    fn test_arena_navigation() {
+        let tree = CallTree::from_trace(&make_trace()).unwrap();
+        let root = tree.root();
+        assert_eq!(root.children, vec![1]);
+        assert!(root.parent.is_none());
+
+        let child = tree.children(root).next().unwrap();
+        assert_eq!((child.index, child.parent), (1, Some(0)));
+        assert_eq!(tree.parent(child).unwrap().index, 0);
+        assert_eq!(tree.subtree(0).len(), 2);
+        assert_eq!(tree.subtree(1).len(), 1);
+        assert!(tree.subtree(9).is_empty());
+        assert_eq!(tree.nodes().len(), 2);
+    }
 
-        let mut to_calls = Vec::new();
-        CallTree::collect_calls_to_address(&tree.root, &Address::from_low_u64_be(3), &mut to_calls);
-        assert_eq!(to_calls.len(), 1);
+    #[test]
+    fn test_lossy_build_limits_depth_and_accepts_hex() {
+        let mut trace = make_trace();
+        trace.value = "0x10".into();
+        trace.calls.as_mut().unwrap()[0].calls = Some(vec![make_trace()]);
+
+        let tree = CallTree::from_trace_lossy(&trace, 1);
+        assert_eq!(tree.total_calls(), 2);
+        assert_eq!(tree.root().value, U256::from(16u64));
+        assert!(matches!(CallTree::from_trace(&trace), Err(DeepTraceError::TraceDecode(_))));
+
+        // Árvores profundas são construídas sem recursão
+        let mut deep = make_trace();
+        for _ in 0..5_000 {
+            let mut parent = make_trace();
+            parent.calls = Some(vec![deep]);
+            deep = parent;
+        }
+        let tree = CallTree::from_trace_lossy(&deep, usize::MAX);
+        assert_eq!(tree.max_depth(), 5_001);
+        assert_eq!(tree.path_to_node(tree.total_calls() - 1).unwrap().len(), 5_002);
+        let mut count = 0;
+        tree.traverse_postorder(|_| count += 1);
+        assert_eq!(count, tree.total_calls());
+    }
 
-        let mut from_calls = Vec::new();
-        CallTree::collect_calls_from_address(&tree.root, &Address::from_low_u64_be(1), &mut from_calls);
-        assert_eq!(from_calls.len(), 2);
+    #[test]
+    fn test_apply_proxy_info() {
+        let mut tree = CallTree::from_trace(&make_trace()).unwrap();
+        let implementation = Address::from_low_u64_be(9);
+        let info = ProxyInfo { implementation, selector: None, function: None };
+        tree.apply_proxy_info(&HashMap::from([(1, info.clone())]));
+
+        assert!(tree.root().proxy.is_none());
+        assert_eq!(tree.root().logical_target(), Some(Address::from_low_u64_be(2)));
+        assert_eq!(tree.find_by_index(1).unwrap().proxy, Some(info));
+        assert_eq!(tree.calls_to_implementation(&implementation).len(), 1);
+        assert_eq!(tree.calls_to_address(&Address::from_low_u64_be(3)).len(), 1);
     }
 }
-
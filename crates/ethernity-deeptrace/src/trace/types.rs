@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Estrutura de trace de chamada
 #[derive(Debug, Clone, Deserialize)]
@@ -15,10 +15,26 @@ pub struct CallTrace {
     pub calls: Option<Vec<CallTrace>>,
     #[serde(rename = "type")]
     pub call_type: Option<String>,
+    /// Logs emitidos pela chamada (presentes com `callTracer` + `withLog`)
+    #[serde(default)]
+    pub logs: Option<Vec<CallLog>>,
+}
+
+/// Log emitido dentro de um frame do call trace
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallLog {
+    pub address: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub data: String,
+    /// Quantidade de subchamadas do frame executadas antes do log
+    #[serde(default)]
+    pub position: Option<String>,
 }
 
 /// Tipo de chamada
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CallType {
     Call,
     StaticCall,
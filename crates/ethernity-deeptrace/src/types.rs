@@ -1,8 +1,10 @@
 use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
 use crate::trace::{CallTree, CallType};
+use crate::{AddressLabel, DetectedEvent, FailureReport, LiquidityAction, SwapAction};
 
 /// Resultado da análise de uma transação
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionAnalysis {
     pub tx_hash: H256,
     pub block_number: u64,
@@ -14,13 +16,54 @@ pub struct TransactionAnalysis {
     pub status: bool,
     pub call_tree: CallTree,
     pub token_transfers: Vec<TokenTransfer>,
+    /// Swaps decodificados dos eventos de DEX
+    pub swaps: Vec<SwapAction>,
+    /// Adições e remoções de liquidez em pools de DEX
+    pub liquidity_changes: Vec<LiquidityAction>,
+    /// Approvals e permits concedidos na transação
+    pub approvals: Vec<Approval>,
+    pub eth_transfers: Vec<EthTransfer>,
     pub contract_creations: Vec<ContractCreation>,
     pub detected_patterns: Vec<DetectedPattern>,
     pub execution_path: Vec<ExecutionStep>,
+    /// Alterações de estado, quando `enable_state_diff` está habilitado
+    pub state_diff: Option<StateDiff>,
+    /// SSTOREs da transação, quando o trace veio do struct logger (`structLogs`)
+    pub storage_writes: Vec<StorageWrite>,
+    /// Rótulos conhecidos dos endereços presentes no trace
+    #[serde(default)]
+    pub labels: Vec<AddressLabel>,
+    /// Ocorrências de endereços monitorados pelas watchlists
+    #[serde(default)]
+    pub watchlist_events: Vec<DetectedEvent>,
+    /// Origem e propagação dos reverts; `None` quando nenhuma chamada falhou
+    #[serde(default)]
+    pub failure: Option<FailureReport>,
+    /// Análise incompleta: a detecção de padrões foi interrompida pelo timeout
+    /// (`detected_patterns` é parcial) ou chamadas abaixo de `max_depth` foram descartadas
+    pub partial: bool,
+}
+
+#[cfg(test)]
+impl TransactionAnalysis {
+    /// Análise bem-sucedida de uma chamada `from -> to` sem eventos, base das fixtures dos testes
+    pub(crate) fn empty(tx_hash: H256, from: Address, to: Option<Address>) -> Self {
+        let root = crate::CallNode {
+            index: 0, depth: 0, call_type: CallType::Call, from, to, value: U256::zero(), gas: U256::zero(), gas_used: U256::zero(),
+            input: vec![], output: vec![], error: None, children: vec![], parent: None, proxy: None,
+        };
+        Self {
+            tx_hash, block_number: 0, timestamp: chrono::Utc::now(),
+            from, to, value: U256::zero(), gas_used: U256::zero(), status: true,
+            call_tree: CallTree::from_nodes(vec![root]), token_transfers: vec![], swaps: vec![], liquidity_changes: vec![], approvals: vec![], eth_transfers: vec![],
+            contract_creations: vec![], detected_patterns: vec![], execution_path: vec![], state_diff: None, storage_writes: vec![],
+            labels: vec![], watchlist_events: vec![], failure: None, partial: false,
+        }
+    }
 }
 
 /// Transferência de token
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenTransfer {
     pub token_type: TokenType,
     pub token_address: Address,
@@ -28,11 +71,126 @@ pub struct TokenTransfer {
     pub to: Address,
     pub amount: U256,
     pub token_id: Option<U256>,
+    /// Índice (pré-ordem da árvore) da chamada que emitiu o log
+    pub call_index: usize,
+}
+
+/// Autorização de gasto concedida na transação
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Approval {
+    pub source: ApprovalSource,
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    /// Valor autorizado; em `ApprovalForAll` é `U256::MAX` ao conceder e zero ao revogar
+    pub amount: U256,
+    /// Prazo da assinatura (permits) ou expiração da allowance (Permit2)
+    pub deadline: Option<U256>,
+    /// Índice (pré-ordem da árvore) da chamada que emitiu o log ou recebeu o permit
+    pub call_index: usize,
+}
+
+impl Approval {
+    /// Allowance ilimitada: valores a partir de `2^160 - 1`, o máximo do Permit2
+    pub fn is_unlimited(&self) -> bool {
+        self.amount >= (U256::one() << 160) - 1
+    }
+}
+
+/// Origem de uma autorização
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalSource {
+    /// Evento ERC20 `Approval`
+    ApprovalEvent,
+    /// Evento `ApprovalForAll` (ERC721/ERC1155)
+    ApprovalForAll,
+    /// Chamada `permit` EIP-2612
+    Erc2612Permit,
+    /// Chamada `permit` no formato do DAI (`allowed` em vez de valor)
+    DaiPermit,
+    /// Chamada `permit` do Permit2 (individual ou em lote)
+    Permit2,
+}
+
+/// Transferência de ETH nativo extraída do call trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EthTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    pub call_type: CallType,
+    pub call_index: usize,
+}
+
+/// Alterações de estado de uma transação (`prestateTracer` em modo diff)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    /// Contas alteradas, ordenadas por endereço
+    pub accounts: Vec<AccountDiff>,
+}
+
+impl StateDiff {
+    /// Alterações de uma conta, se ela foi modificada
+    pub fn account(&self, address: &Address) -> Option<&AccountDiff> {
+        self.accounts.iter().find(|a| &a.address == address)
+    }
+}
+
+/// Alterações de estado de uma conta
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountDiff {
+    pub address: Address,
+    pub balance: Option<ValueChange<U256>>,
+    pub nonce: Option<ValueChange<u64>>,
+    pub code_changed: bool,
+    /// Slots de storage cujo valor mudou
+    pub storage: Vec<StorageChange>,
+    /// A conta não existia antes da transação
+    pub created: bool,
+    /// A conta foi removida (SELFDESTRUCT)
+    pub destroyed: bool,
+}
+
+impl AccountDiff {
+    /// Alteração de um slot de storage, se houver
+    pub fn storage_change(&self, slot: &H256) -> Option<&StorageChange> {
+        self.storage.iter().find(|s| &s.slot == slot)
+    }
+}
+
+/// Valor antes e depois da transação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+/// Alteração de um slot de storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageChange {
+    pub slot: H256,
+    pub before: H256,
+    pub after: H256,
+}
+
+/// Escrita de storage (SSTORE) reconstruída de um trace de opcodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageWrite {
+    /// Índice (pré-ordem da árvore) da chamada que executou o SSTORE
     pub call_index: usize,
+    /// Contrato cujo storage foi alterado (o chamador em DELEGATECALL)
+    pub address: Address,
+    pub slot: H256,
+    pub value: H256,
+    /// A chamada ou um de seus ancestrais reverteu
+    pub reverted: bool,
+    /// Última subchamada do mesmo frame concluída antes do SSTORE
+    #[serde(default)]
+    pub after_call: Option<usize>,
 }
 
 /// Tipo de token
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TokenType {
     Erc20,
     Erc721,
@@ -41,17 +199,18 @@ pub enum TokenType {
 }
 
 /// Criação de contrato
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractCreation {
     pub creator: Address,
     pub contract_address: Address,
+    #[serde(with = "crate::utils::hex_bytes")]
     pub init_code: Vec<u8>,
     pub contract_type: ContractType,
     pub call_index: usize,
 }
 
 /// Tipo de contrato
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContractType {
     Erc20Token,
     Erc721Token,
@@ -64,7 +223,7 @@ pub enum ContractType {
 }
 
 /// Padrão detectado
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectedPattern {
     pub pattern_type: PatternType,
     pub confidence: f64,
@@ -74,21 +233,25 @@ pub struct DetectedPattern {
 }
 
 /// Tipo de padrão
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PatternType {
     Erc20Creation,
+    FlashLoan,
+    Reentrancy,
     Unknown,
 }
 
 /// Passo de execução
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionStep {
     pub depth: usize,
     pub call_type: CallType,
     pub from: Address,
     pub to: Address,
     pub value: U256,
+    #[serde(with = "crate::utils::hex_bytes")]
     pub input: Vec<u8>,
+    #[serde(with = "crate::utils::hex_bytes")]
     pub output: Vec<u8>,
     pub gas_used: U256,
     pub error: Option<String>,
@@ -337,6 +337,128 @@ impl GasAnalyzer {
 
         anomalies
     }
+
+    /// Atribui o gas da transação aos contratos chamados e aos seletores de cada contrato
+    ///
+    /// O gas próprio de uma chamada é o `gas_used` menos o das subchamadas, de
+    /// modo que as participações somam 100%. O gas inclusivo soma o `gas_used`
+    /// das chamadas sem contar de novo as aninhadas em outra do mesmo contrato
+    /// (ou seletor).
+    pub fn attribute_gas(execution_path: &[crate::ExecutionStep]) -> GasAttribution {
+        let mut self_gas: Vec<U256> = execution_path.iter().map(|step| step.gas_used).collect();
+        let mut parents: Vec<usize> = Vec::new();
+        for (index, step) in execution_path.iter().enumerate() {
+            while parents.last().is_some_and(|&p| execution_path[p].depth >= step.depth) {
+                parents.pop();
+            }
+            if let Some(&parent) = parents.last() {
+                self_gas[parent] = self_gas[parent].saturating_sub(step.gas_used);
+            }
+            parents.push(index);
+        }
+
+        let mut by_contract: Vec<ContractGas> = Vec::new();
+        let mut by_selector: Vec<SelectorGas> = Vec::new();
+        let mut ancestors: Vec<(usize, Address, Option<[u8; 4]>)> = Vec::new();
+        for (step, own) in execution_path.iter().zip(&self_gas) {
+            while ancestors.last().is_some_and(|(depth, _, _)| *depth >= step.depth) {
+                ancestors.pop();
+            }
+            let selector: Option<[u8; 4]> = step.input.get(..4).map(|s| s.try_into().expect("slice de 4 bytes"));
+            let nested_contract = ancestors.iter().any(|(_, to, _)| *to == step.to);
+            let nested_selector = ancestors.iter().any(|(_, to, sel)| *to == step.to && *sel == selector);
+            ancestors.push((step.depth, step.to, selector));
+
+            let contract = match by_contract.iter_mut().position(|c| c.address == step.to) {
+                Some(i) => &mut by_contract[i],
+                None => {
+                    by_contract.push(ContractGas { address: step.to, ..Default::default() });
+                    by_contract.last_mut().expect("entrada inserida")
+                }
+            };
+            contract.calls += 1;
+            contract.self_gas = contract.self_gas.saturating_add(*own);
+            if !nested_contract {
+                contract.inclusive_gas = contract.inclusive_gas.saturating_add(step.gas_used);
+            }
+
+            let entry = match by_selector.iter_mut().position(|s| s.address == step.to && s.selector == selector) {
+                Some(i) => &mut by_selector[i],
+                None => {
+                    by_selector.push(SelectorGas {
+                        address: step.to,
+                        selector,
+                        function: selector.and_then(|s| known_selector_name(&s)).map(str::to_string),
+                        ..Default::default()
+                    });
+                    by_selector.last_mut().expect("entrada inserida")
+                }
+            };
+            entry.calls += 1;
+            entry.self_gas = entry.self_gas.saturating_add(*own);
+            if !nested_selector {
+                entry.inclusive_gas = entry.inclusive_gas.saturating_add(step.gas_used);
+            }
+        }
+
+        let total_gas = self_gas.iter().fold(U256::zero(), |acc, gas| acc.saturating_add(*gas));
+        for contract in &mut by_contract {
+            contract.share = gas_ratio(contract.self_gas, total_gas);
+        }
+        for entry in &mut by_selector {
+            entry.share = gas_ratio(entry.self_gas, total_gas);
+        }
+        by_contract.sort_by_key(|c| std::cmp::Reverse(c.self_gas));
+        by_selector.sort_by_key(|s| std::cmp::Reverse(s.self_gas));
+
+        GasAttribution { total_gas, by_contract, by_selector }
+    }
+}
+
+fn gas_ratio(part: U256, total: U256) -> f64 {
+    if total.is_zero() {
+        0.0
+    } else {
+        part.low_u128() as f64 / total.low_u128() as f64
+    }
+}
+
+/// Gas da transação distribuído por contrato e por seletor
+#[derive(Debug, Clone, Default)]
+pub struct GasAttribution {
+    /// Soma do gas próprio de todas as chamadas
+    pub total_gas: U256,
+    /// Contratos chamados, do maior para o menor gas próprio
+    pub by_contract: Vec<ContractGas>,
+    /// Pares (contrato, seletor), do maior para o menor gas próprio
+    pub by_selector: Vec<SelectorGas>,
+}
+
+/// Gas atribuído a um contrato
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContractGas {
+    pub address: Address,
+    pub calls: usize,
+    /// Gas gasto no código do próprio contrato, sem as subchamadas
+    pub self_gas: U256,
+    /// Gas das chamadas ao contrato incluindo as subchamadas
+    pub inclusive_gas: U256,
+    /// Fração de `self_gas` no total da transação (0.0 a 1.0)
+    pub share: f64,
+}
+
+/// Gas atribuído a um seletor de um contrato
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectorGas {
+    pub address: Address,
+    /// `None` para chamadas sem calldata (transferências de ETH, fallback)
+    pub selector: Option<[u8; 4]>,
+    /// Assinatura, quando o seletor é conhecido
+    pub function: Option<String>,
+    pub calls: usize,
+    pub self_gas: U256,
+    pub inclusive_gas: U256,
+    pub share: f64,
 }
 
 /// Análise de uso de gas
@@ -481,6 +603,55 @@ pub fn parse_u256_hex(hex_val: &str) -> U256 {
     U256::from_str_radix(hex_val.trim_start_matches("0x"), 16).unwrap_or_else(|_| U256::zero())
 }
 
+/// Converte uma quantidade do trace em U256, aceitando hexadecimal (`0x`) ou decimal.
+pub fn parse_quantity(value: &str) -> U256 {
+    if value.starts_with("0x") {
+        parse_u256_hex(value)
+    } else {
+        U256::from_dec_str(value).unwrap_or_else(|_| U256::zero())
+    }
+}
+
+/// Seletores de funções comuns e suas assinaturas
+const KNOWN_SELECTORS: &[([u8; 4], &str)] = &[
+    ([0xa9, 0x05, 0x9c, 0xbb], "transfer(address,uint256)"),
+    ([0x23, 0xb8, 0x72, 0xdd], "transferFrom(address,address,uint256)"),
+    ([0x09, 0x5e, 0xa7, 0xb3], "approve(address,uint256)"),
+    ([0x70, 0xa0, 0x82, 0x31], "balanceOf(address)"),
+    ([0xd0, 0xe3, 0x0d, 0xb0], "deposit()"),
+    ([0x2e, 0x1a, 0x7d, 0x4d], "withdraw(uint256)"),
+    ([0x02, 0x2c, 0x0d, 0x9f], "swap(uint256,uint256,address,bytes)"),
+    ([0x12, 0x8a, 0xcb, 0x08], "swap(address,bool,int256,uint160,bytes)"),
+    ([0x09, 0x02, 0xf1, 0xac], "getReserves()"),
+    ([0x38, 0xed, 0x17, 0x39], "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)"),
+    ([0x7f, 0xf3, 0x6a, 0xb5], "swapExactETHForTokens(uint256,address[],address,uint256)"),
+    ([0x18, 0xcb, 0xaf, 0xe5], "swapExactTokensForETH(uint256,uint256,address[],address,uint256)"),
+    ([0x41, 0x4b, 0xf3, 0x89], "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))"),
+    ([0x35, 0x93, 0x56, 0x4c], "execute(bytes,bytes[],uint256)"),
+];
+
+/// Assinatura de um seletor de função conhecido
+pub fn known_selector_name(selector: &[u8; 4]) -> Option<&'static str> {
+    KNOWN_SELECTORS
+        .iter()
+        .find(|(known, _)| known == selector)
+        .map(|(_, name)| *name)
+}
+
+/// Serialização de bytes como string hexadecimal com prefixo `0x`
+pub mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        hex::decode(value.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,6 +731,84 @@ mod tests {
         assert_eq!(anomalies.len(), 3);
     }
 
+    #[test]
+    fn test_gas_attribution_by_contract_and_selector() {
+        let addr = |n| Address::from_low_u64_be(n);
+        let step = |depth, to, input: Vec<u8>, gas: u64| ExecutionStep{depth, call_type:crate::trace::CallType::Call, from:addr(1), to:addr(to), value:U256::zero(), input, output:vec![], gas_used:U256::from(gas), error:None};
+        let transfer = vec![0xa9, 0x05, 0x9c, 0xbb, 0x00];
+        // Router(2) chama o token(3) duas vezes; a segunda chamada reentra no router
+        let steps = vec![
+            step(0, 2, vec![0x12, 0x34, 0x56, 0x78], 100_000),
+            step(1, 3, transfer.clone(), 30_000),
+            step(1, 3, transfer.clone(), 40_000),
+            step(2, 2, vec![], 10_000),
+        ];
+
+        let attribution = GasAnalyzer::attribute_gas(&steps);
+        assert_eq!(attribution.total_gas, U256::from(100_000u64));
+
+        let token = &attribution.by_contract[0];
+        assert_eq!(token.address, addr(3));
+        assert_eq!((token.self_gas, token.inclusive_gas), (U256::from(60_000u64), U256::from(70_000u64)));
+        let router = &attribution.by_contract[1];
+        assert_eq!(router.calls, 2);
+        assert_eq!(router.self_gas, U256::from(40_000u64));
+        assert_eq!(router.inclusive_gas, U256::from(100_000u64));
+        assert!((router.share - 0.4).abs() < 1e-9);
+
+        let top = &attribution.by_selector[0];
+        assert_eq!(top.selector, Some([0xa9, 0x05, 0x9c, 0xbb]));
+        assert_eq!(top.function.as_deref(), Some("transfer(address,uint256)"));
+        assert_eq!(top.calls, 2);
+        assert!((top.share - 0.6).abs() < 1e-9);
+        assert_eq!(attribution.by_selector.len(), 3);
+        assert!(GasAnalyzer::attribute_gas(&[]).by_contract.is_empty());
+    }
+
+    #[test]
+    fn test_transaction_analysis_serde_roundtrip() {
+        let addr = Address::from_low_u64_be(1);
+        let root = CallNode{index:0, depth:0, call_type:CallType::DelegateCall, from:addr, to:None, value:U256::from(255u64), gas:U256::zero(), gas_used:U256::zero(), input:vec![0xa9, 0x05], output:vec![], error:None, children:vec![], parent:None, proxy:None};
+        let analysis = TransactionAnalysis{
+            tx_hash:H256::from_low_u64_be(2),
+            block_number:1,
+            timestamp:Utc::now(),
+            from:addr,
+            to:None,
+            value:U256::zero(),
+            gas_used:U256::from(1234u64),
+            status:true,
+            call_tree:CallTree::from_nodes(vec![root]),
+            token_transfers:vec![TokenTransfer{token_type:TokenType::Erc20, token_address:addr, from:addr, to:addr, amount:U256::from(10u64), token_id:None, call_index:0}],
+            swaps:vec![],
+            liquidity_changes:vec![],
+            approvals:vec![],
+            eth_transfers:vec![],
+            contract_creations:vec![],
+            detected_patterns:vec![],
+            execution_path:vec![],
+            state_diff:None,
+            storage_writes:vec![],
+            labels:vec![],
+            watchlist_events:vec![],
+            failure:None,
+            partial:true
+        };
+
+        let value = serde_json::to_value(&analysis).unwrap();
+        assert_eq!(value["from"], "0x0000000000000000000000000000000000000001");
+        assert_eq!(value["gas_used"], "0x4d2");
+        assert_eq!(value["call_tree"]["nodes"][0]["input"], "0xa905");
+        assert_eq!(value["call_tree"]["nodes"][0]["call_type"], "DelegateCall");
+
+        let decoded: TransactionAnalysis = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded.tx_hash, analysis.tx_hash);
+        assert_eq!(decoded.call_tree.root().input, vec![0xa9, 0x05]);
+        assert_eq!(decoded.call_tree.root().value, U256::from(255u64));
+        assert_eq!(decoded.token_transfers, analysis.token_transfers);
+        assert!(decoded.partial);
+    }
+
     #[test]
     fn test_display_and_cache_utils_and_parsing() {
         let addr = Address::from_low_u64_be(1);
@@ -568,7 +817,7 @@ mod tests {
         assert_eq!(DisplayUtils::format_gas(&U256::from(2_000u64)), "2.00K");
         assert_eq!(DisplayUtils::format_gas(&U256::from(500u64)), "500");
 
-        let root = CallNode{index:0, depth:0, call_type:CallType::Call, from:addr, to:Some(addr), value:U256::zero(), gas:U256::zero(), gas_used:U256::zero(), input:vec![], output:vec![], error:None, children:vec![]};
+        let root = CallNode{index:0, depth:0, call_type:CallType::Call, from:addr, to:Some(addr), value:U256::zero(), gas:U256::zero(), gas_used:U256::zero(), input:vec![], output:vec![], error:None, children:vec![], parent:None, proxy:None};
         let analysis = TransactionAnalysis{
             tx_hash:H256::from_low_u64_be(1),
             block_number:1,
@@ -578,11 +827,21 @@ mod tests {
             value:U256::zero(),
             gas_used:U256::from(1234u64),
             status:true,
-            call_tree:CallTree{root},
+            call_tree:CallTree::from_nodes(vec![root]),
             token_transfers:vec![],
+            swaps:vec![],
+            liquidity_changes:vec![],
+            approvals:vec![],
+            eth_transfers:vec![],
             contract_creations:vec![],
             detected_patterns:vec![DetectedPattern{pattern_type:PatternType::Unknown, confidence:0.9, addresses:vec![], data:json!(null), description:"p".into()}],
-            execution_path:vec![]
+            execution_path:vec![],
+            state_diff:None,
+            storage_writes:vec![],
+            labels:vec![],
+            watchlist_events:vec![],
+            failure:None,
+            partial:false
         };
         let summary = DisplayUtils::create_analysis_summary(&analysis);
         assert!(summary.contains("Transação: 0x0000000000000000000000000000000000000001"));
@@ -596,5 +855,8 @@ mod tests {
         assert_eq!(decode_hex("0x0102"), vec![1u8,2u8]);
         assert_eq!(parse_address("0x0000000000000000000000000000000000000001"), addr);
         assert_eq!(parse_u256_hex("0xff"), U256::from(255u64));
+        assert_eq!(parse_quantity("0xff"), U256::from(255u64));
+        assert_eq!(parse_quantity("255"), U256::from(255u64));
+        assert_eq!(parse_quantity("abc"), U256::zero());
     }
 }
\ No newline at end of file
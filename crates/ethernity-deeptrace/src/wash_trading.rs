@@ -0,0 +1,284 @@
+use crate::{BlockAnalysis, SuspiciousPattern, TokenTransfer, ValueFlowAnalyzer, WashTradingConfig};
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Grupo de endereços que movimentou um token em circuito dentro da janela
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WashTradingAlert {
+    pub token: Address,
+    /// Endereços do grupo, ordenados
+    pub addresses: Vec<Address>,
+    /// Soma das transferências entre os endereços do grupo
+    pub volume: U256,
+    /// Saldo líquido acumulado pelo grupo em relação ao volume, em pontos-base
+    pub imbalance_bps: u64,
+    /// Fluxos entre pares do grupo, como `SuspiciousPattern::CircularFlow`
+    #[serde(skip)]
+    pub flows: Vec<SuspiciousPattern>,
+    /// Transações com transferências internas ao grupo
+    pub transactions: Vec<H256>,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+/// Transferências de um bloco observado
+struct BlockTransfers {
+    block_number: u64,
+    transfers: Vec<(H256, TokenTransfer)>,
+}
+
+/// Detector de wash trading sobre uma janela deslizante de blocos.
+///
+/// A cada bloco observado o grafo de transferências de cada token na janela é
+/// dividido em componentes fortemente conexas; grupos pequenos que trocam o
+/// token entre si várias vezes e terminam com saldo líquido próximo de zero
+/// são sinalizados. Um grupo só é reportado novamente depois de sair da janela.
+pub struct WashTradingDetector {
+    config: WashTradingConfig,
+    window: VecDeque<BlockTransfers>,
+    active: HashSet<(Address, Vec<Address>)>,
+}
+
+impl WashTradingDetector {
+    pub fn new(config: WashTradingConfig) -> Self {
+        Self { config, window: VecDeque::new(), active: HashSet::new() }
+    }
+
+    /// Adiciona o bloco à janela e retorna os grupos sinalizados pela primeira vez
+    pub fn observe_block(&mut self, block: &BlockAnalysis) -> Vec<WashTradingAlert> {
+        let transfers = block
+            .transactions
+            .iter()
+            .filter(|tx| tx.status)
+            .flat_map(|tx| tx.token_transfers.iter().map(move |t| (tx.tx_hash, t.clone())))
+            .filter(|(_, t)| t.token_id.is_none() && t.from != t.to && !t.from.is_zero() && !t.to.is_zero())
+            .collect();
+        self.window.push_back(BlockTransfers { block_number: block.block_number, transfers });
+
+        let oldest = block.block_number.saturating_sub(self.config.window_blocks.saturating_sub(1));
+        self.window.retain(|b| b.block_number >= oldest && b.block_number <= block.block_number);
+
+        let alerts = self.detect();
+        let previous = std::mem::take(&mut self.active);
+        self.active = alerts.iter().map(|a| (a.token, a.addresses.clone())).collect();
+        alerts
+            .into_iter()
+            .filter(|a| !previous.contains(&(a.token, a.addresses.clone())))
+            .collect()
+    }
+
+    /// Blocos atualmente na janela
+    pub fn window_blocks(&self) -> Vec<u64> {
+        self.window.iter().map(|b| b.block_number).collect()
+    }
+
+    fn detect(&self) -> Vec<WashTradingAlert> {
+        let mut by_token: BTreeMap<Address, Vec<(u64, H256, &TokenTransfer)>> = BTreeMap::new();
+        for block in &self.window {
+            for (hash, transfer) in &block.transfers {
+                by_token.entry(transfer.token_address).or_default().push((block.block_number, *hash, transfer));
+            }
+        }
+
+        let mut alerts = Vec::new();
+        for (token, transfers) in by_token {
+            let edges: Vec<(Address, Address)> = transfers.iter().map(|(_, _, t)| (t.from, t.to)).collect();
+            for cluster in strongly_connected(&edges) {
+                if cluster.len() < 2 || cluster.len() > self.config.max_cluster_size {
+                    continue;
+                }
+                let members: HashSet<Address> = cluster.iter().copied().collect();
+                let internal: Vec<&(u64, H256, &TokenTransfer)> = transfers
+                    .iter()
+                    .filter(|(_, _, t)| members.contains(&t.from) && members.contains(&t.to))
+                    .collect();
+                if internal.len() < self.config.min_transfers {
+                    continue;
+                }
+                if let Some(alert) = self.evaluate(token, cluster, &internal) {
+                    alerts.push(alert);
+                }
+            }
+        }
+        alerts
+    }
+
+    fn evaluate(
+        &self,
+        token: Address,
+        mut addresses: Vec<Address>,
+        internal: &[&(u64, H256, &TokenTransfer)],
+    ) -> Option<WashTradingAlert> {
+        let owned: Vec<TokenTransfer> = internal.iter().map(|(_, _, t)| (*t).clone()).collect();
+        let flow = ValueFlowAnalyzer::analyze_value_flow(&owned);
+        let volume = owned.iter().fold(U256::zero(), |acc, t| acc.saturating_add(t.amount));
+        if volume.is_zero() {
+            return None;
+        }
+        let imbalance = flow.net_senders.iter().fold(U256::zero(), |acc, (_, amount)| acc.saturating_add(*amount));
+        let imbalance_bps = (imbalance.saturating_mul(U256::from(10_000u64)) / volume).low_u64();
+        if imbalance_bps > self.config.max_imbalance_bps {
+            return None;
+        }
+
+        let mut pairs: BTreeMap<(Address, Address), U256> = BTreeMap::new();
+        for t in &owned {
+            let amount = pairs.entry((t.from, t.to)).or_default();
+            *amount = amount.saturating_add(t.amount);
+        }
+        let flows = pairs
+            .into_iter()
+            .map(|((address1, address2), amount)| SuspiciousPattern::CircularFlow { address1, address2, amount })
+            .collect();
+
+        let mut transactions = Vec::new();
+        for (_, hash, _) in internal {
+            if !transactions.contains(hash) {
+                transactions.push(*hash);
+            }
+        }
+        addresses.sort();
+        Some(WashTradingAlert {
+            token,
+            addresses,
+            volume,
+            imbalance_bps,
+            flows,
+            transactions,
+            first_block: internal.iter().map(|(block, _, _)| *block).min()?,
+            last_block: internal.iter().map(|(block, _, _)| *block).max()?,
+        })
+    }
+}
+
+impl Default for WashTradingDetector {
+    fn default() -> Self {
+        Self::new(WashTradingConfig::default())
+    }
+}
+
+/// Componentes fortemente conexas do grafo dirigido (Kosaraju, sem recursão)
+fn strongly_connected(edges: &[(Address, Address)]) -> Vec<Vec<Address>> {
+    let mut index: HashMap<Address, usize> = HashMap::new();
+    let mut nodes = Vec::new();
+    for (from, to) in edges {
+        for address in [from, to] {
+            index.entry(*address).or_insert_with(|| {
+                nodes.push(*address);
+                nodes.len() - 1
+            });
+        }
+    }
+    let mut forward = vec![Vec::new(); nodes.len()];
+    let mut backward = vec![Vec::new(); nodes.len()];
+    for (from, to) in edges {
+        forward[index[from]].push(index[to]);
+        backward[index[to]].push(index[from]);
+    }
+
+    // Ordem de término da busca em profundidade no grafo original
+    let mut visited = vec![false; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+    for start in 0..nodes.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![(start, 0)];
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&child) = forward[node].get(next) {
+                stack.push((node, next + 1));
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+            } else {
+                order.push(node);
+            }
+        }
+    }
+
+    // Componentes no grafo transposto, em ordem decrescente de término
+    let mut component = vec![usize::MAX; nodes.len()];
+    let mut components = Vec::new();
+    for &start in order.iter().rev() {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        let id = components.len();
+        let mut members = Vec::new();
+        component[start] = id;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            members.push(nodes[node]);
+            for &parent in &backward[node] {
+                if component[parent] == usize::MAX {
+                    component[parent] = id;
+                    stack.push(parent);
+                }
+            }
+        }
+        components.push(members);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenType, TransactionAnalysis};
+
+    fn addr(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    fn transfer(from: u64, to: u64, amount: u64) -> TokenTransfer {
+        TokenTransfer { token_type: TokenType::Erc20, token_address: addr(100), from: addr(from), to: addr(to), amount: U256::from(amount), token_id: None, call_index: 0 }
+    }
+
+    fn block(number: u64, transfers: Vec<TokenTransfer>) -> BlockAnalysis {
+        let tx = TransactionAnalysis {
+            block_number: number,
+            token_transfers: transfers,
+            ..TransactionAnalysis::empty(H256::from_low_u64_be(number), addr(1), None)
+        };
+        BlockAnalysis::new(number, vec![tx], vec![])
+    }
+
+    #[test]
+    fn test_flags_circular_cluster_across_blocks() {
+        let mut detector = WashTradingDetector::default();
+        // 1 -> 2 -> 3 -> 1 em blocos diferentes; 4 só recebe (fora do ciclo)
+        assert!(detector.observe_block(&block(10, vec![transfer(1, 2, 100), transfer(5, 4, 7)])).is_empty());
+        assert!(detector.observe_block(&block(11, vec![transfer(2, 3, 98)])).is_empty());
+        let alerts = detector.observe_block(&block(12, vec![transfer(3, 1, 97)]));
+
+        assert_eq!(alerts.len(), 1);
+        let alert = &alerts[0];
+        assert_eq!(alert.addresses, vec![addr(1), addr(2), addr(3)]);
+        assert_eq!(alert.volume, U256::from(295u64));
+        assert_eq!(alert.imbalance_bps, 101);
+        assert_eq!((alert.first_block, alert.last_block), (10, 12));
+        assert_eq!(alert.transactions.len(), 3);
+        assert_eq!(alert.flows.len(), 3);
+        assert!(alert.flows.iter().all(|f| matches!(f, SuspiciousPattern::CircularFlow { .. })));
+
+        // O mesmo grupo não é reportado de novo enquanto permanece na janela
+        assert!(detector.observe_block(&block(13, vec![transfer(1, 2, 1)])).is_empty());
+    }
+
+    #[test]
+    fn test_window_and_imbalance_limits() {
+        let mut detector = WashTradingDetector::new(WashTradingConfig { window_blocks: 2, ..Default::default() });
+        detector.observe_block(&block(1, vec![transfer(1, 2, 100)]));
+        detector.observe_block(&block(2, vec![transfer(2, 1, 100)]));
+        assert_eq!(detector.window_blocks(), vec![1, 2]);
+        // O bloco 1 sai da janela antes de o ciclo ter transferências suficientes
+        assert!(detector.observe_block(&block(3, vec![transfer(1, 2, 100)])).is_empty());
+        assert_eq!(detector.window_blocks(), vec![2, 3]);
+
+        // Ciclo com saldo líquido alto não é wash trading
+        let mut detector = WashTradingDetector::default();
+        let alerts = detector.observe_block(&block(1, vec![transfer(1, 2, 1000), transfer(2, 1, 10), transfer(1, 2, 1000)]));
+        assert!(alerts.is_empty());
+    }
+}
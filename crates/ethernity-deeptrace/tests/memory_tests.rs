@@ -1,4 +1,4 @@
-use ethernity_deeptrace::{BufferPool, MemoryManager, MemoryMonitor, SmartCache};
+use ethernity_deeptrace::{BufferPool, DeepTraceError, MemoryManager, MemoryMonitor, SmartCache};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -74,3 +74,15 @@ async fn test_memory_manager_and_monitor() {
     assert!((pool_stats.reuse_ratio - 0.5).abs() < f64::EPSILON);
 }
 
+
+#[test]
+fn test_memory_reservation_limit_and_release() {
+    let manager = MemoryManager::new();
+    let first = manager.reserve(60, 100).unwrap();
+    assert_eq!(first.bytes(), 60);
+    assert_eq!(manager.allocated_bytes(), 60);
+    assert!(matches!(manager.reserve(50, 100), Err(DeepTraceError::MemoryLimit(100))));
+    drop(first);
+    assert_eq!(manager.memory_usage().allocated_bytes, 0);
+    let _second = manager.reserve(100, 100).unwrap();
+}
@@ -11,25 +11,31 @@ fn addr(n: u64) -> Address {
 
 fn basic_analysis() -> TraceAnalysisResult {
     TraceAnalysisResult {
-        call_tree: CallTree {
-            root: CallNode {
-                index: 0,
-                depth: 0,
-                call_type: CallType::Call,
-                from: addr(0),
-                to: Some(addr(1)),
-                value: U256::zero(),
-                gas: U256::zero(),
-                gas_used: U256::zero(),
-                input: Vec::new(),
-                output: Vec::new(),
-                error: None,
-                children: Vec::new(),
-            },
-        },
+        call_tree: CallTree::from_nodes(vec![CallNode {
+            index: 0,
+            depth: 0,
+            call_type: CallType::Call,
+            from: addr(0),
+            to: Some(addr(1)),
+            value: U256::zero(),
+            gas: U256::zero(),
+            gas_used: U256::zero(),
+            input: Vec::new(),
+            output: Vec::new(),
+            error: None,
+            children: Vec::new(),
+            parent: None,
+            proxy: None,
+        }]),
         token_transfers: Vec::new(),
+        swaps: Vec::new(),
+        liquidity_changes: Vec::new(),
+        approvals: Vec::new(),
+        eth_transfers: Vec::new(),
         contract_creations: Vec::new(),
         execution_path: Vec::new(),
+        state_diff: None,
+        storage_writes: Vec::new(),
     }
 }
 
@@ -24,6 +24,7 @@ fn mktrace(
         call_type: call_type.map(|s| s.into()),
         error: error.map(|s| s.into()),
         calls,
+        logs: None,
     }
 }
 
@@ -225,8 +226,8 @@ fn test_invalid_output_panic() {
 fn test_default_calltype_and_edges() {
     let trace = default_calltype_trace();
     let tree = CallTree::from_trace(&trace).unwrap();
-    assert_eq!(tree.root.call_type, CallType::Call);
-    assert!(tree.root.to.is_none());
+    assert_eq!(tree.root().call_type, CallType::Call);
+    assert!(tree.root().to.is_none());
     assert_eq!(tree.max_depth(), 2);
     assert_eq!(tree.total_calls(), 3);
     assert_eq!(tree.nodes_at_depth(1).len(), 1);
@@ -15,8 +15,22 @@ use web3::{
 use std::sync::Arc;
 use std::time::Duration;
 use std::collections::HashMap;
+use std::future::Future;
 use parking_lot::RwLock;
 use async_trait::async_trait;
+use dashmap::DashMap;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use tokio::sync::OnceCell;
+
+mod nonce;
+mod registry;
+mod trace_compat;
+
+pub use nonce::NonceManager;
+pub use registry::{RegisteredProvider, RpcRegistry};
+pub use trace_compat::{TraceApi, parity_block_to_call_traces, parity_state_diff_to_prestate, parity_to_call_trace};
 
 /// Configuração do cliente RPC
 #[derive(Debug, Clone)]
@@ -28,6 +42,12 @@ pub struct RpcConfig {
     pub use_cache: bool,
     pub cache_ttl: Duration,
     pub connection_pool_size: usize,
+    /// API de trace a utilizar; `None` detecta automaticamente
+    pub trace_api: Option<TraceApi>,
+    /// Timeout repassado ao tracer do node em debug_traceTransaction
+    pub trace_timeout: Duration,
+    /// Tamanho máximo aceito para respostas de trace (256 MB por padrão); `None` não limita
+    pub max_response_size: Option<usize>,
 }
 
 impl Default for RpcConfig {
@@ -40,6 +60,9 @@ impl Default for RpcConfig {
             use_cache: true,
             cache_ttl: Duration::from_secs(60),
             connection_pool_size: 10,
+            trace_api: None,
+            trace_timeout: Duration::from_secs(60),
+            max_response_size: Some(DEFAULT_MAX_RESPONSE_SIZE),
         }
     }
 }
@@ -50,11 +73,38 @@ pub enum TransportType {
     WebSocket(Web3<WebSocket>),
 }
 
+/// Envelope de resposta JSON-RPC com o resultado mantido como JSON bruto
+#[derive(Deserialize)]
+struct RawRpcResponse<'a> {
+    #[serde(borrow)]
+    result: Option<&'a RawValue>,
+    error: Option<RawRpcError>,
+}
+
+#[derive(Deserialize)]
+struct RawRpcError {
+    code: i64,
+    message: String,
+}
+
+/// Limite padrão para respostas de trace
+const DEFAULT_MAX_RESPONSE_SIZE: usize = 256 * 1024 * 1024;
+
+/// Folga somada ao timeout das requisições de trace para que o node encerre o tracer antes do cliente
+const TRACE_REQUEST_MARGIN: Duration = Duration::from_secs(5);
+
+/// Requisições em andamento, compartilhadas entre chamadores concorrentes
+type InFlightMap = DashMap<String, Arc<OnceCell<Result<Vec<u8>>>>>;
+
 /// Cliente RPC para Ethereum
 pub struct EthernityRpcClient {
     transport: TransportType,
     config: RpcConfig,
     cache: Arc<RwLock<HashMap<String, (Vec<u8>, std::time::Instant)>>>,
+    in_flight: Arc<InFlightMap>,
+    trace_api: RwLock<Option<TraceApi>>,
+    nonce_manager: NonceManager,
+    http_client: reqwest::Client,
 }
 
 impl EthernityRpcClient {
@@ -72,8 +122,12 @@ impl EthernityRpcClient {
         
         Ok(Self {
             transport: TransportType::Http(web3),
+            trace_api: RwLock::new(config.trace_api),
+            nonce_manager: NonceManager::new(),
+            http_client: Self::build_http_client(&config)?,
             config,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            in_flight: Arc::new(DashMap::new()),
         })
     }
 
@@ -92,11 +146,22 @@ impl EthernityRpcClient {
         
         Ok(Self {
             transport: TransportType::WebSocket(web3),
+            trace_api: RwLock::new(config.trace_api),
+            nonce_manager: NonceManager::new(),
+            http_client: Self::build_http_client(&config)?,
             config,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            in_flight: Arc::new(DashMap::new()),
         })
     }
 
+    fn build_http_client(config: &RpcConfig) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| Error::RpcError(format!("Falha ao criar cliente HTTP: {}", e)))
+    }
+
     /// Cria um novo cliente baseado na URL
     pub async fn new(config: RpcConfig) -> Result<Self> {
         if config.endpoint.starts_with("ws") {
@@ -111,68 +176,51 @@ impl EthernityRpcClient {
         let cache_key = format!("trace_{:x}", tx_hash);
         
         // Verifica o cache
-        if self.config.use_cache {
-            let cache = self.cache.read();
-            if let Some((data, timestamp)) = cache.get(&cache_key) {
-                if timestamp.elapsed() < self.config.cache_ttl {
-                    return Ok(data.clone());
-                }
-            }
+        if let Some(data) = self.cached(&cache_key) {
+            return Ok(data);
         }
-        
+
+        // Chamadas simultâneas para o mesmo trace compartilham uma única requisição
+        self.coalesce(&cache_key, self.fetch_transaction_trace(tx_hash, &cache_key)).await
+    }
+
+    async fn fetch_transaction_trace(&self, tx_hash: TransactionHash, cache_key: &str) -> Result<Vec<u8>> {
         // Converte para o formato do web3
         let web3_hash = Web3H256::from_slice(tx_hash.as_bytes());
-        
-        // Parâmetros para debug_traceTransaction
-        let params = vec![
-            serde_json::Value::String(format!("{:?}", web3_hash)),
-            serde_json::json!({
-                "tracer": "callTracer",
-                "timeout": "60s"
-            })
-        ];
-        
-        // Executa a chamada RPC diretamente
-        let result = match &self.transport {
-            TransportType::Http(web3) => {
-                match web3.transport().execute("debug_traceTransaction", params).await {
-                    Ok(res) => res,
-                    Err(e) => {
-                        let msg = e.to_string();
-                        if msg.contains("not allowed") || msg.contains("forbidden") || msg.contains("denied") || msg.contains("Method not found") {
-                            eprintln!("\u{26A0}\u{FE0F} Não foi possível continuar: uso de callTrace não permitido pelo RPC fornecido");
-                            return Err(Error::RpcError("Uso de callTrace não permitido pelo RPC fornecido".to_string()));
-                        } else {
-                            return Err(Error::RpcError(format!("Falha ao obter trace da transação: {}", e)));
+
+        let trace_bytes = match self.trace_api().await {
+            TraceApi::Debug => match self.debug_trace_transaction(web3_hash).await {
+                Ok(bytes) => bytes,
+                Err(Error::RpcError(msg)) if Self::is_method_unavailable(&msg) => {
+                    // Nodes como Erigon e Nethermind podem expor apenas trace_transaction
+                    match self.parity_trace_transaction(web3_hash).await {
+                        Ok(res) => {
+                            *self.trace_api.write() = Some(TraceApi::Parity);
+                            self.encode_trace(&res)?
                         }
-                    }
-                }
-            },
-            TransportType::WebSocket(web3) => {
-                match web3.transport().execute("debug_traceTransaction", params).await {
-                    Ok(res) => res,
-                    Err(e) => {
-                        let msg = e.to_string();
-                        if msg.contains("not allowed") || msg.contains("forbidden") || msg.contains("denied") || msg.contains("Method not found") {
+                        Err(_) => {
                             eprintln!("\u{26A0}\u{FE0F} Não foi possível continuar: uso de callTrace não permitido pelo RPC fornecido");
                             return Err(Error::RpcError("Uso de callTrace não permitido pelo RPC fornecido".to_string()));
-                        } else {
-                            return Err(Error::RpcError(format!("Falha ao obter trace da transação: {}", e)));
                         }
                     }
                 }
+                Err(Error::RpcError(msg)) if Self::is_tracer_unavailable(&msg) => {
+                    // Sem callTracer o node ainda responde com o struct logger padrão (structLogs)
+                    self.debug_struct_logs(web3_hash).await?
+                }
+                Err(Error::RpcError(msg)) => {
+                    return Err(Error::RpcError(format!("Falha ao obter trace da transação: {}", msg)));
+                }
+                Err(e) => return Err(e),
+            },
+            TraceApi::Parity => {
+                let res = self.parity_trace_transaction(web3_hash).await?;
+                self.encode_trace(&res)?
             }
         };
         
-        // Converte o resultado para bytes
-        let trace_bytes = serde_json::to_vec(&result)
-            .map_err(|e| Error::EncodeError(format!("Falha ao serializar trace: {}", e)))?;
-        
         // Atualiza o cache
-        if self.config.use_cache {
-            let mut cache = self.cache.write();
-            cache.insert(cache_key, (trace_bytes.clone(), std::time::Instant::now()));
-        }
+        self.store(cache_key, &trace_bytes);
         
         Ok(trace_bytes)
     }
@@ -182,15 +230,15 @@ impl EthernityRpcClient {
         let cache_key = format!("receipt_{:x}", tx_hash);
         
         // Verifica o cache
-        if self.config.use_cache {
-            let cache = self.cache.read();
-            if let Some((data, timestamp)) = cache.get(&cache_key) {
-                if timestamp.elapsed() < self.config.cache_ttl {
-                    return Ok(data.clone());
-                }
-            }
+        if let Some(data) = self.cached(&cache_key) {
+            return Ok(data);
         }
-        
+
+        // Chamadas simultâneas para o mesmo recibo compartilham uma única requisição
+        self.coalesce(&cache_key, self.fetch_transaction_receipt(tx_hash, &cache_key)).await
+    }
+
+    async fn fetch_transaction_receipt(&self, tx_hash: TransactionHash, cache_key: &str) -> Result<Vec<u8>> {
         // Converte para o formato do web3
         let web3_hash = Web3H256::from_slice(tx_hash.as_bytes());
         
@@ -215,12 +263,323 @@ impl EthernityRpcClient {
             .map_err(|e| Error::EncodeError(format!("Falha ao serializar recibo: {}", e)))?;
         
         // Atualiza o cache
+        self.store(cache_key, &receipt_bytes);
+        
+        Ok(receipt_bytes)
+    }
+
+    /// Retorna a entrada do cache se ainda estiver válida
+    fn cached(&self, cache_key: &str) -> Option<Vec<u8>> {
+        if !self.config.use_cache {
+            return None;
+        }
+        let cache = self.cache.read();
+        cache
+            .get(cache_key)
+            .filter(|(_, timestamp)| timestamp.elapsed() < self.config.cache_ttl)
+            .map(|(data, _)| data.clone())
+    }
+
+    /// Armazena uma resposta no cache quando habilitado
+    fn store(&self, cache_key: &str, data: &[u8]) {
         if self.config.use_cache {
             let mut cache = self.cache.write();
-            cache.insert(cache_key, (receipt_bytes.clone(), std::time::Instant::now()));
+            cache.insert(cache_key.to_string(), (data.to_vec(), std::time::Instant::now()));
+        }
+    }
+
+    /// Agrupa requisições idênticas em andamento em uma única chamada ao node.
+    ///
+    /// O primeiro chamador executa `fetch`; os demais aguardam o mesmo resultado.
+    /// A entrada é removida assim que a requisição termina, de modo que falhas
+    /// não ficam memorizadas.
+    async fn coalesce<F>(&self, key: &str, fetch: F) -> Result<Vec<u8>>
+    where
+        F: Future<Output = Result<Vec<u8>>>,
+    {
+        let cell = self
+            .in_flight
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+
+        let result = cell.get_or_init(|| fetch).await.clone();
+
+        self.in_flight.remove_if(key, |_, current| Arc::ptr_eq(current, &cell));
+        result
+    }
+
+    /// Executa uma chamada JSON-RPC arbitrária no transporte configurado
+    async fn execute(&self, method: &str, params: Vec<serde_json::Value>) -> web3::Result<serde_json::Value> {
+        match &self.transport {
+            TransportType::Http(web3) => web3.transport().execute(method, params).await,
+            TransportType::WebSocket(web3) => web3.transport().execute(method, params).await,
         }
-        
-        Ok(receipt_bytes)
+    }
+
+    /// Verifica se o erro indica que o método não está disponível no node
+    fn is_method_unavailable(msg: &str) -> bool {
+        msg.contains("not allowed")
+            || msg.contains("forbidden")
+            || msg.contains("denied")
+            || msg.contains("Method not found")
+            || msg.contains("MethodNotFound")
+            || msg.contains("does not exist")
+    }
+
+    /// Verifica se o erro indica que o tracer solicitado não existe no node
+    fn is_tracer_unavailable(msg: &str) -> bool {
+        msg.contains("tracer")
+            && (msg.contains("not found") || msg.contains("not supported") || msg.contains("unknown") || msg.contains("not defined"))
+    }
+
+    /// Obtém o trace de opcodes (structLogs) do struct logger padrão
+    ///
+    /// A memória é incluída para que o calldata das subchamadas possa ser
+    /// reconstruído; o storage completo por passo é omitido.
+    async fn debug_struct_logs(&self, tx_hash: Web3H256) -> Result<Vec<u8>> {
+        let params = vec![
+            serde_json::Value::String(format!("{:?}", tx_hash)),
+            serde_json::json!({
+                "enableMemory": true,
+                "disableStorage": true,
+                "timeout": format!("{}ms", self.config.trace_timeout.as_millis())
+            })
+        ];
+
+        match &self.transport {
+            TransportType::Http(_) => self.execute_http_raw("debug_traceTransaction", params).await,
+            TransportType::WebSocket(_) => {
+                let result = self
+                    .execute("debug_traceTransaction", params)
+                    .await
+                    .map_err(|e| Error::RpcError(e.to_string()))?;
+                self.encode_trace(&result)
+            }
+        }
+    }
+
+    /// Obtém o trace via debug_traceTransaction com callTracer (incluindo os logs de cada chamada)
+    async fn debug_trace_transaction(&self, tx_hash: Web3H256) -> Result<Vec<u8>> {
+        let params = vec![
+            serde_json::Value::String(format!("{:?}", tx_hash)),
+            serde_json::json!({
+                "tracer": "callTracer",
+                "tracerConfig": { "withLog": true },
+                "timeout": format!("{}ms", self.config.trace_timeout.as_millis())
+            })
+        ];
+
+        match &self.transport {
+            TransportType::Http(_) => self.execute_http_raw("debug_traceTransaction", params).await,
+            TransportType::WebSocket(_) => {
+                let result = self
+                    .execute("debug_traceTransaction", params)
+                    .await
+                    .map_err(|e| Error::RpcError(e.to_string()))?;
+                self.encode_trace(&result)
+            }
+        }
+    }
+
+    /// Executa uma chamada de trace e decodifica o resultado
+    ///
+    /// Em HTTP a resposta passa por `execute_http_raw`, que respeita
+    /// `max_response_size`; em WebSocket o limite é aplicado por `encode_trace`.
+    async fn execute_trace(&self, method: &str, params: Vec<serde_json::Value>) -> Result<serde_json::Value> {
+        match &self.transport {
+            TransportType::Http(_) => {
+                let raw = self.execute_http_raw(method, params).await?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| Error::DecodeError(format!("Resposta de {} inválida: {}", method, e)))
+            }
+            TransportType::WebSocket(_) => self
+                .execute(method, params)
+                .await
+                .map_err(|e| Error::RpcError(e.to_string())),
+        }
+    }
+
+    /// Acrescenta contexto às falhas do node, preservando os demais erros
+    fn rpc_context(error: Error, context: &str) -> Error {
+        match error {
+            Error::RpcError(msg) => Error::RpcError(format!("{}: {}", context, msg)),
+            other => other,
+        }
+    }
+
+    /// Executa uma chamada JSON-RPC via HTTP retornando o campo `result` bruto
+    ///
+    /// O corpo é acumulado em blocos até `max_response_size`, abortando assim
+    /// que o limite é ultrapassado, e o campo `result` é extraído como JSON
+    /// bruto, sem árvore intermediária.
+    async fn execute_http_raw(&self, method: &str, params: Vec<serde_json::Value>) -> Result<Vec<u8>> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        // O tracer pode rodar até `trace_timeout` no node, que costuma exceder o timeout geral
+        let request_timeout = self.config.timeout.max(self.config.trace_timeout) + TRACE_REQUEST_MARGIN;
+        let response = self
+            .http_client
+            .post(&self.config.endpoint)
+            .timeout(request_timeout)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| Error::RpcError(e.to_string()))?;
+
+        if let (Some(limit), Some(length)) = (self.config.max_response_size, response.content_length()) {
+            if length as usize > limit {
+                return Err(Self::response_too_large(length as usize, limit));
+            }
+        }
+
+        let mut body = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| Error::RpcError(e.to_string()))?;
+            if let Some(limit) = self.config.max_response_size {
+                if body.len() + chunk.len() > limit {
+                    return Err(Self::response_too_large(body.len() + chunk.len(), limit));
+                }
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let envelope: RawRpcResponse = serde_json::from_slice(&body)
+            .map_err(|e| Error::DecodeError(format!("Resposta JSON-RPC inválida: {}", e)))?;
+
+        if let Some(error) = envelope.error {
+            let message = if error.code == -32601 {
+                format!("Method not found: {}", error.message)
+            } else {
+                format!("{} (código {})", error.message, error.code)
+            };
+            return Err(Error::RpcError(message));
+        }
+
+        envelope
+            .result
+            .map(|raw| raw.get().as_bytes().to_vec())
+            .ok_or_else(|| Error::NotFound("Resposta JSON-RPC sem resultado".to_string()))
+    }
+
+    /// Serializa um trace aplicando o limite de tamanho configurado
+    fn encode_trace(&self, trace: &serde_json::Value) -> Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(trace)
+            .map_err(|e| Error::EncodeError(format!("Falha ao serializar trace: {}", e)))?;
+        match self.config.max_response_size {
+            Some(limit) if bytes.len() > limit => Err(Self::response_too_large(bytes.len(), limit)),
+            _ => Ok(bytes),
+        }
+    }
+
+    fn response_too_large(size: usize, limit: usize) -> Error {
+        Error::ResponseTooLarge(format!("{} bytes excedem o limite de {} bytes", size, limit))
+    }
+
+    /// Obtém o trace via trace_transaction e normaliza para o formato do callTracer
+    async fn parity_trace_transaction(&self, tx_hash: Web3H256) -> Result<serde_json::Value> {
+        let params = vec![serde_json::Value::String(format!("{:?}", tx_hash))];
+        let result = self
+            .execute_trace("trace_transaction", params)
+            .await
+            .map_err(|e| Self::rpc_context(e, "Falha ao obter trace da transação"))?;
+
+        let traces = result
+            .as_array()
+            .ok_or_else(|| Error::DecodeError("Resposta de trace_transaction não é uma lista".to_string()))?;
+        parity_to_call_trace(traces)
+    }
+
+    /// Obtém as alterações de estado de uma transação
+    ///
+    /// Usa `debug_traceTransaction` com `prestateTracer` em modo diff ou, em
+    /// nodes no estilo Parity, `trace_replayTransaction` com `stateDiff`
+    /// convertido para o formato `{"pre": ..., "post": ...}`.
+    pub async fn get_state_diff(&self, tx_hash: TransactionHash) -> Result<Vec<u8>> {
+        let cache_key = format!("state_diff_{:x}", tx_hash);
+
+        // Verifica o cache
+        if let Some(data) = self.cached(&cache_key) {
+            return Ok(data);
+        }
+
+        let web3_hash = Web3H256::from_slice(tx_hash.as_bytes());
+        let diff = match self.trace_api().await {
+            TraceApi::Debug => match self.debug_state_diff(web3_hash).await {
+                Err(Error::RpcError(msg)) if Self::is_method_unavailable(&msg) => {
+                    let diff = self.parity_state_diff(web3_hash).await?;
+                    *self.trace_api.write() = Some(TraceApi::Parity);
+                    diff
+                }
+                other => other?,
+            },
+            TraceApi::Parity => self.parity_state_diff(web3_hash).await?,
+        };
+
+        let bytes = self.encode_trace(&diff)?;
+        self.store(&cache_key, &bytes);
+        Ok(bytes)
+    }
+
+    /// Obtém o diff de estado via debug_traceTransaction com prestateTracer
+    async fn debug_state_diff(&self, tx_hash: Web3H256) -> Result<serde_json::Value> {
+        let params = vec![
+            serde_json::Value::String(format!("{:?}", tx_hash)),
+            serde_json::json!({
+                "tracer": "prestateTracer",
+                "tracerConfig": { "diffMode": true },
+                "timeout": format!("{}ms", self.config.trace_timeout.as_millis())
+            })
+        ];
+        self.execute_trace("debug_traceTransaction", params)
+            .await
+            .map_err(|e| Self::rpc_context(e, "Falha ao obter diff de estado"))
+    }
+
+    /// Obtém o diff de estado via trace_replayTransaction e normaliza para o formato do prestateTracer
+    async fn parity_state_diff(&self, tx_hash: Web3H256) -> Result<serde_json::Value> {
+        let params = vec![
+            serde_json::Value::String(format!("{:?}", tx_hash)),
+            serde_json::json!(["stateDiff"]),
+        ];
+        let result = self
+            .execute_trace("trace_replayTransaction", params)
+            .await
+            .map_err(|e| Self::rpc_context(e, "Falha ao obter diff de estado"))?;
+
+        let state_diff = result
+            .get("stateDiff")
+            .ok_or_else(|| Error::DecodeError("Resposta de trace_replayTransaction sem stateDiff".to_string()))?;
+        parity_state_diff_to_prestate(state_diff)
+    }
+
+    /// API de trace utilizada pelo cliente, detectada na primeira chamada
+    pub async fn trace_api(&self) -> TraceApi {
+        if let Some(api) = *self.trace_api.read() {
+            return api;
+        }
+        let api = self.probe_trace_api().await;
+        *self.trace_api.write() = Some(api);
+        api
+    }
+
+    /// Consulta `web3_clientVersion` para descobrir a API de trace suportada
+    pub async fn probe_trace_api(&self) -> TraceApi {
+        match self.execute("web3_clientVersion", vec![]).await {
+            Ok(serde_json::Value::String(version)) => TraceApi::from_client_version(&version),
+            _ => TraceApi::Debug,
+        }
+    }
+
+    /// Número de requisições atualmente em andamento
+    pub fn in_flight_requests(&self) -> usize {
+        self.in_flight.len()
     }
 
     /// Obtém informações de um bloco
@@ -311,6 +670,117 @@ impl EthernityRpcClient {
         Ok(block_bytes)
     }
 
+    /// Obtém os hashes das transações de um bloco, na ordem de inclusão
+    pub async fn get_block_transactions(&self, block_number: u64) -> Result<Vec<TransactionHash>> {
+        let bytes = self.get_block(block_number).await?;
+        let block: web3::types::Block<Web3H256> = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::DecodeError(format!("Falha ao decodificar bloco: {}", e)))?;
+        Ok(block
+            .transactions
+            .iter()
+            .map(|hash| TransactionHash::from_slice(hash.as_bytes()))
+            .collect())
+    }
+
+    /// Obtém os traces de todas as transações de um bloco em uma única chamada
+    ///
+    /// Usa `debug_traceBlockByNumber` com `callTracer` ou, em nodes no estilo
+    /// Parity, `trace_block` normalizado para o formato do `callTracer`.
+    pub async fn get_block_traces(&self, block_number: u64) -> Result<Vec<u8>> {
+        let cache_key = format!("block_traces_{}", block_number);
+
+        // Verifica o cache
+        if let Some(data) = self.cached(&cache_key) {
+            return Ok(data);
+        }
+
+        let traces = match self.trace_api().await {
+            TraceApi::Debug => match self.debug_trace_block(block_number).await {
+                Err(Error::RpcError(msg)) if Self::is_method_unavailable(&msg) => {
+                    let traces = self.parity_trace_block(block_number).await?;
+                    *self.trace_api.write() = Some(TraceApi::Parity);
+                    traces
+                }
+                other => other?,
+            },
+            TraceApi::Parity => self.parity_trace_block(block_number).await?,
+        };
+
+        let bytes = self.encode_trace(&serde_json::Value::Array(traces))?;
+        self.store(&cache_key, &bytes);
+        Ok(bytes)
+    }
+
+    /// Obtém os traces do bloco via debug_traceBlockByNumber
+    async fn debug_trace_block(&self, block_number: u64) -> Result<Vec<serde_json::Value>> {
+        let params = vec![
+            serde_json::Value::String(format!("0x{:x}", block_number)),
+            serde_json::json!({
+                "tracer": "callTracer",
+                "tracerConfig": { "withLog": true },
+                "timeout": format!("{}ms", self.config.trace_timeout.as_millis())
+            })
+        ];
+        let result = self
+            .execute_trace("debug_traceBlockByNumber", params)
+            .await
+            .map_err(|e| Self::rpc_context(e, "Falha ao obter traces do bloco"))?;
+
+        let entries = result
+            .as_array()
+            .ok_or_else(|| Error::DecodeError("Resposta de debug_traceBlockByNumber não é uma lista".to_string()))?;
+
+        // Cada entrada tem a forma {"txHash": ..., "result": {...}}
+        entries
+            .iter()
+            .map(|entry| {
+                entry.get("result").cloned().ok_or_else(|| {
+                    let reason = entry.get("error").and_then(|e| e.as_str()).unwrap_or("sem resultado");
+                    Error::RpcError(format!("Falha ao obter trace de transação do bloco: {}", reason))
+                })
+            })
+            .collect()
+    }
+
+    /// Obtém os traces do bloco via trace_block e normaliza por transação
+    async fn parity_trace_block(&self, block_number: u64) -> Result<Vec<serde_json::Value>> {
+        let params = vec![serde_json::Value::String(format!("0x{:x}", block_number))];
+        let result = self
+            .execute_trace("trace_block", params)
+            .await
+            .map_err(|e| Self::rpc_context(e, "Falha ao obter traces do bloco"))?;
+
+        let traces = result
+            .as_array()
+            .ok_or_else(|| Error::DecodeError("Resposta de trace_block não é uma lista".to_string()))?;
+        parity_block_to_call_traces(traces)
+    }
+
+    /// Obtém os recibos de todas as transações de um bloco via eth_getBlockReceipts
+    pub async fn get_block_receipts(&self, block_number: u64) -> Result<Vec<u8>> {
+        let cache_key = format!("block_receipts_{}", block_number);
+
+        // Verifica o cache
+        if let Some(data) = self.cached(&cache_key) {
+            return Ok(data);
+        }
+
+        let params = vec![serde_json::Value::String(format!("0x{:x}", block_number))];
+        let result = self
+            .execute("eth_getBlockReceipts", params)
+            .await
+            .map_err(|e| Error::RpcError(format!("Falha ao obter recibos do bloco: {}", e)))?;
+
+        if !result.is_array() {
+            return Err(Error::NotFound("Recibos do bloco não encontrados".to_string()));
+        }
+
+        let bytes = serde_json::to_vec(&result)
+            .map_err(|e| Error::EncodeError(format!("Falha ao serializar recibos: {}", e)))?;
+        self.store(&cache_key, &bytes);
+        Ok(bytes)
+    }
+
     /// Obtém o número do bloco atual
     pub async fn get_block_number(&self) -> Result<u64> {
         let block_number = match &self.transport {
@@ -329,6 +799,41 @@ impl EthernityRpcClient {
         Ok(block_number.as_u64())
     }
 
+    /// Assina novos blocos (`eth_subscribe("newHeads")`) e retorna seus números
+    ///
+    /// Disponível apenas via WebSocket; heads sem número são descartados.
+    pub async fn subscribe_new_heads(&self) -> Result<futures::stream::BoxStream<'static, u64>> {
+        match &self.transport {
+            TransportType::Http(_) => Err(Error::RpcError("Assinatura de novos blocos requer WebSocket".to_string())),
+            TransportType::WebSocket(web3) => {
+                let stream = web3.eth_subscribe().subscribe_new_heads()
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao assinar novos blocos: {}", e)))?;
+                Ok(stream
+                    .filter_map(|head| async move { head.ok().and_then(|h| h.number).map(|n| n.as_u64()) })
+                    .boxed())
+            }
+        }
+    }
+
+    /// Obtém o chain ID do node
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        let chain_id = match &self.transport {
+            TransportType::Http(web3) => {
+                web3.eth().chain_id()
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao obter chain ID: {}", e)))?
+            },
+            TransportType::WebSocket(web3) => {
+                web3.eth().chain_id()
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao obter chain ID: {}", e)))?
+            }
+        };
+
+        Ok(chain_id.low_u64())
+    }
+
     /// Obtém o hash de um bloco específico
     pub async fn get_block_hash(&self, block_number: u64) -> Result<H256> {
         let bytes = self.get_block(block_number).await?;
@@ -356,6 +861,97 @@ impl EthernityRpcClient {
         Ok(result.0)
     }
 
+    /// Lê um slot de storage de um contrato no bloco informado (ou no mais recente)
+    pub async fn get_storage_at(&self, address: Address, slot: H256, block_number: Option<u64>) -> Result<H256> {
+        let block = block_number.map(|n| BlockNumber::Number(U64::from(n)));
+        let index = web3::types::U256::from_big_endian(slot.as_bytes());
+        let value = match &self.transport {
+            TransportType::Http(web3) => {
+                web3.eth().storage(H160::from_slice(address.as_bytes()), index, block)
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao ler storage: {}", e)))?
+            },
+            TransportType::WebSocket(web3) => {
+                web3.eth().storage(H160::from_slice(address.as_bytes()), index, block)
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao ler storage: {}", e)))?
+            }
+        };
+
+        Ok(H256::from_slice(value.as_bytes()))
+    }
+
+    /// Obtém a quantidade de transações enviadas por um endereço
+    ///
+    /// Com `pending` verdadeiro inclui as transações ainda no mempool.
+    pub async fn get_transaction_count(&self, address: Address, pending: bool) -> Result<u64> {
+        let block = if pending { BlockNumber::Pending } else { BlockNumber::Latest };
+        let count = match &self.transport {
+            TransportType::Http(web3) => {
+                web3.eth().transaction_count(H160::from_slice(address.as_bytes()), Some(block))
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao obter nonce: {}", e)))?
+            },
+            TransportType::WebSocket(web3) => {
+                web3.eth().transaction_count(H160::from_slice(address.as_bytes()), Some(block))
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao obter nonce: {}", e)))?
+            }
+        };
+
+        Ok(count.low_u64())
+    }
+
+    /// Reserva o próximo nonce de um remetente usando o gerenciador local
+    pub async fn next_nonce(&self, sender: Address) -> Result<u64> {
+        self.nonce_manager
+            .next_nonce(sender, || self.get_transaction_count(sender, true))
+            .await
+    }
+
+    /// Gerenciador de nonces do cliente
+    pub fn nonce_manager(&self) -> &NonceManager {
+        &self.nonce_manager
+    }
+
+    /// Envia uma transação assinada via eth_sendRawTransaction
+    pub async fn send_raw_transaction(&self, raw_tx: Vec<u8>) -> Result<H256> {
+        let hash = match &self.transport {
+            TransportType::Http(web3) => {
+                web3.eth().send_raw_transaction(Bytes(raw_tx))
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao enviar transação: {}", e)))?
+            },
+            TransportType::WebSocket(web3) => {
+                web3.eth().send_raw_transaction(Bytes(raw_tx))
+                    .await
+                    .map_err(|e| Error::RpcError(format!("Falha ao enviar transação: {}", e)))?
+            }
+        };
+
+        Ok(H256::from_slice(hash.as_bytes()))
+    }
+
+    /// Envia uma transação assinada de forma privada via eth_sendPrivateTransaction
+    ///
+    /// Destinado a endpoints no estilo Flashbots Protect, que não propagam a
+    /// transação no mempool público. `max_block_number` limita o bloco em que
+    /// a transação ainda pode ser incluída.
+    pub async fn send_private_transaction(&self, raw_tx: Vec<u8>, max_block_number: Option<u64>) -> Result<H256> {
+        let mut request = serde_json::json!({ "tx": Bytes(raw_tx) });
+        if let Some(block) = max_block_number {
+            request["maxBlockNumber"] = serde_json::Value::String(format!("0x{:x}", block));
+        }
+
+        let result = self
+            .execute("eth_sendPrivateTransaction", vec![request])
+            .await
+            .map_err(|e| Error::RpcError(format!("Falha ao enviar transação privada: {}", e)))?;
+
+        serde_json::from_value::<H256>(result)
+            .map_err(|e| Error::DecodeError(format!("Hash de transação inválido: {}", e)))
+    }
+
     /// Limpa o cache
     pub fn clear_cache(&self) {
         let mut cache = self.cache.write();
@@ -447,6 +1043,26 @@ impl ethernity_core::traits::RpcProvider for EthernityRpcClient {
     async fn get_block_hash(&self, block_number: u64) -> Result<H256> {
         self.get_block_hash(block_number).await
     }
+
+    async fn get_block_transactions(&self, block_number: u64) -> Result<Vec<TransactionHash>> {
+        self.get_block_transactions(block_number).await
+    }
+
+    async fn get_block_traces(&self, block_number: u64) -> Result<Vec<u8>> {
+        self.get_block_traces(block_number).await
+    }
+
+    async fn get_block_receipts(&self, block_number: u64) -> Result<Vec<u8>> {
+        self.get_block_receipts(block_number).await
+    }
+
+    async fn get_state_diff(&self, tx_hash: TransactionHash) -> Result<Vec<u8>> {
+        self.get_state_diff(tx_hash).await
+    }
+
+    async fn get_storage_at(&self, address: Address, slot: H256, block_number: Option<u64>) -> Result<H256> {
+        self.get_storage_at(address, slot, block_number).await
+    }
 }
 
 /// Estatísticas do cache
@@ -546,5 +1162,111 @@ impl ethernity_core::traits::RpcProvider for LoadBalancedRpcClient {
         let client = self.pool.get_client();
         client.get_block_hash(block_number).await
     }
+
+    async fn get_block_transactions(&self, block_number: u64) -> Result<Vec<TransactionHash>> {
+        let client = self.pool.get_client();
+        client.get_block_transactions(block_number).await
+    }
+
+    async fn get_block_traces(&self, block_number: u64) -> Result<Vec<u8>> {
+        let client = self.pool.get_client();
+        client.get_block_traces(block_number).await
+    }
+
+    async fn get_block_receipts(&self, block_number: u64) -> Result<Vec<u8>> {
+        let client = self.pool.get_client();
+        client.get_block_receipts(block_number).await
+    }
+
+    async fn get_state_diff(&self, tx_hash: TransactionHash) -> Result<Vec<u8>> {
+        let client = self.pool.get_client();
+        client.get_state_diff(tx_hash).await
+    }
+
+    async fn get_storage_at(&self, address: Address, slot: H256, block_number: Option<u64>) -> Result<H256> {
+        let client = self.pool.get_client();
+        client.get_storage_at(address, slot, block_number).await
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_node() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "method": "eth_blockNumber" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 0, "result": "0x1"
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_trace_waits_for_trace_timeout() {
+        let server = mock_node().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "method": "debug_traceTransaction" })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "jsonrpc": "2.0", "id": 1, "result": { "type": "CALL", "gasUsed": "0x0" }
+                    }))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = EthernityRpcClient::new_http(RpcConfig {
+            endpoint: server.uri(),
+            timeout: Duration::from_millis(100),
+            trace_api: Some(TraceApi::Debug),
+            trace_timeout: Duration::from_secs(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let trace = client.get_transaction_trace(TransactionHash::zero()).await.unwrap();
+        let trace: serde_json::Value = serde_json::from_slice(&trace).unwrap();
+        assert_eq!(trace["type"], "CALL");
+    }
+
+    #[tokio::test]
+    async fn test_block_traces_respect_response_limit() {
+        let server = mock_node().await;
+        let traces: Vec<serde_json::Value> = (0..64)
+            .map(|i| serde_json::json!({ "txHash": format!("0x{:064x}", i), "result": { "type": "CALL" } }))
+            .collect();
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "method": "debug_traceBlockByNumber" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": traces
+            })))
+            .mount(&server)
+            .await;
+
+        let config = RpcConfig {
+            endpoint: server.uri(),
+            trace_api: Some(TraceApi::Debug),
+            use_cache: false,
+            ..Default::default()
+        };
+        assert_eq!(config.max_response_size, Some(DEFAULT_MAX_RESPONSE_SIZE));
+
+        let client = EthernityRpcClient::new_http(config.clone()).await.unwrap();
+        let bytes = client.get_block_traces(1).await.unwrap();
+        let decoded: Vec<serde_json::Value> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.len(), 64);
+
+        let client = EthernityRpcClient::new_http(RpcConfig { max_response_size: Some(1024), ..config })
+            .await
+            .unwrap();
+        assert!(matches!(client.get_block_traces(1).await, Err(Error::ResponseTooLarge(_))));
+    }
+}
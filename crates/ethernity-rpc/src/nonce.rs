@@ -0,0 +1,98 @@
+/*!
+ * Gerenciamento de nonces
+ *
+ * Mantém o próximo nonce de cada remetente localmente, evitando consultas
+ * repetidas ao node e colisões entre envios concorrentes.
+ */
+
+use dashmap::DashMap;
+use ethereum_types::Address;
+use ethernity_core::error::Result;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Gerenciador de nonces por remetente
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    nonces: DashMap<Address, Arc<Mutex<Option<u64>>>>,
+}
+
+impl NonceManager {
+    /// Cria um gerenciador vazio
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserva o próximo nonce do remetente.
+    ///
+    /// Na primeira reserva o valor inicial é obtido com `fetch` (normalmente
+    /// `eth_getTransactionCount` no bloco `pending`); as seguintes são
+    /// incrementadas localmente. Reservas concorrentes do mesmo remetente são
+    /// serializadas.
+    pub async fn next_nonce<F, Fut>(&self, sender: Address, fetch: F) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let slot = self.nonces.entry(sender).or_default().clone();
+        let mut current = slot.lock().await;
+
+        let nonce = match *current {
+            Some(nonce) => nonce,
+            None => fetch().await?,
+        };
+        *current = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Próximo nonce conhecido do remetente, sem reservá-lo
+    pub async fn peek(&self, sender: Address) -> Option<u64> {
+        let slot = self.nonces.get(&sender)?.clone();
+        let current = slot.lock().await;
+        *current
+    }
+
+    /// Descarta o nonce do remetente, forçando nova consulta ao node
+    ///
+    /// Deve ser chamado quando um envio falha (ex.: `nonce too low`).
+    pub fn reset(&self, sender: Address) {
+        self.nonces.remove(&sender);
+    }
+
+    /// Descarta os nonces de todos os remetentes
+    pub fn clear(&self) {
+        self.nonces.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nonce_is_fetched_once_and_incremented() {
+        let manager = NonceManager::new();
+        let sender = Address::from_low_u64_be(1);
+
+        assert_eq!(manager.next_nonce(sender, || async { Ok(7) }).await.unwrap(), 7);
+        assert_eq!(manager.next_nonce(sender, || async { Ok(0) }).await.unwrap(), 8);
+        assert_eq!(manager.peek(sender).await, Some(9));
+
+        manager.reset(sender);
+        assert_eq!(manager.peek(sender).await, None);
+        assert_eq!(manager.next_nonce(sender, || async { Ok(3) }).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_does_not_reserve() {
+        let manager = NonceManager::new();
+        let sender = Address::from_low_u64_be(2);
+
+        let res = manager
+            .next_nonce(sender, || async { Err(ethernity_core::Error::RpcError("falha".into())) })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(manager.next_nonce(sender, || async { Ok(1) }).await.unwrap(), 1);
+    }
+}
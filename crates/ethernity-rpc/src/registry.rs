@@ -0,0 +1,231 @@
+/*!
+ * Registro de clientes RPC
+ *
+ * Associa nomes e chain IDs a provedores RPC, permitindo que análises
+ * multi-chain solicitem "um provedor para a chain 56" sem repassar endpoints
+ * manualmente.
+ */
+
+use crate::{EthernityRpcClient, RpcConfig};
+use ethernity_core::{Error, error::Result, traits::RpcProvider};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Provedor registrado
+#[derive(Clone)]
+pub struct RegisteredProvider {
+    pub name: String,
+    pub chain_id: u64,
+    pub endpoint: Option<String>,
+    pub provider: Arc<dyn RpcProvider>,
+}
+
+/// Registro de provedores RPC indexados por nome e chain ID
+#[derive(Default)]
+pub struct RpcRegistry {
+    providers: RwLock<HashMap<String, RegisteredProvider>>,
+    chains: RwLock<HashMap<u64, Vec<String>>>,
+    next_index: AtomicUsize,
+}
+
+impl RpcRegistry {
+    /// Cria um registro vazio
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra um provedor já construído para uma chain
+    ///
+    /// Um nome já existente é substituído.
+    pub fn register(&self, name: impl Into<String>, chain_id: u64, provider: Arc<dyn RpcProvider>) {
+        self.insert(RegisteredProvider {
+            name: name.into(),
+            chain_id,
+            endpoint: None,
+            provider,
+        });
+    }
+
+    /// Conecta a um endpoint e registra o cliente resultante
+    ///
+    /// O chain ID informado é conferido com `eth_chainId` do node.
+    pub async fn connect(
+        &self,
+        name: impl Into<String>,
+        chain_id: u64,
+        config: RpcConfig,
+    ) -> Result<Arc<EthernityRpcClient>> {
+        let endpoint = config.endpoint.clone();
+        let client = Arc::new(EthernityRpcClient::new(config).await?);
+
+        let actual = client.get_chain_id().await?;
+        if actual != chain_id {
+            return Err(Error::ValidationError(format!(
+                "Endpoint {} pertence à chain {}, esperado {}",
+                endpoint, actual, chain_id
+            )));
+        }
+
+        self.insert(RegisteredProvider {
+            name: name.into(),
+            chain_id,
+            endpoint: Some(endpoint),
+            provider: client.clone(),
+        });
+        Ok(client)
+    }
+
+    fn insert(&self, entry: RegisteredProvider) {
+        let mut providers = self.providers.write();
+        let mut chains = self.chains.write();
+
+        if let Some(previous) = providers.remove(&entry.name) {
+            Self::detach(&mut chains, previous.chain_id, &previous.name);
+        }
+
+        chains.entry(entry.chain_id).or_default().push(entry.name.clone());
+        providers.insert(entry.name.clone(), entry);
+    }
+
+    /// Remove um provedor pelo nome
+    pub fn unregister(&self, name: &str) -> Option<RegisteredProvider> {
+        let mut providers = self.providers.write();
+        let entry = providers.remove(name)?;
+        Self::detach(&mut self.chains.write(), entry.chain_id, name);
+        Some(entry)
+    }
+
+    fn detach(chains: &mut HashMap<u64, Vec<String>>, chain_id: u64, name: &str) {
+        if let Some(names) = chains.get_mut(&chain_id) {
+            names.retain(|n| n != name);
+            if names.is_empty() {
+                chains.remove(&chain_id);
+            }
+        }
+    }
+
+    /// Obtém um provedor pelo nome
+    pub fn get(&self, name: &str) -> Option<Arc<dyn RpcProvider>> {
+        self.providers.read().get(name).map(|e| e.provider.clone())
+    }
+
+    /// Obtém um provedor para a chain informada
+    ///
+    /// Com vários provedores registrados para a mesma chain a escolha é
+    /// feita em round-robin.
+    pub fn provider_for_chain(&self, chain_id: u64) -> Result<Arc<dyn RpcProvider>> {
+        self.entry_for_chain(chain_id).map(|e| e.provider)
+    }
+
+    /// Obtém o endpoint de um provedor da chain, quando conhecido
+    ///
+    /// Útil para componentes que precisam da URL, como forks de simulação.
+    pub fn endpoint_for_chain(&self, chain_id: u64) -> Option<String> {
+        let names = self.chains.read().get(&chain_id)?.clone();
+        let providers = self.providers.read();
+        names
+            .iter()
+            .filter_map(|name| providers.get(name))
+            .find_map(|e| e.endpoint.clone())
+    }
+
+    fn entry_for_chain(&self, chain_id: u64) -> Result<RegisteredProvider> {
+        // Os locks são obtidos um de cada vez para não inverter a ordem usada em `insert`
+        let name = {
+            let chains = self.chains.read();
+            let names = chains
+                .get(&chain_id)
+                .filter(|names| !names.is_empty())
+                .ok_or_else(|| Error::NotFound(format!("Nenhum provedor RPC para a chain {}", chain_id)))?;
+            let index = self.next_index.fetch_add(1, Ordering::Relaxed) % names.len();
+            names[index].clone()
+        };
+
+        self.providers
+            .read()
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("Provedor {} não encontrado", name)))
+    }
+
+    /// Chains com ao menos um provedor registrado
+    pub fn chains(&self) -> Vec<u64> {
+        let mut chains: Vec<u64> = self.chains.read().keys().copied().collect();
+        chains.sort_unstable();
+        chains
+    }
+
+    /// Provedores registrados
+    pub fn entries(&self) -> Vec<RegisteredProvider> {
+        self.providers.read().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ethereum_types::{Address, H256};
+    use ethernity_core::types::TransactionHash;
+
+    struct StaticProvider(u64);
+
+    #[async_trait]
+    impl RpcProvider for StaticProvider {
+        async fn get_transaction_trace(&self, _tx_hash: TransactionHash) -> Result<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_transaction_receipt(&self, _tx_hash: TransactionHash) -> Result<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_code(&self, _address: Address) -> Result<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> Result<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_block_number(&self) -> Result<u64> {
+            Ok(self.0)
+        }
+
+        async fn get_block_hash(&self, _block_number: u64) -> Result<H256> {
+            Ok(H256::zero())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routing_by_chain_and_name() {
+        let registry = RpcRegistry::new();
+        registry.register("bsc-a", 56, Arc::new(StaticProvider(1)));
+        registry.register("bsc-b", 56, Arc::new(StaticProvider(2)));
+        registry.register("mainnet", 1, Arc::new(StaticProvider(3)));
+
+        assert_eq!(registry.chains(), vec![1, 56]);
+        assert_eq!(registry.get("mainnet").unwrap().get_block_number().await.unwrap(), 3);
+
+        let first = registry.provider_for_chain(56).unwrap().get_block_number().await.unwrap();
+        let second = registry.provider_for_chain(56).unwrap().get_block_number().await.unwrap();
+        assert_ne!(first, second);
+
+        assert!(matches!(registry.provider_for_chain(137), Err(Error::NotFound(_))));
+        assert!(registry.endpoint_for_chain(1).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_unregister() {
+        let registry = RpcRegistry::new();
+        registry.register("node", 1, Arc::new(StaticProvider(1)));
+        registry.register("node", 10, Arc::new(StaticProvider(2)));
+
+        assert_eq!(registry.chains(), vec![10]);
+        assert!(registry.unregister("node").is_some());
+        assert!(registry.chains().is_empty());
+        assert!(registry.get("node").is_none());
+    }
+}
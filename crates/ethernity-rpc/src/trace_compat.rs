@@ -0,0 +1,376 @@
+/*!
+ * Compatibilidade entre APIs de trace
+ *
+ * Traduz traces no formato Parity (`trace_transaction`, usado por Erigon e
+ * Nethermind) para o formato aninhado do `callTracer` do Geth, que é o
+ * formato esperado pelo DeepTrace. O `stateDiff` de `trace_replayTransaction`
+ * é convertido para o formato do `prestateTracer` em modo diff.
+ */
+
+use ethernity_core::{Error, error::Result};
+use serde_json::{Map, Value};
+
+/// API de trace suportada pelo node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceApi {
+    /// `debug_traceTransaction` com `callTracer` (Geth e derivados)
+    Debug,
+    /// `trace_transaction` no formato Parity (Erigon, Nethermind, OpenEthereum)
+    Parity,
+}
+
+impl TraceApi {
+    /// Infere a API de trace preferida a partir de `web3_clientVersion`
+    pub fn from_client_version(version: &str) -> Self {
+        let version = version.to_lowercase();
+        let parity_clients = ["erigon", "nethermind", "openethereum", "parity"];
+        if parity_clients.iter().any(|client| version.contains(client)) {
+            TraceApi::Parity
+        } else {
+            TraceApi::Debug
+        }
+    }
+}
+
+/// Converte a lista plana de traces Parity em uma árvore no formato `callTracer`
+pub fn parity_to_call_trace(traces: &[Value]) -> Result<Value> {
+    let mut root: Option<Value> = None;
+
+    for trace in traces {
+        let trace_address: Vec<usize> = trace
+            .get("traceAddress")
+            .and_then(|v| v.as_array())
+            .map(|path| path.iter().filter_map(|i| i.as_u64()).map(|i| i as usize).collect())
+            .unwrap_or_default();
+
+        let Some(node) = convert_parity_trace(trace) else {
+            continue;
+        };
+
+        if trace_address.is_empty() {
+            root = Some(node);
+            continue;
+        }
+
+        let parent = root
+            .as_mut()
+            .and_then(|root| find_node(root, &trace_address[..trace_address.len() - 1]))
+            .ok_or_else(|| Error::DecodeError(format!("traceAddress sem pai: {:?}", trace_address)))?;
+
+        let calls = parent
+            .as_object_mut()
+            .expect("nó do callTracer é sempre um objeto")
+            .entry("calls")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(calls) = calls {
+            calls.push(node);
+        }
+    }
+
+    root.ok_or_else(|| Error::DecodeError("Trace Parity sem chamada raiz".to_string()))
+}
+
+/// Converte a saída de `trace_block` em uma árvore `callTracer` por transação
+///
+/// As traces são agrupadas por `transactionPosition`, preservando a ordem do
+/// bloco. Entradas sem transação (recompensas de bloco) são descartadas.
+pub fn parity_block_to_call_traces(traces: &[Value]) -> Result<Vec<Value>> {
+    let mut groups: Vec<(u64, Vec<Value>)> = Vec::new();
+
+    for trace in traces {
+        let Some(position) = trace.get("transactionPosition").and_then(|v| v.as_u64()) else {
+            continue;
+        };
+        match groups.last_mut() {
+            Some((current, group)) if *current == position => group.push(trace.clone()),
+            _ => groups.push((position, vec![trace.clone()])),
+        }
+    }
+
+    groups.sort_by_key(|(position, _)| *position);
+    groups.iter().map(|(_, group)| parity_to_call_trace(group)).collect()
+}
+
+/// Converte o `stateDiff` de `trace_replayTransaction` para o formato
+/// `{"pre": ..., "post": ...}` do `prestateTracer` em modo diff
+///
+/// Cada campo Parity é `"="` (inalterado), `{"+": v}` (criado), `{"-": v}`
+/// (removido) ou `{"*": {"from": a, "to": b}}` (alterado). Campos inalterados
+/// são omitidos; o nonce é convertido para número como no Geth.
+pub fn parity_state_diff_to_prestate(state_diff: &Value) -> Result<Value> {
+    let accounts = state_diff
+        .as_object()
+        .ok_or_else(|| Error::DecodeError("stateDiff não é um objeto".to_string()))?;
+
+    let mut pre = Map::new();
+    let mut post = Map::new();
+    for (address, account) in accounts {
+        let mut pre_account = Map::new();
+        let mut post_account = Map::new();
+
+        for field in ["balance", "nonce", "code"] {
+            let (before, after) = parity_change(account.get(field));
+            let normalize = |value: Value| if field == "nonce" { nonce_to_number(value) } else { value };
+            if let Some(before) = before {
+                pre_account.insert(field.into(), normalize(before));
+            }
+            if let Some(after) = after {
+                post_account.insert(field.into(), normalize(after));
+            }
+        }
+
+        let mut pre_storage = Map::new();
+        let mut post_storage = Map::new();
+        for (slot, change) in account.get("storage").and_then(|s| s.as_object()).into_iter().flatten() {
+            let (before, after) = parity_change(Some(change));
+            if let Some(before) = before {
+                pre_storage.insert(slot.clone(), before);
+            }
+            if let Some(after) = after {
+                post_storage.insert(slot.clone(), after);
+            }
+        }
+        if !pre_storage.is_empty() {
+            pre_account.insert("storage".into(), Value::Object(pre_storage));
+        }
+        if !post_storage.is_empty() {
+            post_account.insert("storage".into(), Value::Object(post_storage));
+        }
+
+        if !pre_account.is_empty() {
+            pre.insert(address.clone(), Value::Object(pre_account));
+        }
+        if !post_account.is_empty() {
+            post.insert(address.clone(), Value::Object(post_account));
+        }
+    }
+
+    let mut diff = Map::new();
+    diff.insert("pre".into(), Value::Object(pre));
+    diff.insert("post".into(), Value::Object(post));
+    Ok(Value::Object(diff))
+}
+
+/// Valores (antes, depois) de um campo do `stateDiff` Parity
+fn parity_change(change: Option<&Value>) -> (Option<Value>, Option<Value>) {
+    let Some(change) = change.and_then(|c| c.as_object()) else {
+        return (None, None);
+    };
+    if let Some(created) = change.get("+") {
+        (None, Some(created.clone()))
+    } else if let Some(removed) = change.get("-") {
+        (Some(removed.clone()), None)
+    } else if let Some(changed) = change.get("*") {
+        (changed.get("from").cloned(), changed.get("to").cloned())
+    } else {
+        (None, None)
+    }
+}
+
+fn nonce_to_number(nonce: Value) -> Value {
+    match nonce.as_str().and_then(|n| u64::from_str_radix(n.trim_start_matches("0x"), 16).ok()) {
+        Some(n) => Value::from(n),
+        None => nonce,
+    }
+}
+
+/// Localiza o nó indicado pelo caminho de índices a partir da raiz
+fn find_node<'a>(node: &'a mut Value, path: &[usize]) -> Option<&'a mut Value> {
+    match path.split_first() {
+        None => Some(node),
+        Some((first, rest)) => {
+            let child = node.get_mut("calls")?.as_array_mut()?.get_mut(*first)?;
+            find_node(child, rest)
+        }
+    }
+}
+
+/// Converte um único trace Parity em um nó `callTracer` sem filhos
+fn convert_parity_trace(trace: &Value) -> Option<Value> {
+    let action = trace.get("action")?;
+    let result = trace.get("result");
+    let field = |value: Option<&Value>, key: &str| -> String {
+        value
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let mut node = Map::new();
+    match trace.get("type").and_then(|v| v.as_str())? {
+        "call" => {
+            let call_type = action
+                .get("callType")
+                .and_then(|v| v.as_str())
+                .unwrap_or("call")
+                .to_uppercase();
+            node.insert("type".into(), Value::String(call_type));
+            node.insert("from".into(), Value::String(field(Some(action), "from")));
+            node.insert("to".into(), Value::String(field(Some(action), "to")));
+            node.insert("input".into(), Value::String(field(Some(action), "input")));
+            node.insert("output".into(), Value::String(field(result, "output")));
+        }
+        "create" => {
+            let create_type = match action.get("creationMethod").and_then(|v| v.as_str()) {
+                Some("create2") => "CREATE2",
+                _ => "CREATE",
+            };
+            node.insert("type".into(), Value::String(create_type.to_string()));
+            node.insert("from".into(), Value::String(field(Some(action), "from")));
+            node.insert("to".into(), Value::String(field(result, "address")));
+            node.insert("input".into(), Value::String(field(Some(action), "init")));
+            node.insert("output".into(), Value::String(field(result, "code")));
+        }
+        "suicide" => {
+            node.insert("type".into(), Value::String("SELFDESTRUCT".to_string()));
+            node.insert("from".into(), Value::String(field(Some(action), "address")));
+            node.insert("to".into(), Value::String(field(Some(action), "refundAddress")));
+            node.insert("input".into(), Value::String("0x".to_string()));
+            node.insert("output".into(), Value::String("0x".to_string()));
+            node.insert("value".into(), Value::String(hex_or_zero(field(Some(action), "balance"))));
+        }
+        _ => return None,
+    }
+
+    if !node.contains_key("value") {
+        node.insert("value".into(), Value::String(hex_or_zero(field(Some(action), "value"))));
+    }
+    node.insert("gas".into(), Value::String(hex_or_zero(field(Some(action), "gas"))));
+    node.insert("gasUsed".into(), Value::String(hex_or_zero(field(result, "gasUsed"))));
+    node.insert(
+        "error".into(),
+        trace.get("error").cloned().unwrap_or(Value::Null),
+    );
+
+    Some(Value::Object(node))
+}
+
+fn hex_or_zero(value: String) -> String {
+    if value.is_empty() { "0x0".to_string() } else { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_version_detection() {
+        assert_eq!(TraceApi::from_client_version("erigon/2.48.1/linux-amd64/go1.20.5"), TraceApi::Parity);
+        assert_eq!(TraceApi::from_client_version("Nethermind/v1.20.3"), TraceApi::Parity);
+        assert_eq!(TraceApi::from_client_version("Geth/v1.13.5-stable"), TraceApi::Debug);
+    }
+
+    #[test]
+    fn test_parity_traces_become_nested_tree() {
+        let traces = vec![
+            json!({
+                "action": {"callType": "call", "from": "0x01", "to": "0x02", "gas": "0x100", "input": "0xabcd", "value": "0x5"},
+                "result": {"gasUsed": "0x50", "output": "0x"},
+                "subtraces": 2, "traceAddress": [], "type": "call"
+            }),
+            json!({
+                "action": {"callType": "delegatecall", "from": "0x02", "to": "0x03", "gas": "0x80", "input": "0x", "value": "0x0"},
+                "result": {"gasUsed": "0x10", "output": "0x01"},
+                "subtraces": 0, "traceAddress": [0], "type": "call"
+            }),
+            json!({
+                "action": {"from": "0x02", "gas": "0x40", "init": "0x6000", "value": "0x0", "creationMethod": "create2"},
+                "result": {"address": "0x04", "code": "0x00", "gasUsed": "0x20"},
+                "subtraces": 0, "traceAddress": [1], "type": "create"
+            }),
+            json!({
+                "action": {"from": "0x02", "gas": "0x40", "input": "0x", "to": "0x05", "value": "0x0", "callType": "staticcall"},
+                "error": "Reverted",
+                "subtraces": 0, "traceAddress": [1, 0], "type": "call"
+            }),
+        ];
+
+        let tree = parity_to_call_trace(&traces).unwrap();
+        assert_eq!(tree["type"], "CALL");
+        assert_eq!(tree["value"], "0x5");
+        let calls = tree["calls"].as_array().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["type"], "DELEGATECALL");
+        assert_eq!(calls[1]["type"], "CREATE2");
+        assert_eq!(calls[1]["to"], "0x04");
+        assert_eq!(calls[1]["calls"][0]["error"], "Reverted");
+        assert_eq!(calls[1]["calls"][0]["gasUsed"], "0x0");
+    }
+
+    #[test]
+    fn test_block_traces_are_grouped_by_transaction() {
+        let call = |position: u64, address: serde_json::Value| json!({
+            "action": {"callType": "call", "from": "0x01", "to": "0x02", "gas": "0x1", "input": "0x", "value": "0x0"},
+            "result": {"gasUsed": "0x1", "output": "0x"},
+            "traceAddress": address, "transactionPosition": position, "type": "call"
+        });
+        let traces = vec![
+            call(0, json!([])),
+            call(0, json!([0])),
+            call(1, json!([])),
+            json!({"action": {"author": "0x03", "rewardType": "block", "value": "0x1"}, "traceAddress": [], "type": "reward"}),
+        ];
+
+        let trees = parity_block_to_call_traces(&traces).unwrap();
+        assert_eq!(trees.len(), 2);
+        assert_eq!(trees[0]["calls"].as_array().unwrap().len(), 1);
+        assert!(trees[1].get("calls").is_none());
+    }
+
+    #[test]
+    fn test_orphan_trace_is_rejected() {
+        let traces = vec![json!({
+            "action": {"callType": "call", "from": "0x01", "to": "0x02", "gas": "0x1", "input": "0x", "value": "0x0"},
+            "traceAddress": [3], "type": "call"
+        })];
+        assert!(parity_to_call_trace(&traces).is_err());
+    }
+
+    #[test]
+    fn test_parity_state_diff_becomes_prestate_diff() {
+        let state_diff = json!({
+            "0x0000000000000000000000000000000000000001": {
+                "balance": {"*": {"from": "0x10", "to": "0x8"}},
+                "nonce": {"*": {"from": "0x1", "to": "0x2"}},
+                "code": "=",
+                "storage": {}
+            },
+            "0x0000000000000000000000000000000000000002": {
+                "balance": "=",
+                "nonce": "=",
+                "code": "=",
+                "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000008": {"*": {
+                        "from": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                        "to": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }}
+                }
+            },
+            "0x0000000000000000000000000000000000000003": {
+                "balance": {"+": "0x5"},
+                "nonce": {"+": "0x1"},
+                "code": {"+": "0x6000"},
+                "storage": {}
+            }
+        });
+
+        let diff = parity_state_diff_to_prestate(&state_diff).unwrap();
+        let sender = "0x0000000000000000000000000000000000000001";
+        assert_eq!(diff["pre"][sender]["balance"], "0x10");
+        assert_eq!(diff["post"][sender]["balance"], "0x8");
+        assert_eq!(diff["post"][sender]["nonce"], 2);
+        assert!(diff["pre"][sender].get("code").is_none());
+
+        let pool = "0x0000000000000000000000000000000000000002";
+        let slot = "0x0000000000000000000000000000000000000000000000000000000000000008";
+        assert!(diff["pre"][pool].get("balance").is_none());
+        assert!(diff["post"][pool]["storage"][slot].as_str().unwrap().ends_with('2'));
+
+        let created = "0x0000000000000000000000000000000000000003";
+        assert!(diff["pre"].get(created).is_none());
+        assert_eq!(diff["post"][created]["code"], "0x6000");
+        assert!(parity_state_diff_to_prestate(&json!([])).is_err());
+    }
+}
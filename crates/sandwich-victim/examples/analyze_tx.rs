@@ -9,7 +9,7 @@
 use std::env;
 use std::time::Duration;
 
-use sandwich_victim::core::analyze_transaction;
+use sandwich_victim::core::analyze_transaction_report;
 use sandwich_victim::types::TransactionData;
 use ethers::prelude::*;
 use ethernity_rpc::{EthernityRpcClient, RpcConfig};
@@ -52,13 +52,18 @@ async fn main() -> anyhow::Result<()> {
     })
     .await?);
 
-    let result = analyze_transaction(
+    let report = analyze_transaction_report(
         rpc_client,
         rpc,
         tx,
         fetched.block_number.map(|b| b.as_u64() - 1),
     )
     .await?;
+    // Mostra o que cada detector decidiu quando nenhum conclui a análise
+    if report.result().is_none() {
+        eprint!("{report}");
+    }
+    let result = report.into_result()?;
 
     println!("Potencial vítima: {}", result.potential_victim);
     println!("Economicamente viável: {}", result.economically_viable);
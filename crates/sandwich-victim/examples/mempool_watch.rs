@@ -3,11 +3,25 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
+use async_trait::async_trait;
 use ethernity_rpc::{EthernityRpcClient, RpcConfig};
 use ethers::prelude::*;
 use futures::StreamExt;
-use sandwich_victim::core::analyze_transaction;
-use sandwich_victim::types::TransactionData;
+use sandwich_victim::core::{analyze_stream, StreamConfig, VictimSink};
+use sandwich_victim::types::{AnalysisResult, TransactionData};
+
+/// Imprime as potenciais vítimas no console
+struct ConsoleSink;
+
+#[async_trait]
+impl VictimSink for ConsoleSink {
+    async fn send(&self, tx: &TransactionData, result: AnalysisResult) -> Result<()> {
+        if result.potential_victim {
+            println!("possível vítima de {:?} (nonce {})\n{:#?}", tx.from, tx.nonce, result.metrics);
+        }
+        Ok(())
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -40,39 +54,14 @@ async fn mempool_listener(
     rpc_client: Arc<EthernityRpcClient>,
     ws_url: String,
 ) -> Result<()> {
-    let stream = provider.subscribe_pending_txs().await?.transactions_unordered(usize::MAX);
+    let stream = provider
+        .subscribe_pending_txs()
+        .await?
+        .transactions_unordered(usize::MAX)
+        .filter_map(|res| async move { res.ok().as_ref().and_then(TransactionData::from_pending) });
     println!("Escutando transações pendentes...");
 
-    stream
-        .for_each_concurrent(usize::MAX, |res| {
-            let rpc_client = rpc_client.clone();
-            let ws_url = ws_url.clone();
-            async move {
-                let tx = match res {
-                    Ok(tx) => tx,
-                    Err(_) => return,
-                };
-
-                let Some(to) = tx.to else { return };
-                let tx_data = TransactionData {
-                    from: tx.from,
-                    to,
-                    data: tx.input.to_vec(),
-                    value: tx.value,
-                    gas: tx.gas.as_u64(),
-                    gas_price: tx.gas_price.unwrap_or_default(),
-                    nonce: tx.nonce,
-                };
-
-                match analyze_transaction(rpc_client, "http://148.251.183.245:8545".to_string(), tx_data, None).await {
-                    Ok(result) if result.potential_victim => {
-                        println!("possível vítima {:?}\n{:#?}", tx.hash, result.metrics);
-                    }
-                    Ok(_) => {}
-                    Err(err) => eprintln!("Erro ao analisar tx {:?}: {err}", tx.hash),
-                }
-            }
-        })
-        .await;
+    let stats = analyze_stream(rpc_client, ws_url, stream, Arc::new(ConsoleSink), StreamConfig::default()).await?;
+    println!("{stats:?}");
     Ok(())
 }
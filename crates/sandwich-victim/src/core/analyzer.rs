@@ -1,11 +1,13 @@
-use crate::detectors::{DetectorRegistry};
+use crate::detectors::{AnalysisReport, DetectorRegistry};
 use crate::dex::{identify_router, router_from_logs, RouterInfo};
-use crate::filters::{FilterPipeline, SwapLogFilter};
+use crate::filters::{sender_is_funded, FilterPipeline, SwapLogFilter};
 use crate::simulation::{simulate_transaction, SimulationConfig};
 use crate::types::{AnalysisResult, TransactionData};
 use anyhow::{Result, anyhow};
 use ethernity_core::traits::RpcProvider;
+use ethers::prelude::{Http, Provider};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, thiserror::Error)]
 enum AnalysisError {
@@ -13,6 +15,8 @@ enum AnalysisError {
     NoSwapEvent,
     #[error("Router not found in logs")]
     NoRouterFound,
+    #[error("Sender lacks the input balance or router allowance")]
+    Unfunded,
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -23,6 +27,29 @@ pub async fn analyze_transaction(
     tx: TransactionData,
     block: Option<u64>,
 ) -> Result<AnalysisResult> {
+    analyze_transaction_report(rpc_client, rpc_endpoint, tx, block)
+        .await?
+        .into_result()
+        .map_err(|e| anyhow!(e))
+}
+
+/// Como [`analyze_transaction`], mas devolve o [`AnalysisReport`] com todos os
+/// detectores consultados. Falhas anteriores aos detectores (remetente sem saldo
+/// ou allowance, simulação, ausência de swap ou de router) continuam sendo
+/// retornadas como erro.
+pub async fn analyze_transaction_report(
+    rpc_client: Arc<dyn RpcProvider>,
+    rpc_endpoint: String,
+    tx: TransactionData,
+    block: Option<u64>,
+) -> Result<AnalysisReport> {
+    // Descarta transações que reverteriam por falta de fundos antes de simular;
+    // falhas na consulta deixam a análise seguir
+    let provider = Provider::<Http>::try_from(rpc_endpoint.as_str())?.interval(Duration::from_millis(1));
+    if let Ok(false) = sender_is_funded(&provider, &tx, block).await {
+        return Err(AnalysisError::Unfunded.into());
+    }
+
     let sim_config = SimulationConfig {
         rpc_endpoint: rpc_endpoint.clone(),
         block_number: block,
@@ -38,9 +65,7 @@ pub async fn analyze_transaction(
     let router: RouterInfo = identify_router(&*rpc_client, router_address).await?;
 
     let registry = DetectorRegistry::default();
-    registry
-        .analyze(rpc_client, rpc_endpoint, tx, block, outcome, router)
-        .await
-        .map_err(|e| anyhow!(e))
+    Ok(registry
+        .analyze_report(rpc_client, rpc_endpoint, tx, block, outcome, router)
+        .await)
 }
-
@@ -0,0 +1,143 @@
+use crate::types::GasCost;
+use anyhow::{anyhow, Result};
+use ethereum_types::U256;
+use ethers::prelude::Middleware;
+use ethers::types::BlockNumber;
+use ethers::utils::{
+    eip1559_default_estimator, EIP1559_FEE_ESTIMATION_PAST_BLOCKS, EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE,
+};
+
+/// Gas estimado de um swap direto em um par V2 pelo contrato do atacante
+/// (transação base, transferência do token de entrada e `swap` no par)
+pub const V2_SWAP_GAS: u64 = 120_000;
+
+impl GasCost {
+    /// Monta o custo do sanduíche com a base fee do bloco e a priority fee do
+    /// oráculo. O frontrun precisa superar a priority fee da vítima em 10%
+    /// (como na substituição de transações) para ser ordenado antes dela; o
+    /// backrun só concorre com os demais pela priority fee do oráculo.
+    pub fn estimate(
+        frontrun_gas: u64,
+        backrun_gas: u64,
+        base_fee: U256,
+        oracle_priority_fee: U256,
+        victim_gas_price: U256,
+    ) -> Self {
+        let victim_priority_fee = victim_gas_price.saturating_sub(base_fee);
+        let outbid = victim_priority_fee + victim_priority_fee / U256::from(10u64) + U256::one();
+        let frontrun_priority_fee = outbid.max(oracle_priority_fee);
+        let backrun_priority_fee = oracle_priority_fee;
+        let total_cost = U256::from(frontrun_gas) * (base_fee + frontrun_priority_fee)
+            + U256::from(backrun_gas) * (base_fee + backrun_priority_fee);
+        Self {
+            frontrun_gas,
+            backrun_gas,
+            base_fee,
+            victim_priority_fee,
+            frontrun_priority_fee,
+            backrun_priority_fee,
+            total_cost,
+            profit_in_native: None,
+        }
+    }
+
+    /// Lucro em wei descontado o gas; `None` sem cotação ou quando o gas consome todo o lucro
+    pub fn net_profit(&self) -> Option<U256> {
+        self.profit_in_native?
+            .checked_sub(self.total_cost)
+            .filter(|net| !net.is_zero())
+    }
+
+    pub fn economically_viable(&self) -> bool {
+        self.net_profit().is_some()
+    }
+}
+
+/// Custo de gas de um frontrun e um backrun de `V2_SWAP_GAS` no bloco da
+/// simulação (o último quando `block` é `None`): a base fee do bloco e a
+/// priority fee estimada do `eth_feeHistory` até ele, como no oráculo do
+/// provider. Em redes sem EIP-1559 usa `eth_gasPrice` como base e priority
+/// fee zero.
+pub async fn v2_sandwich_gas_cost<M>(provider: &M, block: Option<u64>, victim_gas_price: U256) -> Result<GasCost>
+where
+    M: Middleware + 'static,
+{
+    let block = block.map_or(BlockNumber::Latest, |b| BlockNumber::Number(b.into()));
+    let base_fee = provider
+        .get_block(block)
+        .await
+        .map_err(|e| anyhow!(e))?
+        .and_then(|block| block.base_fee_per_gas);
+    let (base_fee, priority_fee) = match base_fee {
+        Some(base_fee) => {
+            let history = provider
+                .fee_history(EIP1559_FEE_ESTIMATION_PAST_BLOCKS, block, &[EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE])
+                .await
+                .map_err(|e| anyhow!(e))?;
+            let (_, priority_fee) = eip1559_default_estimator(base_fee, history.reward);
+            (base_fee, priority_fee)
+        }
+        None => (provider.get_gas_price().await.map_err(|e| anyhow!(e))?, U256::zero()),
+    };
+    Ok(GasCost::estimate(V2_SWAP_GAS, V2_SWAP_GAS, base_fee, priority_fee, victim_gas_price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::Provider;
+    use ethers::types::{Block, FeeHistory, H256};
+
+    fn gwei(n: u64) -> U256 {
+        U256::exp10(9) * n
+    }
+
+    #[test]
+    fn frontrun_outbids_victim_priority_fee() {
+        // Vítima paga 2 gwei de priority fee; o oráculo sugere 1 gwei
+        let cost = GasCost::estimate(100_000, 100_000, gwei(20), gwei(1), gwei(22));
+        assert_eq!(cost.victim_priority_fee, gwei(2));
+        assert_eq!(cost.frontrun_priority_fee, gwei(2) + gwei(2) / 10 + 1);
+        assert_eq!(cost.backrun_priority_fee, gwei(1));
+        assert_eq!(
+            cost.total_cost,
+            U256::from(100_000u64) * (gwei(20) + cost.frontrun_priority_fee) + U256::from(100_000u64) * gwei(21)
+        );
+
+        // Vítima abaixo da base fee: o frontrun segue o oráculo
+        let cost = GasCost::estimate(100_000, 100_000, gwei(20), gwei(1), gwei(10));
+        assert_eq!((cost.victim_priority_fee, cost.frontrun_priority_fee), (U256::zero(), gwei(1)));
+    }
+
+    #[tokio::test]
+    async fn fees_read_at_the_simulation_block() {
+        let (provider, mock) = Provider::mocked();
+        let history = FeeHistory {
+            base_fee_per_gas: vec![gwei(20); 11],
+            gas_used_ratio: vec![0.5; 10],
+            oldest_block: 7.into(),
+            reward: vec![vec![gwei(2)]; 10],
+        };
+        // O mock responde na ordem inversa das chamadas
+        mock.push(history).unwrap();
+        mock.push(Block::<H256> { base_fee_per_gas: Some(gwei(20)), ..Default::default() }).unwrap();
+
+        let cost = v2_sandwich_gas_cost(&provider, Some(16), gwei(21)).await.unwrap();
+        assert_eq!(cost.base_fee, gwei(20));
+        mock.assert_request("eth_getBlockByNumber", ("0x10", false)).unwrap();
+        mock.assert_request("eth_feeHistory", (U256::from(EIP1559_FEE_ESTIMATION_PAST_BLOCKS), "0x10", [5.0])).unwrap();
+    }
+
+    #[test]
+    fn viability_needs_profit_above_gas() {
+        let mut cost = GasCost::estimate(100_000, 100_000, gwei(10), gwei(1), gwei(11));
+        assert!(!cost.economically_viable());
+
+        cost.profit_in_native = Some(cost.total_cost);
+        assert!(!cost.economically_viable());
+
+        cost.profit_in_native = Some(cost.total_cost + 5);
+        assert_eq!(cost.net_profit(), Some(5.into()));
+        assert!(cost.economically_viable());
+    }
+}
@@ -1,4 +1,5 @@
-use ethereum_types::U256;
+use crate::types::HopMetrics;
+use ethereum_types::{U256, U512};
 
 pub trait U256Ext {
     fn to_f64_lossy(&self) -> f64;
@@ -24,18 +25,6 @@ pub fn constant_product_output(amount_in: U256, reserve_in: U256, reserve_out: U
     numerator / (reserve_in + amount_in)
 }
 
-pub fn simulate_sandwich_profit(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U256 {
-    let front = amount_in / U256::from(10u64);
-    let out_front = constant_product_output(front, reserve_in, reserve_out);
-    let res_in_after_front = reserve_in + front;
-    let res_out_after_front = reserve_out - out_front;
-    let _victim_out = constant_product_output(amount_in, res_in_after_front, res_out_after_front);
-    let res_in_after_victim = res_in_after_front + amount_in;
-    let res_out_after_victim = res_out_after_front - _victim_out;
-    let back_out = constant_product_output(out_front, res_out_after_victim, res_in_after_victim);
-    if back_out > front { back_out - front } else { U256::zero() }
-}
-
 pub fn constant_product_input(
     amount_out: U256,
     reserve_in: U256,
@@ -52,4 +41,106 @@ pub fn constant_product_input(
     Some(numerator / denominator + U256::one())
 }
 
+/// Saída de um swap dentro do tick atual de um pool de liquidez concentrada.
+///
+/// `amount_in` já deve estar líquido da taxa; travessias de tick são ignoradas,
+/// então o valor é uma aproximação para swaps maiores que a liquidez do tick.
+pub fn concentrated_liquidity_output(
+    amount_in: U256,
+    sqrt_price_x96: U256,
+    liquidity: U256,
+    zero_for_one: bool,
+) -> U256 {
+    if amount_in.is_zero() || liquidity.is_zero() || sqrt_price_x96.is_zero() {
+        return U256::zero();
+    }
+    let q96 = U512::one() << 96;
+    let sqrt_q = next_sqrt_price(amount_in, sqrt_price_x96, liquidity, zero_for_one);
+    let (sqrt_p, liquidity) = (U512::from(sqrt_price_x96), U512::from(liquidity));
+    let out = if zero_for_one {
+        liquidity * (sqrt_p - sqrt_q) / q96
+    } else {
+        liquidity * q96 * (sqrt_q - sqrt_p) / (sqrt_q * sqrt_p)
+    };
+    U256::try_from(out).unwrap_or(U256::MAX)
+}
+
+/// `sqrtPriceX96` depois de um swap de `amount_in` (líquido de taxa) dentro do tick atual
+pub fn concentrated_liquidity_sqrt_price_after(
+    amount_in: U256,
+    sqrt_price_x96: U256,
+    liquidity: U256,
+    zero_for_one: bool,
+) -> U256 {
+    if amount_in.is_zero() || liquidity.is_zero() || sqrt_price_x96.is_zero() {
+        return sqrt_price_x96;
+    }
+    U256::try_from(next_sqrt_price(amount_in, sqrt_price_x96, liquidity, zero_for_one)).unwrap_or(U256::MAX)
+}
+
+fn next_sqrt_price(amount_in: U256, sqrt_price_x96: U256, liquidity: U256, zero_for_one: bool) -> U512 {
+    let q96 = U512::one() << 96;
+    let (amount_in, sqrt_p, liquidity) = (U512::from(amount_in), U512::from(sqrt_price_x96), U512::from(liquidity));
+    if zero_for_one {
+        liquidity * q96 * sqrt_p / (liquidity * q96 + amount_in * sqrt_p)
+    } else {
+        sqrt_p + amount_in * q96 / liquidity
+    }
+}
+
+/// Entrada líquida de taxa necessária para obter `amount_out` dentro do tick atual.
+/// Retorna `None` quando a liquidez do tick não cobre a saída.
+pub fn concentrated_liquidity_input(
+    amount_out: U256,
+    sqrt_price_x96: U256,
+    liquidity: U256,
+    zero_for_one: bool,
+) -> Option<U256> {
+    if liquidity.is_zero() || sqrt_price_x96.is_zero() {
+        return None;
+    }
+    let q96 = U512::one() << 96;
+    let (amount_out, sqrt_p, liquidity) = (U512::from(amount_out), U512::from(sqrt_price_x96), U512::from(liquidity));
+    let amount_in = if zero_for_one {
+        let delta = (amount_out * q96).div_mod(liquidity);
+        let delta = delta.0 + if delta.1.is_zero() { U512::zero() } else { U512::one() };
+        if delta >= sqrt_p {
+            return None;
+        }
+        let sqrt_q = sqrt_p - delta;
+        liquidity * q96 * (sqrt_p - sqrt_q) / (sqrt_p * sqrt_q) + U512::one()
+    } else {
+        let denominator = (liquidity * q96).checked_sub(amount_out * sqrt_p).filter(|d| !d.is_zero())?;
+        let sqrt_q = liquidity * q96 * sqrt_p / denominator;
+        liquidity * (sqrt_q - sqrt_p) / q96 + U512::one()
+    };
+    U256::try_from(amount_in).ok()
+}
+
+/// Reserva virtual do token de entrada (`L / sqrtP` para token0, `L * sqrtP` para token1),
+/// usada no lugar das reservas de pools V2
+pub fn concentrated_liquidity_reserve(sqrt_price_x96: U256, liquidity: U256, zero_for_one: bool) -> U256 {
+    if sqrt_price_x96.is_zero() {
+        return U256::zero();
+    }
+    let reserve = if zero_for_one {
+        (U512::from(liquidity) << 96) / U512::from(sqrt_price_x96)
+    } else {
+        (U512::from(liquidity) * U512::from(sqrt_price_x96)) >> 96
+    };
+    U256::try_from(reserve).unwrap_or(U256::MAX)
+}
+
+/// Fração da saída esperada que não foi entregue; zero quando a saída efetiva é maior
+pub fn hop_slippage(expected_out: U256, actual_out: U256) -> f64 {
+    if expected_out > actual_out && !expected_out.is_zero() {
+        (expected_out - actual_out).to_f64_lossy() / expected_out.to_f64_lossy()
+    } else {
+        0.0
+    }
+}
 
+/// Slippage ponta a ponta de uma rota, compondo as perdas de cada hop
+pub fn route_slippage(hops: &[HopMetrics]) -> f64 {
+    1.0 - hops.iter().map(|hop| 1.0 - hop.slippage).product::<f64>()
+}
@@ -1,5 +1,15 @@
 pub mod analyzer;
+pub mod gas;
 pub mod metrics;
+pub mod sandwich;
+pub mod stream;
+pub mod transfer_tax;
+pub mod weth;
 
 pub use analyzer::*;
+pub use gas::*;
 pub use metrics::*;
+pub use sandwich::*;
+pub use stream::*;
+pub use transfer_tax::*;
+pub use weth::*;
@@ -0,0 +1,276 @@
+use crate::core::metrics::{
+    concentrated_liquidity_output, concentrated_liquidity_reserve, concentrated_liquidity_sqrt_price_after, U256Ext,
+};
+use crate::types::SandwichPlan;
+use ethereum_types::{U256, U512};
+
+/// Maior frontrun em um par de produto constante, em bips da reserva de
+/// entrada: o capital que um atacante realisticamente coloca num único swap.
+/// Sem esse teto, vítimas sem `amountOutMin` levariam a frontruns maiores que
+/// a própria reserva.
+pub const MAX_FRONTRUN_RESERVE_BPS: u64 = 1_000;
+
+/// Pool usado para dimensionar o sanduíche. `forward` é o sentido da vítima
+/// (e do frontrun); o backrun troca no sentido inverso.
+pub trait SwapCurve: Sized {
+    /// Saída de `amount_in` e o estado do pool depois do swap
+    fn swap(&self, amount_in: U256, forward: bool) -> Option<(U256, Self)>;
+
+    /// Limite superior para o frontrun; com `victim_min_out`, o maior frontrun
+    /// que ainda deixa a vítima receber essa quantidade (pode ser aproximado
+    /// para cima, a busca confirma a viabilidade com o próprio `swap`)
+    fn frontrun_bound(&self, victim_in: U256, victim_min_out: Option<U256>) -> U256;
+}
+
+/// Par de produto constante (`x * y = k`) com taxa `fee_numerator / fee_denominator`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantProductPool {
+    pub reserve_in: U256,
+    pub reserve_out: U256,
+    pub fee_numerator: u64,
+    pub fee_denominator: u64,
+}
+
+impl ConstantProductPool {
+    /// Par Uniswap V2 com a taxa padrão de 0,3%
+    pub fn v2(reserve_in: U256, reserve_out: U256) -> Self {
+        Self { reserve_in, reserve_out, fee_numerator: 997, fee_denominator: 1000 }
+    }
+}
+
+impl SwapCurve for ConstantProductPool {
+    fn swap(&self, amount_in: U256, forward: bool) -> Option<(U256, Self)> {
+        let (reserve_in, reserve_out) = if forward {
+            (self.reserve_in, self.reserve_out)
+        } else {
+            (self.reserve_out, self.reserve_in)
+        };
+        if reserve_in.is_zero() || reserve_out.is_zero() {
+            return None;
+        }
+        let amount_with_fee = U512::from(amount_in) * U512::from(self.fee_numerator);
+        let out = amount_with_fee * U512::from(reserve_out)
+            / (U512::from(reserve_in) * U512::from(self.fee_denominator) + amount_with_fee);
+        let out = U256::try_from(out).ok()?;
+        let (reserve_in, reserve_out) = (reserve_in.checked_add(amount_in)?, reserve_out - out);
+        let next = if forward {
+            Self { reserve_in, reserve_out, ..*self }
+        } else {
+            Self { reserve_in: reserve_out, reserve_out: reserve_in, ..*self }
+        };
+        Some((out, next))
+    }
+
+    /// Raiz positiva de `m·γ·x² + m·(a(1+γ) + γ²v)·x + m·a(a + γv) − γ·v·a·b = 0`, em que
+    /// `a`/`b` são as reservas, `γ` a fração após a taxa, `v` a entrada e `m` a saída
+    /// mínima da vítima: o frontrun `x` a partir do qual a vítima recebe menos que `m`.
+    /// Limitada a `MAX_FRONTRUN_RESERVE_BPS` da reserva de entrada.
+    fn frontrun_bound(&self, victim_in: U256, victim_min_out: Option<U256>) -> U256 {
+        let capital = U256::try_from(U512::from(self.reserve_in) * U512::from(MAX_FRONTRUN_RESERVE_BPS) / U512::from(10_000u64))
+            .unwrap_or(U256::MAX);
+        let Some(min_out) = victim_min_out.filter(|m| !m.is_zero()) else {
+            // Sem limite da vítima o lucro cresce com o frontrun até várias vezes a reserva
+            return capital;
+        };
+        let gamma = self.fee_numerator as f64 / self.fee_denominator as f64;
+        let (a, b) = (self.reserve_in.to_f64_lossy(), self.reserve_out.to_f64_lossy());
+        let (v, m) = (victim_in.to_f64_lossy(), min_out.to_f64_lossy());
+        let qa = m * gamma;
+        let qb = m * (a * (1.0 + gamma) + gamma * gamma * v);
+        let qc = m * a * (a + gamma * v) - gamma * v * a * b;
+        if qc >= 0.0 {
+            return U256::zero();
+        }
+        let root = (-qb + (qb * qb - 4.0 * qa * qc).sqrt()) / (2.0 * qa);
+        // Folga para os erros de arredondamento de f64; a busca ajusta o valor exato
+        f64_to_u256(root * (1.0 + 1e-9) + 1.0).min(capital)
+    }
+}
+
+/// Pool de liquidez concentrada restrito ao tick atual; `zero_for_one` é o sentido da vítima
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcentratedPool {
+    pub sqrt_price_x96: U256,
+    pub liquidity: U256,
+    pub zero_for_one: bool,
+    /// Taxa do pool em centésimos de bip
+    pub fee: u32,
+}
+
+impl SwapCurve for ConcentratedPool {
+    fn swap(&self, amount_in: U256, forward: bool) -> Option<(U256, Self)> {
+        let zero_for_one = self.zero_for_one == forward;
+        let net = U256::try_from(U512::from(amount_in) * U512::from(1_000_000 - self.fee.min(1_000_000)) / U512::from(1_000_000u64)).ok()?;
+        let out = concentrated_liquidity_output(net, self.sqrt_price_x96, self.liquidity, zero_for_one);
+        let sqrt_price_x96 = concentrated_liquidity_sqrt_price_after(net, self.sqrt_price_x96, self.liquidity, zero_for_one);
+        Some((out, Self { sqrt_price_x96, ..*self }))
+    }
+
+    /// Reserva virtual do token de entrada: além dela o swap sairia do tick
+    fn frontrun_bound(&self, _victim_in: U256, _victim_min_out: Option<U256>) -> U256 {
+        concentrated_liquidity_reserve(self.sqrt_price_x96, self.liquidity, self.zero_for_one)
+    }
+}
+
+fn f64_to_u256(value: f64) -> U256 {
+    if !value.is_finite() || value <= 0.0 {
+        return U256::zero();
+    }
+    if value < u128::MAX as f64 {
+        return U256::from(value as u128);
+    }
+    let exponent = value.log2().floor() as usize - 100;
+    U256::from((value / 2f64.powi(exponent as i32)) as u128) << exponent
+}
+
+/// Frontrun `frontrun_in`, a vítima e o backrun em sequência: (saída da vítima, saída do backrun)
+fn sandwich_round<C: SwapCurve>(pool: &C, frontrun_in: U256, victim_in: U256) -> Option<(U256, U256)> {
+    let (front_out, pool) = pool.swap(frontrun_in, true)?;
+    let (victim_out, pool) = pool.swap(victim_in, true)?;
+    let (back_out, _) = pool.swap(front_out, false)?;
+    Some((victim_out, back_out))
+}
+
+/// Frontrun que maximiza o lucro do sanduíche sobre uma vítima que vende
+/// `victim_in` exigindo pelo menos `victim_min_out`.
+///
+/// O maior frontrun tolerado pela vítima vem de [`SwapCurve::frontrun_bound`]
+/// (em forma fechada no produto constante) e é confirmado por bisseção; o
+/// lucro, unimodal na entrada do frontrun, é maximizado por busca ternária
+/// dentro desse intervalo. Retorna `None` quando a vítima não tolera nenhum
+/// frontrun ou o pool não pode ser simulado.
+pub fn optimal_sandwich<C: SwapCurve>(pool: &C, victim_in: U256, victim_min_out: Option<U256>) -> Option<SandwichPlan> {
+    if victim_in.is_zero() {
+        return None;
+    }
+    let feasible = |x: U256| {
+        sandwich_round(pool, x, victim_in)
+            .is_some_and(|(victim_out, _)| victim_min_out.is_none_or(|min| victim_out >= min))
+    };
+    if !feasible(U256::zero()) {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (U256::zero(), pool.frontrun_bound(victim_in, victim_min_out));
+    if !feasible(hi) {
+        while hi - lo > U256::one() {
+            let mid = lo + (hi - lo) / 2;
+            if feasible(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        hi = lo;
+    }
+
+    // Compara lucros com sinal sem subtrair: back_a - a < back_b - b <=> back_a + b < back_b + a
+    let back_out = |x: U256| sandwich_round(pool, x, victim_in).map_or(U256::zero(), |(_, back)| back);
+    let bound = hi;
+    let mut lo = U256::zero();
+    while hi - lo > U256::from(2u64) {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - third);
+        if back_out(m1).saturating_add(m2) < back_out(m2).saturating_add(m1) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    // O limite também é candidato: com o teto de capital o ótimo costuma estar nele
+    let frontrun_in = [lo, lo + 1, hi, bound]
+        .into_iter()
+        .filter(|x| *x <= bound)
+        .max_by_key(|x| back_out(*x).saturating_sub(*x))?;
+
+    let (victim_out, back_out) = sandwich_round(pool, frontrun_in, victim_in)?;
+    Some(SandwichPlan {
+        frontrun_in,
+        profit: back_out.saturating_sub(frontrun_in),
+        victim_out,
+        victim_execution_price: victim_out.to_f64_lossy() / victim_in.to_f64_lossy(),
+    })
+}
+
+/// Saída mínima tolerada em um hop que entregou `hop_out`, aplicando a folga
+/// da rota `numerator / denominator` (`amountOutMin / saída` em entradas
+/// exatas ou `entrada / amountInMax` em saídas exatas)
+pub fn scaled_min_out(hop_out: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    if hop_out.is_zero() || denominator.is_zero() {
+        return None;
+    }
+    U256::try_from(U512::from(hop_out) * U512::from(numerator) / U512::from(denominator)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force<C: SwapCurve>(pool: &C, victim_in: U256, min_out: Option<U256>, step: U256, steps: u64) -> U256 {
+        (0..=steps)
+            .map(|i| step * i)
+            .filter_map(|x| sandwich_round(pool, x, victim_in).map(|(victim_out, back)| (x, victim_out, back)))
+            .filter(|(_, victim_out, _)| min_out.is_none_or(|min| *victim_out >= min))
+            .map(|(x, _, back)| back.saturating_sub(x))
+            .max()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn constant_product_bound_is_the_victim_limit() {
+        let pool = ConstantProductPool::v2(U256::exp10(24), U256::exp10(24));
+        let victim_in = U256::exp10(22);
+        let (quote, _) = pool.swap(victim_in, true).unwrap();
+        // Vítima aceita 2% abaixo da cotação
+        let min_out = quote * 98u64 / 100u64;
+
+        let plan = optimal_sandwich(&pool, victim_in, Some(min_out)).unwrap();
+        assert!(plan.victim_out >= min_out);
+        // Um wei a mais de frontrun faria a transação da vítima reverter
+        let (victim_out, _) = sandwich_round(&pool, plan.frontrun_in + U256::exp10(15), victim_in).unwrap();
+        assert!(victim_out < min_out);
+        assert!(plan.profit > U256::zero());
+        assert!(plan.profit >= brute_force(&pool, victim_in, Some(min_out), U256::exp10(20), 1000));
+        assert!((plan.victim_execution_price - min_out.to_f64_lossy() / victim_in.to_f64_lossy()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unconstrained_victim_is_capped_by_the_reserve() {
+        let pool = ConstantProductPool::v2(U256::exp10(21), U256::exp10(21));
+        let victim_in = U256::exp10(19);
+        let plan = optimal_sandwich(&pool, victim_in, None).unwrap();
+        // O lucro só cresceria com frontruns maiores que a reserva; o teto de capital limita a busca
+        let capital = pool.reserve_in * MAX_FRONTRUN_RESERVE_BPS / 10_000u64;
+        assert!(plan.frontrun_in <= capital && plan.frontrun_in > capital * 99u64 / 100u64);
+        assert!(plan.profit >= brute_force(&pool, victim_in, None, U256::exp10(17), 1000));
+
+        // Vítima muito tolerante: o limite da vítima fica além do teto
+        let (quote, _) = pool.swap(victim_in, true).unwrap();
+        let plan = optimal_sandwich(&pool, victim_in, Some(quote / 100u64)).unwrap();
+        assert!(plan.frontrun_in <= capital);
+
+        // Vítima sem folga: nenhum frontrun é tolerado
+        let (quote, _) = pool.swap(victim_in, true).unwrap();
+        let plan = optimal_sandwich(&pool, victim_in, Some(quote)).unwrap();
+        assert_eq!((plan.frontrun_in, plan.profit), (U256::zero(), U256::zero()));
+        assert!(optimal_sandwich(&pool, victim_in, Some(quote + 1)).is_none());
+    }
+
+    #[test]
+    fn concentrated_pool_is_searched_numerically() {
+        let pool = ConcentratedPool { sqrt_price_x96: U256::one() << 96, liquidity: U256::exp10(24), zero_for_one: true, fee: 500 };
+        let victim_in = U256::exp10(21);
+        let (quote, _) = pool.swap(victim_in, true).unwrap();
+        let min_out = quote * 99u64 / 100u64;
+        let plan = optimal_sandwich(&pool, victim_in, Some(min_out)).unwrap();
+        assert!(plan.victim_out >= min_out && plan.profit > U256::zero());
+        assert!(plan.profit >= brute_force(&pool, victim_in, Some(min_out), U256::exp10(19), 1000));
+    }
+
+    #[test]
+    fn min_out_scaled_to_hop() {
+        // Rota aceita 95 de 100 recebidos; o hop que entregou 40 tolera até 38
+        assert_eq!(scaled_min_out(40.into(), 95.into(), 100.into()), Some(38.into()));
+        assert_eq!(scaled_min_out(40.into(), 95.into(), U256::zero()), None);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::core::analyzer::analyze_transaction;
+use crate::types::{AnalysisResult, TransactionData};
+use anyhow::Result;
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use ethernity_core::traits::RpcProvider;
+use futures::{future, Future, Stream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Destino dos resultados de [`analyze_stream`] (ex.: um produtor Kafka)
+#[async_trait]
+pub trait VictimSink: Send + Sync {
+    /// Recebe o resultado de uma transação analisada, na ordem em que a análise termina
+    async fn send(&self, tx: &TransactionData, result: AnalysisResult) -> Result<()>;
+
+    /// Chamado quando a transação não pôde ser analisada (sem swap, router não suportado, ...)
+    async fn failed(&self, _tx: &TransactionData, _error: &anyhow::Error) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Configuração de [`analyze_stream`]
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Análises simultâneas
+    pub concurrency: usize,
+    /// Quantidade de transações recentes lembradas para descartar repetições
+    pub dedup_capacity: usize,
+    /// Bloco usado como base da simulação; `None` usa o mais recente
+    pub block: Option<u64>,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self { concurrency: 16, dedup_capacity: 10_000, block: None }
+    }
+}
+
+/// Contadores de uma execução de [`analyze_stream`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    pub received: u64,
+    pub duplicates: u64,
+    /// Resultados entregues ao sink
+    pub analyzed: u64,
+    pub victims: u64,
+    pub failed: u64,
+}
+
+/// Mapa limitado às últimas `capacity` chaves inseridas
+struct RecentMap<K, V> {
+    capacity: usize,
+    order: VecDeque<K>,
+    values: HashMap<K, V>,
+}
+
+impl<K: Eq + std::hash::Hash + Copy, V: PartialOrd> RecentMap<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), order: VecDeque::new(), values: HashMap::new() }
+    }
+
+    /// Registra o valor da chave; retorna `false` se ela já estava entre as
+    /// recentes com um valor igual ou maior
+    fn insert_if_greater(&mut self, key: K, value: V) -> bool {
+        match self.values.get_mut(&key) {
+            Some(current) if *current >= value => return false,
+            Some(current) => {
+                *current = value;
+                return true;
+            }
+            None => {}
+        }
+        self.values.insert(key, value);
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.values.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Analisa um stream de transações pendentes com o registro de detectores padrão.
+///
+/// Transações repetidas, identificadas por `(from, nonce)`, são descartadas;
+/// uma substituição com gas price maior é uma nova transação e é analisada de
+/// novo, pois é ela que tende a ser incluída. Até `concurrency` análises
+/// rodam ao mesmo tempo e cada resultado segue para o sink assim que fica
+/// pronto. O stream é consumido até o fim ou até o primeiro erro do sink.
+pub async fn analyze_stream<S>(
+    rpc_client: Arc<dyn RpcProvider>,
+    rpc_endpoint: String,
+    txs: S,
+    sink: Arc<dyn VictimSink>,
+    config: StreamConfig,
+) -> Result<StreamStats>
+where
+    S: Stream<Item = TransactionData> + Send,
+{
+    let block = config.block;
+    run_stream(txs, sink, &config, |tx| {
+        analyze_transaction(rpc_client.clone(), rpc_endpoint.clone(), tx, block)
+    })
+    .await
+}
+
+async fn run_stream<S, F, Fut>(txs: S, sink: Arc<dyn VictimSink>, config: &StreamConfig, analyze: F) -> Result<StreamStats>
+where
+    S: Stream<Item = TransactionData>,
+    F: Fn(TransactionData) -> Fut,
+    Fut: Future<Output = Result<AnalysisResult>>,
+{
+    let mut stats = StreamStats::default();
+    let mut recent = RecentMap::<(Address, U256), U256>::new(config.dedup_capacity);
+    let (mut received, mut duplicates) = (0u64, 0u64);
+    {
+        let analyses = txs
+            .filter(|tx| {
+                received += 1;
+                let fresh = recent.insert_if_greater((tx.from, tx.nonce), tx.gas_price);
+                if !fresh {
+                    duplicates += 1;
+                }
+                future::ready(fresh)
+            })
+            .map(|tx| {
+                let analysis = analyze(tx.clone());
+                async move { (tx, analysis.await) }
+            })
+            .buffer_unordered(config.concurrency.max(1));
+        let mut analyses = std::pin::pin!(analyses);
+        while let Some((tx, analysis)) = analyses.next().await {
+            match analysis {
+                Ok(result) => {
+                    stats.analyzed += 1;
+                    stats.victims += u64::from(result.potential_victim);
+                    sink.send(&tx, result).await?;
+                }
+                Err(err) => {
+                    stats.failed += 1;
+                    sink.failed(&tx, &err).await?;
+                }
+            }
+        }
+    }
+    stats.received = received;
+    stats.duplicates = duplicates;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::SwapFunction;
+    use crate::types::Metrics;
+    use anyhow::anyhow;
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CollectSink {
+        results: Mutex<Vec<U256>>,
+        failures: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl VictimSink for CollectSink {
+        async fn send(&self, tx: &TransactionData, _result: AnalysisResult) -> Result<()> {
+            self.results.lock().push(tx.nonce);
+            Ok(())
+        }
+
+        async fn failed(&self, _tx: &TransactionData, error: &anyhow::Error) -> Result<()> {
+            self.failures.lock().push(error.to_string());
+            Ok(())
+        }
+    }
+
+    fn tx(from: u64, nonce: u64) -> TransactionData {
+        TransactionData {
+            from: Address::from_low_u64_be(from),
+            to: Address::from_low_u64_be(99),
+            data: vec![],
+            value: U256::zero(),
+            gas: 0,
+            gas_price: U256::zero(),
+            nonce: nonce.into(),
+        }
+    }
+
+    fn result(victim: bool) -> AnalysisResult {
+        AnalysisResult {
+            potential_victim: victim,
+            economically_viable: false,
+            simulated_tx: None,
+            metrics: Metrics {
+                swap_function: SwapFunction::SwapExactTokensForTokens,
+                token_route: Vec::new(),
+                slippage: 0.0,
+                min_tokens_to_affect: U256::zero(),
+                potential_profit: U256::zero(),
+                router_address: Address::zero(),
+                router_name: None,
+                hops: Vec::new(),
+                fee_on_transfer: false,
+                gas_cost: None,
+                sandwich: None,
+            },
+        }
+    }
+
+    #[test]
+    fn recent_map_forgets_oldest() {
+        let mut recent = RecentMap::new(2);
+        assert!(recent.insert_if_greater(1, 0) && recent.insert_if_greater(2, 0));
+        assert!(!recent.insert_if_greater(1, 0));
+        assert!(recent.insert_if_greater(3, 0));
+        assert!(recent.insert_if_greater(1, 0));
+    }
+
+    #[test]
+    fn recent_map_accepts_higher_values() {
+        let mut recent = RecentMap::new(2);
+        assert!(recent.insert_if_greater(1, 10));
+        assert!(!recent.insert_if_greater(1, 9) && !recent.insert_if_greater(1, 10));
+        assert!(recent.insert_if_greater(1, 11));
+        assert!(!recent.insert_if_greater(1, 10));
+    }
+
+    #[tokio::test]
+    async fn stream_deduplicates_and_bounds_concurrency() {
+        let sink = Arc::new(CollectSink::default());
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        // Substituição de (1, 0) com gas price maior é analisada; a repetição com o mesmo gas price não
+        let replacement = TransactionData { gas_price: 5.into(), ..tx(1, 0) };
+        let txs = futures::stream::iter(vec![
+            tx(1, 0),
+            tx(1, 1),
+            tx(1, 0),
+            tx(2, 0),
+            replacement.clone(),
+            tx(1, 1),
+            replacement,
+            tx(3, 7),
+        ]);
+        let config = StreamConfig { concurrency: 2, ..Default::default() };
+
+        let stats = run_stream(txs, sink.clone(), &config, |tx| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if tx.nonce == 7.into() {
+                    Err(anyhow!("unsupported router"))
+                } else {
+                    Ok(result(tx.from == Address::from_low_u64_be(2)))
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(stats, StreamStats { received: 8, duplicates: 3, analyzed: 4, victims: 1, failed: 1 });
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(sink.results.lock().len(), 4);
+        assert_eq!(*sink.failures.lock(), vec!["unsupported router".to_string()]);
+    }
+}
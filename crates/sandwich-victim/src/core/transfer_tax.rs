@@ -0,0 +1,136 @@
+use crate::types::HopMetrics;
+use ethereum_types::{Address, H256, U256};
+use ethers::types::Log;
+use ethers::utils::keccak256;
+
+use super::metrics::U256Ext;
+
+/// Taxa de um token fee-on-transfer, medida pela diferença entre o que foi
+/// enviado e o que chegou ao destino durante a simulação
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransferTax {
+    pub token: Address,
+    /// Fração retida pelo token em cada transferência
+    pub rate: f64,
+}
+
+impl TransferTax {
+    /// `amount` depois de uma transferência tributada
+    pub fn apply(&self, amount: U256) -> U256 {
+        const SCALE: u64 = 1_000_000;
+        let kept = ((1.0 - self.rate).clamp(0.0, 1.0) * SCALE as f64) as u64;
+        amount * U256::from(kept) / U256::from(SCALE)
+    }
+}
+
+/// Soma dos eventos `Transfer` de `token` que satisfazem `filter(from, to)`
+pub(crate) fn transferred(logs: &[Log], token: Address, filter: impl Fn(Address, Address) -> bool) -> U256 {
+    let topic = H256::from_slice(&keccak256("Transfer(address,address,uint256)"));
+    logs.iter()
+        .filter(|log| log.address == token && log.topics.first() == Some(&topic) && log.topics.len() == 3 && log.data.len() >= 32)
+        .filter(|log| {
+            let from = Address::from_slice(&log.topics[1].as_bytes()[12..]);
+            let to = Address::from_slice(&log.topics[2].as_bytes()[12..]);
+            filter(from, to)
+        })
+        .fold(U256::zero(), |total, log| total.saturating_add(U256::from_big_endian(&log.data[..32])))
+}
+
+fn shortfall(token: Address, sent: U256, received: U256) -> Option<TransferTax> {
+    (received < sent && !received.is_zero())
+        .then(|| TransferTax { token, rate: (sent - received).to_f64_lossy() / sent.to_f64_lossy() })
+}
+
+/// Tokens da rota que cobram taxa na transferência, comparando:
+/// o que `sender` enviou com o que o primeiro pool recebeu, a saída de cada
+/// pool com a entrada do seguinte e a saída do último pool com o que chegou a
+/// `recipient`. Os hops precisam trazer a entrada efetivamente recebida pelo pool.
+pub fn transfer_taxes(logs: &[Log], hops: &[HopMetrics], sender: Address, recipient: Option<Address>) -> Vec<TransferTax> {
+    let (Some(first), Some(last)) = (hops.first(), hops.last()) else {
+        return Vec::new();
+    };
+    let mut taxes = Vec::new();
+    let sent = transferred(logs, first.token_in, |from, _| from == sender);
+    taxes.extend(shortfall(first.token_in, sent, first.amount_in));
+    for pair in hops.windows(2) {
+        if pair[0].token_out == pair[1].token_in {
+            taxes.extend(shortfall(pair[0].token_out, pair[0].actual_out, pair[1].amount_in));
+        }
+    }
+    if let Some(recipient) = recipient {
+        let received = transferred(logs, last.token_out, |_, to| to == recipient);
+        taxes.extend(shortfall(last.token_out, last.actual_out, received));
+    }
+    taxes.dedup_by_key(|tax| tax.token);
+    taxes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn transfer(token: Address, from: Address, to: Address, value: u64) -> Log {
+        let mut data = [0u8; 32];
+        U256::from(value).to_big_endian(&mut data);
+        Log {
+            address: token,
+            topics: vec![
+                H256::from_slice(&keccak256("Transfer(address,address,uint256)")),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: Bytes::from(data.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    fn hop(token_in: u64, token_out: u64, amount_in: u64, actual_out: u64) -> HopMetrics {
+        HopMetrics {
+            pool: addr(100 + token_in),
+            token_in: addr(token_in),
+            token_out: addr(token_out),
+            amount_in: amount_in.into(),
+            expected_out: actual_out.into(),
+            actual_out: actual_out.into(),
+            slippage: 0.0,
+        }
+    }
+
+    #[test]
+    fn taxes_from_simulation_deltas() {
+        let (user, pair_a, pair_b) = (addr(50), addr(101), addr(102));
+        // Token 2 retém 5% ao passar do primeiro para o segundo par
+        let logs = vec![
+            transfer(addr(1), user, pair_a, 1000),
+            transfer(addr(2), pair_a, pair_b, 950),
+            transfer(addr(2), pair_a, addr(99), 50),
+            transfer(addr(3), pair_b, user, 400),
+        ];
+        let hops = [hop(1, 2, 1000, 1000), hop(2, 3, 950, 400)];
+        let taxes = transfer_taxes(&logs, &hops, user, Some(user));
+        assert_eq!(taxes.len(), 1);
+        assert_eq!(taxes[0].token, addr(2));
+        assert!((taxes[0].rate - 0.05).abs() < 1e-9);
+        assert_eq!(taxes[0].apply(1000.into()), 950.into());
+
+        // Sem taxa quando o destino recebe tudo o que foi enviado
+        assert!(transfer_taxes(&logs, &hops[1..], user, Some(user)).is_empty());
+    }
+
+    #[test]
+    fn malformed_transfer_data_is_bounded() {
+        let (token, user) = (addr(1), addr(50));
+        let mut long = transfer(token, user, addr(101), 700);
+        long.data = Bytes::from([long.data.to_vec(), vec![0xff; 32]].concat());
+        let mut short = transfer(token, user, addr(101), 300);
+        short.data = Bytes::from(vec![0x01; 8]);
+
+        // Só a primeira palavra conta; dados curtos são ignorados
+        assert_eq!(transferred(&[long, short], token, |from, _| from == user), 700.into());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::filters::is_swap_log;
+use ethereum_types::{Address, H256, U256};
+use ethers::types::Log;
+use ethers::utils::keccak256;
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// ETH nativo em `Metrics::token_route`, igual à `Currency` nativa do Uniswap V4
+pub const NATIVE_TOKEN: Address = Address::zero();
+
+/// Token nativo embrulhado (WETH e equivalentes) das redes suportadas
+static WRAPPED_NATIVE_TOKENS: Lazy<HashSet<Address>> = Lazy::new(|| {
+    [
+        // Ethereum
+        "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        // Optimism e Base
+        "0x4200000000000000000000000000000000000006",
+        // Arbitrum
+        "0x82af49447d8a07e3bd95bd0d56f35241523fbab1",
+        // Polygon (WMATIC)
+        "0x0d500b1d8e8ef31e21c99d1db9a6444d3adf1270",
+        // BSC (WBNB)
+        "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c",
+        // Avalanche (WAVAX)
+        "0xb31f66aa3c1e785363f0875a1b74e27b85fd66c7",
+    ]
+    .into_iter()
+    .map(|s| Address::from_str(s).expect("valid address"))
+    .collect()
+});
+
+/// Verifica se `token` é o token nativo embrulhado de uma das redes suportadas
+pub fn is_wrapped_native(token: Address) -> bool {
+    WRAPPED_NATIVE_TOKENS.contains(&token)
+}
+
+/// ETH embrulhado ou desembrulhado no contrato WETH durante a transação
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WethLeg {
+    pub weth: Address,
+    pub amount: U256,
+}
+
+/// Pernas de WETH em torno dos swaps da simulação
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WethLegs {
+    /// `Deposit` antes do primeiro swap: a entrada foi paga em ETH nativo
+    pub wrap: Option<WethLeg>,
+    /// `Withdrawal` depois do último swap: a saída foi entregue em ETH nativo
+    pub unwrap: Option<WethLeg>,
+}
+
+/// Soma os eventos `signature` do WETH: o `weth` informado ou, sem ele, o
+/// primeiro token nativo embrulhado conhecido que os emitiu. Outros contratos
+/// com eventos de mesma assinatura são ignorados.
+fn leg(logs: &[Log], signature: &str, weth: Option<Address>) -> Option<WethLeg> {
+    let topic = H256::from_slice(&keccak256(signature));
+    let is_weth = |address: Address| weth.map_or_else(|| is_wrapped_native(address), |weth| address == weth);
+    let mut events = logs
+        .iter()
+        .filter(|log| log.topics.first() == Some(&topic) && log.data.len() >= 32 && is_weth(log.address));
+    let weth = events.next()?.address;
+    let amount = logs
+        .iter()
+        .filter(|log| log.address == weth && log.topics.first() == Some(&topic) && log.data.len() >= 32)
+        .fold(U256::zero(), |total, log| total.saturating_add(U256::from_big_endian(&log.data[..32])));
+    Some(WethLeg { weth, amount })
+}
+
+impl WethLegs {
+    /// Localiza os `Deposit`/`Withdrawal` do WETH anteriores ao primeiro e
+    /// posteriores ao último evento de swap; sem swaps nos logs, todos são
+    /// considerados. `weth` é o WETH do router, quando conhecido; sem ele só
+    /// os tokens nativos embrulhados das redes suportadas são aceitos.
+    pub fn from_logs(logs: &[Log], weth: Option<Address>) -> Self {
+        let first_swap = logs.iter().position(is_swap_log).unwrap_or(logs.len());
+        let after_last_swap = logs.iter().rposition(is_swap_log).map_or(0, |i| i + 1);
+        Self {
+            wrap: leg(&logs[..first_swap], "Deposit(address,uint256)", weth),
+            unwrap: leg(&logs[after_last_swap..], "Withdrawal(address,uint256)", weth),
+        }
+    }
+
+    /// Inclui as pernas nativas na rota: `NATIVE_TOKEN` antes do WETH
+    /// embrulhado e depois do desembrulhado, e o WETH quando a rota já
+    /// começa ou termina em `NATIVE_TOKEN`. Rotas que não passam pelo WETH
+    /// da perna ficam como estão.
+    pub fn expand_route(&self, route: &[Address]) -> Vec<Address> {
+        let mut route = route.to_vec();
+        if let Some(WethLeg { weth, .. }) = self.wrap {
+            match route.first() {
+                Some(&first) if first == NATIVE_TOKEN && route.get(1) != Some(&weth) => route.insert(1, weth),
+                Some(&first) if first == weth => route.insert(0, NATIVE_TOKEN),
+                _ => {}
+            }
+        }
+        if let Some(WethLeg { weth, .. }) = self.unwrap {
+            let len = route.len();
+            match route.last() {
+                Some(&last) if last == NATIVE_TOKEN && (len < 2 || route[len - 2] != weth) => {
+                    route.insert(len - 1, weth)
+                }
+                Some(&last) if last == weth => route.push(NATIVE_TOKEN),
+                _ => {}
+            }
+        }
+        route
+    }
+
+    /// Quantidade paga em ETH nativo quando a rota expandida começa no nativo
+    pub fn native_in(&self, route: &[Address]) -> Option<U256> {
+        self.wrap.filter(|_| route.first() == Some(&NATIVE_TOKEN)).map(|leg| leg.amount)
+    }
+
+    /// Quantidade recebida em ETH nativo quando a rota expandida termina no nativo
+    pub fn native_out(&self, route: &[Address]) -> Option<U256> {
+        self.unwrap.filter(|_| route.last() == Some(&NATIVE_TOKEN)).map(|leg| leg.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+    use ethers::types::Bytes;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn log(address: Address, signature: &str, value: u64) -> Log {
+        Log {
+            address,
+            topics: vec![H256::from_slice(&keccak256(signature)), H256::from(addr(60))],
+            data: Bytes::from(encode(&[Token::Uint(value.into())])),
+            ..Default::default()
+        }
+    }
+
+    fn swap(pair: Address) -> Log {
+        let signature = "Swap(address,uint256,uint256,uint256,uint256,address)";
+        Log { address: pair, topics: vec![H256::from_slice(&keccak256(signature))], ..Default::default() }
+    }
+
+    #[test]
+    fn legs_around_swaps() {
+        let weth = addr(10);
+        let logs = vec![
+            log(weth, "Deposit(address,uint256)", 1000),
+            swap(addr(20)),
+            // Withdrawal no meio da rota não é a saída da vítima
+            log(weth, "Withdrawal(address,uint256)", 5),
+            swap(addr(21)),
+            log(weth, "Withdrawal(address,uint256)", 900),
+        ];
+        let legs = WethLegs::from_logs(&logs, Some(weth));
+        assert_eq!(legs.wrap, Some(WethLeg { weth, amount: 1000.into() }));
+        assert_eq!(legs.unwrap, Some(WethLeg { weth, amount: 900.into() }));
+
+        let legs = WethLegs::from_logs(&logs[1..4], Some(weth));
+        assert_eq!(legs, WethLegs::default());
+    }
+
+    #[test]
+    fn legs_only_from_weth() {
+        let mainnet_weth = Address::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        // Um vault com evento `Deposit(address,uint256)` antes do WETH não é a perna nativa
+        let logs = vec![
+            log(addr(30), "Deposit(address,uint256)", 7),
+            log(mainnet_weth, "Deposit(address,uint256)", 1000),
+            swap(addr(20)),
+        ];
+        let legs = WethLegs::from_logs(&logs, None);
+        assert_eq!(legs.wrap, Some(WethLeg { weth: mainnet_weth, amount: 1000.into() }));
+        assert_eq!(WethLegs::from_logs(&logs, Some(addr(30))).wrap, Some(WethLeg { weth: addr(30), amount: 7.into() }));
+        assert_eq!(WethLegs::from_logs(&logs[..1], None), WethLegs::default());
+    }
+
+    #[test]
+    fn route_gains_native_legs() {
+        let weth = addr(10);
+        let leg = Some(WethLeg { weth, amount: 1.into() });
+        let wrap = WethLegs { wrap: leg, unwrap: None };
+        let unwrap = WethLegs { wrap: None, unwrap: leg };
+
+        assert_eq!(wrap.expand_route(&[weth, addr(2)]), vec![NATIVE_TOKEN, weth, addr(2)]);
+        assert_eq!(wrap.expand_route(&[NATIVE_TOKEN, addr(2)]), vec![NATIVE_TOKEN, weth, addr(2)]);
+        assert_eq!(wrap.expand_route(&[NATIVE_TOKEN, weth, addr(2)]), vec![NATIVE_TOKEN, weth, addr(2)]);
+        assert_eq!(wrap.expand_route(&[addr(1), addr(2)]), vec![addr(1), addr(2)]);
+
+        let route = unwrap.expand_route(&[addr(2), weth]);
+        assert_eq!(route, vec![addr(2), weth, NATIVE_TOKEN]);
+        assert_eq!(unwrap.native_out(&route), Some(1.into()));
+        assert_eq!(unwrap.expand_route(&[addr(2), NATIVE_TOKEN]), vec![addr(2), weth, NATIVE_TOKEN]);
+        assert_eq!(unwrap.native_out(&[addr(2), weth]), None);
+        assert_eq!(unwrap.native_in(&route), None);
+    }
+}
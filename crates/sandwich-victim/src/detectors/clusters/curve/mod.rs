@@ -0,0 +1,305 @@
+use crate::core::metrics::U256Ext;
+use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::simulation::SimulationOutcome;
+use crate::types::{AnalysisResult, Metrics, TransactionData};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use ethernity_core::traits::RpcProvider;
+use ethers::abi::{AbiParser, Token};
+use ethers::prelude::{Http, Middleware, Provider, TransactionRequest};
+use ethers::types::BlockId;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Detector para swaps diretos em pools da Curve (StableSwap, crypto e meta pools).
+///
+/// A saída esperada vem de `get_dy`/`get_dy_underlying` no bloco da simulação,
+/// que já aplica o invariante do próprio pool, e a saída real do evento
+/// `TokenExchange`/`TokenExchangeUnderlying` emitido pelo pool.
+pub struct CurveDetector;
+
+#[async_trait]
+impl crate::detectors::VictimDetector for CurveDetector {
+    fn supports(&self, _router: &RouterInfo) -> bool {
+        true
+    }
+
+    async fn analyze(
+        &self,
+        rpc_client: Arc<dyn RpcProvider>,
+        rpc_endpoint: String,
+        tx: TransactionData,
+        block: Option<u64>,
+        outcome: SimulationOutcome,
+        router: RouterInfo,
+    ) -> Result<AnalysisResult> {
+        analyze_curve(rpc_client, rpc_endpoint, tx, block, outcome, router).await
+    }
+}
+
+/// Parâmetros de uma chamada `exchange`/`exchange_underlying`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurveExchange {
+    pub underlying: bool,
+    /// Índices codificados como `int128` (StableSwap) em vez de `uint256` (crypto pools)
+    pub signed_indices: bool,
+    pub i: u64,
+    pub j: u64,
+    pub dx: U256,
+    pub min_dy: U256,
+    /// Destinatário explícito das variantes de factory pools
+    pub receiver: Option<Address>,
+}
+
+impl CurveExchange {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (func, f) = detect_swap_function(data)?;
+        let underlying = match func {
+            SwapFunction::CurveExchange => false,
+            SwapFunction::CurveExchangeUnderlying => true,
+            _ => return None,
+        };
+        let tokens = f.decode_input(&data[4..]).ok()?;
+        let index = |token: &Token| match token {
+            Token::Int(v) | Token::Uint(v) if *v <= U256::from(u64::MAX) => Some(v.low_u64()),
+            _ => None,
+        };
+        Some(Self {
+            underlying,
+            signed_indices: matches!(tokens.first()?, Token::Int(_)),
+            i: index(tokens.first()?)?,
+            j: index(tokens.get(1)?)?,
+            dx: tokens.get(2)?.clone().into_uint()?,
+            min_dy: tokens.get(3)?.clone().into_uint()?,
+            receiver: tokens.get(4).and_then(|t| t.clone().into_address()),
+        })
+    }
+
+    fn index_type(&self) -> &'static str {
+        if self.signed_indices {
+            "int128"
+        } else {
+            "uint256"
+        }
+    }
+
+    fn index_token(&self, index: u64) -> Token {
+        if self.signed_indices {
+            Token::Int(index.into())
+        } else {
+            Token::Uint(index.into())
+        }
+    }
+
+    /// Cotação correspondente à chamada (`get_dy` ou `get_dy_underlying`)
+    fn quote_signature(&self) -> String {
+        let name = if self.underlying { "get_dy_underlying" } else { "get_dy" };
+        format!("{name}({t},{t},uint256) view returns (uint256)", t = self.index_type())
+    }
+}
+
+pub async fn analyze_curve(
+    rpc_client: Arc<dyn RpcProvider>,
+    rpc_endpoint: String,
+    tx: TransactionData,
+    block: Option<u64>,
+    outcome: SimulationOutcome,
+    router: RouterInfo,
+) -> Result<AnalysisResult> {
+    let exchange = CurveExchange::decode(&tx.data).ok_or_else(|| anyhow!("not a curve exchange"))?;
+    let pool = tx.to;
+    let provider = Provider::<Http>::try_from(rpc_endpoint)?.interval(Duration::from_millis(1));
+    let call_block = block.map(|b| BlockId::Number(b.into()));
+
+    let quote = AbiParser::default().parse_function(&exchange.quote_signature())?;
+    let data = quote.encode_input(&[
+        exchange.index_token(exchange.i),
+        exchange.index_token(exchange.j),
+        Token::Uint(exchange.dx),
+    ])?;
+    let tx_call = TransactionRequest::new().to(pool).data(data);
+    let out = provider
+        .call(&tx_call.into(), call_block)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let expected_out = quote
+        .decode_output(&out)?
+        .first()
+        .and_then(|t| t.clone().into_uint())
+        .ok_or_else(|| anyhow!("get_dy decode"))?;
+
+    let actual_out = exchanged_amount(&outcome, pool).ok_or_else(|| anyhow!("no curve exchange event"))?;
+    let slippage = if expected_out > actual_out && !expected_out.is_zero() {
+        (expected_out - actual_out).to_f64_lossy() / expected_out.to_f64_lossy()
+    } else {
+        0.0
+    };
+
+    let token_in = coin_address(&*rpc_client, pool, exchange.i, exchange.underlying).await?;
+    let token_out = coin_address(&*rpc_client, pool, exchange.j, exchange.underlying).await?;
+
+    // Saldo da moeda de entrada no pool; trocas underlying não têm saldo direto no pool
+    let min_tokens_to_affect = if exchange.underlying {
+        U256::zero()
+    } else {
+        let balances = AbiParser::default()
+            .parse_function(&format!("balances({}) view returns (uint256)", exchange.index_type()))?;
+        let tx_call = TransactionRequest::new()
+            .to(pool)
+            .data(balances.encode_input(&[exchange.index_token(exchange.i)])?);
+        match provider.call(&tx_call.into(), call_block).await {
+            Ok(out) => balances
+                .decode_output(&out)?
+                .first()
+                .and_then(|t| t.clone().into_uint())
+                .unwrap_or_default()
+                / U256::from(100u64),
+            Err(_) => U256::zero(),
+        }
+    };
+
+    let swap_function = if exchange.underlying {
+        SwapFunction::CurveExchangeUnderlying
+    } else {
+        SwapFunction::CurveExchange
+    };
+    let metrics = Metrics {
+        swap_function,
+        token_route: vec![token_in, token_out],
+        slippage,
+        min_tokens_to_affect,
+        potential_profit: U256::zero(),
+        router_address: router.address,
+        router_name: Some(router.name.clone().unwrap_or_else(|| format!("{:#x}", router.address))),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: slippage > 0.0 && expected_out >= exchange.min_dy,
+        economically_viable: false,
+        simulated_tx: outcome.tx_hash,
+        metrics,
+    })
+}
+
+/// `tokens_bought` do evento de troca emitido pelo pool
+fn exchanged_amount(outcome: &SimulationOutcome, pool: Address) -> Option<U256> {
+    outcome
+        .decoded_logs()
+        .into_iter()
+        .filter(|log| log.address == pool)
+        .filter(|log| log.event == "TokenExchange" || log.event == "TokenExchangeUnderlying")
+        .find_map(|log| {
+            let (_, amount) = log.params.into_iter().find(|(name, _)| name == "tokens_bought")?;
+            amount.into_uint()
+        })
+}
+
+/// Endereço da moeda `index` do pool.
+///
+/// Em trocas underlying, pools de lending expõem `underlying_coins`; meta
+/// pools usam a moeda 0 do próprio pool e, a partir do índice 1, as moedas do
+/// `base_pool`.
+async fn coin_address(provider: &dyn RpcProvider, pool: Address, index: u64, underlying: bool) -> Result<Address> {
+    if underlying {
+        if let Ok(coin) = call_coin(provider, pool, "underlying_coins", index).await {
+            return Ok(coin);
+        }
+        if index > 0 {
+            if let Ok(base_pool) = call_address(provider, pool, "base_pool() view returns (address)", &[]).await {
+                return call_coin(provider, base_pool, "coins", index - 1).await;
+            }
+        }
+    }
+    call_coin(provider, pool, "coins", index).await
+}
+
+async fn call_coin(provider: &dyn RpcProvider, pool: Address, method: &str, index: u64) -> Result<Address> {
+    // Pools antigos indexam as moedas com int128, os mais novos com uint256
+    for (index_type, token) in [("uint256", Token::Uint(index.into())), ("int128", Token::Int(index.into()))] {
+        let signature = format!("{method}({index_type}) view returns (address)");
+        if let Ok(coin) = call_address(provider, pool, &signature, &[token]).await {
+            return Ok(coin);
+        }
+    }
+    Err(anyhow!("{method}({index}) unavailable"))
+}
+
+async fn call_address(provider: &dyn RpcProvider, to: Address, signature: &str, args: &[Token]) -> Result<Address> {
+    let f = AbiParser::default().parse_function(signature)?;
+    let out = provider
+        .call(to, f.encode_input(args)?)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    f.decode_output(&out)?
+        .first()
+        .and_then(|t| t.clone().into_address())
+        .ok_or_else(|| anyhow!("{signature} decode"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::types::{Bytes, Log, H256};
+    use ethers::utils::{id, keccak256};
+
+    fn calldata(signature: &str, tokens: &[Token]) -> Vec<u8> {
+        [id(signature).to_vec(), encode(tokens)].concat()
+    }
+
+    #[test]
+    fn decode_exchange_variants() {
+        let data = calldata(
+            "exchange(int128,int128,uint256,uint256)",
+            &[Token::Int(0.into()), Token::Int(2.into()), Token::Uint(1000.into()), Token::Uint(990.into())],
+        );
+        let exchange = CurveExchange::decode(&data).unwrap();
+        assert!(!exchange.underlying && exchange.signed_indices);
+        assert_eq!((exchange.i, exchange.j, exchange.dx, exchange.min_dy), (0, 2, 1000.into(), 990.into()));
+        assert_eq!(exchange.quote_signature(), "get_dy(int128,int128,uint256) view returns (uint256)");
+
+        let receiver = Address::from_low_u64_be(7);
+        let data = calldata(
+            "exchange_underlying(int128,int128,uint256,uint256,address)",
+            &[Token::Int(1.into()), Token::Int(3.into()), Token::Uint(5.into()), Token::Uint(4.into()), Token::Address(receiver)],
+        );
+        let exchange = CurveExchange::decode(&data).unwrap();
+        assert!(exchange.underlying);
+        assert_eq!(exchange.receiver, Some(receiver));
+        assert_eq!(exchange.quote_signature(), "get_dy_underlying(int128,int128,uint256) view returns (uint256)");
+
+        let data = calldata(
+            "exchange(uint256,uint256,uint256,uint256)",
+            &[Token::Uint(1.into()), Token::Uint(0.into()), Token::Uint(5.into()), Token::Uint(4.into())],
+        );
+        assert!(!CurveExchange::decode(&data).unwrap().signed_indices);
+
+        // Índice negativo não é uma moeda válida
+        let data = calldata(
+            "exchange(int128,int128,uint256,uint256)",
+            &[Token::Int(U256::MAX), Token::Int(0.into()), Token::Uint(5.into()), Token::Uint(4.into())],
+        );
+        assert!(CurveExchange::decode(&data).is_none());
+    }
+
+    #[test]
+    fn exchanged_amount_from_pool_event() {
+        let pool = Address::from_low_u64_be(9);
+        let topic = H256::from_slice(keccak256("TokenExchange(address,int128,uint256,int128,uint256)").as_slice());
+        let event = |address| Log {
+            address,
+            topics: vec![topic, H256::from_low_u64_be(1)],
+            data: Bytes::from(encode(&[Token::Int(0.into()), Token::Uint(1000.into()), Token::Int(1.into()), Token::Uint(997.into())])),
+            ..Default::default()
+        };
+        let outcome = SimulationOutcome { tx_hash: None, logs: vec![event(Address::from_low_u64_be(8)), event(pool)] };
+
+        assert_eq!(exchanged_amount(&outcome, pool), Some(U256::from(997u64)));
+        assert_eq!(exchanged_amount(&outcome, Address::from_low_u64_be(5)), None);
+    }
+}
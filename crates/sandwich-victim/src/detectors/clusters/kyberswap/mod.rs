@@ -0,0 +1,340 @@
+use crate::core::metrics::U256Ext;
+use crate::core::transfer_tax::transferred;
+use crate::core::weth::{WethLegs, NATIVE_TOKEN};
+use crate::detectors::clusters::uniswap_v2::router_weth;
+use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::simulation::SimulationOutcome;
+use crate::types::{AnalysisResult, Metrics, TransactionData};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use ethernity_core::traits::RpcProvider;
+use ethers::abi::{AbiParser, ParamType, Token};
+use ethers::types::Log;
+use std::sync::Arc;
+
+/// Detector para o MetaAggregationRouterV2 da KyberSwap.
+///
+/// `swap`/`swapGeneric` e `swapSimpleMode` carregam a `SwapDescriptionV2` com
+/// os tokens de origem e destino, a quantidade e `minReturnAmount`; a rota em
+/// si fica no calldata do executor. Como a rota pode passar por qualquer DEX,
+/// o slippage reportado é a tolerância aceita pela vítima: a diferença entre o
+/// que chegou a `dstReceiver` na simulação e `minReturnAmount`.
+pub struct KyberSwapDetector;
+
+#[async_trait]
+impl crate::detectors::VictimDetector for KyberSwapDetector {
+    fn supports(&self, _router: &RouterInfo) -> bool {
+        true
+    }
+
+    async fn analyze(
+        &self,
+        rpc_client: Arc<dyn RpcProvider>,
+        _rpc_endpoint: String,
+        tx: TransactionData,
+        _block: Option<u64>,
+        outcome: SimulationOutcome,
+        router: RouterInfo,
+    ) -> Result<AnalysisResult> {
+        analyze_kyberswap(rpc_client, tx, outcome, router).await
+    }
+}
+
+/// Endereço usado pela KyberSwap para o token nativo (`0xEeee...EEeE`)
+pub fn is_native_token(token: Address) -> bool {
+    token == Address::repeat_byte(0xee)
+}
+
+/// `SwapDescriptionV2` do router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KyberSwapDescription {
+    pub src_token: Address,
+    pub dst_token: Address,
+    /// Destinos da entrada (executor ou primeiros pools) e as quantidades de cada um
+    pub src_receivers: Vec<Address>,
+    pub src_amounts: Vec<U256>,
+    pub dst_receiver: Address,
+    pub amount: U256,
+    pub min_return_amount: U256,
+    pub flags: U256,
+    /// Permit do token de entrada executado pelo router antes de puxar os fundos
+    pub permit: Vec<u8>,
+}
+
+impl KyberSwapDescription {
+    fn from_token(token: &Token) -> Option<Self> {
+        let fields = token.clone().into_tuple()?;
+        let address = |i: usize| fields.get(i)?.clone().into_address();
+        let uint = |i: usize| fields.get(i)?.clone().into_uint();
+        let array = |i: usize| fields.get(i)?.clone().into_array();
+        Some(Self {
+            src_token: address(0)?,
+            dst_token: address(1)?,
+            src_receivers: array(2)?.into_iter().filter_map(Token::into_address).collect(),
+            src_amounts: array(3)?.into_iter().filter_map(Token::into_uint).collect(),
+            dst_receiver: address(6)?,
+            amount: uint(7)?,
+            min_return_amount: uint(8)?,
+            flags: uint(9)?,
+            permit: fields.get(10)?.clone().into_bytes()?,
+        })
+    }
+
+    /// Destinatário efetivo: o router usa `msg.sender` quando `dstReceiver` é zero
+    pub fn recipient(&self, sender: Address) -> Address {
+        if self.dst_receiver.is_zero() {
+            sender
+        } else {
+            self.dst_receiver
+        }
+    }
+}
+
+/// Chamada `swap`/`swapSimpleMode` decodificada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KyberSwapCall {
+    pub function: SwapFunction,
+    /// Executor da rota (`callTarget` ou `caller`)
+    pub executor: Address,
+    pub desc: KyberSwapDescription,
+    /// Calldata repassado ao executor (`targetData` ou `executorData`)
+    pub executor_data: Vec<u8>,
+    /// Primeiro pool de cada sequência, do `SimpleSwapData` do modo simples
+    pub first_pools: Vec<Address>,
+    pub first_swap_amounts: Vec<U256>,
+}
+
+impl KyberSwapCall {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (function, f) = detect_swap_function(data)?;
+        let tokens = f.decode_input(&data[4..]).ok()?;
+        match function {
+            SwapFunction::KyberSwap => {
+                let execution = tokens.first()?.clone().into_tuple()?;
+                Some(Self {
+                    function,
+                    executor: execution.first()?.clone().into_address()?,
+                    desc: KyberSwapDescription::from_token(execution.get(3)?)?,
+                    executor_data: execution.get(2)?.clone().into_bytes()?,
+                    first_pools: Vec::new(),
+                    first_swap_amounts: Vec::new(),
+                })
+            }
+            SwapFunction::KyberSwapSimpleMode => {
+                let executor_data = tokens.get(2)?.clone().into_bytes()?;
+                let (first_pools, first_swap_amounts) = decode_simple_swap_data(&executor_data).unwrap_or_default();
+                Some(Self {
+                    function,
+                    executor: tokens.first()?.clone().into_address()?,
+                    desc: KyberSwapDescription::from_token(tokens.get(1)?)?,
+                    executor_data,
+                    first_pools,
+                    first_swap_amounts,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `firstPools` e `firstSwapAmounts` de
+/// `SimpleSwapData(address[],uint256[],bytes[],uint256,bytes)`
+pub fn decode_simple_swap_data(data: &[u8]) -> Option<(Vec<Address>, Vec<U256>)> {
+    let tuple = ParamType::Tuple(vec![
+        ParamType::Array(Box::new(ParamType::Address)),
+        ParamType::Array(Box::new(ParamType::Uint(256))),
+        ParamType::Array(Box::new(ParamType::Bytes)),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]);
+    let fields = ethers::abi::decode(&[tuple], data).ok()?.pop()?.into_tuple()?;
+    let pools = fields.first()?.clone().into_array()?.into_iter().filter_map(Token::into_address).collect();
+    let amounts = fields.get(1)?.clone().into_array()?.into_iter().filter_map(Token::into_uint).collect();
+    Some((pools, amounts))
+}
+
+/// Quantidade de `dst_token` entregue a `recipient` na simulação.
+///
+/// Saídas em token nativo não geram `Transfer`; nesse caso usa os eventos
+/// `Withdrawal` do WETH (o do router, quando conhecido) desembrulhado depois
+/// do último swap.
+fn received_amount(logs: &[Log], desc: &KyberSwapDescription, recipient: Address, weth: Option<Address>) -> Option<U256> {
+    let received = if is_native_token(desc.dst_token) {
+        WethLegs::from_logs(logs, weth).unwrap.map_or(U256::zero(), |leg| leg.amount)
+    } else {
+        transferred(logs, desc.dst_token, |_, to| to == recipient)
+    };
+    (!received.is_zero()).then_some(received)
+}
+
+pub async fn analyze_kyberswap(
+    rpc_client: Arc<dyn RpcProvider>,
+    tx: TransactionData,
+    outcome: SimulationOutcome,
+    router: RouterInfo,
+) -> Result<AnalysisResult> {
+    let call = KyberSwapCall::decode(&tx.data).ok_or_else(|| anyhow!("not a kyberswap swap"))?;
+    let desc = &call.desc;
+    let weth = router_weth(&*rpc_client, router.address).await;
+    let actual_out = received_amount(&outcome.logs, desc, desc.recipient(tx.from), weth)
+        .ok_or_else(|| anyhow!("no kyberswap output transfer"))?;
+
+    let slippage = if actual_out > desc.min_return_amount {
+        (actual_out - desc.min_return_amount).to_f64_lossy() / actual_out.to_f64_lossy()
+    } else {
+        0.0
+    };
+
+    let legs = WethLegs::from_logs(&outcome.logs, weth);
+    // ETH nativo aparece como `NATIVE_TOKEN` na rota, seguido do WETH quando o executor o embrulha
+    let route_token = |token: Address| if is_native_token(token) { NATIVE_TOKEN } else { token };
+    let token_route = legs.expand_route(&[route_token(desc.src_token), route_token(desc.dst_token)]);
+    // Entrada em ETH nativo chega aos pools como o WETH embrulhado
+    let pool_token = match legs.wrap {
+        Some(leg) if is_native_token(desc.src_token) => Some(leg.weth),
+        _ => Some(desc.src_token).filter(|token| !is_native_token(*token)),
+    };
+
+    // Saldo do token de entrada no primeiro pool (ou no primeiro destino da entrada)
+    let first_pool = call.first_pools.first().or(desc.src_receivers.first()).copied();
+    let min_tokens_to_affect = match (first_pool, pool_token) {
+        (Some(pool), Some(token)) => {
+            let balance_of = AbiParser::default().parse_function("balanceOf(address) view returns (uint256)")?;
+            let data = balance_of.encode_input(&[Token::Address(pool)])?;
+            match rpc_client.call(token, data).await {
+                Ok(out) => balance_of
+                    .decode_output(&out)?
+                    .first()
+                    .and_then(|t| t.clone().into_uint())
+                    .unwrap_or_default()
+                    / U256::from(100u64),
+                Err(_) => U256::zero(),
+            }
+        }
+        _ => U256::zero(),
+    };
+
+    let metrics = Metrics {
+        swap_function: call.function.clone(),
+        token_route,
+        slippage,
+        min_tokens_to_affect,
+        potential_profit: U256::zero(),
+        router_address: router.address,
+        router_name: Some(router.name.clone().unwrap_or_else(|| format!("{:#x}", router.address))),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: slippage > 0.0,
+        economically_viable: false,
+        simulated_tx: outcome.tx_hash,
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::H256;
+    use ethers::abi::encode;
+    use ethers::types::Bytes;
+    use ethers::utils::{id, keccak256};
+
+    const DESC: &str = "(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes)";
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn description(dst_token: Address, dst_receiver: Address) -> Token {
+        Token::Tuple(vec![
+            Token::Address(addr(1)),
+            Token::Address(dst_token),
+            Token::Array(vec![Token::Address(addr(10))]),
+            Token::Array(vec![Token::Uint(1000.into())]),
+            Token::Array(vec![]),
+            Token::Array(vec![]),
+            Token::Address(dst_receiver),
+            Token::Uint(1000.into()),
+            Token::Uint(950.into()),
+            Token::Uint(0x20.into()),
+            Token::Bytes(vec![]),
+        ])
+    }
+
+    fn log(address: Address, signature: &str, topics: &[Address], value: u64) -> Log {
+        let mut all = vec![H256::from_slice(&keccak256(signature))];
+        all.extend(topics.iter().map(|a| H256::from(*a)));
+        Log { address, topics: all, data: Bytes::from(encode(&[Token::Uint(value.into())])), ..Default::default() }
+    }
+
+    #[test]
+    fn decode_swap_and_simple_mode() {
+        let executor = addr(7);
+        let target_data = vec![0xde, 0xad];
+        let execution = Token::Tuple(vec![
+            Token::Address(executor),
+            Token::Address(addr(8)),
+            Token::Bytes(target_data.clone()),
+            description(addr(2), Address::zero()),
+            Token::Bytes(vec![]),
+        ]);
+        let data = [id(format!("swap((address,address,bytes,{DESC},bytes))")).to_vec(), encode(&[execution])].concat();
+        let call = KyberSwapCall::decode(&data).unwrap();
+        assert_eq!(call.function, SwapFunction::KyberSwap);
+        assert_eq!((call.executor, call.executor_data), (executor, target_data));
+        assert_eq!((call.desc.src_token, call.desc.dst_token), (addr(1), addr(2)));
+        assert_eq!((call.desc.amount, call.desc.min_return_amount), (1000.into(), 950.into()));
+        assert_eq!(call.desc.src_receivers, vec![addr(10)]);
+        assert_eq!(call.desc.recipient(addr(50)), addr(50));
+
+        let simple = Token::Tuple(vec![
+            Token::Array(vec![Token::Address(addr(20)), Token::Address(addr(21))]),
+            Token::Array(vec![Token::Uint(600.into()), Token::Uint(400.into())]),
+            Token::Array(vec![Token::Bytes(vec![1]), Token::Bytes(vec![2])]),
+            Token::Uint(u64::MAX.into()),
+            Token::Bytes(vec![]),
+        ]);
+        let data = [
+            id(format!("swapSimpleMode(address,{DESC},bytes,bytes)")).to_vec(),
+            encode(&[
+                Token::Address(executor),
+                description(Address::repeat_byte(0xee), addr(51)),
+                Token::Bytes(encode(&[simple])),
+                Token::Bytes(vec![]),
+            ]),
+        ]
+        .concat();
+        let call = KyberSwapCall::decode(&data).unwrap();
+        assert_eq!(call.function, SwapFunction::KyberSwapSimpleMode);
+        assert_eq!(call.first_pools, vec![addr(20), addr(21)]);
+        assert_eq!(call.first_swap_amounts, vec![U256::from(600u64), U256::from(400u64)]);
+        assert!(is_native_token(call.desc.dst_token));
+        assert_eq!(call.desc.recipient(addr(50)), addr(51));
+    }
+
+    #[test]
+    fn received_amount_from_transfers_or_withdrawals() {
+        let user = addr(50);
+        let token = addr(2);
+        let desc = KyberSwapDescription::from_token(&description(token, user)).unwrap();
+        let logs = vec![
+            log(token, "Transfer(address,address,uint256)", &[addr(30), addr(7)], 990),
+            log(token, "Transfer(address,address,uint256)", &[addr(7), user], 980),
+        ];
+        assert_eq!(received_amount(&logs, &desc, user, None), Some(980.into()));
+        assert_eq!(received_amount(&logs, &desc, addr(51), None), None);
+
+        let desc = KyberSwapDescription::from_token(&description(Address::repeat_byte(0xee), user)).unwrap();
+        let logs = vec![log(addr(3), "Withdrawal(address,uint256)", &[addr(7)], 975)];
+        assert_eq!(received_amount(&logs, &desc, user, Some(addr(3))), Some(975.into()));
+        // `Withdrawal` de outro contrato não é a saída nativa
+        assert_eq!(received_amount(&logs, &desc, user, Some(addr(4))), None);
+        assert_eq!(received_amount(&logs, &desc, user, None), None);
+    }
+}
@@ -1,10 +1,14 @@
+pub mod curve;
+pub mod kyberswap;
 pub mod oneinch_aggregation_router_v6;
 pub mod oneinch_generic_router;
+pub mod pancake_v3_algebra;
 pub mod smart_router;
 pub mod uniswap_universal_router;
 pub mod uniswap_v2;
 pub mod uniswap_v3;
 pub mod uniswap_v4;
+pub mod uniswapx;
 
 /// Agrupamento semântico das implementações de detectores.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +18,10 @@ pub enum Cluster {
     UniswapV4,
     SmartRouter,
     UniswapUniversalRouter,
+    PancakeV3Algebra,
+    UniswapX,
+    Curve,
+    KyberSwap,
     Unknown,
 }
 use crate::dex::SwapFunction;
@@ -40,6 +48,12 @@ impl From<&SwapFunction> for Cluster {
             SwapFunction::UniversalRouterSwap | SwapFunction::UniversalRouterSwapDeadline => {
                 Cluster::UniswapUniversalRouter
             }
+            SwapFunction::PancakeV3ExactInputSingle
+            | SwapFunction::PancakeV3ExactInput
+            | SwapFunction::AlgebraExactInputSingle => Cluster::PancakeV3Algebra,
+            SwapFunction::UniswapXExecute | SwapFunction::UniswapXExecuteBatch => Cluster::UniswapX,
+            SwapFunction::CurveExchange | SwapFunction::CurveExchangeUnderlying => Cluster::Curve,
+            SwapFunction::KyberSwap | SwapFunction::KyberSwapSimpleMode => Cluster::KyberSwap,
             SwapFunction::AggregationRouterV6Swap => Cluster::Unknown,
         }
     }
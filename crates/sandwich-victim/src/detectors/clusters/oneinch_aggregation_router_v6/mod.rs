@@ -115,6 +115,10 @@ pub async fn analyze_oneinch_aggregation_router_v6(
         potential_profit: U256::zero(),
         router_address: router.address,
         router_name: None,
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
     };
 
     Ok(AnalysisResult {
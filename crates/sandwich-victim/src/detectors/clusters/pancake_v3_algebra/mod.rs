@@ -0,0 +1,279 @@
+use super::uniswap_v3::{decode_fee_path, multicall_calls};
+use crate::core::metrics::U256Ext;
+use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::filters::CONCENTRATED_SWAP_TOPICS;
+use crate::simulation::SimulationOutcome;
+use crate::types::{AnalysisResult, Metrics, TransactionData};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethereum_types::{Address, H256, U256};
+use ethernity_core::traits::RpcProvider;
+use ethers::abi::{AbiParser, Token};
+use std::sync::Arc;
+
+/// Detector para o SmartRouter da PancakeSwap V3 e routers baseados em Algebra
+/// (QuickSwap V3, Camelot V3).
+///
+/// Pancake V3 usa `exactInputSingle`/`exactInput` sem `deadline` na struct,
+/// enquanto Algebra não tem `fee` (a taxa é dinâmica no pool) e codifica o
+/// path apenas com endereços de 20 bytes. Como não há um quoter comum às
+/// redes, o slippage reportado é a tolerância aceita pela vítima: a diferença
+/// entre a saída simulada e `amountOutMinimum`.
+pub struct PancakeV3AlgebraDetector;
+
+#[async_trait]
+impl crate::detectors::VictimDetector for PancakeV3AlgebraDetector {
+    fn supports(&self, _router: &RouterInfo) -> bool {
+        true
+    }
+
+    async fn analyze(
+        &self,
+        rpc_client: Arc<dyn RpcProvider>,
+        _rpc_endpoint: String,
+        tx: TransactionData,
+        _block: Option<u64>,
+        outcome: SimulationOutcome,
+        router: RouterInfo,
+    ) -> Result<AnalysisResult> {
+        analyze_pancake_v3_algebra(rpc_client, tx, outcome, router).await
+    }
+}
+
+/// Swap de entrada exata decodificado do calldata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactInputSwap {
+    pub function: SwapFunction,
+    pub route: Vec<Address>,
+    /// Taxas de cada hop em centésimos de bip; vazio em pools Algebra
+    pub fees: Vec<u32>,
+    pub recipient: Address,
+    pub amount_in: U256,
+    pub amount_out_min: U256,
+}
+
+impl ExactInputSwap {
+    /// Decodifica a chamada, procurando dentro de `multicall` quando necessário
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if let Some(calls) = multicall_calls(data) {
+            return calls.iter().find_map(|call| Self::decode(call));
+        }
+        let (function, f) = detect_swap_function(data)?;
+        let tokens = f.decode_input(&data[4..]).ok()?;
+        let params = tokens.first()?.clone().into_tuple()?;
+        let address = |i: usize| params.get(i)?.clone().into_address();
+        let uint = |i: usize| params.get(i)?.clone().into_uint();
+        match function {
+            SwapFunction::PancakeV3ExactInputSingle => Some(Self {
+                route: vec![address(0)?, address(1)?],
+                fees: vec![uint(2)?.low_u32()],
+                recipient: address(3)?,
+                amount_in: uint(4)?,
+                amount_out_min: uint(5)?,
+                function,
+            }),
+            SwapFunction::PancakeV3ExactInput => {
+                let (route, fees) = decode_fee_path(&params.first()?.clone().into_bytes()?)?;
+                Some(Self { route, fees, recipient: address(1)?, amount_in: uint(2)?, amount_out_min: uint(3)?, function })
+            }
+            SwapFunction::AlgebraExactInputSingle => Some(Self {
+                route: vec![address(0)?, address(1)?],
+                fees: Vec::new(),
+                recipient: address(2)?,
+                amount_in: uint(4)?,
+                amount_out_min: uint(5)?,
+                function,
+            }),
+            // Mesmo seletor do Uniswap V3; só o formato do path identifica o Algebra
+            SwapFunction::ExactInput => {
+                let route = decode_algebra_path(&params.first()?.clone().into_bytes()?)?;
+                Some(Self { route, fees: Vec::new(), recipient: address(1)?, amount_in: uint(3)?, amount_out_min: uint(4)?, function })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Path do Algebra, apenas com endereços; rejeita paths que também seriam válidos com taxas
+pub fn decode_algebra_path(path: &[u8]) -> Option<Vec<Address>> {
+    if path.len() < 40 || !path.len().is_multiple_of(20) || (path.len() - 20).is_multiple_of(23) {
+        return None;
+    }
+    Some(path.chunks(20).map(Address::from_slice).collect())
+}
+
+/// Pools e quantidades de saída dos eventos `Swap` de liquidez concentrada, na ordem dos logs
+fn pool_swaps(outcome: &SimulationOutcome) -> Vec<(Address, U256)> {
+    let topics: Vec<H256> = CONCENTRATED_SWAP_TOPICS
+        .iter()
+        .map(|t| t.parse().expect("valid topic hex"))
+        .collect();
+    outcome
+        .logs
+        .iter()
+        .filter(|log| log.topics.first().is_some_and(|t| topics.contains(t)) && log.data.len() >= 64)
+        .filter_map(|log| {
+            // amount0 e amount1 são int256; o valor negativo é o que saiu do pool
+            let amount0 = U256::from_big_endian(&log.data[..32]);
+            let amount1 = U256::from_big_endian(&log.data[32..64]);
+            let out = negative_abs(amount0).or_else(|| negative_abs(amount1))?;
+            Some((log.address, out))
+        })
+        .collect()
+}
+
+fn negative_abs(value: U256) -> Option<U256> {
+    value.bit(255).then(|| (!value).overflowing_add(U256::one()).0)
+}
+
+pub async fn analyze_pancake_v3_algebra(
+    rpc_client: Arc<dyn RpcProvider>,
+    tx: TransactionData,
+    outcome: SimulationOutcome,
+    router: RouterInfo,
+) -> Result<AnalysisResult> {
+    let swap = ExactInputSwap::decode(&tx.data).ok_or_else(|| anyhow!("not a pancake v3 / algebra swap"))?;
+    let swaps = pool_swaps(&outcome);
+    let (first_pool, _) = *swaps.first().ok_or_else(|| anyhow!("no concentrated liquidity swap event"))?;
+    let (_, actual_out) = *swaps.last().expect("non-empty swaps");
+
+    let slippage = if actual_out > swap.amount_out_min {
+        (actual_out - swap.amount_out_min).to_f64_lossy() / actual_out.to_f64_lossy()
+    } else {
+        0.0
+    };
+
+    // Saldo do token de entrada no primeiro pool, análogo às reservas do V2
+    let balance_of = AbiParser::default().parse_function("balanceOf(address) view returns (uint256)")?;
+    let data = balance_of.encode_input(&[Token::Address(first_pool)])?;
+    let min_tokens_to_affect = match rpc_client.call(swap.route[0], data).await {
+        Ok(out) => balance_of
+            .decode_output(&out)?
+            .first()
+            .and_then(|t| t.clone().into_uint())
+            .unwrap_or_default()
+            / U256::from(100u64),
+        Err(_) => U256::zero(),
+    };
+
+    let metrics = Metrics {
+        swap_function: swap.function,
+        token_route: swap.route,
+        slippage,
+        min_tokens_to_affect,
+        potential_profit: U256::zero(),
+        router_address: router.address,
+        router_name: Some(router.name.clone().unwrap_or_else(|| format!("{:#x}", router.address))),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: slippage > 0.0,
+        economically_viable: false,
+        simulated_tx: outcome.tx_hash,
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::types::{Bytes, Log};
+    use ethers::utils::id;
+
+    fn calldata(signature: &str, tokens: &[Token]) -> Vec<u8> {
+        [id(signature).to_vec(), encode(tokens)].concat()
+    }
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    #[test]
+    fn decode_pancake_and_algebra_single() {
+        let pancake = calldata(
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+            &[Token::Tuple(vec![
+                Token::Address(addr(1)),
+                Token::Address(addr(2)),
+                Token::Uint(2500.into()),
+                Token::Address(addr(9)),
+                Token::Uint(1000.into()),
+                Token::Uint(950.into()),
+                Token::Uint(0.into()),
+            ])],
+        );
+        // Pancake SmartRouter envolve o swap em multicall(uint256,bytes[])
+        let wrapped = calldata(
+            "multicall(uint256,bytes[])",
+            &[Token::Uint(1.into()), Token::Array(vec![Token::Bytes(pancake)])],
+        );
+        let swap = ExactInputSwap::decode(&wrapped).unwrap();
+        assert_eq!(swap.function, SwapFunction::PancakeV3ExactInputSingle);
+        assert_eq!((swap.route, swap.fees), (vec![addr(1), addr(2)], vec![2500]));
+        assert_eq!((swap.amount_in, swap.amount_out_min), (1000.into(), 950.into()));
+
+        let algebra = calldata(
+            "exactInputSingleSupportingFeeOnTransferTokens((address,address,address,uint256,uint256,uint256,uint160))",
+            &[Token::Tuple(vec![
+                Token::Address(addr(1)),
+                Token::Address(addr(2)),
+                Token::Address(addr(9)),
+                Token::Uint(u64::MAX.into()),
+                Token::Uint(1000.into()),
+                Token::Uint(900.into()),
+                Token::Uint(0.into()),
+            ])],
+        );
+        let swap = ExactInputSwap::decode(&algebra).unwrap();
+        assert_eq!(swap.function, SwapFunction::AlgebraExactInputSingle);
+        assert!(swap.fees.is_empty());
+        assert_eq!((swap.recipient, swap.amount_out_min), (addr(9), 900.into()));
+    }
+
+    #[test]
+    fn decode_paths_by_fee_encoding() {
+        let algebra_path: Vec<u8> = [addr(1), addr(2), addr(3)].iter().flat_map(|a| a.as_bytes().to_vec()).collect();
+        let params = |path: Vec<u8>| {
+            Token::Tuple(vec![
+                Token::Bytes(path),
+                Token::Address(addr(9)),
+                Token::Uint(u64::MAX.into()),
+                Token::Uint(1000.into()),
+                Token::Uint(990.into()),
+            ])
+        };
+        let data = calldata("exactInput((bytes,address,uint256,uint256,uint256))", &[params(algebra_path)]);
+        let swap = ExactInputSwap::decode(&data).unwrap();
+        assert_eq!(swap.route, vec![addr(1), addr(2), addr(3)]);
+
+        // Path com taxas no mesmo seletor pertence ao Uniswap V3
+        let fee_path = [addr(1).as_bytes(), &[0, 0x01, 0xf4], addr(2).as_bytes()].concat();
+        let data = calldata("exactInput((bytes,address,uint256,uint256,uint256))", &[params(fee_path.clone())]);
+        assert!(ExactInputSwap::decode(&data).is_none());
+
+        assert_eq!(decode_fee_path(&fee_path), Some((vec![addr(1), addr(2)], vec![500])));
+    }
+
+    #[test]
+    fn output_from_last_pool_swap() {
+        let topic: H256 = CONCENTRATED_SWAP_TOPICS[1].parse().unwrap();
+        let minus = |v: u64| (!U256::from(v)).overflowing_add(U256::one()).0;
+        let swap_log = |pool, amount0, amount1| Log {
+            address: addr(pool),
+            topics: vec![topic, H256::zero(), H256::zero()],
+            data: Bytes::from(encode(&[Token::Int(amount0), Token::Int(amount1)])),
+            ..Default::default()
+        };
+        let outcome = SimulationOutcome {
+            tx_hash: None,
+            logs: vec![swap_log(5, 1000.into(), minus(40)), swap_log(6, minus(37), 40.into())],
+        };
+
+        assert_eq!(pool_swaps(&outcome), vec![(addr(5), 40.into()), (addr(6), 37.into())]);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::detectors::clusters::uniswap_v3::decode_fee_path;
+use anyhow::{anyhow, Result};
+use ethereum_types::{Address, U256};
+use ethers::abi::{decode, ParamType, Token};
+
+/// `Constants.MSG_SENDER`: o destinatário é quem chamou o router
+const MSG_SENDER: u64 = 1;
+/// `Constants.ADDRESS_THIS`: o destinatário é o próprio router
+const ADDRESS_THIS: u64 = 2;
+
+/// Swap V2 ou V3 decodificado de um comando do Universal Router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapCommand {
+    pub exact_in: bool,
+    pub recipient: Address,
+    /// `amountIn` em entradas exatas ou `amountOut` em saídas exatas
+    pub amount: U256,
+    /// `amountOutMin` em entradas exatas ou `amountInMax` em saídas exatas
+    pub amount_limit: U256,
+    /// Tokens na ordem de execução
+    pub path: Vec<Address>,
+    /// Taxas de cada hop V3; vazio em swaps V2
+    pub fees: Vec<u32>,
+    /// Se os tokens de entrada saem do usuário (via Permit2) ou do saldo do router
+    pub payer_is_user: bool,
+}
+
+/// Comando do Universal Router com os parâmetros relevantes para a análise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouterCommand {
+    V2Swap(SwapCommand),
+    V3Swap(SwapCommand),
+    V4Swap,
+    Permit2Permit { token: Address, amount: U256, spender: Address },
+    WrapEth { recipient: Address, amount_min: U256 },
+    UnwrapWeth { recipient: Address, amount_min: U256 },
+    Sweep { token: Address, recipient: Address, amount_min: U256 },
+    PayPortion { token: Address, recipient: Address, bips: U256 },
+    Other(u8),
+}
+
+impl RouterCommand {
+    pub fn is_swap(&self) -> bool {
+        matches!(self, RouterCommand::V2Swap(_) | RouterCommand::V3Swap(_) | RouterCommand::V4Swap)
+    }
+}
+
+/// Decodifica a sequência de comandos; cada comando consome a entrada de mesmo índice
+pub fn decode_commands(commands: &[u8], inputs: &[Vec<u8>]) -> Result<Vec<RouterCommand>> {
+    commands
+        .iter()
+        .enumerate()
+        .map(|(i, command)| {
+            let input = inputs.get(i).ok_or_else(|| anyhow!("missing input for command {i}"))?;
+            decode_command(command & 0x3f, input).ok_or_else(|| anyhow!("invalid input for command {command:#04x}"))
+        })
+        .collect()
+}
+
+fn decode_command(op: u8, input: &[u8]) -> Option<RouterCommand> {
+    let address = ParamType::Address;
+    let uint = ParamType::Uint(256);
+    let command = match op {
+        // V3_SWAP_EXACT_IN, V3_SWAP_EXACT_OUT
+        0x00 | 0x01 => {
+            let tokens = decode(&[address, uint.clone(), uint, ParamType::Bytes, ParamType::Bool], input).ok()?;
+            let (mut path, mut fees) = decode_fee_path(&tokens.get(3)?.clone().into_bytes()?)?;
+            // O path de saídas exatas vai do token de saída para o de entrada
+            if op == 0x01 {
+                path.reverse();
+                fees.reverse();
+            }
+            RouterCommand::V3Swap(swap_command(op == 0x00, &tokens, path, fees)?)
+        }
+        // V2_SWAP_EXACT_IN, V2_SWAP_EXACT_OUT
+        0x08 | 0x09 => {
+            let path_type = ParamType::Array(Box::new(ParamType::Address));
+            let tokens = decode(&[address, uint.clone(), uint, path_type, ParamType::Bool], input).ok()?;
+            let path = tokens
+                .get(3)?
+                .clone()
+                .into_array()?
+                .into_iter()
+                .map(Token::into_address)
+                .collect::<Option<Vec<_>>>()?;
+            RouterCommand::V2Swap(swap_command(op == 0x08, &tokens, path, Vec::new())?)
+        }
+        0x10 => RouterCommand::V4Swap,
+        // PERMIT2_PERMIT: (PermitSingle, bytes signature)
+        0x0a => {
+            let details = ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::Uint(160),
+                ParamType::Uint(48),
+                ParamType::Uint(48),
+            ]);
+            let permit = ParamType::Tuple(vec![details, address, uint]);
+            let tokens = decode(&[permit, ParamType::Bytes], input).ok()?;
+            let permit = tokens.first()?.clone().into_tuple()?;
+            let details = permit.first()?.clone().into_tuple()?;
+            RouterCommand::Permit2Permit {
+                token: details.first()?.clone().into_address()?,
+                amount: details.get(1)?.clone().into_uint()?,
+                spender: permit.get(1)?.clone().into_address()?,
+            }
+        }
+        // WRAP_ETH, UNWRAP_WETH: (recipient, amountMin)
+        0x0b | 0x0c => {
+            let tokens = decode(&[address, uint], input).ok()?;
+            let recipient = tokens.first()?.clone().into_address()?;
+            let amount_min = tokens.get(1)?.clone().into_uint()?;
+            if op == 0x0b {
+                RouterCommand::WrapEth { recipient, amount_min }
+            } else {
+                RouterCommand::UnwrapWeth { recipient, amount_min }
+            }
+        }
+        // SWEEP: (token, recipient, amountMin); PAY_PORTION: (token, recipient, bips)
+        0x04 | 0x06 => {
+            let tokens = decode(&[address.clone(), address, uint], input).ok()?;
+            let token = tokens.first()?.clone().into_address()?;
+            let recipient = tokens.get(1)?.clone().into_address()?;
+            let amount = tokens.get(2)?.clone().into_uint()?;
+            if op == 0x04 {
+                RouterCommand::Sweep { token, recipient, amount_min: amount }
+            } else {
+                RouterCommand::PayPortion { token, recipient, bips: amount }
+            }
+        }
+        _ => RouterCommand::Other(op),
+    };
+    Some(command)
+}
+
+fn swap_command(exact_in: bool, tokens: &[Token], path: Vec<Address>, fees: Vec<u32>) -> Option<SwapCommand> {
+    if path.len() < 2 {
+        return None;
+    }
+    Some(SwapCommand {
+        exact_in,
+        recipient: tokens.first()?.clone().into_address()?,
+        amount: tokens.get(1)?.clone().into_uint()?,
+        amount_limit: tokens.get(2)?.clone().into_uint()?,
+        path,
+        fees,
+        payer_is_user: tokens.get(4)?.clone().into_bool()?,
+    })
+}
+
+/// Resolve os destinatários especiais `MSG_SENDER` e `ADDRESS_THIS`
+pub fn resolve_recipient(recipient: Address, sender: Address, router: Address) -> Address {
+    if recipient == Address::from_low_u64_be(MSG_SENDER) {
+        sender
+    } else if recipient == Address::from_low_u64_be(ADDRESS_THIS) {
+        router
+    } else {
+        recipient
+    }
+}
+
+/// Rota de tokens encadeando os swaps. As pernas em ETH nativo de `WRAP_ETH` e
+/// `UNWRAP_WETH` vêm dos logs da simulação (`core::WethLegs`).
+/// Swaps que não continuam a rota (rotas divididas) não são repetidos.
+pub fn token_route(commands: &[RouterCommand]) -> Vec<Address> {
+    let mut route = Vec::new();
+    let mut swapped = false;
+    for command in commands {
+        if let RouterCommand::V2Swap(swap) | RouterCommand::V3Swap(swap) = command {
+            let continues = !swapped || route.last() == swap.path.first();
+            if continues {
+                let skip = usize::from(route.last() == swap.path.first());
+                route.extend(swap.path.iter().skip(skip));
+            }
+            swapped = true;
+        }
+    }
+    route
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn v3_input(path: &[Address], fee: u32) -> Vec<u8> {
+        let mut packed = path[0].as_bytes().to_vec();
+        for token in &path[1..] {
+            packed.extend_from_slice(&fee.to_be_bytes()[1..]);
+            packed.extend_from_slice(token.as_bytes());
+        }
+        encode(&[
+            Token::Address(addr(MSG_SENDER)),
+            Token::Uint(1000.into()),
+            Token::Uint(900.into()),
+            Token::Bytes(packed),
+            Token::Bool(false),
+        ])
+    }
+
+    #[test]
+    fn decode_wrap_swap_unwrap_sequence() {
+        let weth = addr(10);
+        let usdc = addr(11);
+        let dai = addr(12);
+        let permit = encode(&[
+            Token::Tuple(vec![
+                Token::Tuple(vec![
+                    Token::Address(usdc),
+                    Token::Uint(5000.into()),
+                    Token::Uint(0.into()),
+                    Token::Uint(0.into()),
+                ]),
+                Token::Address(addr(99)),
+                Token::Uint(0.into()),
+            ]),
+            Token::Bytes(vec![1; 65]),
+        ]);
+        let wrap = encode(&[Token::Address(addr(ADDRESS_THIS)), Token::Uint(1000.into())]);
+        let v2 = encode(&[
+            Token::Address(addr(ADDRESS_THIS)),
+            Token::Uint(1000.into()),
+            Token::Uint(0.into()),
+            Token::Array(vec![Token::Address(usdc), Token::Address(dai)]),
+            Token::Bool(false),
+        ]);
+        let unwrap = encode(&[Token::Address(addr(MSG_SENDER)), Token::Uint(0.into())]);
+        let pay = encode(&[Token::Address(weth), Token::Address(addr(7)), Token::Uint(25.into())]);
+
+        // PERMIT2_PERMIT, WRAP_ETH, V3_SWAP_EXACT_IN, V2_SWAP_EXACT_IN (rota dividida), PAY_PORTION, UNWRAP_WETH
+        let commands = [0x0a, 0x0b, 0x00, 0x08, 0x06, 0x0c];
+        let inputs = vec![permit, wrap, v3_input(&[weth, usdc], 500), v2, pay, unwrap];
+        let decoded = decode_commands(&commands, &inputs).unwrap();
+
+        assert_eq!(decoded[0], RouterCommand::Permit2Permit { token: usdc, amount: 5000.into(), spender: addr(99) });
+        assert_eq!(decoded[4], RouterCommand::PayPortion { token: weth, recipient: addr(7), bips: 25.into() });
+        let RouterCommand::V3Swap(swap) = &decoded[2] else { panic!("expected v3 swap") };
+        assert_eq!((swap.path.clone(), swap.fees.clone()), (vec![weth, usdc], vec![500]));
+        assert_eq!(resolve_recipient(swap.recipient, addr(50), addr(60)), addr(50));
+        assert_eq!(token_route(&decoded), vec![weth, usdc, dai]);
+    }
+
+    #[test]
+    fn missing_input_is_an_error() {
+        assert!(decode_commands(&[0x0b, 0x0c], &[vec![0; 64]]).is_err());
+    }
+}
@@ -1,18 +1,25 @@
-use crate::core::metrics::{constant_product_input, constant_product_output, U256Ext};
-use crate::dex::query::get_pair_tokens;
+mod commands;
+
+pub use commands::{decode_commands, resolve_recipient, token_route, RouterCommand, SwapCommand};
+
+use crate::core::metrics::route_slippage;
+use crate::core::sandwich::scaled_min_out;
+use crate::core::transfer_tax::transfer_taxes;
+use crate::core::weth::{is_wrapped_native, WethLegs};
+use crate::detectors::clusters::uniswap_v2::{sandwich_gas_cost, v2_route_hops};
+use crate::detectors::clusters::uniswap_v3::concentrated_route_hops;
 use crate::dex::{RouterInfo, SwapFunction};
 use crate::filters::{FilterPipeline, SwapLogFilter};
 use crate::simulation::SimulationOutcome;
-use crate::types::{AnalysisResult, Metrics, TransactionData};
+use crate::types::{AnalysisResult, GasCost, Metrics, TransactionData};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use ethereum_types::H256;
 use ethereum_types::{Address, U256};
 use ethernity_core::traits::RpcProvider;
 use ethers::abi::AbiParser;
-use ethers::prelude::{Http, Middleware, Provider, TransactionRequest};
+use ethers::prelude::{Http, Provider};
 use ethers::types::BlockId;
-use ethers::utils::{id, keccak256};
+use ethers::utils::id;
 use once_cell::sync::Lazy;
 use std::collections::HashSet;
 use std::str::FromStr;
@@ -120,194 +127,80 @@ pub async fn analyze_universal_router(
         .map(|v| v.into_bytes().ok_or_else(|| anyhow!("invalid input type")))
         .collect::<Result<Vec<_>>>()?;
 
-    const SWAP_OPS: [u8; 5] = [
-        0x00, // V3_SWAP_EXACT_IN
-        0x01, // V3_SWAP_EXACT_OUT
-        0x08, // V2_SWAP_EXACT_IN
-        0x09, // V2_SWAP_EXACT_OUT
-        0x10, // V4_SWAP
-    ];
-
-    // Only a subset of commands actually consumes an entry from the `inputs`
-    // array. Counting all commands would misalign the index and decode the
-    // wrong data for swap operations.
-    fn consumes_input(op: u8) -> bool {
-        matches!(op, 0x00..=0x13)
+    let commands = decode_commands(&commands, &inputs)?;
+    if !commands.iter().any(RouterCommand::is_swap) {
+        return Err(anyhow!("no universal router swap commands"));
     }
 
-    let has_swap = commands
-        .iter()
-        .map(|c| c & 0x3f)
-        .any(|c| SWAP_OPS.contains(&c));
-
-    if has_swap {
-        // attempt to decode the first swap command to extract basic info
-        let mut token_route = Vec::new();
-        let mut slippage = 0.0f64;
-        let mut input_idx = 0usize;
-        for cmd in commands.iter() {
-            let op = cmd & 0x3f;
-            if op == 0x08 || op == 0x09 {
-                // V2 swap commands
-                let func_sig = if op == 0x08 {
-                    "v2SwapExactInput(address,uint256,uint256,address[],address)"
-                } else {
-                    "v2SwapExactOutput(address,uint256,uint256,address[],address)"
+    // Native ETH legs come from the chain's WETH Deposit/Withdrawal logs around the swaps
+    let token_route = WethLegs::from_logs(&outcome.logs, None).expand_route(&token_route(&commands));
+    // Every swap command is evaluated hop by hop and the reported slippage composes all hops
+    let mut hops = Vec::new();
+    let mut v2_hops = Vec::new();
+    let mut v2_min_outs = Vec::new();
+    for command in &commands {
+        match command {
+            RouterCommand::V2Swap(swap) => {
+                let swap_hops = v2_route_hops(&*rpc_client, &provider, call_block, &outcome.logs, &swap.path).await?;
+                // The command's amountOutMin/amountInMax slack bounds the frontrun on each of its pairs
+                let slack = match (swap.exact_in, swap_hops.first(), swap_hops.last()) {
+                    (true, _, Some(last)) => Some((swap.amount_limit, last.metrics.actual_out)),
+                    (false, Some(first), _) => Some((first.metrics.amount_in, swap.amount_limit)),
+                    _ => None,
                 };
-                let f = AbiParser::default().parse_function(func_sig)?;
-                let input_data = inputs
-                    .get(input_idx)
-                    .ok_or_else(|| anyhow!("missing input for swap command"))?;
-                let tokens = f.decode_input(input_data)?;
-                let path_tokens = tokens
-                    .get(3)
-                    .and_then(|t| t.clone().into_array())
-                    .ok_or_else(|| anyhow!("missing path"))?;
-                let mut path = Vec::new();
-                for t in path_tokens {
-                    path.push(
-                        t.into_address()
-                            .ok_or_else(|| anyhow!("invalid address in path"))?,
-                    );
-                }
-                token_route = path.clone();
-
-                if path.len() == 2 {
-                    let swap_topic: H256 = H256::from_slice(
-                        keccak256("Swap(address,uint256,uint256,uint256,uint256,address)")
-                            .as_slice(),
-                    );
-                    let mut selected_log = None;
-                    for (_idx, log) in outcome.logs.iter().enumerate() {
-                        if log.topics.get(0) != Some(&swap_topic) {
-                            continue;
-                        }
-                        let pair = log.address;
-                        let (token0, token1) = match get_pair_tokens(&*rpc_client, pair).await {
-                            Ok(t) => t,
-                            Err(_) => continue,
-                        };
-                        if (token0 == path[0] && token1 == path[1])
-                            || (token1 == path[0] && token0 == path[1])
-                        {
-                            selected_log = Some((pair, token0, token1));
-                            break;
-                        }
-                    }
-
-                    if let Some((pair, token0, token1)) = selected_log {
-                        let abi_res = AbiParser::default()
-                            .parse_function("getReserves() returns (uint112,uint112,uint32)")?;
-                        let tx_call = TransactionRequest::new()
-                            .to(pair)
-                            .data(abi_res.encode_input(&[])?);
-                        let res_out = provider
-                            .call(&tx_call.into(), call_block)
-                            .await
-                            .map_err(|e| anyhow!(e))?;
-                        let r = abi_res.decode_output(&res_out)?;
-                        let reserve0 = r
-                            .get(0)
-                            .and_then(|v| v.clone().into_uint())
-                            .ok_or_else(|| anyhow!("reserve0 decode"))?;
-                        let reserve1 = r
-                            .get(1)
-                            .and_then(|v| v.clone().into_uint())
-                            .ok_or_else(|| anyhow!("reserve1 decode"))?;
-                        let (reserve_in, reserve_out) = if token0 == path[0] && token1 == path[1] {
-                            (reserve0, reserve1)
-                        } else {
-                            (reserve1, reserve0)
-                        };
-                        let transfer_sig: H256 = H256::from_slice(
-                            keccak256("Transfer(address,address,uint256)").as_slice(),
-                        );
-                        if op == 0x08 {
-                            let amount_in = tokens
-                                .get(1)
-                                .and_then(|t| t.clone().into_uint())
-                                .ok_or_else(|| anyhow!("missing amountIn"))?;
-                            let expected =
-                                constant_product_output(amount_in, reserve_in, reserve_out);
-                            let recipient = tokens
-                                .get(0)
-                                .and_then(|t| t.clone().into_address())
-                                .unwrap_or(tx.from);
-                            let mut actual_out = U256::zero();
-                            for log in &outcome.logs {
-                                if log.topics.get(0) == Some(&transfer_sig) && log.topics.len() >= 3
-                                {
-                                    let to_addr =
-                                        Address::from_slice(&log.topics[2].as_bytes()[12..]);
-                                    let from_addr =
-                                        Address::from_slice(&log.topics[1].as_bytes()[12..]);
-                                    if to_addr == recipient && from_addr == pair {
-                                        actual_out = U256::from_big_endian(&log.data.0);
-                                    }
-                                }
-                            }
-                            if expected > actual_out && !expected.is_zero() {
-                                slippage = (expected - actual_out).to_f64_lossy()
-                                    / expected.to_f64_lossy();
-                            }
-                        } else {
-                            let amount_out = tokens
-                                .get(1)
-                                .and_then(|t| t.clone().into_uint())
-                                .ok_or_else(|| anyhow!("missing amountOut"))?;
-                            if let Some(expected_in) =
-                                constant_product_input(amount_out, reserve_in, reserve_out)
-                            {
-                                let payer = tokens
-                                    .get(4)
-                                    .and_then(|t| t.clone().into_address())
-                                    .unwrap_or(tx.from);
-                                let mut actual_in = U256::zero();
-                                for log in &outcome.logs {
-                                    if log.topics.get(0) == Some(&transfer_sig)
-                                        && log.topics.len() >= 3
-                                    {
-                                        let from_addr =
-                                            Address::from_slice(&log.topics[1].as_bytes()[12..]);
-                                        let to_addr =
-                                            Address::from_slice(&log.topics[2].as_bytes()[12..]);
-                                        if from_addr == payer && to_addr == pair {
-                                            actual_in = U256::from_big_endian(&log.data.0);
-                                        }
-                                    }
-                                }
-                                if actual_in > expected_in && !expected_in.is_zero() {
-                                    slippage = (actual_in - expected_in).to_f64_lossy()
-                                        / expected_in.to_f64_lossy();
-                                }
-                            }
-                        }
-                    }
-                }
-                break;
+                v2_min_outs.extend(
+                    swap_hops
+                        .iter()
+                        .map(|hop| slack.and_then(|(num, den)| scaled_min_out(hop.metrics.actual_out, num, den))),
+                );
+                v2_hops.extend(swap_hops);
             }
-
-            if consumes_input(op) {
-                input_idx += 1;
+            RouterCommand::V3Swap(swap) => {
+                hops.extend(concentrated_route_hops(&provider, call_block, &outcome.logs, &swap.path, &swap.fees).await?);
             }
+            _ => {}
         }
-
-        let metrics = Metrics {
-            swap_function: swap_variant,
-            token_route,
-            slippage,
-            min_tokens_to_affect: U256::zero(),
-            potential_profit: U256::zero(),
-            router_address: tx.to,
-            router_name: Some(format!("{:#x}", tx.to)),
-        };
-        Ok(AnalysisResult {
-            potential_victim: true,
-            economically_viable: false,
-            simulated_tx: None,
-            metrics,
-        })
-    } else {
-        Err(anyhow!("no universal router swap commands"))
     }
+    let target = v2_hops
+        .iter()
+        .zip(&v2_min_outs)
+        .max_by(|(a, _), (b, _)| a.exposure().total_cmp(&b.exposure()));
+    let (min_tokens_to_affect, sandwich) = target
+        .map(|(hop, min_out)| (hop.reserve_in / U256::from(100u64), hop.sandwich(*min_out)))
+        .unwrap_or_default();
+    let potential_profit = sandwich.as_ref().map_or(U256::zero(), |plan| plan.profit);
+    // The router exposes no WETH getter; the profit is priced through the chain's wrapped native token
+    let gas_cost = match target {
+        Some((hop, _)) => {
+            let pair = (hop.metrics.token_in, hop.metrics.token_out, hop.reserve_in, hop.reserve_out);
+            let weth = [pair.0, pair.1].into_iter().find(|token| is_wrapped_native(*token));
+            sandwich_gas_cost(&provider, block, tx.gas_price, potential_profit, pair, weth).await
+        }
+        None => None,
+    };
+    hops.extend(v2_hops.into_iter().map(|hop| hop.metrics));
+    let slippage = route_slippage(&hops);
+    // Hop slippage already starts from what each pool received; the output side is not
+    // compared because PAY_PORTION/SWEEP legitimately reduce what reaches the recipient
+    let fee_on_transfer = !transfer_taxes(&outcome.logs, &hops, tx.from, None).is_empty();
+
+    let metrics = Metrics {
+        swap_function: swap_variant,
+        token_route,
+        slippage,
+        min_tokens_to_affect,
+        potential_profit,
+        router_address: tx.to,
+        router_name: Some(format!("{:#x}", tx.to)),
+        hops,
+        fee_on_transfer,
+        gas_cost,
+        sandwich,
+    };
+    Ok(AnalysisResult {
+        potential_victim: true,
+        economically_viable: metrics.gas_cost.as_ref().is_some_and(GasCost::economically_viable),
+        simulated_tx: None,
+        metrics,
+    })
 }
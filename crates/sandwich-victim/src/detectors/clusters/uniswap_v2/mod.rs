@@ -1,13 +1,18 @@
 pub mod exact_in;
+pub mod route;
 pub use exact_in::SwapV2ExactInDetector;
+pub use route::{most_exposed_hop, profit_in_native, router_weth, sandwich_gas_cost, v2_route_hops, V2Hop};
+use route::victim_amounts;
 
 use crate::core::metrics::{
-    constant_product_input, constant_product_output, simulate_sandwich_profit, U256Ext,
+    constant_product_input, constant_product_output, route_slippage, U256Ext,
 };
+use crate::core::sandwich::{optimal_sandwich, scaled_min_out, ConstantProductPool};
+use crate::core::transfer_tax::transfer_taxes;
 use crate::dex::{detect_swap_function, get_pair_address, RouterInfo, SwapFunction};
 use crate::filters::{FilterPipeline, SwapLogFilter};
 use crate::simulation::{simulate_transaction, SimulationConfig, SimulationOutcome};
-use crate::types::{AnalysisResult, Metrics, TransactionData};
+use crate::types::{AnalysisResult, GasCost, Metrics, TransactionData};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use ethereum_types::{Address, H256, U256};
@@ -315,22 +320,9 @@ pub async fn analyze_uniswap_v2(
         (None, None)
     };
 
-    let transfer_sig: H256 =
-        H256::from_slice(keccak256("Transfer(address,address,uint256)").as_slice());
-    let mut actual_out = U256::zero();
-    let mut actual_in = U256::zero();
-    for log in &logs {
-        if log.topics.get(0) == Some(&transfer_sig) && log.topics.len() == 3 {
-            let from_addr = Address::from_slice(&log.topics[1].as_bytes()[12..]);
-            let to_addr = Address::from_slice(&log.topics[2].as_bytes()[12..]);
-            if to_addr == tx.from {
-                actual_out = U256::from_big_endian(&log.data.0);
-            }
-            if from_addr == tx.from {
-                actual_in = U256::from_big_endian(&log.data.0);
-            }
-        }
-    }
+    // Entrada e saída no token de cada ponta, inclusive ETH nativo embrulhado pelo router
+    let weth = router_weth(&*rpc_client, router.address).await;
+    let (actual_in, actual_out, token_route) = victim_amounts(&logs, &path, tx.from, weth);
 
     let slippage = if let Some(exp_out) = expected_out {
         if exp_out > actual_out {
@@ -348,9 +340,48 @@ pub async fn analyze_uniswap_v2(
         0.0
     };
 
-    let min_tokens_to_affect = reserve_in / U256::from(100u64);
-    let input_for_profit = amount_in.unwrap_or(actual_in);
-    let potential_profit = simulate_sandwich_profit(input_for_profit, reserve_in, reserve_out);
+    let hops = v2_route_hops(&*rpc_client, &provider, call_block, &logs, &path).await?;
+    let hop_metrics: Vec<_> = hops.iter().map(|hop| hop.metrics.clone()).collect();
+    let taxes = transfer_taxes(&logs, &hop_metrics, tx.from, Some(tx.from));
+    let fee_on_transfer = !taxes.is_empty();
+    let complete_route = hops.len() == path.len() - 1;
+    let multi_hop = complete_route && path.len() > 2;
+    // Em rotas multi-hop o slippage é composto hop a hop e o sanduíche mira o par mais exposto.
+    // Com tokens fee-on-transfer a cotação do router ignora a taxa, então os hops, que partem
+    // do que cada par efetivamente recebeu, também são usados em rotas de um único par.
+    let slippage = if multi_hop || (complete_route && fee_on_transfer) {
+        route_slippage(&hop_metrics)
+    } else {
+        slippage
+    };
+    let expected_out = expected_out.map(|out| taxes.iter().fold(out, |out, tax| tax.apply(out)));
+    // Folga da vítima na rota, repassada ao par atacado para limitar o frontrun
+    let first_hop = hops.first().filter(|_| complete_route);
+    let route_out = hops.last().filter(|_| complete_route).map_or(actual_out, |hop| hop.metrics.actual_out);
+    let slack = match (amount_out_min, amount_in_max) {
+        (Some(out_min), _) => Some((out_min, route_out)),
+        (None, Some(in_max)) => Some((actual_in, in_max)),
+        _ => None,
+    };
+    let (min_tokens_to_affect, sandwich, target) = match most_exposed_hop(&hops).filter(|_| multi_hop) {
+        Some(hop) => (
+            hop.reserve_in / U256::from(100u64),
+            hop.sandwich(slack.and_then(|(num, den)| scaled_min_out(hop.metrics.actual_out, num, den))),
+            (hop.metrics.token_in, hop.metrics.token_out, hop.reserve_in, hop.reserve_out),
+        ),
+        None => {
+            let victim_in = first_hop.map_or(amount_in.unwrap_or(actual_in), |hop| hop.metrics.amount_in);
+            let pair_out = first_hop.map_or(actual_out, |hop| hop.metrics.actual_out);
+            let min_out = slack.and_then(|(num, den)| scaled_min_out(pair_out, num, den));
+            (
+                reserve_in / U256::from(100u64),
+                optimal_sandwich(&ConstantProductPool::v2(reserve_in, reserve_out), victim_in, min_out),
+                (path[0], path[1], reserve_in, reserve_out),
+            )
+        }
+    };
+    let potential_profit = sandwich.as_ref().map_or(U256::zero(), |plan| plan.profit);
+    let gas_cost = sandwich_gas_cost(&provider, block, tx.gas_price, potential_profit, target, weth).await;
 
     let router_name = router
         .name
@@ -359,12 +390,16 @@ pub async fn analyze_uniswap_v2(
 
     let metrics = Metrics {
         swap_function: swap_kind,
-        token_route: path.clone(),
+        token_route,
         slippage,
         min_tokens_to_affect,
         potential_profit,
         router_address: router.address,
         router_name: Some(router_name),
+        hops: hop_metrics,
+        fee_on_transfer,
+        gas_cost,
+        sandwich,
     };
 
     let potential_victim = if let Some(out_min) = amount_out_min {
@@ -377,7 +412,7 @@ pub async fn analyze_uniswap_v2(
 
     Ok(AnalysisResult {
         potential_victim,
-        economically_viable: potential_profit > U256::zero(),
+        economically_viable: metrics.gas_cost.as_ref().is_some_and(GasCost::economically_viable),
         simulated_tx: tx_hash,
         metrics,
     })
@@ -646,22 +681,9 @@ pub async fn analyze_uniswap_v2_with_outcome(
         (None, None)
     };
 
-    let transfer_sig: H256 =
-        H256::from_slice(keccak256("Transfer(address,address,uint256)").as_slice());
-    let mut actual_out = U256::zero();
-    let mut actual_in = U256::zero();
-    for log in &logs {
-        if log.topics.get(0) == Some(&transfer_sig) && log.topics.len() == 3 {
-            let from_addr = Address::from_slice(&log.topics[1].as_bytes()[12..]);
-            let to_addr = Address::from_slice(&log.topics[2].as_bytes()[12..]);
-            if to_addr == tx.from {
-                actual_out = U256::from_big_endian(&log.data.0);
-            }
-            if from_addr == tx.from {
-                actual_in = U256::from_big_endian(&log.data.0);
-            }
-        }
-    }
+    // Entrada e saída no token de cada ponta, inclusive ETH nativo embrulhado pelo router
+    let weth = router_weth(&*rpc_client, router.address).await;
+    let (actual_in, actual_out, token_route) = victim_amounts(&logs, &path, tx.from, weth);
 
     let slippage = if let Some(exp_out) = expected_out {
         if exp_out > actual_out {
@@ -679,9 +701,48 @@ pub async fn analyze_uniswap_v2_with_outcome(
         0.0
     };
 
-    let min_tokens_to_affect = reserve_in / U256::from(100u64);
-    let input_for_profit = amount_in.unwrap_or(actual_in);
-    let potential_profit = simulate_sandwich_profit(input_for_profit, reserve_in, reserve_out);
+    let hops = v2_route_hops(&*rpc_client, &provider, call_block, &logs, &path).await?;
+    let hop_metrics: Vec<_> = hops.iter().map(|hop| hop.metrics.clone()).collect();
+    let taxes = transfer_taxes(&logs, &hop_metrics, tx.from, Some(tx.from));
+    let fee_on_transfer = !taxes.is_empty();
+    let complete_route = hops.len() == path.len() - 1;
+    let multi_hop = complete_route && path.len() > 2;
+    // Em rotas multi-hop o slippage é composto hop a hop e o sanduíche mira o par mais exposto.
+    // Com tokens fee-on-transfer a cotação do router ignora a taxa, então os hops, que partem
+    // do que cada par efetivamente recebeu, também são usados em rotas de um único par.
+    let slippage = if multi_hop || (complete_route && fee_on_transfer) {
+        route_slippage(&hop_metrics)
+    } else {
+        slippage
+    };
+    let expected_out = expected_out.map(|out| taxes.iter().fold(out, |out, tax| tax.apply(out)));
+    // Folga da vítima na rota, repassada ao par atacado para limitar o frontrun
+    let first_hop = hops.first().filter(|_| complete_route);
+    let route_out = hops.last().filter(|_| complete_route).map_or(actual_out, |hop| hop.metrics.actual_out);
+    let slack = match (amount_out_min, amount_in_max) {
+        (Some(out_min), _) => Some((out_min, route_out)),
+        (None, Some(in_max)) => Some((actual_in, in_max)),
+        _ => None,
+    };
+    let (min_tokens_to_affect, sandwich, target) = match most_exposed_hop(&hops).filter(|_| multi_hop) {
+        Some(hop) => (
+            hop.reserve_in / U256::from(100u64),
+            hop.sandwich(slack.and_then(|(num, den)| scaled_min_out(hop.metrics.actual_out, num, den))),
+            (hop.metrics.token_in, hop.metrics.token_out, hop.reserve_in, hop.reserve_out),
+        ),
+        None => {
+            let victim_in = first_hop.map_or(amount_in.unwrap_or(actual_in), |hop| hop.metrics.amount_in);
+            let pair_out = first_hop.map_or(actual_out, |hop| hop.metrics.actual_out);
+            let min_out = slack.and_then(|(num, den)| scaled_min_out(pair_out, num, den));
+            (
+                reserve_in / U256::from(100u64),
+                optimal_sandwich(&ConstantProductPool::v2(reserve_in, reserve_out), victim_in, min_out),
+                (path[0], path[1], reserve_in, reserve_out),
+            )
+        }
+    };
+    let potential_profit = sandwich.as_ref().map_or(U256::zero(), |plan| plan.profit);
+    let gas_cost = sandwich_gas_cost(&provider, block, tx.gas_price, potential_profit, target, weth).await;
 
     let router_name = router
         .name
@@ -690,12 +751,16 @@ pub async fn analyze_uniswap_v2_with_outcome(
 
     let metrics = Metrics {
         swap_function: swap_kind,
-        token_route: path.clone(),
+        token_route,
         slippage,
         min_tokens_to_affect,
         potential_profit,
         router_address: router.address,
         router_name: Some(router_name),
+        hops: hop_metrics,
+        fee_on_transfer,
+        gas_cost,
+        sandwich,
     };
 
     let potential_victim = if let Some(out_min) = amount_out_min {
@@ -708,7 +773,7 @@ pub async fn analyze_uniswap_v2_with_outcome(
 
     Ok(AnalysisResult {
         potential_victim,
-        economically_viable: potential_profit > U256::zero(),
+        economically_viable: metrics.gas_cost.as_ref().is_some_and(GasCost::economically_viable),
         simulated_tx: tx_hash,
         metrics,
     })
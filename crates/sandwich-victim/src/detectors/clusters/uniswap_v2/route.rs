@@ -0,0 +1,301 @@
+use crate::core::metrics::{constant_product_output, hop_slippage, U256Ext};
+use crate::core::sandwich::{optimal_sandwich, ConstantProductPool};
+use crate::core::transfer_tax::transferred;
+use crate::core::weth::WethLegs;
+use crate::dex::query::get_pair_tokens;
+use crate::core::gas::v2_sandwich_gas_cost;
+use crate::types::{GasCost, HopMetrics, SandwichPlan};
+use anyhow::{anyhow, Result};
+use ethereum_types::{Address, H256, U256};
+use ethernity_core::traits::RpcProvider;
+use ethers::abi::AbiParser;
+use ethers::prelude::{Http, Middleware, Provider, TransactionRequest};
+use ethers::types::{BlockId, Log};
+use ethers::utils::keccak256;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Taxa padrão dos pares V2 (0,3%), em milésimos
+const FEE_NUMERATOR: u64 = 997;
+const FEE_DENOMINATOR: u64 = 1000;
+
+/// Hop V2 avaliado, com as reservas do par antes da transação
+#[derive(Debug, Clone)]
+pub struct V2Hop {
+    pub metrics: HopMetrics,
+    pub reserve_in: U256,
+    pub reserve_out: U256,
+}
+
+impl V2Hop {
+    /// Frontrun ótimo neste par para a entrada da vítima no hop, com o lucro no token de entrada
+    pub fn sandwich(&self, victim_min_out: Option<U256>) -> Option<SandwichPlan> {
+        optimal_sandwich(&ConstantProductPool::v2(self.reserve_in, self.reserve_out), self.metrics.amount_in, victim_min_out)
+    }
+
+    /// Tamanho da entrada em relação à reserva, usado para comparar hops de tokens diferentes
+    pub(crate) fn exposure(&self) -> f64 {
+        if self.reserve_in.is_zero() {
+            return 0.0;
+        }
+        self.metrics.amount_in.to_f64_lossy() / self.reserve_in.to_f64_lossy()
+    }
+}
+
+/// Evento `Swap` de um par V2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PairSwap {
+    pair: Address,
+    amount0_in: U256,
+    amount1_in: U256,
+    amount0_out: U256,
+    amount1_out: U256,
+}
+
+fn pair_swaps(logs: &[Log]) -> Vec<PairSwap> {
+    let topic = H256::from_slice(&keccak256("Swap(address,uint256,uint256,uint256,uint256,address)"));
+    logs.iter()
+        .filter(|log| log.topics.first() == Some(&topic) && log.data.len() >= 128)
+        .map(|log| {
+            let word = |i: usize| U256::from_big_endian(&log.data[i * 32..(i + 1) * 32]);
+            PairSwap { pair: log.address, amount0_in: word(0), amount1_in: word(1), amount0_out: word(2), amount1_out: word(3) }
+        })
+        .collect()
+}
+
+/// Associa cada hop do path ao próximo evento `Swap` cujo par negocia o mesmo par de tokens.
+/// Retorna, por hop, o índice do evento e se o token de entrada é o `token0`.
+fn match_hops(path: &[Address], pair_tokens: &[Option<(Address, Address)>]) -> Vec<Option<(usize, bool)>> {
+    let mut next = 0;
+    path.windows(2)
+        .map(|hop| {
+            let found = pair_tokens.iter().enumerate().skip(next).find_map(|(i, tokens)| match *tokens {
+                Some((token0, token1)) if token0 == hop[0] && token1 == hop[1] => Some((i, true)),
+                Some((token0, token1)) if token1 == hop[0] && token0 == hop[1] => Some((i, false)),
+                _ => None,
+            });
+            if let Some((i, _)) = found {
+                next = i + 1;
+            }
+            found
+        })
+        .collect()
+}
+
+fn evaluate_hop(swap: &PairSwap, zero_for_one: bool, hop: &[Address], reserves: (U256, U256)) -> V2Hop {
+    let (amount_in, actual_out, reserve_in, reserve_out) = if zero_for_one {
+        (swap.amount0_in, swap.amount1_out, reserves.0, reserves.1)
+    } else {
+        (swap.amount1_in, swap.amount0_out, reserves.1, reserves.0)
+    };
+    let net_in = amount_in * FEE_NUMERATOR / FEE_DENOMINATOR;
+    let expected_out = constant_product_output(net_in, reserve_in, reserve_out);
+    V2Hop {
+        metrics: HopMetrics {
+            pool: swap.pair,
+            token_in: hop[0],
+            token_out: hop[1],
+            amount_in,
+            expected_out,
+            actual_out,
+            slippage: hop_slippage(expected_out, actual_out),
+        },
+        reserve_in,
+        reserve_out,
+    }
+}
+
+/// Avalia cada hop de um path V2 com os eventos `Swap` da simulação e as reservas
+/// de cada par no bloco da chamada. Hops sem evento correspondente são omitidos.
+pub async fn v2_route_hops(
+    rpc_client: &dyn RpcProvider,
+    provider: &Provider<Http>,
+    call_block: Option<BlockId>,
+    logs: &[Log],
+    path: &[Address],
+) -> Result<Vec<V2Hop>> {
+    let swaps = pair_swaps(logs);
+    let mut tokens_by_pair = HashMap::new();
+    let mut pair_tokens = Vec::with_capacity(swaps.len());
+    for swap in &swaps {
+        if let Entry::Vacant(entry) = tokens_by_pair.entry(swap.pair) {
+            entry.insert(get_pair_tokens(rpc_client, swap.pair).await.ok());
+        }
+        pair_tokens.push(tokens_by_pair[&swap.pair]);
+    }
+
+    let get_reserves = AbiParser::default().parse_function("getReserves() returns (uint112,uint112,uint32)")?;
+    let mut hops = Vec::new();
+    for (hop, matched) in path.windows(2).zip(match_hops(path, &pair_tokens)) {
+        let Some((index, zero_for_one)) = matched else {
+            continue;
+        };
+        let swap = &swaps[index];
+        let tx_call = TransactionRequest::new().to(swap.pair).data(get_reserves.encode_input(&[])?);
+        let out = provider.call(&tx_call.into(), call_block).await.map_err(|e| anyhow!(e))?;
+        let reserves = get_reserves.decode_output(&out)?;
+        let reserve = |i: usize| reserves.get(i).and_then(|t| t.clone().into_uint()).ok_or_else(|| anyhow!("reserve{i} decode"));
+        hops.push(evaluate_hop(swap, zero_for_one, hop, (reserve(0)?, reserve(1)?)));
+    }
+    Ok(hops)
+}
+
+/// Hop em que a vítima move mais o preço; é onde um sanduíche rende mais
+pub fn most_exposed_hop(hops: &[V2Hop]) -> Option<&V2Hop> {
+    hops.iter().max_by(|a, b| a.exposure().total_cmp(&b.exposure()))
+}
+
+/// Converte o lucro do sanduíche, no token de entrada do par `(token_in, token_out,
+/// reserve_in, reserve_out)`, para wei do token nativo: direto quando a entrada é o
+/// WETH, pelo preço spot do par quando a saída é o WETH e `None` nos demais casos
+pub fn profit_in_native(profit: U256, pair: (Address, Address, U256, U256), weth: Option<Address>) -> Option<U256> {
+    let (token_in, token_out, reserve_in, reserve_out) = pair;
+    let weth = weth?;
+    if token_in == weth {
+        Some(profit)
+    } else if token_out == weth && !reserve_in.is_zero() {
+        Some(profit.checked_mul(reserve_out)? / reserve_in)
+    } else {
+        None
+    }
+}
+
+/// Custo de gas do sanduíche no bloco da simulação, com o lucro no par
+/// `target` convertido para wei pelo `weth`. Sem dados de taxa do node não há
+/// como descontar o gas: o resultado é `None` e a oportunidade não é viável.
+pub async fn sandwich_gas_cost(
+    provider: &Provider<Http>,
+    block: Option<u64>,
+    victim_gas_price: U256,
+    profit: U256,
+    target: (Address, Address, U256, U256),
+    weth: Option<Address>,
+) -> Option<GasCost> {
+    let mut cost = v2_sandwich_gas_cost(provider, block, victim_gas_price).await.ok()?;
+    cost.profit_in_native = profit_in_native(profit, target, weth);
+    Some(cost)
+}
+
+/// Endereço do WETH exposto pelo router (`WETH()`)
+pub async fn router_weth(rpc_client: &dyn RpcProvider, router: Address) -> Option<Address> {
+    let f = AbiParser::default().parse_function("WETH() view returns (address)").ok()?;
+    let out = rpc_client.call(router, f.encode_input(&[]).ok()?).await.ok()?;
+    f.decode_output(&out).ok()?.first()?.clone().into_address()
+}
+
+/// Entrada paga e saída recebida por `account`, medidas no token de cada ponta
+/// da rota: ETH nativo pelas pernas de WETH da simulação e os demais tokens
+/// pelos seus `Transfer`. Devolve também a rota com as pernas nativas.
+pub(crate) fn victim_amounts(logs: &[Log], path: &[Address], account: Address, weth: Option<Address>) -> (U256, U256, Vec<Address>) {
+    let legs = WethLegs::from_logs(logs, weth);
+    let route = legs.expand_route(path);
+    let actual_in = legs
+        .native_in(&route)
+        .or_else(|| path.first().map(|&token| transferred(logs, token, |from, _| from == account)))
+        .unwrap_or_default();
+    let actual_out = legs
+        .native_out(&route)
+        .or_else(|| path.last().map(|&token| transferred(logs, token, |_, to| to == account)))
+        .unwrap_or_default();
+    (actual_in, actual_out, route)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::metrics::route_slippage;
+    use ethers::abi::{encode, Token};
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    #[test]
+    fn match_hops_in_log_order() {
+        let path = [addr(1), addr(2), addr(3)];
+        // Um swap não relacionado antes e o segundo par com os tokens invertidos
+        let pair_tokens = [Some((addr(8), addr(9))), Some((addr(1), addr(2))), None, Some((addr(3), addr(2)))];
+        assert_eq!(match_hops(&path, &pair_tokens), vec![Some((1, true)), Some((3, false))]);
+        assert_eq!(match_hops(&[addr(2), addr(3), addr(1)], &pair_tokens), vec![Some((3, false)), None]);
+    }
+
+    #[test]
+    fn route_metrics_across_hops() {
+        let reserves = (U256::exp10(24), U256::exp10(24));
+        let path = [addr(1), addr(2), addr(3)];
+        let amount_in = U256::exp10(21);
+        let expected = constant_product_output(amount_in * 997u64 / 1000u64, reserves.0, reserves.1);
+        let first = PairSwap {
+            pair: addr(10),
+            amount0_in: amount_in,
+            amount1_in: U256::zero(),
+            amount0_out: U256::zero(),
+            amount1_out: expected,
+        };
+        let first = evaluate_hop(&first, true, &path[..2], reserves);
+        assert_eq!(first.metrics.slippage, 0.0);
+
+        // O segundo hop entrega 1% a menos que o esperado
+        let expected = constant_product_output(expected * 997u64 / 1000u64, reserves.1, reserves.0);
+        let second = PairSwap {
+            pair: addr(11),
+            amount0_in: U256::zero(),
+            amount1_in: first.metrics.actual_out,
+            amount0_out: expected * 99u64 / 100u64,
+            amount1_out: U256::zero(),
+        };
+        let second = evaluate_hop(&second, false, &path[1..], reserves);
+        assert_eq!((second.metrics.token_in, second.metrics.token_out), (addr(2), addr(3)));
+        assert!((second.metrics.slippage - 0.01).abs() < 1e-6);
+
+        let hops = [first, second];
+        let metrics: Vec<_> = hops.iter().map(|hop| hop.metrics.clone()).collect();
+        assert!((route_slippage(&metrics) - 0.01).abs() < 1e-6);
+        assert_eq!(most_exposed_hop(&hops).unwrap().metrics.pool, addr(10));
+    }
+
+    fn log(address: Address, signature: &str, topics: &[Address], value: u64) -> Log {
+        let mut all = vec![H256::from_slice(&keccak256(signature))];
+        all.extend(topics.iter().map(|a| H256::from(*a)));
+        Log { address, topics: all, data: encode(&[Token::Uint(value.into())]).into(), ..Default::default() }
+    }
+
+    #[test]
+    fn victim_amounts_follow_native_legs() {
+        let (weth, token, user, router, pair) = (addr(1), addr(2), addr(50), addr(60), addr(10));
+        let transfer = "Transfer(address,address,uint256)";
+        let swap = "Swap(address,uint256,uint256,uint256,uint256,address)";
+        // swapExactETHForTokens: o router embrulha o ETH e o token chega ao usuário
+        let logs = vec![
+            log(weth, "Deposit(address,uint256)", &[router], 1000),
+            log(weth, transfer, &[router, pair], 1000),
+            log(token, transfer, &[pair, user], 3000),
+            log(pair, swap, &[router, user], 0),
+        ];
+        let (actual_in, actual_out, route) = victim_amounts(&logs, &[weth, token], user, Some(weth));
+        assert_eq!((actual_in, actual_out), (1000.into(), 3000.into()));
+        assert_eq!(route, vec![Address::zero(), weth, token]);
+
+        // swapExactTokensForETH: a saída é o WETH desembrulhado, sem `Transfer` ao usuário
+        let logs = vec![
+            log(token, transfer, &[user, pair], 3000),
+            log(weth, transfer, &[pair, router], 990),
+            log(pair, swap, &[router, router], 0),
+            log(weth, "Withdrawal(address,uint256)", &[router], 990),
+        ];
+        let (actual_in, actual_out, route) = victim_amounts(&logs, &[token, weth], user, Some(weth));
+        assert_eq!((actual_in, actual_out), (3000.into(), 990.into()));
+        assert_eq!(route, vec![token, weth, Address::zero()]);
+    }
+
+    #[test]
+    fn profit_priced_against_weth() {
+        let weth = Some(addr(1));
+        let reserves = (U256::from(4_000u64), U256::from(1_000u64));
+        assert_eq!(profit_in_native(400.into(), (addr(1), addr(2), reserves.0, reserves.1), weth), Some(400.into()));
+        // Lucro em token 2 vendido pelo preço spot de 4 tokens por WETH
+        assert_eq!(profit_in_native(400.into(), (addr(2), addr(1), reserves.0, reserves.1), weth), Some(100.into()));
+        assert_eq!(profit_in_native(400.into(), (addr(2), addr(3), reserves.0, reserves.1), weth), None);
+        assert_eq!(profit_in_native(400.into(), (addr(1), addr(2), reserves.0, reserves.1), None), None);
+    }
+}
@@ -1,80 +1,461 @@
+use crate::core::metrics::{
+    concentrated_liquidity_input, concentrated_liquidity_output, concentrated_liquidity_reserve, hop_slippage, U256Ext,
+};
 use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::filters::CONCENTRATED_SWAP_TOPICS;
 use crate::simulation::SimulationOutcome;
-use crate::types::{AnalysisResult, Metrics, TransactionData};
+use crate::types::{AnalysisResult, HopMetrics, Metrics, TransactionData};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use ethereum_types::{Address, U256};
+use ethereum_types::{Address, H256, U256};
 use ethernity_core::traits::RpcProvider;
+use ethers::abi::{AbiParser, Token};
+use ethers::prelude::{Http, Middleware, Provider, TransactionRequest};
+use ethers::types::{BlockId, Log};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Detector para funções do Uniswap V3 Router.
+///
+/// `exactInput[Single]` e `exactOutput[Single]` são decodificados via ABI,
+/// cada hop do path é resolvido no pool da factory e a quantidade esperada é
+/// calculada com `slot0`/`liquidity` no bloco da simulação.
 pub struct UniswapV3Detector;
 
 #[async_trait]
 impl crate::detectors::VictimDetector for UniswapV3Detector {
-    fn supports(&self, router: &RouterInfo) -> bool {
-        router.factory.is_none()
+    fn supports(&self, _router: &RouterInfo) -> bool {
+        true
     }
 
     async fn analyze(
         &self,
-        _rpc_client: Arc<dyn RpcProvider>,
-        _rpc_endpoint: String,
+        rpc_client: Arc<dyn RpcProvider>,
+        rpc_endpoint: String,
         tx: TransactionData,
-        _block: Option<u64>,
-        _outcome: SimulationOutcome,
-        _router: RouterInfo,
+        block: Option<u64>,
+        outcome: SimulationOutcome,
+        router: RouterInfo,
     ) -> Result<AnalysisResult> {
-        let (func, f) = detect_swap_function(&tx.data).ok_or(anyhow!("unrecognized swap"))?;
-        if func != SwapFunction::SwapV3ExactIn {
+        if V3Swap::decode(&tx.data).is_some() {
+            return analyze_uniswap_v3(rpc_client, rpc_endpoint, tx, block, outcome, router).await;
+        }
+        let (func, _) = detect_swap_function(&tx.data).ok_or(anyhow!("unrecognized swap"))?;
+        if func != SwapFunction::SwapV3ExactIn || router.factory.is_some() {
             return Err(anyhow!("unsupported swap"));
         }
-        let tokens = f.decode_input(&tx.data[4..])?;
-        let params = tokens
-            .get(0)
-            .and_then(|t| t.clone().into_tuple())
-            .ok_or_else(|| anyhow!("invalid params"))?;
-        let token_in = params
-            .get(0)
-            .and_then(|t| t.clone().into_address())
-            .ok_or_else(|| anyhow!("tokenIn"))?;
-        let token_out = params
-            .get(1)
-            .and_then(|t| t.clone().into_address())
-            .ok_or_else(|| anyhow!("tokenOut"))?;
-        let through1 = params
-            .get(2)
-            .and_then(|t| t.clone().into_address())
-            .unwrap_or(Address::zero());
-        let through2 = params
-            .get(3)
-            .and_then(|t| t.clone().into_address())
-            .unwrap_or(Address::zero());
-
-        let mut token_route = vec![token_in];
-        if through1 != Address::zero() {
-            token_route.push(through1);
+        analyze_swap_v3_exact_in(tx)
+    }
+}
+
+/// Chamadas internas de `multicall(bytes[])` ou `multicall(uint256,bytes[])`
+pub(crate) fn multicall_calls(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let signature = match data.get(..4)? {
+        [0xac, 0x96, 0x50, 0xd8] => "multicall(bytes[])",
+        [0x5a, 0xe4, 0x01, 0xdc] => "multicall(uint256,bytes[])",
+        _ => return None,
+    };
+    let f = AbiParser::default().parse_function(signature).ok()?;
+    let tokens = f.decode_input(&data[4..]).ok()?;
+    tokens.last()?.clone().into_array()?.into_iter().map(Token::into_bytes).collect()
+}
+
+/// Path no formato `token | fee (uint24) | token ...` usado por Uniswap e Pancake V3
+pub fn decode_fee_path(path: &[u8]) -> Option<(Vec<Address>, Vec<u32>)> {
+    if path.len() < 43 || !(path.len() - 20).is_multiple_of(23) {
+        return None;
+    }
+    let mut route = vec![Address::from_slice(&path[..20])];
+    let mut fees = Vec::new();
+    for hop in path[20..].chunks(23) {
+        fees.push(u32::from_be_bytes([0, hop[0], hop[1], hop[2]]));
+        route.push(Address::from_slice(&hop[3..]));
+    }
+    Some((route, fees))
+}
+
+/// Swap do SwapRouter V3 decodificado do calldata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V3Swap {
+    pub function: SwapFunction,
+    pub exact_input: bool,
+    /// Tokens na ordem de execução, do token de entrada ao de saída
+    pub route: Vec<Address>,
+    /// Taxa de cada hop em centésimos de bip
+    pub fees: Vec<u32>,
+    /// `amountIn` em entradas exatas ou `amountOut` em saídas exatas
+    pub amount_specified: U256,
+    /// `amountOutMinimum` em entradas exatas ou `amountInMaximum` em saídas exatas
+    pub amount_limit: U256,
+}
+
+impl V3Swap {
+    /// Decodifica a chamada, procurando dentro de `multicall` quando necessário
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if let Some(calls) = multicall_calls(data) {
+            return calls.iter().find_map(|call| Self::decode(call));
+        }
+        let (function, f) = detect_swap_function(data)?;
+        let tokens = f.decode_input(&data[4..]).ok()?;
+        let params = tokens.first()?.clone().into_tuple()?;
+        let address = |i: usize| params.get(i)?.clone().into_address();
+        let uint = |i: usize| params.get(i)?.clone().into_uint();
+        let (exact_input, route, fees, amount_specified, amount_limit) = match function {
+            SwapFunction::ExactInputSingle | SwapFunction::ExactOutputSingle => (
+                function == SwapFunction::ExactInputSingle,
+                vec![address(0)?, address(1)?],
+                vec![uint(2)?.low_u32()],
+                uint(5)?,
+                uint(6)?,
+            ),
+            SwapFunction::ExactInput => {
+                let (route, fees) = decode_fee_path(&params.first()?.clone().into_bytes()?)?;
+                (true, route, fees, uint(3)?, uint(4)?)
+            }
+            // O path de saídas exatas é codificado do token de saída para o de entrada
+            SwapFunction::ExactOutput => {
+                let (mut route, mut fees) = decode_fee_path(&params.first()?.clone().into_bytes()?)?;
+                route.reverse();
+                fees.reverse();
+                (false, route, fees, uint(3)?, uint(4)?)
+            }
+            _ => return None,
+        };
+        Some(Self { function, exact_input, route, fees, amount_specified, amount_limit })
+    }
+}
+
+/// Análise estática de `swapV3ExactIn`, sem consulta ao estado dos pools
+fn analyze_swap_v3_exact_in(tx: TransactionData) -> Result<AnalysisResult> {
+    let (func, f) = detect_swap_function(&tx.data).ok_or(anyhow!("unrecognized swap"))?;
+    let tokens = f.decode_input(&tx.data[4..])?;
+    let params = tokens
+        .get(0)
+        .and_then(|t| t.clone().into_tuple())
+        .ok_or_else(|| anyhow!("invalid params"))?;
+    let token_in = params
+        .get(0)
+        .and_then(|t| t.clone().into_address())
+        .ok_or_else(|| anyhow!("tokenIn"))?;
+    let token_out = params
+        .get(1)
+        .and_then(|t| t.clone().into_address())
+        .ok_or_else(|| anyhow!("tokenOut"))?;
+    let through1 = params
+        .get(2)
+        .and_then(|t| t.clone().into_address())
+        .unwrap_or(Address::zero());
+    let through2 = params
+        .get(3)
+        .and_then(|t| t.clone().into_address())
+        .unwrap_or(Address::zero());
+
+    let mut token_route = vec![token_in];
+    if through1 != Address::zero() {
+        token_route.push(through1);
+    }
+    if through2 != Address::zero() {
+        token_route.push(through2);
+    }
+    token_route.push(token_out);
+
+    let metrics = Metrics {
+        swap_function: func,
+        token_route,
+        slippage: 0.0,
+        min_tokens_to_affect: U256::zero(),
+        potential_profit: U256::zero(),
+        router_address: tx.to,
+        router_name: Some(format!("{:#x}", tx.to)),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: true,
+        economically_viable: false,
+        simulated_tx: None,
+        metrics,
+    })
+}
+
+/// Deltas (`amount0`, `amount1`) do evento `Swap` do pool, do ponto de vista do pool
+fn pool_deltas(logs: &[Log], pool: Address) -> Option<(U256, U256)> {
+    let topics: Vec<H256> = CONCENTRATED_SWAP_TOPICS
+        .iter()
+        .map(|t| t.parse().expect("valid topic hex"))
+        .collect();
+    let log = logs.iter().find(|log| {
+        log.address == pool && log.topics.first().is_some_and(|t| topics.contains(t)) && log.data.len() >= 64
+    })?;
+    Some((U256::from_big_endian(&log.data[..32]), U256::from_big_endian(&log.data[32..64])))
+}
+
+fn abs_delta(delta: U256) -> U256 {
+    if delta.bit(255) {
+        (!delta).overflowing_add(U256::one()).0
+    } else {
+        delta
+    }
+}
+
+async fn call_word<M: Middleware>(provider: &M, to: Address, signature: &str, args: &[Token], block: Option<BlockId>) -> Result<U256> {
+    let f = AbiParser::default().parse_function(signature)?;
+    let tx_call = TransactionRequest::new().to(to).data(f.encode_input(args)?);
+    let out = provider.call(&tx_call.into(), block).await.map_err(|e| anyhow!(e.to_string()))?;
+    // Apenas a primeira palavra interessa (`sqrtPriceX96` em `slot0`, que varia entre forks)
+    out.get(..32).map(U256::from_big_endian).ok_or_else(|| anyhow!("{signature} decode"))
+}
+
+fn word_address(word: U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+/// Avalia cada hop de um path V3 a partir dos eventos `Swap` da simulação, na ordem dos logs.
+/// O pool de cada hop é reconhecido por `token0`/`token1`/`fee`, dispensando a factory;
+/// hops sem evento correspondente são omitidos.
+pub(crate) async fn concentrated_route_hops<M: Middleware>(
+    provider: &M,
+    call_block: Option<BlockId>,
+    logs: &[Log],
+    route: &[Address],
+    fees: &[u32],
+) -> Result<Vec<HopMetrics>> {
+    let topics: Vec<H256> = CONCENTRATED_SWAP_TOPICS
+        .iter()
+        .map(|t| t.parse().expect("valid topic hex"))
+        .collect();
+    let swaps: Vec<&Log> = logs
+        .iter()
+        .filter(|log| log.topics.first().is_some_and(|t| topics.contains(t)) && log.data.len() >= 64)
+        .collect();
+
+    const FEE_DENOMINATOR: u64 = 1_000_000;
+    let mut pool_keys = HashMap::new();
+    let mut hops = Vec::new();
+    let mut next = 0;
+    for (pair, fee) in route.windows(2).zip(fees) {
+        let (token0, token1) = if pair[0] < pair[1] { (pair[0], pair[1]) } else { (pair[1], pair[0]) };
+        let mut found = None;
+        for (i, log) in swaps.iter().enumerate().skip(next) {
+            let pool = log.address;
+            if let Entry::Vacant(entry) = pool_keys.entry(pool) {
+                let pool_token0 = word_address(call_word(provider, pool, "token0()", &[], call_block).await?);
+                let pool_token1 = word_address(call_word(provider, pool, "token1()", &[], call_block).await?);
+                let pool_fee = call_word(provider, pool, "fee()", &[], call_block).await?;
+                entry.insert((pool_token0, pool_token1, pool_fee));
+            }
+            if pool_keys[&pool] == (token0, token1, U256::from(*fee)) {
+                found = Some((i, *log));
+                break;
+            }
+        }
+        let Some((i, log)) = found else {
+            continue;
+        };
+        next = i + 1;
+
+        let zero_for_one = pair[0] == token0;
+        let amount0 = U256::from_big_endian(&log.data[..32]);
+        let amount1 = U256::from_big_endian(&log.data[32..64]);
+        // Deltas do ponto de vista do pool: positivo entrou, negativo saiu
+        let (amount_in, actual_out) = if zero_for_one { (amount0, abs_delta(amount1)) } else { (amount1, abs_delta(amount0)) };
+        let sqrt_price_x96 = call_word(provider, log.address, "slot0()", &[], call_block).await? & ((U256::one() << 160) - 1);
+        let liquidity = call_word(provider, log.address, "liquidity() view returns (uint128)", &[], call_block).await?;
+        let net_in = amount_in * (FEE_DENOMINATOR - *fee as u64) / FEE_DENOMINATOR;
+        let expected_out = concentrated_liquidity_output(net_in, sqrt_price_x96, liquidity, zero_for_one);
+        hops.push(HopMetrics {
+            pool: log.address,
+            token_in: pair[0],
+            token_out: pair[1],
+            amount_in,
+            expected_out,
+            actual_out,
+            slippage: hop_slippage(expected_out, actual_out),
+        });
+    }
+    Ok(hops)
+}
+
+pub async fn analyze_uniswap_v3(
+    rpc_client: Arc<dyn RpcProvider>,
+    rpc_endpoint: String,
+    tx: TransactionData,
+    block: Option<u64>,
+    outcome: SimulationOutcome,
+    router: RouterInfo,
+) -> Result<AnalysisResult> {
+    let swap = V3Swap::decode(&tx.data).ok_or_else(|| anyhow!("not a uniswap v3 swap"))?;
+    let provider = Provider::<Http>::try_from(rpc_endpoint)?.interval(Duration::from_millis(1));
+    let call_block = block.map(|b| BlockId::Number(b.into()));
+
+    let factory = match router.factory {
+        Some(factory) => factory,
+        None => {
+            let f = AbiParser::default().parse_function("factory() view returns (address)")?;
+            let out = rpc_client.call(tx.to, f.encode_input(&[])?).await.map_err(|e| anyhow!(e))?;
+            f.decode_output(&out)?
+                .first()
+                .and_then(|t| t.clone().into_address())
+                .ok_or_else(|| anyhow!("router does not expose factory"))?
+        }
+    };
+
+    // Resolve o pool de cada hop e lê o estado no bloco da simulação
+    let mut pools = Vec::with_capacity(swap.fees.len());
+    for (pair, fee) in swap.route.windows(2).zip(&swap.fees) {
+        let pool = call_word(
+            &provider,
+            factory,
+            "getPool(address,address,uint24) view returns (address)",
+            &[Token::Address(pair[0]), Token::Address(pair[1]), Token::Uint((*fee).into())],
+            call_block,
+        )
+        .await
+        .map(word_address)?;
+        if pool.is_zero() {
+            return Err(anyhow!("pool not found"));
+        }
+        let sqrt_price_x96 = call_word(&provider, pool, "slot0()", &[], call_block).await? & ((U256::one() << 160) - 1);
+        let liquidity = call_word(&provider, pool, "liquidity() view returns (uint128)", &[], call_block).await?;
+        pools.push((pool, pair[0] < pair[1], *fee, sqrt_price_x96, liquidity));
+    }
+
+    const FEE_DENOMINATOR: u64 = 1_000_000;
+    let (slippage, within_limit) = if swap.exact_input {
+        let mut expected_out = swap.amount_specified;
+        for (_, zero_for_one, fee, sqrt_price_x96, liquidity) in &pools {
+            let net_in = expected_out * (FEE_DENOMINATOR - *fee as u64) / FEE_DENOMINATOR;
+            expected_out = concentrated_liquidity_output(net_in, *sqrt_price_x96, *liquidity, *zero_for_one);
         }
-        if through2 != Address::zero() {
-            token_route.push(through2);
+        let (pool, zero_for_one, ..) = pools.last().expect("path with at least one hop");
+        let (amount0, amount1) = pool_deltas(&outcome.logs, *pool).ok_or_else(|| anyhow!("no uniswap v3 swap event"))?;
+        let actual_out = abs_delta(if *zero_for_one { amount1 } else { amount0 });
+        let slippage = if expected_out > actual_out && !expected_out.is_zero() {
+            (expected_out - actual_out).to_f64_lossy() / expected_out.to_f64_lossy()
+        } else {
+            0.0
+        };
+        (slippage, expected_out >= swap.amount_limit)
+    } else {
+        let mut expected_in = Some(swap.amount_specified);
+        for (_, zero_for_one, fee, sqrt_price_x96, liquidity) in pools.iter().rev() {
+            expected_in = expected_in
+                .and_then(|out| concentrated_liquidity_input(out, *sqrt_price_x96, *liquidity, *zero_for_one))
+                .map(|net_in| net_in * FEE_DENOMINATOR / (FEE_DENOMINATOR - *fee as u64));
         }
-        token_route.push(token_out);
-
-        let metrics = Metrics {
-            swap_function: func,
-            token_route,
-            slippage: 0.0,
-            min_tokens_to_affect: U256::zero(),
-            potential_profit: U256::zero(),
-            router_address: tx.to,
-            router_name: Some(format!("{:#x}", tx.to)),
+        let (pool, zero_for_one, ..) = pools[0];
+        let (amount0, amount1) = pool_deltas(&outcome.logs, pool).ok_or_else(|| anyhow!("no uniswap v3 swap event"))?;
+        let actual_in = abs_delta(if zero_for_one { amount0 } else { amount1 });
+        let slippage = match expected_in {
+            Some(expected_in) if actual_in > expected_in && !expected_in.is_zero() => {
+                (actual_in - expected_in).to_f64_lossy() / expected_in.to_f64_lossy()
+            }
+            _ => 0.0,
         };
+        (slippage, actual_in <= swap.amount_limit)
+    };
+
+    let (_, zero_for_one, _, sqrt_price_x96, liquidity) = pools[0];
+    let metrics = Metrics {
+        swap_function: swap.function,
+        token_route: swap.route,
+        slippage,
+        min_tokens_to_affect: concentrated_liquidity_reserve(sqrt_price_x96, liquidity, zero_for_one) / U256::from(100u64),
+        potential_profit: U256::zero(),
+        router_address: router.address,
+        router_name: Some(router.name.clone().unwrap_or_else(|| format!("{:#x}", router.address))),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: slippage > 0.0 && within_limit,
+        economically_viable: false,
+        simulated_tx: outcome.tx_hash,
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::utils::id;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn fee_path(tokens: &[Address], fees: &[u32]) -> Vec<u8> {
+        let mut path = tokens[0].as_bytes().to_vec();
+        for (token, fee) in tokens[1..].iter().zip(fees) {
+            path.extend_from_slice(&fee.to_be_bytes()[1..]);
+            path.extend_from_slice(token.as_bytes());
+        }
+        path
+    }
+
+    #[test]
+    fn decode_exact_output_reverses_path() {
+        // exactOutput codifica o path de tokenOut para tokenIn
+        let path = fee_path(&[addr(3), addr(2), addr(1)], &[500, 3000]);
+        let data = [
+            id("exactOutput((bytes,address,uint256,uint256,uint256))").to_vec(),
+            encode(&[Token::Tuple(vec![
+                Token::Bytes(path),
+                Token::Address(addr(9)),
+                Token::Uint(u64::MAX.into()),
+                Token::Uint(100.into()),
+                Token::Uint(120.into()),
+            ])]),
+        ]
+        .concat();
+
+        let swap = V3Swap::decode(&data).unwrap();
+        assert!(!swap.exact_input);
+        assert_eq!(swap.route, vec![addr(1), addr(2), addr(3)]);
+        assert_eq!(swap.fees, vec![3000, 500]);
+        assert_eq!((swap.amount_specified, swap.amount_limit), (100.into(), 120.into()));
+    }
+
+    #[test]
+    fn decode_exact_input_single_in_multicall() {
+        let single = [
+            id("exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))").to_vec(),
+            encode(&[Token::Tuple(vec![
+                Token::Address(addr(1)),
+                Token::Address(addr(2)),
+                Token::Uint(10000.into()),
+                Token::Address(addr(9)),
+                Token::Uint(u64::MAX.into()),
+                Token::Uint(1000.into()),
+                Token::Uint(950.into()),
+                Token::Uint(0.into()),
+            ])]),
+        ]
+        .concat();
+        let data = [id("multicall(bytes[])").to_vec(), encode(&[Token::Array(vec![Token::Bytes(single)])])].concat();
+
+        let swap = V3Swap::decode(&data).unwrap();
+        assert_eq!(swap.function, SwapFunction::ExactInputSingle);
+        assert!(swap.exact_input);
+        assert_eq!((swap.route, swap.fees), (vec![addr(1), addr(2)], vec![10000]));
+        assert_eq!((swap.amount_specified, swap.amount_limit), (1000.into(), 950.into()));
+    }
 
-        Ok(AnalysisResult {
-            potential_victim: true,
-            economically_viable: false,
-            simulated_tx: None,
-            metrics,
-        })
+    #[test]
+    fn fee_path_rejects_truncated_hops() {
+        let path = fee_path(&[addr(1), addr(2)], &[500]);
+        assert_eq!(decode_fee_path(&path), Some((vec![addr(1), addr(2)], vec![500])));
+        assert_eq!(decode_fee_path(&path[..42]), None);
     }
 }
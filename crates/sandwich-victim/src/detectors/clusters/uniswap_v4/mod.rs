@@ -1,21 +1,36 @@
-use crate::dex::RouterInfo;
+use crate::core::metrics::{
+    concentrated_liquidity_input, concentrated_liquidity_output, concentrated_liquidity_reserve, U256Ext,
+};
+use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::filters::UNISWAP_V4_SWAP_TOPIC;
 use crate::simulation::SimulationOutcome;
-use crate::types::{AnalysisResult, TransactionData};
+use crate::types::{AnalysisResult, Metrics, TransactionData};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use ethereum_types::{Address, H256, U256};
 use ethernity_core::traits::RpcProvider;
-use ethers::types::H256;
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::prelude::{Http, Middleware, Provider};
+use ethers::types::{BlockId, Log};
+use ethers::utils::keccak256;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-/// Detector para interações de swap na arquitetura Uniswap V4.
-/// Atualmente realiza apenas a identificação do swap através do evento
-/// `Swap(bytes32,address,int128,int128,uint160,uint128,int24,uint24)`.
-/// Caso identificado, o detector retorna um erro indicando que a
-/// implementação detalhada ainda não está disponível.
+/// Detector para swaps na arquitetura Uniswap V4 executados pelo Universal Router.
+///
+/// Decodifica o comando `V4_SWAP` (ações `SWAP_EXACT_IN[_SINGLE]` e
+/// `SWAP_EXACT_OUT[_SINGLE]`), lê `slot0` e a liquidez de cada pool no
+/// `PoolManager` no bloco da simulação e compara a quantidade esperada dentro
+/// do tick atual com a registrada nos eventos `Swap`.
 pub struct UniswapV4Detector;
 
-const UNISWAP_V4_SWAP_TOPIC: &str = "0xfbc3feb9544dba19141913965b8f867f5d0d220b898fc1b39e7d7111686a8f51";
+/// Comando `V4_SWAP` do Universal Router
+const V4_SWAP_COMMAND: u8 = 0x10;
+/// Slot do mapeamento `pools` no `PoolManager`
+const POOLS_SLOT: u64 = 6;
+/// Deslocamento de `liquidity` em `Pool.State`
+const LIQUIDITY_OFFSET: u64 = 3;
 
 #[async_trait]
 impl crate::detectors::VictimDetector for UniswapV4Detector {
@@ -26,17 +41,433 @@ impl crate::detectors::VictimDetector for UniswapV4Detector {
     async fn analyze(
         &self,
         _rpc_client: Arc<dyn RpcProvider>,
-        _rpc_endpoint: String,
-        _tx: TransactionData,
-        _block: Option<u64>,
+        rpc_endpoint: String,
+        tx: TransactionData,
+        block: Option<u64>,
         outcome: SimulationOutcome,
-        _router: RouterInfo,
+        router: RouterInfo,
     ) -> Result<AnalysisResult> {
-        let topic = H256::from_str(UNISWAP_V4_SWAP_TOPIC).expect("valid topic hex");
-        if outcome.logs.iter().any(|log| log.topics.get(0) == Some(&topic)) {
-            Err(anyhow!("uniswap v4 detector not implemented"))
+        analyze_uniswap_v4(rpc_endpoint, tx, block, outcome, router).await
+    }
+}
+
+/// Identificação de um pool V4
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolKey {
+    pub currency0: Address,
+    pub currency1: Address,
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: Address,
+}
+
+impl PoolKey {
+    fn from_token(token: Token) -> Option<Self> {
+        let fields = token.into_tuple()?;
+        Some(Self {
+            currency0: fields.first()?.clone().into_address()?,
+            currency1: fields.get(1)?.clone().into_address()?,
+            fee: fields.get(2)?.clone().into_uint()?.low_u32(),
+            tick_spacing: fields.get(3)?.clone().into_int()?.low_u32() as i32,
+            hooks: fields.get(4)?.clone().into_address()?,
+        })
+    }
+
+    /// `PoolId` = `keccak256(abi.encode(poolKey))`
+    pub fn id(&self) -> H256 {
+        let tick_spacing = if self.tick_spacing < 0 {
+            !U256::from(self.tick_spacing.unsigned_abs()) + U256::one()
         } else {
-            Err(anyhow!("no uniswap v4 swap event"))
+            U256::from(self.tick_spacing as u32)
+        };
+        H256(keccak256(encode(&[
+            Token::Address(self.currency0),
+            Token::Address(self.currency1),
+            Token::Uint(self.fee.into()),
+            Token::Int(tick_spacing),
+            Token::Address(self.hooks),
+        ])))
+    }
+}
+
+/// Um pool percorrido pelo swap, na ordem de execução
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V4Hop {
+    pub key: PoolKey,
+    pub zero_for_one: bool,
+}
+
+/// Swap decodificado de uma ação do `V4Router`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V4Swap {
+    pub exact_input: bool,
+    pub hops: Vec<V4Hop>,
+    pub token_route: Vec<Address>,
+    /// `amountIn` em entradas exatas ou `amountOut` em saídas exatas
+    pub amount_specified: U256,
+    /// `amountOutMinimum` em entradas exatas ou `amountInMaximum` em saídas exatas
+    pub amount_limit: U256,
+    /// Presente apenas na versão das structs single-hop que ainda tinha o limite
+    pub sqrt_price_limit: Option<U256>,
+}
+
+impl V4Swap {
+    /// Primeiro swap V4 no calldata de `execute` do Universal Router
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (function, f) = detect_swap_function(data)?;
+        if !matches!(function, SwapFunction::UniversalRouterSwap | SwapFunction::UniversalRouterSwapDeadline) {
+            return None;
         }
+        let tokens = f.decode_input(&data[4..]).ok()?;
+        let commands = tokens.first()?.clone().into_bytes()?;
+        let inputs = tokens.get(1)?.clone().into_array()?;
+        commands
+            .iter()
+            .zip(inputs)
+            .filter(|(command, _)| *command & 0x3f == V4_SWAP_COMMAND)
+            .find_map(|(_, input)| Self::decode_actions(&input.into_bytes()?))
+    }
+
+    /// Decodifica `abi.encode(bytes actions, bytes[] params)` do comando `V4_SWAP`
+    fn decode_actions(input: &[u8]) -> Option<Self> {
+        let tokens = decode(&[ParamType::Bytes, ParamType::Array(Box::new(ParamType::Bytes))], input).ok()?;
+        let actions = tokens.first()?.clone().into_bytes()?;
+        let params = tokens.get(1)?.clone().into_array()?;
+        actions.iter().zip(params).find_map(|(action, params)| {
+            let params = params.into_bytes()?;
+            match action {
+                0x06 => Self::decode_single(&params, true),
+                0x07 => Self::decode_path(&params, true),
+                0x08 => Self::decode_single(&params, false),
+                0x09 => Self::decode_path(&params, false),
+                _ => None,
+            }
+        })
+    }
+
+    /// `ExactInputSingleParams`/`ExactOutputSingleParams`, com ou sem `sqrtPriceLimitX96`
+    fn decode_single(params: &[u8], exact_input: bool) -> Option<Self> {
+        let pool_key = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(24),
+            ParamType::Int(24),
+            ParamType::Address,
+        ]);
+        let current = ParamType::Tuple(vec![
+            pool_key.clone(),
+            ParamType::Bool,
+            ParamType::Uint(128),
+            ParamType::Uint(128),
+            ParamType::Bytes,
+        ]);
+        let with_limit = ParamType::Tuple(vec![
+            pool_key,
+            ParamType::Bool,
+            ParamType::Uint(128),
+            ParamType::Uint(128),
+            ParamType::Uint(160),
+            ParamType::Bytes,
+        ]);
+        let fields = decode(&[current], params)
+            .or_else(|_| decode(&[with_limit], params))
+            .ok()?
+            .into_iter()
+            .next()?
+            .into_tuple()?;
+        let key = PoolKey::from_token(fields.first()?.clone())?;
+        let zero_for_one = fields.get(1)?.clone().into_bool()?;
+        let token_route = if zero_for_one {
+            vec![key.currency0, key.currency1]
+        } else {
+            vec![key.currency1, key.currency0]
+        };
+        Some(Self {
+            exact_input,
+            hops: vec![V4Hop { key, zero_for_one }],
+            token_route,
+            amount_specified: fields.get(2)?.clone().into_uint()?,
+            amount_limit: fields.get(3)?.clone().into_uint()?,
+            sqrt_price_limit: (fields.len() == 6).then(|| fields[4].clone().into_uint()).flatten(),
+        })
+    }
+
+    /// `ExactInputParams`/`ExactOutputParams` com `PathKey[]`
+    fn decode_path(params: &[u8], exact_input: bool) -> Option<Self> {
+        let path_key = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Uint(24),
+            ParamType::Int(24),
+            ParamType::Address,
+            ParamType::Bytes,
+        ]);
+        let layout = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Array(Box::new(path_key)),
+            ParamType::Uint(128),
+            ParamType::Uint(128),
+        ]);
+        let fields = decode(&[layout], params).ok()?.into_iter().next()?.into_tuple()?;
+        let currency = fields.first()?.clone().into_address()?;
+        let path = fields.get(1)?.clone().into_array()?;
+        if path.is_empty() {
+            return None;
+        }
+        // Entradas exatas partem de `currencyIn`; saídas exatas terminam em `currencyOut`
+        let mut token_route = Vec::with_capacity(path.len() + 1);
+        let mut pools = Vec::with_capacity(path.len());
+        for path_key in path {
+            let fields = path_key.into_tuple()?;
+            token_route.push(fields.first()?.clone().into_address()?);
+            pools.push((
+                fields.get(1)?.clone().into_uint()?.low_u32(),
+                fields.get(2)?.clone().into_int()?.low_u32() as i32,
+                fields.get(3)?.clone().into_address()?,
+            ));
+        }
+        if exact_input {
+            token_route.insert(0, currency);
+        } else {
+            token_route.push(currency);
+        }
+        let hops = token_route
+            .windows(2)
+            .zip(pools)
+            .map(|(pair, (fee, tick_spacing, hooks))| {
+                let zero_for_one = pair[0] < pair[1];
+                let (currency0, currency1) = if zero_for_one { (pair[0], pair[1]) } else { (pair[1], pair[0]) };
+                V4Hop { key: PoolKey { currency0, currency1, fee, tick_spacing, hooks }, zero_for_one }
+            })
+            .collect();
+        Some(Self {
+            exact_input,
+            hops,
+            token_route,
+            amount_specified: fields.get(2)?.clone().into_uint()?,
+            amount_limit: fields.get(3)?.clone().into_uint()?,
+            sqrt_price_limit: None,
+        })
+    }
+}
+
+/// Estado de um pool lido do `PoolManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolState {
+    pub sqrt_price_x96: U256,
+    pub lp_fee: u32,
+    pub liquidity: U256,
+}
+
+impl PoolState {
+    /// `slot0` empacota `sqrtPriceX96 | tick | protocolFee | lpFee`
+    pub fn from_words(slot0: H256, liquidity: H256) -> Self {
+        let slot0 = U256::from_big_endian(slot0.as_bytes());
+        Self {
+            sqrt_price_x96: slot0 & ((U256::one() << 160) - 1),
+            lp_fee: ((slot0 >> 208) & U256::from(0xff_ffffu32)).low_u32(),
+            liquidity: U256::from_big_endian(liquidity.as_bytes()) & U256::from(u128::MAX),
+        }
+    }
+}
+
+/// Slots de `slot0` e `liquidity` do pool no `PoolManager`
+pub fn pool_state_slots(id: H256) -> (H256, H256) {
+    let state = keccak256(encode(&[Token::FixedBytes(id.as_bytes().to_vec()), Token::Uint(POOLS_SLOT.into())]));
+    let liquidity = U256::from_big_endian(&state).overflowing_add(LIQUIDITY_OFFSET.into()).0;
+    let mut liquidity_slot = [0u8; 32];
+    liquidity.to_big_endian(&mut liquidity_slot);
+    (H256(state), H256(liquidity_slot))
+}
+
+/// Deltas (`amount0`, `amount1`) do evento `Swap` do pool `id`, do ponto de vista do chamador
+fn swap_deltas(logs: &[Log], id: H256) -> Option<(Address, U256, U256)> {
+    let topic = H256::from_str(UNISWAP_V4_SWAP_TOPIC).expect("valid topic hex");
+    let log = logs
+        .iter()
+        .find(|log| log.topics.first() == Some(&topic) && log.topics.get(1) == Some(&id) && log.data.len() >= 64)?;
+    Some((log.address, U256::from_big_endian(&log.data[..32]), U256::from_big_endian(&log.data[32..64])))
+}
+
+/// Valor absoluto de um int128 estendido para 256 bits
+fn abs_delta(delta: U256) -> U256 {
+    if delta.bit(255) {
+        (!delta).overflowing_add(U256::one()).0
+    } else {
+        delta
+    }
+}
+
+pub async fn analyze_uniswap_v4(
+    rpc_endpoint: String,
+    tx: TransactionData,
+    block: Option<u64>,
+    outcome: SimulationOutcome,
+    router: RouterInfo,
+) -> Result<AnalysisResult> {
+    let swap = V4Swap::decode(&tx.data).ok_or_else(|| anyhow!("no uniswap v4 swap command"))?;
+    let (swap_function, _) = detect_swap_function(&tx.data).ok_or_else(|| anyhow!("unrecognized swap"))?;
+    let first = &swap.hops[0];
+    let last = swap.hops.last().expect("non-empty hops");
+    let (pool_manager, first_amount0, first_amount1) =
+        swap_deltas(&outcome.logs, first.key.id()).ok_or_else(|| anyhow!("no uniswap v4 swap event"))?;
+    let (_, last_amount0, last_amount1) =
+        swap_deltas(&outcome.logs, last.key.id()).ok_or_else(|| anyhow!("no uniswap v4 swap event"))?;
+
+    let provider = Provider::<Http>::try_from(rpc_endpoint)?.interval(Duration::from_millis(1));
+    let call_block = block.map(|b| BlockId::Number(b.into()));
+    let mut states = Vec::with_capacity(swap.hops.len());
+    for hop in &swap.hops {
+        let (slot0, liquidity) = pool_state_slots(hop.key.id());
+        let slot0 = provider.get_storage_at(pool_manager, slot0, call_block).await.map_err(|e| anyhow!(e))?;
+        let liquidity = provider.get_storage_at(pool_manager, liquidity, call_block).await.map_err(|e| anyhow!(e))?;
+        states.push(PoolState::from_words(slot0, liquidity));
+    }
+
+    const FEE_DENOMINATOR: u64 = 1_000_000;
+    let (slippage, within_limit) = if swap.exact_input {
+        let mut expected_out = swap.amount_specified;
+        for (hop, state) in swap.hops.iter().zip(&states) {
+            let net_in = expected_out * (FEE_DENOMINATOR - state.lp_fee as u64) / FEE_DENOMINATOR;
+            expected_out = concentrated_liquidity_output(net_in, state.sqrt_price_x96, state.liquidity, hop.zero_for_one);
+        }
+        // Deltas positivos são o que o chamador recebe
+        let actual_out = abs_delta(if last.zero_for_one { last_amount1 } else { last_amount0 });
+        let slippage = if expected_out > actual_out && !expected_out.is_zero() {
+            (expected_out - actual_out).to_f64_lossy() / expected_out.to_f64_lossy()
+        } else {
+            0.0
+        };
+        (slippage, expected_out >= swap.amount_limit)
+    } else {
+        let mut expected_in = Some(swap.amount_specified);
+        for (hop, state) in swap.hops.iter().zip(&states).rev() {
+            expected_in = expected_in
+                .and_then(|out| concentrated_liquidity_input(out, state.sqrt_price_x96, state.liquidity, hop.zero_for_one))
+                .map(|net_in| net_in * FEE_DENOMINATOR / (FEE_DENOMINATOR - state.lp_fee as u64));
+        }
+        let actual_in = abs_delta(if first.zero_for_one { first_amount0 } else { first_amount1 });
+        let slippage = match expected_in {
+            Some(expected_in) if actual_in > expected_in && !expected_in.is_zero() => {
+                (actual_in - expected_in).to_f64_lossy() / expected_in.to_f64_lossy()
+            }
+            _ => 0.0,
+        };
+        (slippage, actual_in <= swap.amount_limit)
+    };
+
+    let metrics = Metrics {
+        swap_function,
+        token_route: swap.token_route.clone(),
+        slippage,
+        min_tokens_to_affect: concentrated_liquidity_reserve(states[0].sqrt_price_x96, states[0].liquidity, first.zero_for_one)
+            / U256::from(100u64),
+        potential_profit: U256::zero(),
+        router_address: router.address,
+        router_name: Some(router.name.clone().unwrap_or_else(|| format!("{:#x}", router.address))),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: slippage > 0.0 && within_limit,
+        economically_viable: false,
+        simulated_tx: outcome.tx_hash,
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::id;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn pool_key(currency0: Address, currency1: Address) -> Token {
+        Token::Tuple(vec![
+            Token::Address(currency0),
+            Token::Address(currency1),
+            Token::Uint(3000.into()),
+            Token::Int(60.into()),
+            Token::Address(Address::zero()),
+        ])
+    }
+
+    fn execute(actions: Vec<u8>, params: Vec<Vec<u8>>) -> Vec<u8> {
+        let v4_input = encode(&[Token::Bytes(actions), Token::Array(params.into_iter().map(Token::Bytes).collect())]);
+        [
+            id("execute(bytes,bytes[],uint256)").to_vec(),
+            encode(&[
+                Token::Bytes(vec![0x0b, V4_SWAP_COMMAND]),
+                Token::Array(vec![Token::Bytes(vec![0; 64]), Token::Bytes(v4_input)]),
+                Token::Uint(u64::MAX.into()),
+            ]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decode_exact_input_single() {
+        let params = encode(&[Token::Tuple(vec![
+            pool_key(addr(1), addr(2)),
+            Token::Bool(false),
+            Token::Uint(1000.into()),
+            Token::Uint(990.into()),
+            Token::Bytes(vec![]),
+        ])]);
+        // SWAP_EXACT_IN_SINGLE, SETTLE_ALL, TAKE_ALL
+        let data = execute(vec![0x06, 0x0c, 0x0f], vec![params, vec![], vec![]]);
+
+        let swap = V4Swap::decode(&data).unwrap();
+        assert!(swap.exact_input);
+        assert_eq!(swap.token_route, vec![addr(2), addr(1)]);
+        assert_eq!((swap.amount_specified, swap.amount_limit), (1000.into(), 990.into()));
+        assert_eq!(swap.sqrt_price_limit, None);
+        assert_eq!(swap.hops[0].key.tick_spacing, 60);
+        assert!(!swap.hops[0].zero_for_one);
+    }
+
+    #[test]
+    fn decode_exact_output_path() {
+        let path_key = |currency| {
+            Token::Tuple(vec![
+                Token::Address(currency),
+                Token::Uint(500.into()),
+                Token::Int(10.into()),
+                Token::Address(Address::zero()),
+                Token::Bytes(vec![]),
+            ])
+        };
+        let params = encode(&[Token::Tuple(vec![
+            Token::Address(addr(3)),
+            Token::Array(vec![path_key(addr(5)), path_key(addr(1))]),
+            Token::Uint(100.into()),
+            Token::Uint(120.into()),
+        ])]);
+        let data = execute(vec![0x09], vec![params]);
+
+        let swap = V4Swap::decode(&data).unwrap();
+        assert!(!swap.exact_input);
+        assert_eq!(swap.token_route, vec![addr(5), addr(1), addr(3)]);
+        assert_eq!(swap.hops.len(), 2);
+        assert_eq!((swap.hops[0].key.currency0, swap.hops[0].key.currency1), (addr(1), addr(5)));
+        assert!(!swap.hops[0].zero_for_one && swap.hops[1].zero_for_one);
+    }
+
+    #[test]
+    fn pool_state_unpacks_slot0() {
+        let sqrt_price = U256::one() << 96;
+        let slot0 = sqrt_price | (U256::from(3000u64) << 208) | (U256::from(100u64) << 184);
+        let mut word = [0u8; 32];
+        slot0.to_big_endian(&mut word);
+        let state = PoolState::from_words(H256(word), H256::from_low_u64_be(1_000_000));
+
+        assert_eq!(state.sqrt_price_x96, sqrt_price);
+        assert_eq!(state.lp_fee, 3000);
+        assert_eq!(state.liquidity, 1_000_000.into());
     }
 }
@@ -0,0 +1,290 @@
+use crate::core::metrics::U256Ext;
+use crate::dex::{detect_swap_function, RouterInfo, SwapFunction};
+use crate::filters::{FilterPipeline, SwapLogFilter};
+use crate::simulation::SimulationOutcome;
+use crate::types::{AnalysisResult, Metrics, TransactionData};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use ethernity_core::traits::RpcProvider;
+use ethers::abi::{decode, ParamType, Token};
+use ethers::prelude::{Http, Middleware, Provider};
+use ethers::types::{BlockId, BlockNumber};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Detector para execuções de ordens UniswapX (`execute`/`executeBatch` nos reactors).
+///
+/// O usuário assina uma ordem holandesa cuja saída decai de `startAmount` até
+/// `endAmount`; o filler entrega a saída já resolvida e costuma buscar a
+/// liquidez em pools públicos dentro da mesma transação. O slippage reportado
+/// é o envelope de decaimento da primeira saída, e a ordem só é considerada
+/// sanduichável quando a execução passa por um swap em AMM.
+pub struct UniswapXDetector;
+
+#[async_trait]
+impl crate::detectors::VictimDetector for UniswapXDetector {
+    fn supports(&self, _router: &RouterInfo) -> bool {
+        true
+    }
+
+    async fn analyze(
+        &self,
+        _rpc_client: Arc<dyn RpcProvider>,
+        rpc_endpoint: String,
+        tx: TransactionData,
+        block: Option<u64>,
+        outcome: SimulationOutcome,
+        _router: RouterInfo,
+    ) -> Result<AnalysisResult> {
+        analyze_uniswapx(rpc_endpoint, tx, block, outcome).await
+    }
+}
+
+/// Quantidade que decai linearmente entre `decay_start` e `decay_end`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DutchAmount {
+    pub token: Address,
+    pub start_amount: U256,
+    pub end_amount: U256,
+    /// Destinatário; ausente na entrada da ordem
+    pub recipient: Option<Address>,
+}
+
+impl DutchAmount {
+    /// Fração entre os extremos do decaimento em relação ao valor inicial
+    pub fn envelope(&self) -> f64 {
+        let (high, low) = if self.start_amount >= self.end_amount {
+            (self.start_amount, self.end_amount)
+        } else {
+            (self.end_amount, self.start_amount)
+        };
+        if self.start_amount.is_zero() {
+            return 0.0;
+        }
+        (high - low).to_f64_lossy() / self.start_amount.to_f64_lossy()
+    }
+}
+
+/// Ordem holandesa do UniswapX (`DutchOrder` ou `ExclusiveDutchOrder`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DutchOrder {
+    pub reactor: Address,
+    pub swapper: Address,
+    pub deadline: U256,
+    pub decay_start: U256,
+    pub decay_end: U256,
+    /// Filler com exclusividade até `decay_start`, só em `ExclusiveDutchOrder`
+    pub exclusive_filler: Option<Address>,
+    pub input: DutchAmount,
+    pub outputs: Vec<DutchAmount>,
+}
+
+impl DutchOrder {
+    /// Decodifica o campo `order` de um `SignedOrder`
+    pub fn decode(order: &[u8]) -> Option<Self> {
+        let order_info = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Address,
+            ParamType::Bytes,
+        ]);
+        let input = ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)]);
+        let outputs = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Address,
+        ])));
+        let exclusive = ParamType::Tuple(vec![
+            order_info.clone(),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Address,
+            ParamType::Uint(256),
+            input.clone(),
+            outputs.clone(),
+        ]);
+        let legacy = ParamType::Tuple(vec![order_info, ParamType::Uint(256), ParamType::Uint(256), input, outputs]);
+
+        let (fields, exclusive_filler) = match decode(&[exclusive], order) {
+            Ok(tokens) => {
+                let mut fields = tokens.into_iter().next()?.into_tuple()?;
+                // exclusiveFiller e exclusivityOverrideBps ficam entre o decaimento e a entrada
+                let filler = fields.get(3)?.clone().into_address()?;
+                fields.drain(3..5);
+                (fields, Some(filler).filter(|f| !f.is_zero()))
+            }
+            Err(_) => (decode(&[legacy], order).ok()?.into_iter().next()?.into_tuple()?, None),
+        };
+        let [info, decay_start, decay_end, input, outputs]: [Token; 5] = fields.try_into().ok()?;
+        let info = info.into_tuple()?;
+        let amount = |token: Token| -> Option<DutchAmount> {
+            let fields = token.into_tuple()?;
+            Some(DutchAmount {
+                token: fields.first()?.clone().into_address()?,
+                start_amount: fields.get(1)?.clone().into_uint()?,
+                end_amount: fields.get(2)?.clone().into_uint()?,
+                recipient: fields.get(3).and_then(|t| t.clone().into_address()),
+            })
+        };
+        let order = Self {
+            reactor: info.first()?.clone().into_address()?,
+            swapper: info.get(1)?.clone().into_address()?,
+            deadline: info.get(3)?.clone().into_uint()?,
+            decay_start: decay_start.into_uint()?,
+            decay_end: decay_end.into_uint()?,
+            exclusive_filler,
+            input: amount(input)?,
+            outputs: outputs.into_array()?.into_iter().map(amount).collect::<Option<_>>()?,
+        };
+        (order.decay_end >= order.decay_start && !order.outputs.is_empty()).then_some(order)
+    }
+
+    /// Quantidade resolvida no instante `timestamp`, como em `DutchDecayLib`
+    pub fn resolve(&self, amount: &DutchAmount, timestamp: U256) -> U256 {
+        if timestamp >= self.decay_end || self.decay_end == self.decay_start {
+            return amount.end_amount;
+        }
+        if timestamp <= self.decay_start {
+            return amount.start_amount;
+        }
+        let elapsed = timestamp - self.decay_start;
+        let duration = self.decay_end - self.decay_start;
+        if amount.start_amount > amount.end_amount {
+            amount.start_amount - (amount.start_amount - amount.end_amount) * elapsed / duration
+        } else {
+            amount.start_amount + (amount.end_amount - amount.start_amount) * elapsed / duration
+        }
+    }
+}
+
+/// Ordens contidas em `execute`/`executeBatch` (com ou sem callback)
+pub fn decode_orders(data: &[u8]) -> Option<(SwapFunction, Vec<DutchOrder>)> {
+    let (function, f) = detect_swap_function(data)?;
+    if !matches!(function, SwapFunction::UniswapXExecute | SwapFunction::UniswapXExecuteBatch) {
+        return None;
+    }
+    let tokens = f.decode_input(&data[4..]).ok()?;
+    let signed_orders = match tokens.into_iter().next()? {
+        Token::Array(orders) => orders,
+        order => vec![order],
+    };
+    let orders = signed_orders
+        .into_iter()
+        .filter_map(|signed| DutchOrder::decode(&signed.into_tuple()?.first()?.clone().into_bytes()?))
+        .collect::<Vec<_>>();
+    (!orders.is_empty()).then_some((function, orders))
+}
+
+pub async fn analyze_uniswapx(
+    rpc_endpoint: String,
+    tx: TransactionData,
+    block: Option<u64>,
+    outcome: SimulationOutcome,
+) -> Result<AnalysisResult> {
+    let (function, orders) = decode_orders(&tx.data).ok_or_else(|| anyhow!("not a uniswapx fill"))?;
+    let order = &orders[0];
+    let output = &order.outputs[0];
+
+    // Fills feitos com inventário próprio do filler não tocam pools públicos
+    let tx_hash = outcome.tx_hash;
+    let touches_amm = FilterPipeline::new().push(SwapLogFilter).run(outcome).is_some();
+
+    let provider = Provider::<Http>::try_from(rpc_endpoint)?.interval(Duration::from_millis(1));
+    let block_id = BlockId::Number(block.map(|b| b.into()).unwrap_or(BlockNumber::Latest));
+    let timestamp = match provider.get_block(block_id).await {
+        Ok(Some(block)) => block.timestamp,
+        _ => order.decay_start,
+    };
+    let resolved_out = order.resolve(output, timestamp);
+    let slippage = output.envelope();
+
+    let metrics = Metrics {
+        swap_function: function,
+        token_route: vec![order.input.token, output.token],
+        slippage,
+        // Margem entre a saída resolvida e o piso da ordem
+        min_tokens_to_affect: resolved_out.saturating_sub(output.end_amount),
+        potential_profit: U256::zero(),
+        router_address: tx.to,
+        router_name: Some(format!("{:#x}", order.reactor)),
+        hops: Vec::new(),
+        fee_on_transfer: false,
+        gas_cost: None,
+        sandwich: None,
+    };
+
+    Ok(AnalysisResult {
+        potential_victim: touches_amm && slippage > 0.0,
+        economically_viable: false,
+        simulated_tx: tx_hash,
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::utils::id;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn exclusive_order(filler: Address) -> Vec<u8> {
+        encode(&[Token::Tuple(vec![
+            Token::Tuple(vec![
+                Token::Address(addr(1)),
+                Token::Address(addr(2)),
+                Token::Uint(7.into()),
+                Token::Uint(2000.into()),
+                Token::Address(Address::zero()),
+                Token::Bytes(vec![]),
+            ]),
+            Token::Uint(1000.into()),
+            Token::Uint(1100.into()),
+            Token::Address(filler),
+            Token::Uint(100.into()),
+            Token::Tuple(vec![Token::Address(addr(3)), Token::Uint(500.into()), Token::Uint(500.into())]),
+            Token::Array(vec![Token::Tuple(vec![
+                Token::Address(addr(4)),
+                Token::Uint(1000.into()),
+                Token::Uint(900.into()),
+                Token::Address(addr(2)),
+            ])]),
+        ])])
+    }
+
+    #[test]
+    fn decode_execute_batch_with_exclusive_order() {
+        let signed = |filler| Token::Tuple(vec![Token::Bytes(exclusive_order(filler)), Token::Bytes(vec![1; 65])]);
+        let data = [
+            id("executeBatch((bytes,bytes)[])").to_vec(),
+            encode(&[Token::Array(vec![signed(addr(9)), signed(Address::zero())])]),
+        ]
+        .concat();
+
+        let (function, orders) = decode_orders(&data).unwrap();
+        assert_eq!(function, SwapFunction::UniswapXExecuteBatch);
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].exclusive_filler, Some(addr(9)));
+        assert_eq!(orders[1].exclusive_filler, None);
+        assert_eq!((orders[0].reactor, orders[0].swapper), (addr(1), addr(2)));
+        assert_eq!(orders[0].outputs[0].recipient, Some(addr(2)));
+        assert!((orders[0].outputs[0].envelope() - 0.1).abs() < 1e-9);
+        assert_eq!(orders[0].input.envelope(), 0.0);
+    }
+
+    #[test]
+    fn resolve_linear_decay() {
+        let order = DutchOrder::decode(&exclusive_order(Address::zero())).unwrap();
+        let output = &order.outputs[0];
+        assert_eq!(order.resolve(output, 900.into()), 1000.into());
+        assert_eq!(order.resolve(output, 1050.into()), 950.into());
+        assert_eq!(order.resolve(output, 1200.into()), 900.into());
+    }
+}
@@ -7,6 +7,9 @@ use ethernity_core::traits::RpcProvider;
 use std::sync::Arc;
 
 pub mod clusters;
+pub mod report;
+pub use report::{AnalysisReport, DetectorAttempt, DetectorOutcome};
+
 use clusters::uniswap_v2::{UniswapV2Detector, SwapV2ExactInDetector};
 use clusters::uniswap_v3::UniswapV3Detector;
 use clusters::uniswap_v4::UniswapV4Detector;
@@ -15,9 +18,19 @@ use clusters::smart_router::custom::SmartRouterUniswapV3Detector;
 use clusters::oneinch_generic_router::OneInchGenericRouterDetector;
 use clusters::oneinch_aggregation_router_v6::OneInchAggregationRouterV6Detector;
 use clusters::uniswap_universal_router::UniswapUniversalRouterDetector;
+use clusters::curve::CurveDetector;
+use clusters::kyberswap::KyberSwapDetector;
+use clusters::pancake_v3_algebra::PancakeV3AlgebraDetector;
+use clusters::uniswapx::UniswapXDetector;
 
 #[async_trait]
 pub trait VictimDetector: Send + Sync {
+    /// Nome usado no [`AnalysisReport`]; por padrão, o nome do tipo
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
     fn supports(&self, router: &RouterInfo) -> bool;
     async fn analyze(
         &self,
@@ -39,12 +52,16 @@ impl Default for DetectorRegistry {
         Self {
             detectors: vec![
                 Box::new(UniswapV3Detector),
+                Box::new(PancakeV3AlgebraDetector),
                 Box::new(SmartRouterUniswapV3Detector),
                 Box::new(MulticallBytesDetector),
                 Box::new(OneInchGenericRouterDetector),
                 Box::new(OneInchAggregationRouterV6Detector),
-                Box::new(UniswapUniversalRouterDetector),
+                Box::new(KyberSwapDetector),
                 Box::new(UniswapV4Detector),
+                Box::new(UniswapUniversalRouterDetector),
+                Box::new(UniswapXDetector),
+                Box::new(CurveDetector),
                 Box::new(UniswapV2Detector),
                 Box::new(SwapV2ExactInDetector),
             ],
@@ -53,6 +70,11 @@ impl Default for DetectorRegistry {
 }
 
 impl DetectorRegistry {
+    /// Registro com os detectores informados, consultados na ordem dada
+    pub fn new(detectors: Vec<Box<dyn VictimDetector>>) -> Self {
+        Self { detectors }
+    }
+
     pub async fn analyze(
         &self,
         rpc_client: Arc<dyn RpcProvider>,
@@ -62,9 +84,27 @@ impl DetectorRegistry {
         outcome: SimulationOutcome,
         router: RouterInfo,
     ) -> Result<AnalysisResult> {
-        let mut last_err = None;
+        self.analyze_report(rpc_client, rpc_endpoint, tx, block, outcome, router)
+            .await
+            .into_result()
+    }
+
+    /// Como [`analyze`](Self::analyze), mas registra cada detector consultado,
+    /// a decisão de `supports` e o erro ou resultado obtido
+    pub async fn analyze_report(
+        &self,
+        rpc_client: Arc<dyn RpcProvider>,
+        rpc_endpoint: String,
+        tx: TransactionData,
+        block: Option<u64>,
+        outcome: SimulationOutcome,
+        router: RouterInfo,
+    ) -> AnalysisReport {
+        let mut report = AnalysisReport::default();
         for d in &self.detectors {
-            if d.supports(&router) {
+            let attempt = if !d.supports(&router) {
+                DetectorOutcome::Unsupported
+            } else {
                 match d
                     .analyze(
                         rpc_client.clone(),
@@ -76,15 +116,16 @@ impl DetectorRegistry {
                     )
                     .await
                 {
-                    Ok(res) => return Ok(res),
-                    Err(e) => last_err = Some(e),
+                    Ok(res) => DetectorOutcome::Analyzed(Box::new(res)),
+                    Err(e) => DetectorOutcome::Failed(e),
                 }
+            };
+            let done = matches!(attempt, DetectorOutcome::Analyzed(_));
+            report.attempts.push(DetectorAttempt { detector: d.name(), outcome: attempt });
+            if done {
+                break;
             }
         }
-        if let Some(err) = last_err {
-            Err(err)
-        } else {
-            Err(anyhow::anyhow!("unsupported router"))
-        }
+        report
     }
 }
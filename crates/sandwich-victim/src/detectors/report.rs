@@ -0,0 +1,230 @@
+use crate::types::AnalysisResult;
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+/// O que aconteceu com um detector durante a análise
+#[derive(Debug)]
+pub enum DetectorOutcome {
+    /// `supports` recusou o router
+    Unsupported,
+    /// O detector aceitou o router mas a análise falhou
+    Failed(anyhow::Error),
+    /// O detector produziu o resultado da análise
+    Analyzed(Box<AnalysisResult>),
+}
+
+/// Tentativa de um detector, na ordem do registro
+#[derive(Debug)]
+pub struct DetectorAttempt {
+    pub detector: &'static str,
+    pub outcome: DetectorOutcome,
+}
+
+/// Relatório de [`DetectorRegistry::analyze_report`](super::DetectorRegistry::analyze_report).
+///
+/// Lista todos os detectores consultados até o primeiro que produziu um
+/// resultado, com a decisão de `supports` e o erro de cada um que falhou.
+#[derive(Debug, Default)]
+pub struct AnalysisReport {
+    pub attempts: Vec<DetectorAttempt>,
+}
+
+impl AnalysisReport {
+    /// Resultado do detector que concluiu a análise, se houver
+    pub fn result(&self) -> Option<&AnalysisResult> {
+        self.attempts.iter().find_map(|attempt| match &attempt.outcome {
+            DetectorOutcome::Analyzed(result) => Some(result.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Detectores que aceitaram o router mas falharam, com os respectivos erros
+    pub fn failures(&self) -> impl Iterator<Item = (&'static str, &anyhow::Error)> {
+        self.attempts.iter().filter_map(|attempt| match &attempt.outcome {
+            DetectorOutcome::Failed(err) => Some((attempt.detector, err)),
+            _ => None,
+        })
+    }
+
+    /// Converte no resultado de [`DetectorRegistry::analyze`](super::DetectorRegistry::analyze):
+    /// o resultado encontrado, o erro do último detector que falhou ou `unsupported router`
+    pub fn into_result(self) -> Result<AnalysisResult> {
+        let mut last_err = None;
+        for attempt in self.attempts {
+            match attempt.outcome {
+                DetectorOutcome::Analyzed(result) => return Ok(*result),
+                DetectorOutcome::Failed(err) => last_err = Some(err),
+                DetectorOutcome::Unsupported => {}
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("unsupported router")))
+    }
+}
+
+impl fmt::Display for AnalysisReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for attempt in &self.attempts {
+            match &attempt.outcome {
+                DetectorOutcome::Unsupported => writeln!(f, "{}: unsupported", attempt.detector)?,
+                DetectorOutcome::Failed(err) => writeln!(f, "{}: failed: {err:#}", attempt.detector)?,
+                DetectorOutcome::Analyzed(result) => writeln!(
+                    f,
+                    "{}: analyzed (potential victim: {})",
+                    attempt.detector, result.potential_victim
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detectors::{DetectorRegistry, VictimDetector};
+    use crate::dex::{RouterInfo, SwapFunction};
+    use crate::simulation::SimulationOutcome;
+    use crate::types::{Metrics, TransactionData};
+    use async_trait::async_trait;
+    use ethereum_types::{Address, H256, U256};
+    use ethernity_core::error::Result as CoreResult;
+    use ethernity_core::traits::RpcProvider;
+    use ethernity_core::types::TransactionHash;
+    use std::sync::Arc;
+
+    struct NoopProvider;
+
+    #[async_trait]
+    impl RpcProvider for NoopProvider {
+        async fn get_transaction_trace(&self, _tx_hash: TransactionHash) -> CoreResult<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_transaction_receipt(&self, _tx_hash: TransactionHash) -> CoreResult<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_code(&self, _address: Address) -> CoreResult<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, _to: Address, _data: Vec<u8>) -> CoreResult<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        async fn get_block_number(&self) -> CoreResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_hash(&self, _block_number: u64) -> CoreResult<H256> {
+            Ok(H256::zero())
+        }
+    }
+
+    /// Aceita routers com factory e sempre falha
+    struct FailingDetector;
+
+    /// Aceita qualquer router e sempre conclui a análise
+    struct AnalyzingDetector;
+
+    #[async_trait]
+    impl VictimDetector for FailingDetector {
+        fn supports(&self, router: &RouterInfo) -> bool {
+            router.factory.is_some()
+        }
+
+        async fn analyze(
+            &self,
+            _rpc_client: Arc<dyn RpcProvider>,
+            _rpc_endpoint: String,
+            _tx: TransactionData,
+            _block: Option<u64>,
+            _outcome: SimulationOutcome,
+            _router: RouterInfo,
+        ) -> anyhow::Result<AnalysisResult> {
+            Err(anyhow!("not a swap"))
+        }
+    }
+
+    #[async_trait]
+    impl VictimDetector for AnalyzingDetector {
+        fn supports(&self, _router: &RouterInfo) -> bool {
+            true
+        }
+
+        async fn analyze(
+            &self,
+            _rpc_client: Arc<dyn RpcProvider>,
+            _rpc_endpoint: String,
+            tx: TransactionData,
+            _block: Option<u64>,
+            _outcome: SimulationOutcome,
+            _router: RouterInfo,
+        ) -> anyhow::Result<AnalysisResult> {
+            Ok(AnalysisResult {
+                potential_victim: true,
+                economically_viable: false,
+                simulated_tx: None,
+                metrics: Metrics {
+                    swap_function: SwapFunction::SwapExactTokensForTokens,
+                    token_route: Vec::new(),
+                    slippage: 0.01,
+                    min_tokens_to_affect: U256::zero(),
+                    potential_profit: U256::zero(),
+                    router_address: tx.to,
+                    router_name: None,
+                    hops: Vec::new(),
+                    fee_on_transfer: false,
+                    gas_cost: None,
+                    sandwich: None,
+                },
+            })
+        }
+    }
+
+    async fn run(detectors: Vec<Box<dyn VictimDetector>>, factory: Option<Address>) -> AnalysisReport {
+        let tx = TransactionData {
+            from: Address::zero(),
+            to: Address::from_low_u64_be(1),
+            data: vec![],
+            value: U256::zero(),
+            gas: 0,
+            gas_price: U256::zero(),
+            nonce: U256::zero(),
+        };
+        let router = RouterInfo { address: tx.to, name: None, factory };
+        let outcome = SimulationOutcome { tx_hash: None, logs: vec![] };
+        DetectorRegistry::new(detectors)
+            .analyze_report(Arc::new(NoopProvider), String::new(), tx, None, outcome, router)
+            .await
+    }
+
+    #[tokio::test]
+    async fn report_lists_every_attempt_until_a_result() {
+        let report = run(
+            vec![Box::new(FailingDetector), Box::new(AnalyzingDetector), Box::new(FailingDetector)],
+            Some(Address::from_low_u64_be(2)),
+        )
+        .await;
+
+        assert_eq!(report.attempts.len(), 2);
+        assert_eq!(report.attempts[0].detector, "FailingDetector");
+        let failures: Vec<_> = report.failures().map(|(name, err)| (name, err.to_string())).collect();
+        assert_eq!(failures, vec![("FailingDetector", "not a swap".to_string())]);
+        assert!(report.result().is_some_and(|result| result.potential_victim));
+        assert_eq!(
+            report.to_string(),
+            "FailingDetector: failed: not a swap\nAnalyzingDetector: analyzed (potential victim: true)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn report_without_result_keeps_registry_errors() {
+        let report = run(vec![Box::new(FailingDetector)], None).await;
+        assert!(matches!(report.attempts[0].outcome, DetectorOutcome::Unsupported));
+        assert_eq!(report.into_result().unwrap_err().to_string(), "unsupported router");
+
+        let report = run(vec![Box::new(FailingDetector)], Some(Address::zero())).await;
+        assert_eq!(report.into_result().unwrap_err().to_string(), "not a swap");
+    }
+}
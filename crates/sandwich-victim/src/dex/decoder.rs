@@ -1,4 +1,4 @@
-use ethers::abi::{AbiParser, Function};
+use ethers::abi::{AbiParser, Function, HumanReadableParser};
 use serde::{Deserialize, Serialize};
 
 /// Funções de swap suportadas em routers compatíveis com Uniswap V2
@@ -26,6 +26,24 @@ pub enum SwapFunction {
     UniversalRouterSwap,
     /// `UniversalRouter.execute(bytes,bytes[],uint256)`
     UniversalRouterSwapDeadline,
+    /// `exactInputSingle` sem `deadline` (PancakeSwap V3 SmartRouter, SwapRouter02)
+    PancakeV3ExactInputSingle,
+    /// `exactInput` sem `deadline` (PancakeSwap V3 SmartRouter, SwapRouter02)
+    PancakeV3ExactInput,
+    /// `exactInputSingle` de routers Algebra, sem `fee` (QuickSwap V3, Camelot V3)
+    AlgebraExactInputSingle,
+    /// `execute`/`executeWithCallback` de reactors UniswapX
+    UniswapXExecute,
+    /// `executeBatch`/`executeBatchWithCallback` de reactors UniswapX
+    UniswapXExecuteBatch,
+    /// `exchange` de pools Curve (StableSwap e crypto pools)
+    CurveExchange,
+    /// `exchange_underlying` de pools Curve de lending e meta pools
+    CurveExchangeUnderlying,
+    /// `swap`/`swapGeneric` do MetaAggregationRouterV2 da KyberSwap
+    KyberSwap,
+    /// `swapSimpleMode` do MetaAggregationRouterV2 da KyberSwap
+    KyberSwapSimpleMode,
 }
 
 impl SwapFunction {
@@ -86,6 +104,23 @@ impl SwapFunction {
             SwapFunction::AggregationRouterV6Swap => "aggregationSwap(bytes)",
             SwapFunction::UniversalRouterSwap => "execute(bytes,bytes[])",
             SwapFunction::UniversalRouterSwapDeadline => "execute(bytes,bytes[],uint256)",
+            SwapFunction::PancakeV3ExactInputSingle => {
+                "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))"
+            }
+            SwapFunction::PancakeV3ExactInput => "exactInput((bytes,address,uint256,uint256))",
+            SwapFunction::AlgebraExactInputSingle => {
+                "exactInputSingle((address,address,address,uint256,uint256,uint256,uint160))"
+            }
+            SwapFunction::UniswapXExecute => "execute((bytes,bytes))",
+            SwapFunction::UniswapXExecuteBatch => "executeBatch((bytes,bytes)[])",
+            SwapFunction::CurveExchange => "exchange(int128,int128,uint256,uint256)",
+            SwapFunction::CurveExchangeUnderlying => "exchange_underlying(int128,int128,uint256,uint256)",
+            SwapFunction::KyberSwap => {
+                "swap((address,address,bytes,(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes),bytes))"
+            }
+            SwapFunction::KyberSwapSimpleMode => {
+                "swapSimpleMode(address,(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes),bytes,bytes)"
+            }
         }
     }
 }
@@ -125,6 +160,47 @@ pub fn detect_swap_function(data: &[u8]) -> Option<(SwapFunction, Function)> {
             SwapFunction::UniversalRouterSwapDeadline,
             "execute(bytes,bytes[],uint256)",
         ),
+        // PancakeSwap V3 SmartRouter e Algebra (QuickSwap V3, Camelot V3)
+        (
+            SwapFunction::PancakeV3ExactInputSingle,
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+        ),
+        (SwapFunction::PancakeV3ExactInput, "exactInput((bytes,address,uint256,uint256))"),
+        (
+            SwapFunction::AlgebraExactInputSingle,
+            "exactInputSingle((address,address,address,uint256,uint256,uint256,uint160))",
+        ),
+        (
+            SwapFunction::AlgebraExactInputSingle,
+            "exactInputSingleSupportingFeeOnTransferTokens((address,address,address,uint256,uint256,uint256,uint160))",
+        ),
+        // UniswapX reactors (SignedOrder = (bytes order, bytes sig))
+        (SwapFunction::UniswapXExecute, "execute((bytes,bytes))"),
+        (SwapFunction::UniswapXExecute, "executeWithCallback((bytes,bytes),bytes)"),
+        (SwapFunction::UniswapXExecuteBatch, "executeBatch((bytes,bytes)[])"),
+        (SwapFunction::UniswapXExecuteBatch, "executeBatchWithCallback((bytes,bytes)[],bytes)"),
+        // Curve pools: índices int128 (StableSwap) ou uint256 (crypto pools), com receiver opcional
+        (SwapFunction::CurveExchange, "exchange(int128,int128,uint256,uint256)"),
+        (SwapFunction::CurveExchange, "exchange(int128,int128,uint256,uint256,address)"),
+        (SwapFunction::CurveExchange, "exchange(uint256,uint256,uint256,uint256)"),
+        (SwapFunction::CurveExchange, "exchange(uint256,uint256,uint256,uint256,address)"),
+        (SwapFunction::CurveExchangeUnderlying, "exchange_underlying(int128,int128,uint256,uint256)"),
+        (SwapFunction::CurveExchangeUnderlying, "exchange_underlying(int128,int128,uint256,uint256,address)"),
+        (SwapFunction::CurveExchangeUnderlying, "exchange_underlying(uint256,uint256,uint256,uint256)"),
+        (SwapFunction::CurveExchangeUnderlying, "exchange_underlying(uint256,uint256,uint256,uint256,address)"),
+        // KyberSwap MetaAggregationRouterV2 (SwapExecutionParams e SwapDescriptionV2)
+        (
+            SwapFunction::KyberSwap,
+            "swap((address,address,bytes,(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes),bytes))",
+        ),
+        (
+            SwapFunction::KyberSwap,
+            "swapGeneric((address,address,bytes,(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes),bytes))",
+        ),
+        (
+            SwapFunction::KyberSwapSimpleMode,
+            "swapSimpleMode(address,(address,address,address[],uint256[],address[],uint256[],address,uint256,uint256,uint256,bytes),bytes,bytes)",
+        ),
         // 1inch Aggregation Router V6
         (
             SwapFunction::AggregationRouterV6Swap,
@@ -165,8 +241,11 @@ pub fn detect_swap_function(data: &[u8]) -> Option<(SwapFunction, Function)> {
     ];
     for (func, sig) in mappings {
         if selector == &ethers::utils::id(sig)[..4] {
-            let mut parser = AbiParser::default();
-            let f = parser.parse_function(sig).expect("abi parse");
+            // AbiParser não aceita structs como parâmetros (`f((address,uint24))`)
+            let f = AbiParser::default()
+                .parse_function(sig)
+                .or_else(|_| HumanReadableParser::parse_function(sig).map_err(|e| e.to_string()))
+                .expect("abi parse");
             return Some((func, f));
         }
     }
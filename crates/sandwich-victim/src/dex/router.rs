@@ -56,7 +56,13 @@ where
     })
 }
 
-/// Tenta extrair o endereço do router a partir dos logs de simulação
+/// Tenta extrair o endereço do router a partir dos logs de simulação.
+///
+/// Sem um `Swap` no formato V2, usa o `sender` de um `Swap` de liquidez
+/// concentrada (Uniswap V3, Pancake V3, Algebra) ou do `PoolManager` do V4
+/// e, por último, o pool Curve
+/// que emitiu `TokenExchange`, já que nesses pools o próprio pool recebe a
+/// chamada de swap.
 pub fn router_from_logs(logs: &[Log]) -> Option<Address> {
     let swap_sig = H256::from_slice(
         keccak256("Swap(address,uint256,uint256,uint256,uint256,address)").as_slice(),
@@ -66,7 +72,30 @@ pub fn router_from_logs(logs: &[Log]) -> Option<Address> {
             return Some(Address::from_slice(&log.topics[1].as_bytes()[12..]));
         }
     }
-    None
+    let concentrated_topics: Vec<H256> = crate::filters::CONCENTRATED_SWAP_TOPICS
+        .iter()
+        .map(|t| t.parse().expect("valid topic hex"))
+        .collect();
+    if let Some(log) = logs
+        .iter()
+        .find(|log| log.topics.first().is_some_and(|t| concentrated_topics.contains(t)) && log.topics.len() > 1)
+    {
+        return Some(Address::from_slice(&log.topics[1].as_bytes()[12..]));
+    }
+    let v4_topic: H256 = crate::filters::UNISWAP_V4_SWAP_TOPIC.parse().expect("valid topic hex");
+    if let Some(log) = logs
+        .iter()
+        .find(|log| log.topics.first() == Some(&v4_topic) && log.topics.len() > 2)
+    {
+        return Some(Address::from_slice(&log.topics[2].as_bytes()[12..]));
+    }
+    let curve_topics: Vec<H256> = crate::filters::CURVE_EXCHANGE_TOPICS
+        .iter()
+        .map(|t| t.parse().expect("valid topic hex"))
+        .collect();
+    logs.iter()
+        .find(|log| log.topics.first().is_some_and(|t| curve_topics.contains(t)))
+        .map(|log| log.address)
 }
 
 #[cfg(test)]
@@ -136,6 +165,28 @@ mod tests {
         assert_eq!(router_from_logs(&[log]), None);
     }
 
+    #[test]
+    fn router_from_concentrated_swap_log() {
+        let router = Address::from_low_u64_be(88);
+        let swap_sig = H256::from_slice(
+            keccak256("Swap(address,address,int256,int256,uint160,uint128,int24,uint128,uint128)").as_slice(),
+        );
+        let log = Log { topics: vec![swap_sig, router.into(), H256::zero()], ..Default::default() };
+
+        assert_eq!(router_from_logs(&[log]), Some(router));
+    }
+
+    #[test]
+    fn router_from_curve_exchange_log() {
+        let pool = Address::from_low_u64_be(77);
+        let exchange_sig = H256::from_slice(
+            keccak256("TokenExchange(address,int128,uint256,int128,uint256)").as_slice(),
+        );
+        let log = Log { address: pool, topics: vec![exchange_sig, H256::zero()], ..Default::default() };
+
+        assert_eq!(router_from_logs(&[log]), Some(pool));
+    }
+
     #[tokio::test]
     async fn identify_router_returns_factory() {
         let factory = Address::from_low_u64_be(1);
@@ -0,0 +1,231 @@
+use crate::detectors::clusters::kyberswap::{is_native_token, KyberSwapCall};
+use crate::detectors::clusters::pancake_v3_algebra::ExactInputSwap;
+use crate::detectors::clusters::uniswap_v3::V3Swap;
+use crate::dex::{detect_swap_function, SwapFunction};
+use crate::types::TransactionData;
+use anyhow::{anyhow, Result};
+use ethereum_types::{Address, U256};
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::prelude::{Middleware, TransactionRequest};
+use ethers::types::BlockId;
+use ethers::utils::id;
+
+/// Multicall3, implantado no mesmo endereço nas redes suportadas
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Quantidade que o remetente precisa ter para a transação não reverter logo
+/// na transferência da entrada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendRequirement {
+    /// Token de entrada; `None` para o token nativo
+    pub token: Option<Address>,
+    pub amount: U256,
+    /// Quem puxa os tokens com `transferFrom`; `None` quando não há allowance a verificar
+    pub spender: Option<Address>,
+}
+
+/// Saldo e allowance do remetente no bloco; `None` quando a consulta falhou
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Funding {
+    pub balance: Option<U256>,
+    pub allowance: Option<U256>,
+}
+
+impl SpendRequirement {
+    /// Entrada exata gasta pela transação. Swaps de saída exata (a entrada só
+    /// é conhecida na execução) e chamadas sem decodificação conhecida, como
+    /// o Universal Router com Permit2, não geram requisito.
+    pub fn from_tx(tx: &TransactionData) -> Option<Self> {
+        let native = Self { token: None, amount: tx.value, spender: None };
+        let requirement = if let Some(call) = KyberSwapCall::decode(&tx.data) {
+            if is_native_token(call.desc.src_token) {
+                native
+            } else {
+                // Com permit o router recebe a aprovação na própria chamada
+                let spender = call.desc.permit.is_empty().then_some(tx.to);
+                Self { token: Some(call.desc.src_token), amount: call.desc.amount, spender }
+            }
+        } else if let Some((token, amount)) = V3Swap::decode(&tx.data)
+            .filter(|swap| swap.exact_input)
+            .map(|swap| (swap.route[0], swap.amount_specified))
+            .or_else(|| ExactInputSwap::decode(&tx.data).map(|swap| (swap.route[0], swap.amount_in)))
+        {
+            // Com `msg.value` o router embrulha o ETH em vez de puxar o WETH
+            if tx.value.is_zero() {
+                Self { token: Some(token), amount, spender: Some(tx.to) }
+            } else {
+                native
+            }
+        } else {
+            let (function, f) = detect_swap_function(&tx.data)?;
+            match function {
+                SwapFunction::SwapExactTokensForTokens
+                | SwapFunction::SwapExactTokensForETH
+                | SwapFunction::SwapExactTokensForTokensSupportingFeeOnTransferTokens
+                | SwapFunction::SwapExactTokensForETHSupportingFeeOnTransferTokens => {
+                    let tokens = f.decode_input(&tx.data[4..]).ok()?;
+                    let amount = tokens.first()?.clone().into_uint()?;
+                    let token = tokens.get(2)?.clone().into_array()?.first()?.clone().into_address()?;
+                    Self { token: Some(token), amount, spender: Some(tx.to) }
+                }
+                SwapFunction::SwapExactETHForTokens
+                | SwapFunction::ETHForExactTokens
+                | SwapFunction::SwapExactETHForTokensSupportingFeeOnTransferTokens
+                | SwapFunction::SwapExactETHForTokensSupportingFeeOnTransferTokensWithReferrer => native,
+                _ => return None,
+            }
+        };
+        (!requirement.amount.is_zero()).then_some(requirement)
+    }
+
+    /// Consultas que falharam não reprovam a transação
+    pub fn is_met(&self, funding: &Funding) -> bool {
+        let covers = |value: Option<U256>| value.is_none_or(|v| v >= self.amount);
+        covers(funding.balance) && (self.spender.is_none() || covers(funding.allowance))
+    }
+}
+
+/// Lê saldo e allowance de `owner` no bloco com uma única chamada `aggregate3`
+/// ao Multicall3. O saldo nativo vem de `getEthBalance` do próprio Multicall3.
+pub async fn fetch_funding<M: Middleware + 'static>(
+    provider: &M,
+    requirement: &SpendRequirement,
+    owner: Address,
+    block: Option<u64>,
+) -> Result<Funding> {
+    let multicall: Address = MULTICALL3_ADDRESS.parse()?;
+    let mut calls = vec![match requirement.token {
+        Some(token) => (token, [id("balanceOf(address)").to_vec(), encode(&[Token::Address(owner)])].concat()),
+        None => (multicall, [id("getEthBalance(address)").to_vec(), encode(&[Token::Address(owner)])].concat()),
+    }];
+    if let (Some(token), Some(spender)) = (requirement.token, requirement.spender) {
+        let args = encode(&[Token::Address(owner), Token::Address(spender)]);
+        calls.push((token, [id("allowance(address,address)").to_vec(), args].concat()));
+    }
+    let calls = calls
+        .into_iter()
+        .map(|(target, data)| Token::Tuple(vec![Token::Address(target), Token::Bool(true), Token::Bytes(data)]))
+        .collect();
+    let data = [id("aggregate3((address,bool,bytes)[])").to_vec(), encode(&[Token::Array(calls)])].concat();
+    let out = provider
+        .call(
+            &TransactionRequest::new().to(multicall).data(data).into(),
+            block.map(|b| BlockId::Number(b.into())),
+        )
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let results = decode_aggregate3(&out).ok_or_else(|| anyhow!("invalid aggregate3 output"))?;
+    let value = |i: usize| results.get(i).and_then(|v| *v);
+    Ok(Funding { balance: value(0), allowance: value(1) })
+}
+
+/// Retorno `(bool success, bytes returnData)[]` do `aggregate3` como `uint256`
+fn decode_aggregate3(out: &[u8]) -> Option<Vec<Option<U256>>> {
+    let result = ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])));
+    let results = decode(&[result], out).ok()?.pop()?.into_array()?;
+    results
+        .into_iter()
+        .map(|result| {
+            let mut fields = result.into_tuple()?.into_iter();
+            let success = fields.next()?.into_bool()?;
+            let data = fields.next()?.into_bytes()?;
+            Some((success && data.len() >= 32).then(|| U256::from_big_endian(&data[..32])))
+        })
+        .collect()
+}
+
+/// Verifica se o remetente tem a entrada e a allowance do router no bloco.
+/// Transações sem requisito conhecido são consideradas financiadas.
+pub async fn sender_is_funded<M: Middleware + 'static>(provider: &M, tx: &TransactionData, block: Option<u64>) -> Result<bool> {
+    let Some(requirement) = SpendRequirement::from_tx(tx) else {
+        return Ok(true);
+    };
+    let funding = fetch_funding(provider, &requirement, tx.from, block).await?;
+    Ok(requirement.is_met(&funding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::Provider;
+    use ethers::types::Bytes;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn tx(data: Vec<u8>, value: u64) -> TransactionData {
+        TransactionData {
+            from: addr(50),
+            to: addr(99),
+            data,
+            value: value.into(),
+            gas: 0,
+            gas_price: U256::zero(),
+            nonce: U256::zero(),
+        }
+    }
+
+    fn v2_call(signature: &str, args: &[Token]) -> Vec<u8> {
+        [id(signature).to_vec(), encode(args)].concat()
+    }
+
+    #[test]
+    fn requirement_from_v2_calls() {
+        let path = Token::Array(vec![Token::Address(addr(1)), Token::Address(addr(2))]);
+        let data = v2_call(
+            "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+            &[Token::Uint(1000.into()), Token::Uint(900.into()), path.clone(), Token::Address(addr(50)), Token::Uint(0.into())],
+        );
+        assert_eq!(
+            SpendRequirement::from_tx(&tx(data, 0)),
+            Some(SpendRequirement { token: Some(addr(1)), amount: 1000.into(), spender: Some(addr(99)) })
+        );
+
+        let data = v2_call(
+            "swapExactETHForTokens(uint256,address[],address,uint256)",
+            &[Token::Uint(900.into()), path.clone(), Token::Address(addr(50)), Token::Uint(0.into())],
+        );
+        assert_eq!(
+            SpendRequirement::from_tx(&tx(data, 7)),
+            Some(SpendRequirement { token: None, amount: 7.into(), spender: None })
+        );
+
+        let data = v2_call(
+            "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+            &[Token::Uint(900.into()), Token::Uint(1000.into()), path, Token::Address(addr(50)), Token::Uint(0.into())],
+        );
+        assert_eq!(SpendRequirement::from_tx(&tx(data, 0)), None);
+    }
+
+    #[test]
+    fn requirement_checks_balance_and_allowance() {
+        let requirement = SpendRequirement { token: Some(addr(1)), amount: 100.into(), spender: Some(addr(99)) };
+        let funding = |balance: Option<u64>, allowance: Option<u64>| Funding {
+            balance: balance.map(U256::from),
+            allowance: allowance.map(U256::from),
+        };
+        assert!(requirement.is_met(&funding(Some(100), Some(u64::MAX))));
+        assert!(!requirement.is_met(&funding(Some(99), Some(u64::MAX))));
+        assert!(!requirement.is_met(&funding(Some(100), Some(0))));
+        assert!(requirement.is_met(&funding(None, None)));
+
+        let native = SpendRequirement { spender: None, token: None, ..requirement };
+        assert!(native.is_met(&funding(Some(100), Some(0))));
+    }
+
+    #[tokio::test]
+    async fn funding_read_through_multicall() {
+        let (provider, mock) = Provider::mocked();
+        let results = Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(encode(&[Token::Uint(1000.into())]))]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+        ]);
+        mock.push::<Bytes, Bytes>(encode(&[results]).into()).unwrap();
+
+        let requirement = SpendRequirement { token: Some(addr(1)), amount: 500.into(), spender: Some(addr(99)) };
+        let funding = fetch_funding(&provider, &requirement, addr(50), Some(1)).await.unwrap();
+        assert_eq!(funding, Funding { balance: Some(1000.into()), allowance: None });
+        assert!(requirement.is_met(&funding));
+    }
+}
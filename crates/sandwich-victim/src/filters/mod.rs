@@ -1,7 +1,12 @@
 use crate::simulation::SimulationOutcome;
-use ethers::types::H256;
+use ethers::types::{Log, H256};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
 use std::str::FromStr;
 
+mod funding;
+pub use funding::{fetch_funding, sender_is_funded, Funding, SpendRequirement, MULTICALL3_ADDRESS};
+
 /// Trait para filtros de resultados de simulação
 pub trait Filter: Send + Sync {
     /// Aplica o filtro ao resultado.
@@ -40,15 +45,46 @@ impl FilterPipeline {
     }
 }
 
-/// Filtro que verifica a presença do evento `Swap` nos logs
+/// Filtro que verifica a presença do evento `Swap` (V2, V4 ou de liquidez concentrada) ou
+/// `TokenExchange` da Curve nos logs
 pub struct SwapLogFilter;
 
 const SWAP_TOPIC: &str = "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
 
+/// `TokenExchange`/`TokenExchangeUnderlying` dos pools StableSwap e `TokenExchange` dos crypto pools
+pub(crate) const CURVE_EXCHANGE_TOPICS: [&str; 3] = [
+    "0x8b3e96f2b889fa771c53c981b40daf005f63f637f1869f707052d15a3dd97140",
+    "0xd013ca23e77a65003c2c659c5442c00c805371b7fc1ebd4c206c41d1536bd90b",
+    "0xb2e76ae99761dc136e598d4a629bb347eccb9532a5f8bbd72e18467c3c34cc98",
+];
+
+/// `Swap` de pools Uniswap V3/Algebra e da PancakeSwap V3 (com as taxas de protocolo)
+pub(crate) const CONCENTRATED_SWAP_TOPICS: [&str; 2] = [
+    "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67",
+    "0x19b47279256b2a23a1665c810c8d55a1758940ee09377d4f8d26497a3577dc83",
+];
+
+/// `Swap(bytes32,address,int128,int128,uint160,uint128,int24,uint24)` emitido pelo `PoolManager` do V4
+pub(crate) const UNISWAP_V4_SWAP_TOPIC: &str = "0x40e9cecb9f5f1f1c5b9c97dec2917b7ee92e57ba5563708daca94dd84ad7112f";
+
+/// Tópicos de todos os eventos de swap aceitos por [`SwapLogFilter`]
+static SWAP_LOG_TOPICS: Lazy<HashSet<H256>> = Lazy::new(|| {
+    std::iter::once(SWAP_TOPIC)
+        .chain(CONCENTRATED_SWAP_TOPICS)
+        .chain([UNISWAP_V4_SWAP_TOPIC])
+        .chain(CURVE_EXCHANGE_TOPICS)
+        .map(|t| H256::from_str(t).expect("valid topic hex"))
+        .collect()
+});
+
+/// Verifica se o log é um dos eventos de swap aceitos por [`SwapLogFilter`]
+pub(crate) fn is_swap_log(log: &Log) -> bool {
+    log.topics.first().is_some_and(|t| SWAP_LOG_TOPICS.contains(t))
+}
+
 impl Filter for SwapLogFilter {
     fn apply(&self, outcome: SimulationOutcome) -> Option<SimulationOutcome> {
-        let topic = H256::from_str(SWAP_TOPIC).expect("valid topic hex");
-        if outcome.logs.iter().any(|log| log.topics.get(0) == Some(&topic)) {
+        if outcome.logs.iter().any(is_swap_log) {
             Some(outcome)
         } else {
             None
@@ -59,7 +95,7 @@ impl Filter for SwapLogFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::types::{Address, Bytes, Log};
+    use ethers::types::{Address, Bytes};
 
     fn outcome_with_topics(topics: Vec<H256>) -> SimulationOutcome {
         let log = Log {
@@ -85,6 +121,13 @@ mod tests {
         assert!(pipeline.run(outcome).is_some());
     }
 
+    #[test]
+    fn filter_passes_curve_exchange() {
+        let outcome = outcome_with_topics(vec![H256::from_str(CURVE_EXCHANGE_TOPICS[0]).unwrap()]);
+        let pipeline = FilterPipeline::new().push(SwapLogFilter);
+        assert!(pipeline.run(outcome).is_some());
+    }
+
     #[test]
     fn filter_discards_when_topic_absent() {
         let outcome = outcome_with_topics(vec![H256::zero()]);
@@ -24,6 +24,17 @@ fn build_event_map() -> HashMap<H256, Event> {
         let topic = H256::from_slice(keccak256(ev.abi_signature()).as_slice());
         map.insert(topic, ev);
     }
+    // Curve TokenExchange/TokenExchangeUnderlying (StableSwap com índices int128, crypto pools com uint256)
+    for sig in [
+        "event TokenExchange(address indexed buyer,int128 sold_id,uint256 tokens_sold,int128 bought_id,uint256 tokens_bought)",
+        "event TokenExchangeUnderlying(address indexed buyer,int128 sold_id,uint256 tokens_sold,int128 bought_id,uint256 tokens_bought)",
+        "event TokenExchange(address indexed buyer,uint256 sold_id,uint256 tokens_sold,uint256 bought_id,uint256 tokens_bought)",
+    ] {
+        if let Ok(ev) = parser.parse_event(sig) {
+            let topic = H256::from_slice(keccak256(ev.abi_signature()).as_slice());
+            map.insert(topic, ev);
+        }
+    }
     map
 }
 
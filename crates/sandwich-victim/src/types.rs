@@ -2,11 +2,20 @@ use ethereum_types::{Address, U256, H256};
 use crate::dex::SwapFunction;
 use serde::{Deserialize, Serialize};
 
+/// Versão do JSON schema de [`AnalysisResult`]; muda a cada alteração incompatível
+pub const ANALYSIS_RESULT_SCHEMA_VERSION: u32 = 1;
+
+/// JSON schema de [`AnalysisResult`] serializado com `serde_json`, publicado em
+/// `schema/analysis_result.v1.json`. Quantidades (`U256`), endereços, hashes e
+/// bytes são strings hexadecimais com prefixo `0x`.
+pub const ANALYSIS_RESULT_SCHEMA: &str = include_str!("../schema/analysis_result.v1.json");
+
 /// Dados básicos de uma transação Ethereum
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionData {
     pub from: Address,
     pub to: Address,
+    #[serde(with = "hex_bytes")]
     pub data: Vec<u8>,
     pub value: U256,
     pub gas: u64,
@@ -14,16 +23,92 @@ pub struct TransactionData {
     pub nonce: U256,
 }
 
+impl TransactionData {
+    /// Converte uma transação pendente (ex.: da assinatura `newPendingTransactions`);
+    /// `None` para criação de contrato
+    pub fn from_pending(tx: &ethers::types::Transaction) -> Option<Self> {
+        Some(Self {
+            from: tx.from,
+            to: tx.to?,
+            data: tx.input.to_vec(),
+            value: tx.value,
+            gas: tx.gas.low_u64(),
+            gas_price: tx.gas_price.unwrap_or_default(),
+            nonce: tx.nonce,
+        })
+    }
+}
+
 /// Métricas extraídas durante a simulação
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metrics {
     pub swap_function: SwapFunction,
+    /// Tokens na ordem da troca; ETH nativo aparece como `core::NATIVE_TOKEN`
+    /// antes do WETH embrulhado e depois do desembrulhado
     pub token_route: Vec<Address>,
     pub slippage: f64,
     pub min_tokens_to_affect: U256,
     pub potential_profit: U256,
     pub router_address: Address,
     pub router_name: Option<String>,
+    /// Quantidades de cada hop da rota; vazio quando o detector não avalia hop a hop
+    #[serde(default)]
+    pub hops: Vec<HopMetrics>,
+    /// A rota passa por um token que cobra taxa na transferência; as quantidades
+    /// esperadas já descontam a taxa medida na simulação
+    #[serde(default)]
+    pub fee_on_transfer: bool,
+    /// Custo de gas do frontrun e do backrun usado em `economically_viable`;
+    /// `None` quando o detector não estima lucro ou sem dados de taxa do node
+    #[serde(default)]
+    pub gas_cost: Option<GasCost>,
+    /// Frontrun que maximiza `potential_profit` no par mais exposto
+    #[serde(default)]
+    pub sandwich: Option<SandwichPlan>,
+}
+
+/// Dimensionamento ótimo de um sanduíche em um pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandwichPlan {
+    /// Entrada do frontrun, no token de entrada da vítima
+    pub frontrun_in: U256,
+    /// Saída do backrun menos a entrada do frontrun, no mesmo token
+    pub profit: U256,
+    /// Saída da vítima entre o frontrun e o backrun
+    pub victim_out: U256,
+    /// Preço de execução da vítima nessa situação (saída por unidade de entrada)
+    pub victim_execution_price: f64,
+}
+
+/// Custo estimado de gas do sanduíche, a partir das taxas do node no momento da análise
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasCost {
+    pub frontrun_gas: u64,
+    pub backrun_gas: u64,
+    pub base_fee: U256,
+    /// Priority fee paga pela vítima (gas price acima da base fee)
+    pub victim_priority_fee: U256,
+    /// Priority fee do frontrun, acima da vítima para ser incluído antes dela
+    pub frontrun_priority_fee: U256,
+    /// Priority fee do backrun, a sugerida pelo oráculo de gas
+    pub backrun_priority_fee: U256,
+    /// Custo total do frontrun e do backrun, em wei
+    pub total_cost: U256,
+    /// `potential_profit` convertido para wei; `None` sem cotação contra o token nativo
+    pub profit_in_native: Option<U256>,
+}
+
+/// Quantidade esperada e efetiva de um hop, a partir do estado do pool no bloco da simulação
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HopMetrics {
+    pub pool: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    /// Entrada efetivamente recebida pelo pool
+    pub amount_in: U256,
+    pub expected_out: U256,
+    pub actual_out: U256,
+    pub slippage: f64,
 }
 
 /// Resultado final da análise
@@ -34,3 +119,17 @@ pub struct AnalysisResult {
     pub economically_viable: bool,
     pub simulated_tx: Option<H256>,
 }
+
+/// Serializa `Vec<u8>` como string hexadecimal `0x...`, como `ethers::types::Bytes`
+mod hex_bytes {
+    use ethers::types::Bytes;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        Bytes::from(data.to_vec()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        Bytes::deserialize(deserializer).map(|bytes| bytes.to_vec())
+    }
+}
@@ -18,3 +18,12 @@ fn detect_custom_swap_exact_eth_for_tokens_fee_on_transfer_with_referrer() {
     let (func, _) = detect_swap_function(&data).expect("failed to detect");
     assert_eq!(func, SwapFunction::SwapExactETHForTokensSupportingFeeOnTransferTokensWithReferrer);
 }
+
+#[test]
+fn detect_curve_exchange_underlying() {
+    // exchange_underlying(int128,int128,uint256,uint256) com parâmetros: 0, 2, 1000, 990
+    let data_hex = "a6417ed60000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000003e800000000000000000000000000000000000000000000000000000000000003de";
+    let data = decode(data_hex).unwrap();
+    let (func, _) = detect_swap_function(&data).expect("failed to detect");
+    assert_eq!(func, SwapFunction::CurveExchangeUnderlying);
+}
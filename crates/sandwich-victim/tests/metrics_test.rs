@@ -1,4 +1,7 @@
-use sandwich_victim::core::metrics::constant_product_input;
+use sandwich_victim::core::metrics::{
+    concentrated_liquidity_input, concentrated_liquidity_output, concentrated_liquidity_reserve,
+    constant_product_input,
+};
 use ethereum_types::U256;
 
 #[test]
@@ -8,3 +11,20 @@ fn constant_product_input_invalid_output() {
     let amount_out = U256::from(60u64);
     assert!(constant_product_input(amount_out, reserve_in, reserve_out).is_none());
 }
+
+#[test]
+fn concentrated_liquidity_round_trip() {
+    // preço 1:1 (sqrtPriceX96 = 2^96) com liquidez muito maior que o swap
+    let sqrt_price = U256::one() << 96;
+    let liquidity = U256::exp10(24);
+    let amount_in = U256::exp10(18);
+    for zero_for_one in [true, false] {
+        let out = concentrated_liquidity_output(amount_in, sqrt_price, liquidity, zero_for_one);
+        assert!(out < amount_in && out > amount_in * 999u64 / 1000u64);
+        let back = concentrated_liquidity_input(out, sqrt_price, liquidity, zero_for_one).unwrap();
+        assert!(back >= amount_in - 2u64 && back <= amount_in + 2u64);
+    }
+    assert_eq!(concentrated_liquidity_reserve(sqrt_price, liquidity, true), liquidity);
+    // liquidez insuficiente para a saída pedida
+    assert!(concentrated_liquidity_input(liquidity * 2u64, sqrt_price, liquidity, true).is_none());
+}
@@ -0,0 +1,116 @@
+use ethereum_types::{Address, H256, U256};
+use sandwich_victim::dex::SwapFunction;
+use sandwich_victim::types::{
+    AnalysisResult, GasCost, HopMetrics, Metrics, SandwichPlan, TransactionData, ANALYSIS_RESULT_SCHEMA, ANALYSIS_RESULT_SCHEMA_VERSION,
+};
+use serde_json::Value;
+
+fn schema() -> Value {
+    serde_json::from_str(ANALYSIS_RESULT_SCHEMA).expect("valid schema json")
+}
+
+fn property_names(object: &Value) -> Vec<String> {
+    let mut names: Vec<_> = object["properties"].as_object().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+fn field_names(value: &Value) -> Vec<String> {
+    let mut names: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+fn sample_result() -> AnalysisResult {
+    AnalysisResult {
+        potential_victim: true,
+        economically_viable: false,
+        simulated_tx: Some(H256::repeat_byte(0xab)),
+        metrics: Metrics {
+            swap_function: SwapFunction::SwapExactTokensForTokensSupportingFeeOnTransferTokens,
+            token_route: vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)],
+            slippage: 0.0125,
+            min_tokens_to_affect: U256::exp10(18),
+            potential_profit: U256::from(255u64),
+            router_address: Address::from_low_u64_be(3),
+            router_name: None,
+            hops: vec![HopMetrics {
+                pool: Address::from_low_u64_be(4),
+                token_in: Address::from_low_u64_be(1),
+                token_out: Address::from_low_u64_be(2),
+                amount_in: U256::from(1000u64),
+                expected_out: U256::from(990u64),
+                actual_out: U256::from(977u64),
+                slippage: 0.0131,
+            }],
+            fee_on_transfer: true,
+            gas_cost: Some(GasCost {
+                frontrun_gas: 120_000,
+                backrun_gas: 120_000,
+                base_fee: U256::exp10(10),
+                victim_priority_fee: U256::exp10(9),
+                frontrun_priority_fee: U256::exp10(9) * 11 / 10 + 1,
+                backrun_priority_fee: U256::exp10(9),
+                total_cost: U256::from(2_652_000_000_120_000u64),
+                profit_in_native: None,
+            }),
+            sandwich: Some(SandwichPlan {
+                frontrun_in: U256::exp10(17),
+                profit: U256::from(255u64),
+                victim_out: U256::from(977u64),
+                victim_execution_price: 0.977,
+            }),
+        },
+    }
+}
+
+#[test]
+fn analysis_result_matches_schema() {
+    let schema = schema();
+    assert!(schema["$id"].as_str().unwrap().ends_with(&format!(":v{ANALYSIS_RESULT_SCHEMA_VERSION}")));
+
+    let json = serde_json::to_value(sample_result()).unwrap();
+    assert_eq!(field_names(&json), property_names(&schema));
+    assert_eq!(field_names(&json["metrics"]), property_names(&schema["$defs"]["Metrics"]));
+    assert_eq!(field_names(&json["metrics"]["hops"][0]), property_names(&schema["$defs"]["HopMetrics"]));
+    assert_eq!(field_names(&json["metrics"]["gas_cost"]), property_names(&schema["$defs"]["GasCost"]));
+    assert_eq!(json["metrics"]["gas_cost"]["frontrun_gas"], 120_000);
+    assert_eq!(field_names(&json["metrics"]["sandwich"]), property_names(&schema["$defs"]["SandwichPlan"]));
+
+    // Quantidades e endereços em hexadecimal
+    assert_eq!(json["metrics"]["potential_profit"], "0xff");
+    assert_eq!(json["metrics"]["router_address"], format!("{:#x}", Address::from_low_u64_be(3)));
+    let swap_functions = schema["$defs"]["SwapFunction"]["enum"].as_array().unwrap();
+    assert!(swap_functions.contains(&json["metrics"]["swap_function"]));
+
+    let back: AnalysisResult = serde_json::from_value(json).unwrap();
+    assert_eq!(back.metrics.min_tokens_to_affect, U256::exp10(18));
+    assert!(back.metrics.fee_on_transfer);
+}
+
+#[test]
+fn schema_swap_functions_deserialize() {
+    let schema = schema();
+    for name in schema["$defs"]["SwapFunction"]["enum"].as_array().unwrap() {
+        serde_json::from_value::<SwapFunction>(name.clone()).unwrap_or_else(|_| panic!("unknown variant {name}"));
+    }
+}
+
+#[test]
+fn transaction_data_uses_hex_bytes() {
+    let tx = TransactionData {
+        from: Address::from_low_u64_be(1),
+        to: Address::from_low_u64_be(2),
+        data: vec![0x38, 0xed, 0x17, 0x39],
+        value: U256::zero(),
+        gas: 21_000,
+        gas_price: U256::exp10(9),
+        nonce: U256::from(7u64),
+    };
+    let json = serde_json::to_value(&tx).unwrap();
+    assert_eq!(json["data"], "0x38ed1739");
+    assert_eq!(field_names(&json), property_names(&schema()["$defs"]["TransactionData"]));
+
+    let back: TransactionData = serde_json::from_value(json).unwrap();
+    assert_eq!(back.data, tx.data);
+}